@@ -1,9 +1,12 @@
 use super::{MopAliasPair, MopFnAliasMap, block::Term, graph::*, types::*, value::*};
-use crate::{analysis::graphs::scc::Scc, def_id::*};
+use crate::{
+    analysis::{graphs::scc::Scc, utils::budget},
+    def_id::*,
+};
 use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
-    mir::{Local, Operand, Place, ProjectionElem, TerminatorKind},
+    mir::{Local, Operand, Place, TerminatorKind},
     ty,
 };
 use std::collections::HashSet;
@@ -76,7 +79,8 @@ impl<'tcx> MopGraph<'tcx> {
                     if is_no_alias_intrinsic(target_id) {
                         return;
                     }
-                    if !self.tcx.is_mir_available(target_id) {
+                    // Foreign functions without MIR may have summaries from sidecar files.
+                    if !self.tcx.is_mir_available(target_id) && !fn_map.contains_key(&target_id) {
                         return;
                     }
                     rap_debug!("Sync aliases for function call: {:?}", target_id);
@@ -92,6 +96,7 @@ impl<'tcx> MopGraph<'tcx> {
                         let mut mop_graph = MopGraph::new(self.tcx, target_id);
                         mop_graph.find_scc();
                         mop_graph.check(0, fn_map, recursion_set);
+                        budget::record_incomplete(self.tcx, "alias", target_id, &mop_graph.budget);
                         let ret_alias = mop_graph.ret_alias.clone();
                         rap_debug!("Find aliases of {:?}: {:?}", target_id, ret_alias);
                         fn_map.insert(target_id, ret_alias);
@@ -132,34 +137,27 @@ impl<'tcx> MopGraph<'tcx> {
     pub fn projection(&mut self, place: Place<'tcx>) -> usize {
         let local = place.local.as_usize();
         rap_debug!("projection: place = {:?}, local = {:?}", place, local);
-        let mut value_idx = local;
         // Projections are leveled
         // Case 1: (*6).1 involves two projections: a Deref and a Field.
         // Case 2: (6.0).1 involves two field projections.
         // We should recursively parse the projection.
-        for proj in place.projection {
-            rap_debug!("proj: {:?}", proj);
+        let (mut value_idx, path) = self.field_path(place);
+        let local = self.values[value_idx].local;
+        for (field_idx, ty) in path {
+            rap_debug!("field: {:?}", field_idx);
             let new_value_idx = self.values.len();
-            match proj {
-                ProjectionElem::Deref => {}
-                ProjectionElem::Field(field, ty) => {
-                    let field_idx = field.as_usize();
-                    // If the field has not been created as a value, we crate a value;
-                    if !self.values[value_idx].fields.contains_key(&field_idx) {
-                        let ty_env = ty::TypingEnv::post_analysis(self.tcx, self.def_id);
-                        let need_drop = ty.needs_drop(self.tcx, ty_env);
-                        let may_drop = !is_not_drop(self.tcx, ty);
-                        let mut node =
-                            Value::new(new_value_idx, local, need_drop, need_drop || may_drop);
-                        node.kind = kind(ty);
-                        node.father = Some(FatherInfo::new(value_idx, field_idx));
-                        self.values[value_idx].fields.insert(field_idx, node.index);
-                        self.values.push(node);
-                    }
-                    value_idx = *self.values[value_idx].fields.get(&field_idx).unwrap();
-                }
-                _ => {}
+            // If the field has not been created as a value, we crate a value;
+            if !self.values[value_idx].fields.contains_key(&field_idx) {
+                let ty_env = ty::TypingEnv::post_analysis(self.tcx, self.def_id);
+                let need_drop = ty.needs_drop(self.tcx, ty_env);
+                let may_drop = !is_not_drop(self.tcx, ty);
+                let mut node = Value::new(new_value_idx, local, need_drop, need_drop || may_drop);
+                node.kind = kind(ty);
+                node.father = Some(FatherInfo::new(value_idx, field_idx));
+                self.values[value_idx].fields.insert(field_idx, node.index);
+                self.values.push(node);
             }
+            value_idx = *self.values[value_idx].fields.get(&field_idx).unwrap();
         }
         value_idx
     }
@@ -1,16 +1,19 @@
 use super::{MopFnAliasPairs, assign::*, block::*, types::*, value::*};
 use crate::{
-    analysis::graphs::scc::{Scc, SccExit},
+    analysis::{
+        graphs::scc::{Scc, SccExit},
+        utils::budget::Budget,
+    },
     def_id::*,
     utils::source::*,
 };
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::{
     mir::{
-        AggregateKind, BasicBlock, Const, Operand, Rvalue, StatementKind, TerminatorKind,
-        UnwindAction,
+        AggregateKind, BasicBlock, Const, Operand, Place, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind, UnwindAction,
     },
-    ty::{self, TyCtxt, TypingEnv},
+    ty::{self, CoroutineArgsExt, Ty, TyCtxt, TypingEnv},
 };
 use rustc_span::{Span, def_id::DefId};
 use std::{
@@ -18,6 +21,9 @@ use std::{
     vec::Vec,
 };
 
+/// The most paths enumerated for a function by `get_paths`.
+const PATH_LIMIT: usize = 1024;
+
 #[derive(Clone)]
 pub struct MopGraph<'tcx> {
     pub def_id: DefId,
@@ -37,12 +43,54 @@ pub struct MopGraph<'tcx> {
     pub constants: FxHashMap<usize, usize>,
     // We record the decision of enumerate typed values for path sensitivity.
     pub discriminants: FxHashMap<usize, usize>,
-    // a threhold to avoid path explosion.
-    pub visit_times: usize,
+    // the per-function budget to avoid path explosion.
+    pub budget: Budget,
     pub alias_sets: Vec<FxHashSet<usize>>,
     // contains the return results for inter-procedure analysis.
     pub ret_alias: MopFnAliasPairs,
     pub terminators: Vec<TerminatorKind<'tcx>>,
+    // The state layout if the function is a coroutine body, e.g., the body of an async fn.
+    pub coroutine: Option<CoroutineState<'tcx>>,
+}
+
+/// The state of a coroutine, which lives across suspension points.
+///
+/// Places into the state, e.g., `(((*_30) as variant#3).0)`, are projected onto a single state
+/// value whose fields are the upvars followed by the saved locals. A saved local is thus the same
+/// field in all variants where it is stored.
+#[derive(Clone, Debug)]
+pub struct CoroutineState<'tcx> {
+    /// The index of the state value.
+    pub value: usize,
+    /// The number of upvars, i.e., the arguments of an async fn.
+    pub upvar_count: usize,
+    /// The saved locals stored in each variant, indexed by their field indices in the variant.
+    pub variant_fields: Vec<Vec<usize>>,
+    /// The types of the saved locals.
+    pub saved_tys: Vec<Ty<'tcx>>,
+    /// The places where the upvars are moved to when the coroutine starts.
+    pub arg_places: Vec<Place<'tcx>>,
+    /// The suspension points: the blocks returning after storing a suspended variant.
+    pub suspensions: FxHashMap<usize, usize>,
+}
+
+impl<'tcx> CoroutineState<'tcx> {
+    /// The field of the state value that stores a saved local.
+    pub fn saved_field(&self, saved_local: usize) -> usize {
+        self.upvar_count + saved_local
+    }
+
+    /// The saved locals that are alive when the coroutine is suspended at the block, as fields
+    /// of the state value with their types.
+    pub fn saved_at(&self, bb_idx: usize) -> Vec<(usize, Ty<'tcx>)> {
+        let Some(&variant) = self.suspensions.get(&bb_idx) else {
+            return Vec::new();
+        };
+        self.variant_fields[variant]
+            .iter()
+            .map(|&saved_local| (self.saved_field(saved_local), self.saved_tys[saved_local]))
+            .collect()
+    }
 }
 
 impl<'tcx> MopGraph<'tcx> {
@@ -56,6 +104,8 @@ impl<'tcx> MopGraph<'tcx> {
         let arg_size = body.arg_count;
         let mut values = Vec::<Value>::new();
         let ty_env = TypingEnv::post_analysis(tcx, def_id);
+        let is_state = |ty: Ty<'tcx>| matches!(ty.kind(), ty::Coroutine(did, _) if *did == def_id);
+        let is_state_ref = |ty: Ty<'tcx>| ty.builtin_deref(true).is_some_and(is_state);
         for (local, local_decl) in locals.iter_enumerated() {
             let need_drop = local_decl.ty.needs_drop(tcx, ty_env); // the type is drop
             let may_drop = !is_not_drop(tcx, local_decl.ty);
@@ -68,6 +118,31 @@ impl<'tcx> MopGraph<'tcx> {
             node.kind = kind(local_decl.ty);
             values.push(node);
         }
+        let mut coroutine = body.coroutine_layout_raw().map(|layout| {
+            let state_ty = tcx.type_of(def_id).instantiate_identity();
+            let upvar_count = match state_ty.kind() {
+                ty::Coroutine(_, args) => args.as_coroutine().upvar_tys().len(),
+                _ => 0,
+            };
+            // The state is behind `Pin<&mut Self>`, so its value is considered to be of `_1`.
+            let need_drop = state_ty.needs_drop(tcx, ty_env);
+            let state = Value::new(values.len(), 1, need_drop, true);
+            values.push(state);
+            CoroutineState {
+                value: values.len() - 1,
+                upvar_count,
+                variant_fields: layout
+                    .variant_fields
+                    .iter()
+                    .map(|fields| fields.iter().map(|saved| saved.as_usize()).collect())
+                    .collect(),
+                saved_tys: layout.field_tys.iter().map(|saved| saved.ty).collect(),
+                arg_places: Vec::new(),
+                suspensions: FxHashMap::default(),
+            }
+        });
+        // The locals holding the discriminant of the state, which decides where to resume.
+        let mut state_discrs = FxHashSet::default();
 
         let basicblocks = &body.basic_blocks;
         let mut blocks = Vec::<Block<'tcx>>::new();
@@ -90,6 +165,16 @@ impl<'tcx> MopGraph<'tcx> {
                         match rvalue.clone() {
                             // rvalue is a Rvalue
                             Rvalue::Use(operand) => {
+                                // An upvar moved out of the state, e.g., `_3 = move ((*_29).0)`.
+                                if let Some(state) = coroutine.as_mut()
+                                    && let Operand::Copy(rv_place) | Operand::Move(rv_place) =
+                                        operand
+                                    && let [ProjectionElem::Deref, ProjectionElem::Field(..)] =
+                                        rv_place.projection.as_slice()
+                                    && is_state_ref(locals[rv_place.local].ty)
+                                {
+                                    state.arg_places.push(lv_place);
+                                }
                                 match operand {
                                     Operand::Copy(rv_place) => {
                                         let rv_local = rv_place.local.as_usize();
@@ -328,6 +413,36 @@ impl<'tcx> MopGraph<'tcx> {
                                             }
                                         }
                                     }
+                                    // The operands of a coroutine are its upvars, which are the
+                                    // leading fields of the coroutine state.
+                                    AggregateKind::Coroutine(_, _) => {
+                                        for (field_idx, operand) in operands.iter_enumerated() {
+                                            let (Operand::Copy(rv_place) | Operand::Move(rv_place)) =
+                                                operand
+                                            else {
+                                                continue;
+                                            };
+                                            if !values[lv_local].may_drop
+                                                || !values[rv_place.local.as_usize()].may_drop
+                                            {
+                                                continue;
+                                            }
+                                            let field_ty = rv_place.ty(locals, tcx).ty;
+                                            let lv_field_place =
+                                                tcx.mk_place_field(lv_place, field_idx, field_ty);
+                                            let assign = Assignment::new(
+                                                lv_field_place,
+                                                *rv_place,
+                                                if matches!(operand, Operand::Move(_)) {
+                                                    AssignType::Move
+                                                } else {
+                                                    AssignType::Copy
+                                                },
+                                                span,
+                                            );
+                                            cur_bb.assignments.push(assign);
+                                        }
+                                    }
                                     // TODO: Support alias for array
                                     AggregateKind::Array(_) => {}
                                     // For other aggregate types, simply create an assignment for each aggregated operand
@@ -360,15 +475,30 @@ impl<'tcx> MopGraph<'tcx> {
                                     Assignment::new(lv_place, rv_place, AssignType::Variant, span);
                                 cur_bb.assignments.push(assign);
                                 discriminants.insert(lv_local, rv_place.local.as_usize());
+                                if is_state(rv_place.ty(locals, tcx).ty) {
+                                    state_discrs.insert(lv_local);
+                                }
                             }
                             _ => {}
                         }
                     }
                     StatementKind::SetDiscriminant {
-                        place: _,
-                        variant_index: _,
+                        place,
+                        variant_index,
                     } => {
-                        rap_warn!("SetDiscriminant: {:?} is not handled in RAPx!", stmt);
+                        if let Some(state) = coroutine.as_mut()
+                            && is_state(place.ty(locals, tcx).ty)
+                        {
+                            // The coroutine returns after storing a suspended variant, and
+                            // continues from the resume target of the variant when resumed.
+                            if variant_index.as_usize()
+                                >= ty::CoroutineArgs::<TyCtxt<'tcx>>::RESERVED_VARIANTS
+                            {
+                                state.suspensions.insert(i, variant_index.as_usize());
+                            }
+                        } else {
+                            rap_warn!("SetDiscriminant: {:?} is not handled in RAPx!", stmt);
+                        }
                     }
                     _ => {}
                 }
@@ -496,6 +626,31 @@ impl<'tcx> MopGraph<'tcx> {
             blocks.push(cur_bb);
         }
 
+        // The switch on the discriminant of the state at the entry decides where the coroutine
+        // is resumed. As before the coroutine transformation, the entry only leads to the start
+        // of the body, and the suspension points are connected to their resume targets, so that
+        // each loop around an await has one entry. Resuming a completed or poisoned coroutine
+        // panics, which is not modelled either.
+        if let Some(state) = &coroutine {
+            for (switch_idx, bb) in body.basic_blocks.iter().enumerate() {
+                if let Some(terminator) = &bb.terminator
+                    && let TerminatorKind::SwitchInt {
+                        discr: Operand::Copy(discr) | Operand::Move(discr),
+                        ref targets,
+                    } = terminator.kind
+                    && state_discrs.contains(&discr.local.as_usize())
+                {
+                    for (&bb_idx, &variant) in &state.suspensions {
+                        let resume = targets.target_for_value(variant as u128);
+                        blocks[bb_idx].add_next(resume.as_usize());
+                    }
+                    let start = targets.target_for_value(0);
+                    blocks[switch_idx].next = [start.as_usize()].into_iter().collect();
+                    blocks[switch_idx].terminator = Term::None;
+                }
+            }
+        }
+
         MopGraph {
             def_id,
             tcx,
@@ -506,19 +661,95 @@ impl<'tcx> MopGraph<'tcx> {
             alias_sets: Vec::<FxHashSet<usize>>::new(),
             constants: FxHashMap::default(),
             ret_alias: MopFnAliasPairs::new(arg_size),
-            visit_times: 0,
+            budget: Budget::new(),
             discriminants,
             terminators,
+            coroutine,
+        }
+    }
+
+    /// Split a place into the value it is based on and the field projections on the value.
+    /// Other projections, e.g., derefs, are not distinguished, except that the places into the
+    /// state of a coroutine are based on the state value; see [`CoroutineState`].
+    pub fn field_path(&self, place: Place<'tcx>) -> (usize, Vec<(usize, Ty<'tcx>)>) {
+        let mut value_idx = place.local.as_usize();
+        let mut path = Vec::new();
+        let Some(state) = &self.coroutine else {
+            for proj in place.projection {
+                if let ProjectionElem::Field(field, ty) = proj {
+                    path.push((field.as_usize(), ty));
+                }
+            }
+            return (value_idx, path);
+        };
+        let local_decls = &self.tcx.optimized_mir(self.def_id).local_decls;
+        let mut variant = None;
+        for (base, proj) in place.iter_projections() {
+            match proj {
+                ProjectionElem::Deref => {
+                    let base_ty = base.ty(local_decls, self.tcx).ty;
+                    if let Some(ty::Coroutine(did, _)) = base_ty.builtin_deref(true).map(Ty::kind)
+                        && *did == self.def_id
+                    {
+                        value_idx = state.value;
+                        path.clear();
+                    }
+                }
+                ProjectionElem::Downcast(_, variant_idx) => {
+                    let base_ty = base.ty(local_decls, self.tcx).ty;
+                    if let ty::Coroutine(did, _) = base_ty.kind()
+                        && *did == self.def_id
+                    {
+                        variant = Some(variant_idx.as_usize());
+                    }
+                }
+                ProjectionElem::Field(field, ty) => {
+                    let field_idx = match variant.take() {
+                        Some(variant) => {
+                            state.saved_field(state.variant_fields[variant][field.as_usize()])
+                        }
+                        None => field.as_usize(),
+                    };
+                    path.push((field_idx, ty));
+                }
+                _ => {}
+            }
         }
+        (value_idx, path)
     }
 
+    /// Enumerate the acyclic paths from `index` to the exits of the function, where each scc
+    /// is collapsed into its enter and left through its exits. Cleanup blocks are skipped, and
+    /// at most `PATH_LIMIT` paths are collected.
     pub fn dfs_on_spanning_tree(
         &self,
-        _index: usize,
-        _stack: &mut Vec<usize>,
-        _paths: &mut Vec<Vec<usize>>,
+        index: usize,
+        stack: &mut Vec<usize>,
+        paths: &mut Vec<Vec<usize>>,
     ) {
-        // TOBE REMOVED!
+        if paths.len() >= PATH_LIMIT {
+            return;
+        }
+        let scc = &self.blocks[index].scc;
+        let nexts: FxHashSet<usize> = if scc.enter == index && !scc.nodes.is_empty() {
+            scc.exits.iter().map(|exit| exit.to).collect()
+        } else {
+            self.blocks[index].next.clone()
+        };
+        let mut nexts: Vec<usize> = nexts
+            .into_iter()
+            .filter(|next| !self.blocks[*next].is_cleanup && !stack.contains(next))
+            .collect();
+        if nexts.is_empty() {
+            paths.push(stack.clone());
+            return;
+        }
+        nexts.sort();
+        for next in nexts {
+            stack.push(next);
+            self.dfs_on_spanning_tree(next, stack, paths);
+            stack.pop();
+        }
     }
 
     pub fn get_paths(&self) -> Vec<Vec<usize>> {
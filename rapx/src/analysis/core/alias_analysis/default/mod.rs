@@ -8,19 +8,26 @@ pub mod value;
 
 use super::{AliasAnalysis, AliasPair, FnAliasMap, FnAliasPairs};
 use crate::{
-    analysis::{Analysis, graphs::scc::Scc},
+    analysis::{
+        Analysis,
+        graphs::scc::Scc,
+        utils::{
+            budget,
+            cache::{FnKeys, SummaryCache, external_key},
+            sidecar::dep_summaries,
+        },
+    },
     def_id::*,
     utils::source::*,
 };
 use graph::MopGraph;
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::{fingerprint::Fingerprint, fx::FxHashMap};
 use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
-use std::{cmp::Ordering, collections::HashSet, convert::From, fmt};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashSet, convert::From, env, fmt};
 
-pub const VISIT_LIMIT: usize = 1000;
-
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MopAliasPair {
     pub fact: AliasPair,
     pub lhs_may_drop: bool,
@@ -117,7 +124,7 @@ impl fmt::Display for MopAliasPair {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MopFnAliasPairs {
     arg_size: usize,
     alias_set: HashSet<MopAliasPair>,
@@ -190,9 +197,34 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
 
     fn run(&mut self) {
         rap_debug!("Start alias analysis via MoP.");
+        self.load_dep_summaries();
+
+        // Summaries of unchanged functions are restored from the cache, including those of the
+        // callees in dependencies that were analyzed on demand.
+        let mut cache = SummaryCache::load(self.tcx, "mop", env::var("MOP").unwrap_or_default());
+        let cached = cache.drain_valid(self.tcx, |def_id| self.cache_key(def_id));
+        let cached_ids: HashSet<DefId> = cached.iter().map(|(def_id, _)| *def_id).collect();
+        self.fn_map.extend(cached);
+        rap_debug!(
+            "Restore {} alias summaries from the cache.",
+            cached_ids.len()
+        );
+
         let mir_keys = self.tcx.mir_keys(());
         for local_def_id in mir_keys {
-            self.query_mop(local_def_id.to_def_id());
+            let def_id = local_def_id.to_def_id();
+            if cached_ids.contains(&def_id) {
+                continue;
+            }
+            self.query_mop(def_id);
+        }
+        if cache.is_enabled() {
+            for (def_id, fn_alias) in &self.fn_map {
+                if let Some(key) = self.cache_key(*def_id) {
+                    cache.insert(self.tcx, *def_id, key, fn_alias.clone());
+                }
+            }
+            cache.save();
         }
         // Meaning of output: 0 for ret value; 1,2,3,... for corresponding args.
         for (fn_id, fn_alias) in &mut self.fn_map {
@@ -231,6 +263,45 @@ impl<'tcx> AliasAnalyzer<'tcx> {
         }
     }
 
+    /// The cache key of the alias summary, which covers the function and all its callees.
+    fn cache_key(&self, def_id: DefId) -> Option<Fingerprint> {
+        if def_id.is_local() {
+            FnKeys::get(self.tcx).callee_key(def_id)
+        } else {
+            Some(external_key(self.tcx, def_id))
+        }
+    }
+
+    /// Summaries of dependencies computed by `-summarize-deps` are used for foreign callees.
+    pub fn load_dep_summaries(&mut self) {
+        for (def_id, fn_alias) in &dep_summaries(self.tcx).mop {
+            self.fn_map.insert(*def_id, fn_alias.clone());
+        }
+    }
+
+    /// Return the alias summary of a function, which is computed on demand together with the
+    /// summaries of its callees if it has not been computed yet. Unlike `run`, only the
+    /// functions actually queried are analyzed.
+    pub fn query_fn_alias(&mut self, def_id: DefId) -> Option<&MopFnAliasPairs> {
+        if !self.fn_map.contains_key(&def_id) {
+            if def_id.is_local() {
+                self.query_mop(def_id);
+            } else if self.tcx.is_mir_available(def_id) {
+                let mut recursion_set = HashSet::from([def_id]);
+                let mut mop_graph = MopGraph::new(self.tcx, def_id);
+                mop_graph.find_scc();
+                mop_graph.check(0, &mut self.fn_map, &mut recursion_set);
+                budget::record_incomplete(self.tcx, "alias", def_id, &mop_graph.budget);
+                self.fn_map.insert(def_id, mop_graph.ret_alias);
+            }
+            if let Some(fn_alias) = self.fn_map.get_mut(&def_id) {
+                fn_alias.sort_alias_index();
+            }
+            self.handle_conor_cases();
+        }
+        self.fn_map.get(&def_id)
+    }
+
     fn handle_conor_cases(&mut self) {
         let cases = [
             copy_from_nonoverlapping_opt(),
@@ -269,9 +340,7 @@ impl<'tcx> AliasAnalyzer<'tcx> {
             rap_trace!("After searching scc: {}", mop_graph);
             let mut recursion_set = HashSet::default();
             mop_graph.check(0, &mut self.fn_map, &mut recursion_set);
-            if mop_graph.visit_times > VISIT_LIMIT {
-                rap_trace!("Over visited: {:?}", def_id);
-            }
+            budget::record_incomplete(self.tcx, "alias", def_id, &mop_graph.budget);
             self.fn_map.insert(def_id, mop_graph.ret_alias);
         } else {
             rap_trace!("Mir is not available at {}", self.tcx.def_path_str(def_id));
@@ -219,8 +219,7 @@ impl<'tcx> MopGraph<'tcx> {
             // Prevent rustc stack overflow on extremely deep CFG / SCC exploration.
             return;
         };
-        self.visit_times += 1;
-        if self.visit_times > VISIT_LIMIT {
+        if !self.budget.tick() {
             return;
         }
         let scc_idx = self.blocks[bb_idx].scc.enter;
@@ -451,7 +450,7 @@ impl<'tcx> MopGraph<'tcx> {
                 if let Some(values) = self.possible_switch_values_for_constraint_id(path_discr_id) {
                     // Enumerate each possible value explicitly (bool: 0/1, enum: 0..N).
                     for path_discr_val in values {
-                        if self.visit_times > VISIT_LIMIT {
+                        if self.budget.is_exhausted() {
                             continue;
                         }
                         let next = self.switch_target_for_value(&targets, path_discr_val);
@@ -466,7 +465,7 @@ impl<'tcx> MopGraph<'tcx> {
                 } else {
                     // Fallback: explore explicit branches + otherwise.
                     for iter in targets.iter() {
-                        if self.visit_times > VISIT_LIMIT {
+                        if self.budget.is_exhausted() {
                             continue;
                         }
                         let next = iter.1.as_usize();
@@ -493,7 +492,7 @@ impl<'tcx> MopGraph<'tcx> {
                 }
             } else {
                 for next in cur_block.next {
-                    if self.visit_times > VISIT_LIMIT {
+                    if self.budget.is_exhausted() {
                         continue;
                     }
                     self.split_check(next, fn_map, recursion_set);
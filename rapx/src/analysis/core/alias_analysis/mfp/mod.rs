@@ -12,7 +12,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use super::{AliasAnalysis, FnAliasMap, FnAliasPairs};
-use crate::analysis::Analysis as RapxAnalysis;
+use crate::analysis::{Analysis as RapxAnalysis, utils::sidecar::dep_summaries};
 use intraproc::FnAliasAnalyzer;
 
 /// MFP-based alias analyzer
@@ -163,6 +163,14 @@ impl<'tcx> RapxAnalysis for MfpAliasAnalyzer<'tcx> {
             }
         }
 
+        // Summaries of dependencies computed by `-summarize-deps` are used for foreign callees
+        // that are not analyzed here.
+        for (def_id, summary) in &dep_summaries(self.tcx).mfp {
+            if !reachable_functions.contains(def_id) {
+                self.fn_map.insert(*def_id, summary.clone());
+            }
+        }
+
         // Convert to Vec for iteration
         let reachable_vec: Vec<DefId> = reachable_functions.iter().copied().collect();
 
@@ -6,6 +6,7 @@ use super::super::Analysis;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
 use rustc_span::def_id::LOCAL_CRATE;
+use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, fmt};
 
 /// The data structure to store aliases for a set of functions.
@@ -33,7 +34,7 @@ pub trait AliasAnalysis: Analysis {
 
 /// To store the alias relationships among arguments and return values.
 /// Each function may have multiple return instructions, leading to different RetAlias.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FnAliasPairs {
     arg_size: usize,
     alias_set: HashSet<AliasPair>,
@@ -114,7 +115,7 @@ impl fmt::Display for FnAliasMapWrapper {
 
 /// AliasPair is used to store the alias relationships between two places.
 /// The result is field-sensitive.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AliasPair {
     pub left_local: usize,
     pub lhs_fields: Vec<usize>,
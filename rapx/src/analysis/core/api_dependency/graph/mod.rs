@@ -199,6 +199,25 @@ impl<'tcx> ApiDependencyGraph<'tcx> {
         true
     }
 
+    /// Return the input types (ordered by argument number) and the output type of an API that
+    /// has been added to the graph.
+    pub fn api_signature(&self, api: DepNode<'tcx>) -> Option<(Vec<Ty<'tcx>>, Ty<'tcx>)> {
+        let index = self.get_index(api)?;
+        let mut inputs = Vec::new();
+        for edge in self.graph.edges_directed(index, Direction::Incoming) {
+            if let DepEdge::Arg(no) = edge.weight() {
+                inputs.push((*no, self.graph[edge.source()].expect_ty().ty()));
+            }
+        }
+        inputs.sort_by_key(|(no, _)| *no);
+        let output = self
+            .graph
+            .edges_directed(index, Direction::Outgoing)
+            .find(|edge| matches!(edge.weight(), DepEdge::Ret))
+            .map(|edge| self.graph[edge.target()].expect_ty().ty())?;
+        Some((inputs.into_iter().map(|(_, ty)| ty).collect(), output))
+    }
+
     /// return all transform kind for `ty` that we intersted in.
     pub fn all_transforms(&self, ty: Ty<'tcx>) -> Vec<TransformKind> {
         let mut tfs = Vec::new();
@@ -6,14 +6,15 @@ use rustc_middle::{
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-use super::visitor::CallGraphVisitor;
+use super::{indirect::FnValueAnalysis, visitor::CallGraphVisitor};
 use crate::{
     Analysis,
-    analysis::core::callgraph::{CallGraphAnalysis, FnCallMap},
+    analysis::core::callgraph::{CallGraphAnalysis, CallKind, Config, FnCallMap},
 };
 
 pub struct CallGraphAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
+    pub config: Config,
     pub graph: CallGraph<'tcx>,
 }
 
@@ -31,7 +32,7 @@ impl<'tcx> Analysis for CallGraphAnalyzer<'tcx> {
     }
 }
 
-impl<'tcx> CallGraphAnalysis for CallGraphAnalyzer<'tcx> {
+impl<'tcx> CallGraphAnalysis<'tcx> for CallGraphAnalyzer<'tcx> {
     fn get_fn_calls(&self) -> FnCallMap {
         let fn_calls: HashMap<DefId, Vec<DefId>> = self
             .graph
@@ -39,18 +40,37 @@ impl<'tcx> CallGraphAnalysis for CallGraphAnalyzer<'tcx> {
             .clone()
             .into_iter()
             .map(|(caller, callees)| {
-                let callee_ids = callees.into_iter().map(|(did, _)| did).collect::<Vec<_>>();
+                let callee_ids = callees
+                    .into_iter()
+                    .map(|(did, _, _)| did)
+                    .collect::<Vec<_>>();
                 (caller, callee_ids)
             })
             .collect();
         fn_calls
     }
+
+    fn get_indirect_fn_calls(&self) -> FnCallMap {
+        self.graph
+            .fn_calls
+            .iter()
+            .filter_map(|(caller, callees)| {
+                let callee_ids = callees
+                    .iter()
+                    .filter(|(_, _, kind)| *kind == CallKind::Indirect)
+                    .map(|(did, _, _)| *did)
+                    .collect::<Vec<_>>();
+                (!callee_ids.is_empty()).then_some((*caller, callee_ids))
+            })
+            .collect()
+    }
 }
 
 impl<'tcx> CallGraphAnalyzer<'tcx> {
-    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+    pub fn new(tcx: TyCtxt<'tcx>, config: Config) -> Self {
         Self {
             tcx: tcx,
+            config,
             graph: CallGraph::new(tcx),
         }
     }
@@ -81,19 +101,38 @@ impl<'tcx> CallGraphAnalyzer<'tcx> {
                 };
 
                 let mut call_graph_visitor =
-                    CallGraphVisitor::new(self.tcx, def_id.into(), body, &mut self.graph);
+                    CallGraphVisitor::new(self.tcx, def_id, body, &mut self.graph)
+                        .with_drop_glue(self.config.drop_glue);
                 call_graph_visitor.visit();
             }
         }
+
+        // Resolve the indirect calls with the function values flowing to the callee operands.
+        if !self.graph.indirect_calls.is_empty() {
+            FnValueAnalysis::new(self.tcx).resolve(&mut self.graph);
+        }
+
+        self.graph.prune_virtual_calls(self.config.virtual_calls);
     }
 }
 
-pub type CallMap<'tcx> = HashMap<DefId, Vec<(DefId, Option<&'tcx mir::Terminator<'tcx>>)>>;
+pub type CallMap<'tcx> =
+    HashMap<DefId, Vec<(DefId, Option<&'tcx mir::Terminator<'tcx>>, CallKind)>>;
+
+/// A call whose callee is not known statically, e.g., a call through a function pointer.
+#[derive(Debug, Clone, Copy)]
+pub struct IndirectCall<'tcx> {
+    pub caller: DefId,
+    /// The function pointer, or the receiver of `Fn*::call`.
+    pub callee: mir::Place<'tcx>,
+    pub terminator: &'tcx mir::Terminator<'tcx>,
+}
 
 pub struct CallGraph<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub functions: HashSet<DefId>, // Function-like, including closures
-    pub fn_calls: CallMap<'tcx>,   // caller -> Vec<(callee, terminator)>
+    pub fn_calls: CallMap<'tcx>,   // caller -> Vec<(callee, terminator, kind)>
+    pub indirect_calls: Vec<IndirectCall<'tcx>>, // Indirect calls to be resolved
 }
 
 /// Internal apis for constructing a call graph
@@ -103,6 +142,7 @@ impl<'tcx> CallGraph<'tcx> {
             tcx,
             functions: HashSet::new(),
             fn_calls: HashMap::new(),
+            indirect_calls: Vec::new(),
         }
     }
 
@@ -122,9 +162,31 @@ impl<'tcx> CallGraph<'tcx> {
         caller_id: DefId,
         callee_id: DefId,
         terminator_stmt: Option<&'tcx mir::Terminator<'tcx>>,
+        kind: CallKind,
     ) {
         let entry = self.fn_calls.entry(caller_id).or_insert_with(Vec::new);
-        entry.push((callee_id, terminator_stmt));
+        entry.push((callee_id, terminator_stmt, kind));
+    }
+
+    /// Record an indirect call, which is resolved after all functions are visited.
+    pub fn record_indirect_call(&mut self, indirect_call: IndirectCall<'tcx>) {
+        self.indirect_calls.push(indirect_call);
+    }
+
+    /// Add a resolved target of an indirect call to the call graph.
+    pub fn add_indirect_call(
+        &mut self,
+        caller_id: DefId,
+        callee_id: DefId,
+        terminator_stmt: &'tcx mir::Terminator<'tcx>,
+    ) {
+        self.register_fn(callee_id);
+        self.add_funciton_call(
+            caller_id,
+            callee_id,
+            Some(terminator_stmt),
+            CallKind::Indirect,
+        );
     }
 }
 
@@ -162,7 +224,7 @@ impl<'tcx> CallGraph<'tcx> {
 
         // Visit all callees (children) of the current node
         if let Some(callees) = self.fn_calls.get(&func_def_id) {
-            for (callee_id, _terminator) in callees {
+            for (callee_id, _terminator, _kind) in callees {
                 if !visited.contains(callee_id) {
                     self.dfs_post_order(*callee_id, visited, post_order_ids);
                 }
@@ -178,11 +240,11 @@ impl<'tcx> CallGraph<'tcx> {
         let mut callers_map: CallMap<'tcx> = HashMap::new();
 
         for (&caller_id, calls_vec) in &self.fn_calls {
-            for (callee_id, terminator) in calls_vec {
+            for (callee_id, terminator, kind) in calls_vec {
                 callers_map
                     .entry(*callee_id)
                     .or_insert_with(Vec::new)
-                    .push((caller_id, *terminator));
+                    .push((caller_id, *terminator, *kind));
             }
         }
         callers_map
@@ -194,7 +256,7 @@ impl<'tcx> CallGraph<'tcx> {
             callees
                 .clone()
                 .into_iter()
-                .map(|(did, _)| did)
+                .map(|(did, _, _)| did)
                 .collect::<Vec<_>>()
         } else {
             vec![]
@@ -216,7 +278,7 @@ impl<'tcx> CallGraph<'tcx> {
             callers
                 .clone()
                 .into_iter()
-                .map(|(did, _)| did)
+                .map(|(did, _, _)| did)
                 .collect::<Vec<_>>()
         } else {
             vec![]
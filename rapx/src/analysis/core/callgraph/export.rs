@@ -0,0 +1,371 @@
+//! Export of call graphs to DOT, JSON and GraphML, and queries on the callers and transitive
+//! callees of functions.
+//!
+//! Each node carries the crate, safety and visibility of the function, and each edge carries
+//! its kind and the span of the call site.
+
+use super::{CallKind, default::CallGraph};
+use crate::{report::finding::SourceRegion, utils::log::rap_error_and_exit};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{
+    def::{CtorKind, DefKind},
+    def_id::DefId,
+};
+use rustc_middle::ty::Visibility;
+use serde::Serialize;
+use std::{collections::BTreeSet, fmt::Write, path::Path};
+
+/// The export format of call graphs, selected by `-callgraph=<dot|json|graphml>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallGraphFormat {
+    Dot,
+    Json,
+    GraphMl,
+}
+
+impl CallGraphFormat {
+    pub fn from_arg(value: &str) -> Self {
+        match value {
+            "dot" => CallGraphFormat::Dot,
+            "json" => CallGraphFormat::Json,
+            "graphml" => CallGraphFormat::GraphMl,
+            _ => rap_error_and_exit(format!(
+                "Unknown call graph format `{value}`; expected one of: dot, json, graphml."
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CallGraphFormat::Dot => "dot",
+            CallGraphFormat::Json => "json",
+            CallGraphFormat::GraphMl => "graphml",
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct NodeInfo {
+    id: usize,
+    name: String,
+    #[serde(rename = "crate")]
+    krate: String,
+    safety: &'static str,
+    visibility: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+struct EdgeInfo {
+    from: usize,
+    to: usize,
+    kind: &'static str,
+    span: Option<SourceRegion>,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphInfo {
+    nodes: Vec<NodeInfo>,
+    edges: Vec<EdgeInfo>,
+}
+
+fn span_str(span: &Option<SourceRegion>) -> String {
+    span.as_ref()
+        .map(|region| {
+            format!(
+                "{}:{}:{}",
+                region.file, region.start_line, region.start_column
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Turn the path of a trait impl item `<Foo as Trait>::item` into `Foo::item`.
+fn strip_trait(name: &str) -> Option<String> {
+    let rest = name.strip_prefix('<')?;
+    let mut depth = 0;
+    let mut self_end = None;
+    for (idx, c) in rest.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth == 0 => {
+                let self_ty = &rest[..self_end?];
+                return Some(format!("{}{}", self_ty, &rest[idx + 1..]));
+            }
+            '>' => depth -= 1,
+            ' ' if depth == 0 && self_end.is_none() && rest[idx..].starts_with(" as ") => {
+                self_end = Some(idx);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+impl<'tcx> CallGraph<'tcx> {
+    /// All functions of the graph, sorted by their paths.
+    fn sorted_nodes(&self) -> Vec<DefId> {
+        let mut nodes: Vec<DefId> = self
+            .functions
+            .iter()
+            .copied()
+            .chain(self.fn_calls.keys().copied())
+            .chain(
+                self.fn_calls
+                    .values()
+                    .flatten()
+                    .map(|(callee_id, _, _)| *callee_id),
+            )
+            .collect::<FxHashSet<_>>()
+            .into_iter()
+            .collect();
+        nodes.sort_by_cached_key(|def_id| self.tcx.def_path_str(*def_id));
+        nodes
+    }
+
+    fn safety(&self, def_id: DefId) -> &'static str {
+        match self.tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn | DefKind::Ctor(_, CtorKind::Fn) => {
+                if self.tcx.fn_sig(def_id).skip_binder().safety().is_unsafe() {
+                    "unsafe"
+                } else {
+                    "safe"
+                }
+            }
+            _ => "safe",
+        }
+    }
+
+    /// `public`, `restricted` (e.g., `pub(crate)`), or `private` to the enclosing module.
+    /// Closures and constants are private.
+    fn visibility(&self, def_id: DefId) -> &'static str {
+        if !matches!(
+            self.tcx.def_kind(def_id),
+            DefKind::Fn | DefKind::AssocFn | DefKind::Ctor(..)
+        ) {
+            return "private";
+        }
+        match self.tcx.visibility(def_id) {
+            Visibility::Public => "public",
+            Visibility::Restricted(module)
+                if def_id.as_local().is_some_and(|local_def_id| {
+                    self.tcx.parent_module_from_def_id(local_def_id).to_def_id() == module
+                }) =>
+            {
+                "private"
+            }
+            Visibility::Restricted(_) => "restricted",
+        }
+    }
+
+    fn graph_info(&self) -> GraphInfo {
+        let def_ids = self.sorted_nodes();
+        let index: FxHashMap<DefId, usize> = def_ids
+            .iter()
+            .enumerate()
+            .map(|(id, def_id)| (*def_id, id))
+            .collect();
+        let nodes = def_ids
+            .iter()
+            .enumerate()
+            .map(|(id, def_id)| NodeInfo {
+                id,
+                name: self.tcx.def_path_str(*def_id),
+                krate: self.tcx.crate_name(def_id.krate).to_string(),
+                safety: self.safety(*def_id),
+                visibility: self.visibility(*def_id),
+            })
+            .collect();
+        let mut edges: Vec<EdgeInfo> = self
+            .fn_calls
+            .iter()
+            .flat_map(|(caller_id, callees)| {
+                callees
+                    .iter()
+                    .map(|(callee_id, terminator, kind)| EdgeInfo {
+                        from: index[caller_id],
+                        to: index[callee_id],
+                        kind: kind.as_str(),
+                        span: terminator
+                            .map(|terminator| SourceRegion::from_span(terminator.source_info.span)),
+                    })
+            })
+            .collect();
+        edges.sort_by_cached_key(|edge| (edge.from, edge.to, edge.kind, span_str(&edge.span)));
+        GraphInfo { nodes, edges }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let info = self.graph_info();
+        let mut dot = String::from("digraph CallGraph {\n    node [shape=box];\n");
+        for node in &info.nodes {
+            let color = if node.safety == "unsafe" {
+                "red"
+            } else {
+                "black"
+            };
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\", crate=\"{}\", safety=\"{}\", visibility=\"{}\", color={}];",
+                node.id,
+                escape_dot(&node.name),
+                escape_dot(&node.krate),
+                node.safety,
+                node.visibility,
+                color
+            );
+        }
+        for edge in &info.edges {
+            let style = match edge.kind {
+                "virtual" => "dashed",
+                "indirect" => "dotted",
+                "drop_glue" | "shim" => "bold",
+                _ => "solid",
+            };
+            let _ = writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\", kind=\"{}\", span=\"{}\", style={}];",
+                edge.from,
+                edge.to,
+                edge.kind,
+                edge.kind,
+                escape_dot(&span_str(&edge.span)),
+                style
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.graph_info()).unwrap()
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let info = self.graph_info();
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        for (id, domain) in [
+            ("name", "node"),
+            ("crate", "node"),
+            ("safety", "node"),
+            ("visibility", "node"),
+            ("kind", "edge"),
+            ("span", "edge"),
+        ] {
+            let _ = writeln!(
+                xml,
+                "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{id}\" attr.type=\"string\"/>"
+            );
+        }
+        xml.push_str("  <graph id=\"CallGraph\" edgedefault=\"directed\">\n");
+        for node in &info.nodes {
+            let _ = writeln!(
+                xml,
+                "    <node id=\"n{}\">\n      <data key=\"name\">{}</data>\n      <data key=\"crate\">{}</data>\n      <data key=\"safety\">{}</data>\n      <data key=\"visibility\">{}</data>\n    </node>",
+                node.id,
+                escape_xml(&node.name),
+                escape_xml(&node.krate),
+                node.safety,
+                node.visibility
+            );
+        }
+        for (id, edge) in info.edges.iter().enumerate() {
+            let _ = writeln!(
+                xml,
+                "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\n      <data key=\"kind\">{}</data>\n      <data key=\"span\">{}</data>\n    </edge>",
+                id,
+                edge.from,
+                edge.to,
+                edge.kind,
+                escape_xml(&span_str(&edge.span))
+            );
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Write the call graph to the file in the given format.
+    pub fn dump(&self, format: CallGraphFormat, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let content = match format {
+            CallGraphFormat::Dot => self.to_dot(),
+            CallGraphFormat::Json => self.to_json(),
+            CallGraphFormat::GraphMl => self.to_graphml(),
+        };
+        std::fs::write(path, content)
+    }
+
+    /// Find the functions of the graph by their paths, e.g., `foo::bar` or `Foo::bar`.
+    /// A path matches the full path of a function or a suffix of it at a `::` boundary, and
+    /// the methods of trait impls, e.g., `<Foo as std::ops::Drop>::drop`, also match `Foo::drop`.
+    pub fn find_fns(&self, path: &str) -> Vec<DefId> {
+        let suffix = format!("::{path}");
+        self.sorted_nodes()
+            .into_iter()
+            .filter(|def_id| {
+                let name = self.tcx.def_path_str(*def_id);
+                [Some(name.clone()), strip_trait(&name)]
+                    .into_iter()
+                    .flatten()
+                    .any(|name| name == path || name.ends_with(&suffix))
+            })
+            .collect()
+    }
+
+    /// The functions transitively called by the function, in the text format of `-callgraph`.
+    pub fn reach_to_string(&self, def_id: DefId) -> String {
+        let reachable: BTreeSet<String> = self
+            .get_callees_recursive(def_id)
+            .into_iter()
+            .filter(|callee_id| *callee_id != def_id || self.get_callees(def_id).contains(&def_id))
+            .map(|callee_id| self.tcx.def_path_str(callee_id))
+            .collect();
+        let mut result = format!(
+            "Functions reachable from {}:\n",
+            self.tcx.def_path_str(def_id)
+        );
+        for name in reachable {
+            let _ = writeln!(result, "    -> {}", name);
+        }
+        result
+    }
+
+    /// The direct callers of the function with the call sites.
+    pub fn callers_to_string(&self, def_id: DefId) -> String {
+        let callers: BTreeSet<String> = self
+            .get_callers_map()
+            .get(&def_id)
+            .into_iter()
+            .flatten()
+            .map(|(caller_id, terminator, kind)| {
+                let mut caller = self.tcx.def_path_str(*caller_id);
+                if *kind != CallKind::Static {
+                    let _ = write!(caller, " ({})", kind.as_str());
+                }
+                if let Some(terminator) = terminator {
+                    let span = Some(SourceRegion::from_span(terminator.source_info.span));
+                    let _ = write!(caller, " at {}", span_str(&span));
+                }
+                caller
+            })
+            .collect();
+        let mut result = format!("Callers of {}:\n", self.tcx.def_path_str(def_id));
+        for caller in callers {
+            let _ = writeln!(result, "    <- {}", caller);
+        }
+        result
+    }
+}
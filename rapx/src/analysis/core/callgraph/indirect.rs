@@ -0,0 +1,471 @@
+//! Resolution of indirect calls, i.e., calls through function pointers, closures passed around
+//! as values, `dyn Fn*` objects, and callback tables.
+//!
+//! The function values (fn items and closures) that may flow to each place are computed by a
+//! flow-insensitive, field-based propagation over the MIR of the crate:
+//! - a place is abstracted by its local, or by the last field of a local ADT or closure on its
+//!   projection, so that the values stored into a field are visible to every read of the field;
+//!   references, boxes and the fields of foreign types are smashed into their base;
+//! - the values flow from the arguments of a call to the parameters of the callee and from its
+//!   return place to the destination if the callee has MIR in the crate; otherwise, the values
+//!   flow between the aliased arguments and return value given by the alias summary of the
+//!   callee, e.g., `Box::new` returns a box aliasing its argument, which is only computed for the
+//!   foreign callees reached from the crate;
+//! - the values stored in constants and statics are collected from their allocations.
+//!
+//! Once the values of the callee operand of an indirect call are known, the call is bound to
+//! each target like a direct call, and the propagation continues until no more targets are found.
+
+use super::default::CallGraph;
+use crate::analysis::core::alias_analysis::default::AliasAnalyzer;
+use rustc_abi::{FIRST_VARIANT, FieldIdx, VariantIdx};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{
+    mir::{
+        AggregateKind, Body, Const, ConstValue, Local, Operand, Place, ProjectionElem,
+        RETURN_PLACE, Rvalue, StatementKind, TerminatorKind,
+        interpret::{AllocId, GlobalAlloc, Scalar},
+    },
+    ty::{self, Instance, InstanceKind, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::DUMMY_SP;
+
+/// An abstract location holding function values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Local(DefId, Local),
+    /// A field of a local ADT, closure or coroutine, shared by all its instances.
+    Field(DefId, VariantIdx, FieldIdx),
+}
+
+/// The function values that may flow to each abstract location.
+pub struct FnValueAnalysis<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    alias_analyzer: AliasAnalyzer<'tcx>,
+    bodies: FxHashSet<DefId>,
+    edges: FxHashMap<Node, FxHashSet<Node>>,
+    values: FxHashMap<Node, FxHashSet<DefId>>,
+    worklist: Vec<Node>,
+    visited_allocs: FxHashSet<AllocId>,
+}
+
+impl<'tcx> FnValueAnalysis<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        let mut alias_analyzer = AliasAnalyzer::new(tcx);
+        alias_analyzer.load_dep_summaries();
+        Self {
+            tcx,
+            alias_analyzer,
+            bodies: FxHashSet::default(),
+            edges: FxHashMap::default(),
+            values: FxHashMap::default(),
+            worklist: Vec::new(),
+            visited_allocs: FxHashSet::default(),
+        }
+    }
+
+    /// Resolve the indirect calls recorded in the call graph, and add an indirect edge from the
+    /// caller to each function that may be called.
+    pub fn resolve(&mut self, graph: &mut CallGraph<'tcx>) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if matches!(
+                self.tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            ) {
+                self.add_body(def_id);
+            }
+        }
+
+        let mut bound: FxHashSet<(usize, DefId)> = FxHashSet::default();
+        loop {
+            self.solve();
+            let mut changed = false;
+            for (idx, site) in graph.indirect_calls.iter().enumerate() {
+                let targets: Vec<DefId> = self
+                    .values
+                    .get(&self.node_of(site.caller, site.callee))
+                    .map(|targets| targets.iter().copied().collect())
+                    .unwrap_or_default();
+                for target in targets {
+                    if bound.insert((idx, target)) {
+                        self.bind_indirect_call(site.caller, site.terminator, target);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut resolved: Vec<_> = bound.into_iter().collect();
+        resolved.sort_by_key(|(idx, target)| (*idx, self.tcx.def_path_str(*target)));
+        for (idx, target) in resolved {
+            let site = &graph.indirect_calls[idx];
+            let (caller, terminator) = (site.caller, site.terminator);
+            rap_debug!(
+                "[Callgraph] Indirect call in {:?} resolved to {:?}",
+                caller,
+                target
+            );
+            graph.add_indirect_call(caller, target, terminator);
+        }
+    }
+
+    fn body(&self, def_id: DefId) -> Option<&'tcx Body<'tcx>> {
+        if !def_id.is_local() || !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        match self.tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure => {
+                if self
+                    .tcx
+                    .hir_body_const_context(def_id.expect_local())
+                    .is_some()
+                {
+                    Some(self.tcx.mir_for_ctfe(def_id))
+                } else {
+                    Some(self.tcx.optimized_mir(def_id))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn add_edge(&mut self, src: Node, dst: Node) {
+        if src != dst && self.edges.entry(src).or_default().insert(dst) {
+            self.worklist.push(src);
+        }
+    }
+
+    fn add_value(&mut self, node: Node, value: DefId) {
+        if self.values.entry(node).or_default().insert(value) {
+            self.worklist.push(node);
+        }
+    }
+
+    fn solve(&mut self) {
+        while let Some(node) = self.worklist.pop() {
+            let Some(values) = self.values.get(&node).cloned() else {
+                continue;
+            };
+            let Some(dsts) = self.edges.get(&node).cloned() else {
+                continue;
+            };
+            for dst in dsts {
+                for value in &values {
+                    self.add_value(dst, *value);
+                }
+            }
+        }
+    }
+
+    /// The abstract location of a place in the body of `def_id`.
+    fn node_of(&self, def_id: DefId, place: Place<'tcx>) -> Node {
+        let mut node = Node::Local(def_id, place.local);
+        let Some(body) = self.body(def_id) else {
+            return node;
+        };
+        for (base, elem) in place.iter_projections() {
+            if let ProjectionElem::Field(field, _) = elem {
+                let base_ty = base.ty(body, self.tcx);
+                let variant = base_ty.variant_index.unwrap_or(FIRST_VARIANT);
+                match base_ty.ty.kind() {
+                    ty::Adt(adt_def, _) if adt_def.did().is_local() => {
+                        node = Node::Field(adt_def.did(), variant, field);
+                    }
+                    ty::Closure(closure_id, _)
+                    | ty::CoroutineClosure(closure_id, _)
+                    | ty::Coroutine(closure_id, _) => {
+                        node = Node::Field(*closure_id, FIRST_VARIANT, field);
+                    }
+                    // Tuples, arrays and foreign types are smashed into their base.
+                    _ => {}
+                }
+            }
+        }
+        node
+    }
+
+    /// Let the values of the operand flow into the node.
+    fn add_operand(&mut self, def_id: DefId, operand: &Operand<'tcx>, dst: Node) {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                let src = self.node_of(def_id, *place);
+                self.add_edge(src, dst);
+            }
+            Operand::Constant(constant) => {
+                let ty = constant.const_.ty();
+                match ty.kind() {
+                    ty::FnDef(callee, args) => {
+                        let callee = self.resolve_fn(def_id, *callee, args);
+                        self.add_value(dst, callee);
+                    }
+                    ty::Closure(closure_id, _) => self.add_value(dst, *closure_id),
+                    _ if may_hold_fn_values(ty) => {
+                        let typing_env = TypingEnv::post_analysis(self.tcx, def_id);
+                        let value = match constant.const_ {
+                            Const::Val(value, _) => Some(value),
+                            _ => constant.const_.eval(self.tcx, typing_env, DUMMY_SP).ok(),
+                        };
+                        let alloc_id = match value {
+                            Some(ConstValue::Scalar(Scalar::Ptr(ptr, _))) => {
+                                Some(ptr.provenance.alloc_id())
+                            }
+                            Some(ConstValue::Indirect { alloc_id, .. })
+                            | Some(ConstValue::Slice { alloc_id, .. }) => Some(alloc_id),
+                            _ => None,
+                        };
+                        if let Some(alloc_id) = alloc_id {
+                            let mut values = FxHashSet::default();
+                            self.collect_alloc_values(alloc_id, &mut values);
+                            for value in values {
+                                self.add_value(dst, value);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Collect the functions referenced by a constant allocation, e.g., a callback table.
+    fn collect_alloc_values(&mut self, alloc_id: AllocId, values: &mut FxHashSet<DefId>) {
+        if !self.visited_allocs.insert(alloc_id) {
+            return;
+        }
+        let alloc = match self.tcx.try_get_global_alloc(alloc_id) {
+            Some(GlobalAlloc::Function { instance }) => {
+                values.insert(instance.def_id());
+                return;
+            }
+            Some(GlobalAlloc::Memory(alloc)) => alloc,
+            Some(GlobalAlloc::Static(static_id)) => {
+                match self.tcx.eval_static_initializer(static_id) {
+                    Ok(alloc) => alloc,
+                    Err(_) => return,
+                }
+            }
+            _ => return,
+        };
+        let nested: Vec<AllocId> = alloc
+            .inner()
+            .provenance()
+            .provenances()
+            .map(|prov| prov.alloc_id())
+            .collect();
+        for alloc_id in nested {
+            self.collect_alloc_values(alloc_id, values);
+        }
+    }
+
+    /// Resolve a fn item to the implementation if possible.
+    fn resolve_fn(&self, def_id: DefId, callee: DefId, args: ty::GenericArgsRef<'tcx>) -> DefId {
+        let typing_env = TypingEnv::post_analysis(self.tcx, def_id);
+        match Instance::try_resolve(self.tcx, typing_env, callee, args) {
+            Ok(Some(instance)) if matches!(instance.def, InstanceKind::Item(_)) => {
+                instance.def_id()
+            }
+            _ => callee,
+        }
+    }
+
+    /// Collect the flows of a function body.
+    fn add_body(&mut self, def_id: DefId) {
+        if !self.bodies.insert(def_id) {
+            return;
+        }
+        let Some(body) = self.body(def_id) else {
+            return;
+        };
+        for data in body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                let dst = self.node_of(def_id, *place);
+                match rvalue {
+                    Rvalue::Use(operand)
+                    | Rvalue::Repeat(operand, _)
+                    | Rvalue::Cast(_, operand, _)
+                    | Rvalue::ShallowInitBox(operand, _) => {
+                        self.add_operand(def_id, operand, dst);
+                    }
+                    Rvalue::Ref(_, _, src) | Rvalue::RawPtr(_, src) | Rvalue::CopyForDeref(src) => {
+                        let src = self.node_of(def_id, *src);
+                        self.add_edge(src, dst);
+                    }
+                    Rvalue::Aggregate(kind, operands) => match **kind {
+                        AggregateKind::Adt(adt_id, variant, _, _, active_field)
+                            if adt_id.is_local() =>
+                        {
+                            for (idx, operand) in operands.iter_enumerated() {
+                                let field = active_field.unwrap_or(idx);
+                                self.add_operand(
+                                    def_id,
+                                    operand,
+                                    Node::Field(adt_id, variant, field),
+                                );
+                            }
+                        }
+                        AggregateKind::Closure(closure_id, _)
+                        | AggregateKind::CoroutineClosure(closure_id, _)
+                        | AggregateKind::Coroutine(closure_id, _) => {
+                            if matches!(**kind, AggregateKind::Closure(..)) {
+                                self.add_value(dst, closure_id);
+                            }
+                            for (idx, operand) in operands.iter_enumerated() {
+                                let upvar = Node::Field(closure_id, FIRST_VARIANT, idx);
+                                self.add_operand(def_id, operand, upvar);
+                            }
+                        }
+                        _ => {
+                            for operand in operands.iter() {
+                                self.add_operand(def_id, operand, dst);
+                            }
+                        }
+                    },
+                    _ => {}
+                }
+            }
+
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            let ty::FnDef(callee, callee_args) = *func.ty(body, self.tcx).kind() else {
+                continue;
+            };
+            let callee = self.resolve_fn(def_id, callee, callee_args);
+            let args: Vec<Operand<'tcx>> = args.iter().map(|arg| arg.node.clone()).collect();
+            self.bind_call(def_id, callee, &args, *destination);
+        }
+    }
+
+    /// Let the values flow from the arguments to the callee and back to the destination.
+    fn bind_call(
+        &mut self,
+        def_id: DefId,
+        callee: DefId,
+        args: &[Operand<'tcx>],
+        destination: Place<'tcx>,
+    ) {
+        let dst = self.node_of(def_id, destination);
+        if let Some(callee_body) = self.body(callee) {
+            self.add_body(callee);
+            for (idx, arg) in args.iter().enumerate() {
+                let param = Local::from_usize(idx + 1);
+                if idx + 1 == args.len()
+                    && callee_body.spread_arg.is_none()
+                    && args.len() < callee_body.arg_count
+                {
+                    // The tupled arguments of `Fn*::call` are spread over the parameters of
+                    // the closure.
+                    for param in idx + 1..=callee_body.arg_count {
+                        self.add_operand(
+                            def_id,
+                            arg,
+                            Node::Local(callee, Local::from_usize(param)),
+                        );
+                    }
+                } else if idx < callee_body.arg_count {
+                    self.add_operand(def_id, arg, Node::Local(callee, param));
+                }
+            }
+            self.add_edge(Node::Local(callee, RETURN_PLACE), dst);
+            return;
+        }
+
+        // Foreign callees: the aliased arguments and return value share their values. Without
+        // any alias, e.g., for `Iterator::next` of slices, the return value may hold the values
+        // reachable from the arguments.
+        let aliases = match self.alias_analyzer.query_fn_alias(callee) {
+            Some(aliases) if aliases.len() > 0 => aliases,
+            _ => {
+                let returns_fn_values = self
+                    .body(def_id)
+                    .is_some_and(|body| may_hold_fn_values(destination.ty(body, self.tcx).ty));
+                if returns_fn_values {
+                    for arg in args {
+                        self.add_operand(def_id, arg, dst);
+                    }
+                }
+                return;
+            }
+        };
+        let operand_of = |idx: usize| -> Option<&Operand<'tcx>> {
+            if idx == 0 { None } else { args.get(idx - 1) }
+        };
+        let aliases: Vec<(usize, usize)> = aliases
+            .aliases()
+            .iter()
+            .map(|alias| (alias.left_local(), alias.right_local()))
+            .collect();
+        for (left, right) in aliases {
+            for (from, to) in [(left, right), (right, left)] {
+                let to_node = match to {
+                    0 => dst,
+                    _ => match operand_of(to).and_then(Operand::place) {
+                        Some(place) => self.node_of(def_id, place),
+                        None => continue,
+                    },
+                };
+                match from {
+                    0 => self.add_edge(dst, to_node),
+                    _ => {
+                        if let Some(operand) = operand_of(from) {
+                            self.add_operand(def_id, operand, to_node);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Bind an indirect call to one of its targets. The receiver of `Fn*::call` is the closure
+    /// environment, and the tupled arguments are spread over the parameters of the target.
+    fn bind_indirect_call(
+        &mut self,
+        def_id: DefId,
+        terminator: &'tcx rustc_middle::mir::Terminator<'tcx>,
+        target: DefId,
+    ) {
+        let TerminatorKind::Call {
+            func,
+            args,
+            destination,
+            ..
+        } = &terminator.kind
+        else {
+            return;
+        };
+        let mut args: Vec<Operand<'tcx>> = args.iter().map(|arg| arg.node.clone()).collect();
+        // Calls through fn pointers pass the arguments directly, while `Fn*::call` passes the
+        // callee itself as the first argument.
+        let through_fn_trait = matches!(func, Operand::Constant(_));
+        if through_fn_trait && !self.tcx.is_closure_like(target) && !args.is_empty() {
+            args.remove(0);
+        }
+        self.bind_call(def_id, target, &args, *destination);
+    }
+}
+
+/// Whether a constant of the type may reference functions.
+fn may_hold_fn_values(ty: Ty<'_>) -> bool {
+    ty.walk().any(|arg| {
+        arg.as_type().is_some_and(|ty| {
+            matches!(
+                ty.kind(),
+                ty::FnPtr(..) | ty::FnDef(..) | ty::Closure(..) | ty::Dynamic(..)
+            )
+        })
+    })
+}
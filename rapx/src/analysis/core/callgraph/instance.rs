@@ -0,0 +1,601 @@
+//! An instance-level call graph, whose nodes are the instantiations of functions, i.e., their
+//! `DefId`s together with the generic arguments, so that `Vec::<u8>::push` and
+//! `Vec::<String>::push` are distinct nodes.
+//!
+//! The graph is built the same way rustc's monomorphization collector does: starting from the
+//! roots (the entry function and the externally reachable functions), the bodies of the
+//! instances are walked with their generic arguments substituted, and the instances they use
+//! are added as the neighbors:
+//! - the resolved callees of calls, and the drop glue of dropped values;
+//! - the functions and closures coerced to function pointers, or referenced by constants and
+//!   statics, e.g., callback tables and the tests registered to the test harness;
+//! - the methods of the vtables created by coercions to trait objects. A call through a trait
+//!   object is an edge to the `Virtual` instance of the trait method, and the methods that may
+//!   be called are the neighbors of the functions creating the trait objects.
+//!
+//! Generic externally reachable functions are roots with their own generic parameters, so the
+//! instances reached from them may remain generic. Only the instances of local functions and
+//! the instances whose generic arguments mention local types (e.g., `Option::<Foo>::map` with a
+//! local closure) are walked, since only they may call back into the local crate; the other
+//! instances are leaves.
+
+use super::{CallGraphAnalysis, CallKind, FnCallMap, rta::unsize_tails};
+use crate::Analysis;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{
+    mir::{
+        Body, CastKind, Const, ConstValue, Operand, Rvalue, StatementKind, Terminator,
+        TerminatorKind,
+        interpret::{AllocId, GlobalAlloc, Scalar},
+    },
+    ty::{
+        self, EarlyBinder, GenericArgs, GenericArgsRef, Instance, InstanceKind, List,
+        PolyExistentialPredicate, Ty, TyCtxt, TypeFoldable, TypeVisitableExt, TypingEnv, VtblEntry,
+        adjustment::PointerCoercion,
+    },
+};
+use rustc_span::DUMMY_SP;
+use std::fmt;
+
+/// The instances of a function reached by more than this many recursive calls to new
+/// instantiations of itself, e.g., `f::<T>` calling `f::<Vec<T>>`, are not walked.
+const MAX_RECURSION_DEPTH: usize = 8;
+
+/// caller -> Vec<(callee, call site, kind)>
+pub type InstanceCallMap<'tcx> =
+    FxHashMap<Instance<'tcx>, Vec<(Instance<'tcx>, Option<&'tcx Terminator<'tcx>>, CallKind)>>;
+
+pub struct InstanceCallGraph<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub roots: Vec<Instance<'tcx>>,
+    /// All instances of the graph in the order they are discovered.
+    pub instances: Vec<Instance<'tcx>>,
+    pub instance_calls: InstanceCallMap<'tcx>,
+    /// The typing environments the generic instances are resolved in.
+    typing_envs: FxHashMap<Instance<'tcx>, TypingEnv<'tcx>>,
+}
+
+impl<'tcx> InstanceCallGraph<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            roots: Vec::new(),
+            instances: Vec::new(),
+            instance_calls: FxHashMap::default(),
+            typing_envs: FxHashMap::default(),
+        }
+    }
+
+    /// All instances of the function.
+    pub fn instances_of(&self, def_id: DefId) -> Vec<Instance<'tcx>> {
+        self.instances
+            .iter()
+            .filter(|instance| instance.def_id() == def_id)
+            .copied()
+            .collect()
+    }
+
+    pub fn get_callees(&self, caller: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        let mut callees = Vec::new();
+        for (callee, _, _) in self.instance_calls.get(&caller).into_iter().flatten() {
+            if !callees.contains(callee) {
+                callees.push(*callee);
+            }
+        }
+        callees
+    }
+
+    pub fn get_callers(&self, callee: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        self.instances
+            .iter()
+            .filter(|caller| {
+                self.instance_calls
+                    .get(caller)
+                    .into_iter()
+                    .flatten()
+                    .any(|(instance, _, _)| *instance == callee)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// The instances in post order, i.e., callees before callers except for recursive calls,
+    /// which is the order to compute bottom-up summaries per instantiation.
+    pub fn get_post_order(&self) -> Vec<Instance<'tcx>> {
+        let mut visited = FxHashSet::default();
+        let mut order = Vec::new();
+        for instance in &self.instances {
+            self.dfs_post_order(*instance, &mut visited, &mut order);
+        }
+        order
+    }
+
+    fn dfs_post_order(
+        &self,
+        instance: Instance<'tcx>,
+        visited: &mut FxHashSet<Instance<'tcx>>,
+        order: &mut Vec<Instance<'tcx>>,
+    ) {
+        if !visited.insert(instance) {
+            return;
+        }
+        for callee in self.get_callees(instance) {
+            self.dfs_post_order(callee, visited, order);
+        }
+        order.push(instance);
+    }
+
+    /// The typing environment to resolve the uses in the body of the instance.
+    pub fn typing_env(&self, instance: Instance<'tcx>) -> TypingEnv<'tcx> {
+        self.typing_envs
+            .get(&instance)
+            .copied()
+            .unwrap_or_else(TypingEnv::fully_monomorphized)
+    }
+}
+
+impl<'tcx> fmt::Display for InstanceCallGraph<'tcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Instance CallGraph:")?;
+        for caller in &self.instances {
+            let Some(callees) = self.instance_calls.get(caller) else {
+                continue;
+            };
+            if callees.is_empty() {
+                continue;
+            }
+            writeln!(f, "  {} calls:", caller)?;
+            for (callee, _, kind) in callees {
+                if *kind == CallKind::Static {
+                    writeln!(f, "    -> {}", callee)?;
+                } else {
+                    writeln!(f, "    -> {} ({})", callee, kind.as_str())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct InstanceCallGraphAnalyzer<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub graph: InstanceCallGraph<'tcx>,
+    worklist: Vec<Instance<'tcx>>,
+    /// The depths of polymorphic recursion of the instances, if nonzero.
+    recursion_depths: FxHashMap<Instance<'tcx>, usize>,
+}
+
+impl<'tcx> Analysis for InstanceCallGraphAnalyzer<'tcx> {
+    fn name(&self) -> &'static str {
+        "Instance-level call graph analysis algorithm."
+    }
+
+    fn run(&mut self) {
+        self.start();
+    }
+
+    fn reset(&mut self) {
+        self.graph = InstanceCallGraph::new(self.tcx);
+        self.worklist.clear();
+        self.recursion_depths.clear();
+    }
+}
+
+impl<'tcx> CallGraphAnalysis<'tcx> for InstanceCallGraphAnalyzer<'tcx> {
+    fn get_fn_calls(&self) -> FnCallMap {
+        self.fn_calls(|_| true)
+    }
+
+    fn get_indirect_fn_calls(&self) -> FnCallMap {
+        self.fn_calls(|kind| kind == CallKind::Indirect)
+    }
+
+    fn get_instances(&self, def_id: DefId) -> Vec<Instance<'tcx>> {
+        self.graph.instances_of(def_id)
+    }
+
+    fn get_instance_callees(&self, caller: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        self.graph.get_callees(caller)
+    }
+
+    fn get_instance_callers(&self, callee: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        self.graph.get_callers(callee)
+    }
+}
+
+impl<'tcx> InstanceCallGraphAnalyzer<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            graph: InstanceCallGraph::new(tcx),
+            worklist: Vec::new(),
+            recursion_depths: FxHashMap::default(),
+        }
+    }
+
+    /// The calls of the given kinds between the functions of the instances.
+    fn fn_calls(&self, filter: impl Fn(CallKind) -> bool) -> FnCallMap {
+        let mut fn_calls = FnCallMap::new();
+        for caller in &self.graph.instances {
+            for (callee, _, kind) in self.graph.instance_calls.get(caller).into_iter().flatten() {
+                if !filter(*kind) {
+                    continue;
+                }
+                let callees = fn_calls.entry(caller.def_id()).or_default();
+                if !callees.contains(&callee.def_id()) {
+                    callees.push(callee.def_id());
+                }
+            }
+        }
+        fn_calls
+    }
+
+    pub fn start(&mut self) {
+        for (root, typing_env) in self.roots() {
+            self.graph.roots.push(root);
+            self.add_instance(root, typing_env);
+        }
+        while let Some(instance) = self.worklist.pop() {
+            self.visit_instance(instance);
+        }
+        rap_debug!(
+            "[Callgraph] {} instances are reachable from {} roots.",
+            self.graph.instances.len(),
+            self.graph.roots.len()
+        );
+    }
+
+    /// The entry function and the externally reachable functions. The generic ones are
+    /// instantiated with their own generic parameters.
+    fn roots(&self) -> Vec<(Instance<'tcx>, TypingEnv<'tcx>)> {
+        let tcx = self.tcx;
+        let mut def_ids: Vec<DefId> = Vec::new();
+        if let Some((def_id, _)) = tcx.entry_fn(()) {
+            def_ids.push(def_id);
+        }
+        let mut reachable: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .filter(|local_def_id| {
+                matches!(tcx.def_kind(**local_def_id), DefKind::Fn | DefKind::AssocFn)
+                    && tcx.effective_visibilities(()).is_reachable(**local_def_id)
+            })
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| !def_ids.contains(def_id))
+            .collect();
+        reachable.sort_by_cached_key(|def_id| tcx.def_path_str(*def_id));
+        def_ids.extend(reachable);
+
+        def_ids
+            .into_iter()
+            .map(|def_id| {
+                if tcx.generics_of(def_id).requires_monomorphization(tcx) {
+                    let args = tcx
+                        .erase_and_anonymize_regions(GenericArgs::identity_for_item(tcx, def_id));
+                    (
+                        Instance::new_raw(def_id, args),
+                        TypingEnv::post_analysis(tcx, def_id),
+                    )
+                } else {
+                    (
+                        Instance::mono(tcx, def_id),
+                        TypingEnv::fully_monomorphized(),
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Add the instance to the graph. A generic instance inherits the typing environment of
+    /// the instance it is reached from.
+    fn add_instance(&mut self, instance: Instance<'tcx>, typing_env: TypingEnv<'tcx>) {
+        if self.graph.instance_calls.contains_key(&instance) {
+            return;
+        }
+        self.graph.instance_calls.insert(instance, Vec::new());
+        self.graph.instances.push(instance);
+        if instance.args.has_param() {
+            self.graph.typing_envs.insert(instance, typing_env);
+        }
+        self.worklist.push(instance);
+    }
+
+    fn add_call(
+        &mut self,
+        caller: Instance<'tcx>,
+        callee: Instance<'tcx>,
+        terminator: Option<&'tcx Terminator<'tcx>>,
+        kind: CallKind,
+    ) {
+        if callee.def_id() == caller.def_id()
+            && callee != caller
+            && !self.graph.instance_calls.contains_key(&callee)
+        {
+            let depth = self.recursion_depths.get(&caller).copied().unwrap_or(0) + 1;
+            self.recursion_depths.insert(callee, depth);
+        }
+        let typing_env = self.graph.typing_env(caller);
+        self.add_instance(callee, typing_env);
+        self.graph
+            .instance_calls
+            .entry(caller)
+            .or_default()
+            .push((callee, terminator, kind));
+    }
+
+    /// Whether the generic arguments mention a local type, closure or function.
+    fn mentions_local(&self, args: GenericArgsRef<'tcx>) -> bool {
+        args.iter()
+            .flat_map(|arg| arg.walk())
+            .filter_map(|arg| arg.as_type())
+            .any(|ty| match ty.kind() {
+                ty::Adt(adt_def, _) => adt_def.did().is_local(),
+                ty::FnDef(def_id, _)
+                | ty::Closure(def_id, _)
+                | ty::CoroutineClosure(def_id, _)
+                | ty::Coroutine(def_id, _) => def_id.is_local(),
+                ty::Dynamic(predicates, ..) => predicates
+                    .principal_def_id()
+                    .is_some_and(|def_id| def_id.is_local()),
+                _ => false,
+            })
+    }
+
+    /// The body of the instance to walk, if any.
+    fn body(&self, instance: Instance<'tcx>) -> Option<&'tcx Body<'tcx>> {
+        let tcx = self.tcx;
+        if !instance.def_id().is_local() && !self.mentions_local(instance.args) {
+            return None;
+        }
+        if self
+            .recursion_depths
+            .get(&instance)
+            .is_some_and(|depth| *depth > MAX_RECURSION_DEPTH)
+        {
+            rap_debug!(
+                "[Callgraph] Skip the deeply recursive instance {}.",
+                instance
+            );
+            return None;
+        }
+        match instance.def {
+            InstanceKind::Item(def_id) => {
+                let is_fn = matches!(
+                    tcx.def_kind(def_id),
+                    DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+                );
+                (is_fn && tcx.is_mir_available(def_id)).then(|| tcx.instance_mir(instance.def))
+            }
+            InstanceKind::DropGlue(_, Some(_))
+            | InstanceKind::CloneShim(..)
+            | InstanceKind::ClosureOnceShim { .. }
+            | InstanceKind::FnPtrShim(..)
+            | InstanceKind::ReifyShim(..)
+            | InstanceKind::VTableShim(..) => Some(tcx.instance_mir(instance.def)),
+            _ => None,
+        }
+    }
+
+    /// Substitute the generic arguments of the instance into a value of its body.
+    fn monomorphize<T>(&self, instance: Instance<'tcx>, value: T) -> Option<T>
+    where
+        T: TypeFoldable<TyCtxt<'tcx>>,
+    {
+        instance
+            .try_instantiate_mir_and_normalize_erasing_regions(
+                self.tcx,
+                self.graph.typing_env(instance),
+                EarlyBinder::bind(value),
+            )
+            .ok()
+    }
+
+    fn visit_instance(&mut self, instance: Instance<'tcx>) {
+        let Some(body) = self.body(instance) else {
+            return;
+        };
+        for data in body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                if let StatementKind::Assign(box (_, rvalue)) = &stmt.kind {
+                    self.visit_rvalue(instance, body, rvalue);
+                }
+            }
+            let terminator = data.terminator();
+            match &terminator.kind {
+                TerminatorKind::Call { func, args, .. } => {
+                    args.iter()
+                        .for_each(|arg| self.visit_operand(instance, &arg.node));
+                    self.visit_call(instance, func.ty(body, self.tcx), terminator);
+                }
+                // Drops on the unwinding paths duplicate those on the normal paths.
+                TerminatorKind::Drop { place, .. } if !data.is_cleanup => {
+                    self.visit_drop(instance, place.ty(body, self.tcx).ty, terminator);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_call(
+        &mut self,
+        instance: Instance<'tcx>,
+        func_ty: Ty<'tcx>,
+        terminator: &'tcx Terminator<'tcx>,
+    ) {
+        let Some(func_ty) = self.monomorphize(instance, func_ty) else {
+            return;
+        };
+        let ty::FnDef(def_id, args) = *func_ty.kind() else {
+            // Calls through function pointers and closure values are bound to the functions
+            // coerced to function pointers and the closures created.
+            return;
+        };
+        let typing_env = self.graph.typing_env(instance);
+        let callee = match Instance::try_resolve(self.tcx, typing_env, def_id, args) {
+            Ok(Some(callee)) => callee,
+            // The callee depends on the generic parameters of a generic root.
+            Ok(None) => Instance::new_raw(def_id, args),
+            Err(_) => return,
+        };
+        let kind = match callee.def {
+            InstanceKind::Item(_) | InstanceKind::Intrinsic(_) => CallKind::Static,
+            InstanceKind::Virtual(..) => CallKind::Virtual,
+            InstanceKind::DropGlue(..)
+            | InstanceKind::AsyncDropGlueCtorShim(..)
+            | InstanceKind::AsyncDropGlue(..) => CallKind::DropGlue,
+            _ => CallKind::Shim,
+        };
+        self.add_call(instance, callee, Some(terminator), kind);
+    }
+
+    fn visit_drop(
+        &mut self,
+        instance: Instance<'tcx>,
+        ty: Ty<'tcx>,
+        terminator: &'tcx Terminator<'tcx>,
+    ) {
+        let Some(ty) = self.monomorphize(instance, ty) else {
+            return;
+        };
+        // The drop glue of generic parameters is unknown until they are instantiated.
+        if ty.has_param() || !ty.needs_drop(self.tcx, TypingEnv::fully_monomorphized()) {
+            return;
+        }
+        let callee = Instance::resolve_drop_in_place(self.tcx, ty);
+        if !matches!(callee.def, InstanceKind::DropGlue(_, None)) {
+            self.add_call(instance, callee, Some(terminator), CallKind::DropGlue);
+        }
+    }
+
+    fn visit_rvalue(&mut self, instance: Instance<'tcx>, body: &Body<'tcx>, rvalue: &Rvalue<'tcx>) {
+        let tcx = self.tcx;
+        match rvalue {
+            Rvalue::Cast(CastKind::PointerCoercion(coercion, _), operand, target_ty) => {
+                self.visit_operand(instance, operand);
+                let Some(source_ty) = self.monomorphize(instance, operand.ty(body, tcx)) else {
+                    return;
+                };
+                let typing_env = self.graph.typing_env(instance);
+                match (coercion, source_ty.kind()) {
+                    (PointerCoercion::ReifyFnPointer(_), ty::FnDef(def_id, args)) => {
+                        if let Some(callee) =
+                            Instance::resolve_for_fn_ptr(tcx, typing_env, *def_id, args)
+                        {
+                            self.add_call(instance, callee, None, CallKind::Indirect);
+                        }
+                    }
+                    (PointerCoercion::ClosureFnPointer(_), ty::Closure(def_id, args)) => {
+                        let callee =
+                            Instance::resolve_closure(tcx, *def_id, args, ty::ClosureKind::FnOnce);
+                        self.add_call(instance, callee, None, CallKind::Indirect);
+                    }
+                    (PointerCoercion::Unsize, _) => {
+                        if let Some(target_ty) = self.monomorphize(instance, *target_ty)
+                            && let Some((self_ty, dyn_ty)) = unsize_tails(source_ty, target_ty)
+                            && let ty::Dynamic(predicates, ..) = dyn_ty.kind()
+                        {
+                            self.visit_vtable(instance, self_ty, predicates);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Rvalue::Use(operand) | Rvalue::Repeat(operand, _) | Rvalue::Cast(_, operand, _) => {
+                self.visit_operand(instance, operand)
+            }
+            Rvalue::Aggregate(_, operands) => operands
+                .iter()
+                .for_each(|operand| self.visit_operand(instance, operand)),
+            _ => {}
+        }
+    }
+
+    /// Add the methods of the vtable of the type for the trait object.
+    fn visit_vtable(
+        &mut self,
+        instance: Instance<'tcx>,
+        self_ty: Ty<'tcx>,
+        predicates: &'tcx List<PolyExistentialPredicate<'tcx>>,
+    ) {
+        if self_ty.is_trait() || self_ty.has_param() || predicates.has_param() {
+            return;
+        }
+        let Some(principal) = predicates.principal() else {
+            return;
+        };
+        let trait_ref = self
+            .tcx
+            .instantiate_bound_regions_with_erased(principal.with_self_ty(self.tcx, self_ty));
+        for entry in self.tcx.vtable_entries(trait_ref) {
+            if let VtblEntry::Method(callee) = entry {
+                self.add_call(instance, *callee, None, CallKind::Virtual);
+            }
+        }
+    }
+
+    /// Add the functions referenced by a constant, e.g., a callback table.
+    fn visit_operand(&mut self, instance: Instance<'tcx>, operand: &Operand<'tcx>) {
+        let Operand::Constant(constant) = operand else {
+            return;
+        };
+        if matches!(constant.const_.ty().kind(), ty::FnDef(..) | ty::Closure(..)) {
+            return;
+        }
+        let Some(const_) = self.monomorphize(instance, constant.const_) else {
+            return;
+        };
+        let value = match const_ {
+            Const::Val(value, _) => Some(value),
+            _ => const_
+                .eval(self.tcx, self.graph.typing_env(instance), DUMMY_SP)
+                .ok(),
+        };
+        let alloc_id = match value {
+            Some(ConstValue::Scalar(Scalar::Ptr(ptr, _))) => ptr.provenance.alloc_id(),
+            Some(ConstValue::Indirect { alloc_id, .. })
+            | Some(ConstValue::Slice { alloc_id, .. }) => alloc_id,
+            _ => return,
+        };
+        let mut visited = FxHashSet::default();
+        self.visit_alloc(instance, alloc_id, &mut visited);
+    }
+
+    fn visit_alloc(
+        &mut self,
+        instance: Instance<'tcx>,
+        alloc_id: AllocId,
+        visited: &mut FxHashSet<AllocId>,
+    ) {
+        if !visited.insert(alloc_id) {
+            return;
+        }
+        let alloc = match self.tcx.try_get_global_alloc(alloc_id) {
+            Some(GlobalAlloc::Function { instance: callee }) => {
+                self.add_call(instance, callee, None, CallKind::Indirect);
+                return;
+            }
+            Some(GlobalAlloc::VTable(self_ty, predicates)) => {
+                self.visit_vtable(instance, self_ty, predicates);
+                return;
+            }
+            Some(GlobalAlloc::Memory(alloc)) => alloc,
+            Some(GlobalAlloc::Static(static_id)) => {
+                match self.tcx.eval_static_initializer(static_id) {
+                    Ok(alloc) => alloc,
+                    Err(_) => return,
+                }
+            }
+            _ => return,
+        };
+        let nested: Vec<AllocId> = alloc
+            .inner()
+            .provenance()
+            .provenances()
+            .map(|prov| prov.alloc_id())
+            .collect();
+        for alloc_id in nested {
+            self.visit_alloc(instance, alloc_id, visited);
+        }
+    }
+}
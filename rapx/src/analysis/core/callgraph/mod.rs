@@ -1,15 +1,84 @@
 pub mod default;
+pub mod export;
+pub mod indirect;
+pub mod instance;
+pub mod rta;
 pub mod visitor;
 
-use crate::Analysis;
+use crate::{Analysis, utils::log::rap_error_and_exit};
 use rustc_hir::def_id::DefId;
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{Instance, TyCtxt};
 use std::{collections::HashMap, fmt};
 
 pub type FnCallMap = HashMap<DefId, Vec<DefId>>; // caller_id -> Vec<(callee_id)>
 
+/// The kind of a call edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// A call to a statically known callee.
+    Static,
+    /// A call through a trait object, bound to each implementation of the trait method.
+    Virtual,
+    /// A call through a function pointer, a closure value or a `dyn Fn*` object, bound to the
+    /// functions and closures that may flow to the callee operand.
+    Indirect,
+    /// A call to the `Drop` impl of a dropped value, or to `drop_in_place`.
+    DropGlue,
+    /// A call to a compiler-generated shim, e.g., a `FnOnce` shim of a closure or a reify shim.
+    Shim,
+}
+
+impl CallKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallKind::Static => "static",
+            CallKind::Virtual => "virtual",
+            CallKind::Indirect => "indirect",
+            CallKind::DropGlue => "drop_glue",
+            CallKind::Shim => "shim",
+        }
+    }
+}
+
+/// How the targets of virtual calls are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VirtualCallResolution {
+    /// Class hierarchy analysis: every implementor of the trait method.
+    #[default]
+    Cha,
+    /// Rapid type analysis: the implementors whose self types are instantiated in reachable code.
+    Rta,
+    /// Variable type analysis: the implementors whose self types are coerced to trait objects of
+    /// the trait in reachable code.
+    Vta,
+}
+
+impl VirtualCallResolution {
+    pub fn from_arg(value: &str) -> Self {
+        match value {
+            "cha" => VirtualCallResolution::Cha,
+            "rta" => VirtualCallResolution::Rta,
+            "vta" => VirtualCallResolution::Vta,
+            _ => rap_error_and_exit(format!(
+                "Unknown virtual call resolution `{value}`; expected one of: cha, rta, vta."
+            )),
+        }
+    }
+}
+
+/// The configuration of call graph construction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Config {
+    pub virtual_calls: VirtualCallResolution,
+    /// Whether the drops of values with `Drop` impls are added as drop glue edges. They are only
+    /// wanted in dumps and queries, since they have no call sites for other analyses.
+    pub drop_glue: bool,
+}
+
 pub struct FnCallDisplay<'a, 'tcx> {
     pub fn_calls: &'a FnCallMap,
+    /// The indirect calls among `fn_calls`, which are marked in the output.
+    pub indirect_calls: &'a FnCallMap,
     pub tcx: TyCtxt<'tcx>,
 }
 
@@ -19,9 +88,25 @@ impl<'a, 'tcx> fmt::Display for FnCallDisplay<'a, 'tcx> {
         for (caller, callees) in self.fn_calls {
             let caller_name = self.tcx.def_path_str(*caller);
             writeln!(f, "  {} calls:", caller_name)?;
+            // The indirect calls of a caller follow its direct calls.
+            let mut direct: HashMap<DefId, usize> = HashMap::new();
+            for callee in callees {
+                *direct.entry(*callee).or_default() += 1;
+            }
+            for callee in self.indirect_calls.get(caller).into_iter().flatten() {
+                if let Some(count) = direct.get_mut(callee) {
+                    *count = count.saturating_sub(1);
+                }
+            }
             for callee in callees {
                 let callee_name = self.tcx.def_path_str(*callee);
-                writeln!(f, "    -> {}", callee_name)?;
+                match direct.get_mut(callee) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        writeln!(f, "    -> {}", callee_name)?;
+                    }
+                    _ => writeln!(f, "    -> {} (indirect)", callee_name)?,
+                }
             }
         }
         Ok(())
@@ -29,7 +114,23 @@ impl<'a, 'tcx> fmt::Display for FnCallDisplay<'a, 'tcx> {
 }
 
 /// This trait provides features related to call graph extraction and analysis.
-pub trait CallGraphAnalysis: Analysis {
+pub trait CallGraphAnalysis<'tcx>: Analysis {
     /// Return the call graph.
     fn get_fn_calls(&self) -> FnCallMap;
+    /// Return the indirect calls of the call graph, i.e., the calls through function pointers,
+    /// closure values and `dyn Fn*` objects resolved to their possible targets.
+    fn get_indirect_fn_calls(&self) -> FnCallMap;
+    /// Return the instantiations of the function reached in the crate, i.e., the function with
+    /// the generic arguments it is used with. Call graphs keyed by `DefId` have none.
+    fn get_instances(&self, _def_id: DefId) -> Vec<Instance<'tcx>> {
+        Vec::new()
+    }
+    /// Return the instances called by the instance.
+    fn get_instance_callees(&self, _caller: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        Vec::new()
+    }
+    /// Return the instances calling the instance.
+    fn get_instance_callers(&self, _callee: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,294 @@
+//! Pruning of virtual call targets by rapid type analysis (RTA) and variable type analysis (VTA).
+//!
+//! By default, a virtual call is bound to every implementor of the trait method (class hierarchy
+//! analysis). RTA keeps only the implementors whose self types are instantiated in reachable
+//! code, and VTA further requires the self types to be coerced to a trait object of the trait,
+//! or of one of its subtraits, in reachable code. The reachable code is computed together with
+//! the pruning, starting from the entry function, the externally reachable functions, and the
+//! functions without any caller (e.g., tests and callbacks passed to foreign code).
+//!
+//! The types instantiated by foreign code are approximated by the types returned from foreign
+//! calls. A trait object of a foreign trait created by foreign code, or any trait object passed
+//! into an externally reachable function, may hold any implementor, so the calls through it are
+//! not pruned.
+
+use super::{CallKind, VirtualCallResolution, default::CallGraph};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{
+    mir::{Body, CastKind, Operand, Rvalue, StatementKind, TerminatorKind},
+    ty::{
+        self, Ty, TyCtxt,
+        adjustment::PointerCoercion,
+        fast_reject::{SimplifiedType, TreatParams, simplify_type},
+    },
+};
+use rustc_type_ir::elaborate::supertrait_def_ids;
+
+/// The types instantiated and coerced to trait objects in the reachable code.
+#[derive(Default)]
+struct TypeFacts {
+    instantiated: FxHashSet<SimplifiedType>,
+    /// (trait, self type) of the coercions to trait objects.
+    coerced: FxHashSet<(DefId, SimplifiedType)>,
+    /// Traits whose objects may hold any implementor.
+    opaque: FxHashSet<DefId>,
+}
+
+struct Pruner<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    resolution: VirtualCallResolution,
+    facts: TypeFacts,
+}
+
+impl<'tcx> Pruner<'tcx> {
+    fn body(&self, def_id: DefId) -> Option<&'tcx Body<'tcx>> {
+        if !def_id.is_local() || !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        match self.tcx.def_kind(def_id) {
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure => {
+                Some(self.tcx.optimized_mir(def_id))
+            }
+            DefKind::Const
+            | DefKind::Static { .. }
+            | DefKind::AssocConst
+            | DefKind::InlineConst
+            | DefKind::AnonConst => Some(self.tcx.mir_for_ctfe(def_id)),
+            _ => None,
+        }
+    }
+
+    fn instantiate(&mut self, ty: Ty<'tcx>) {
+        if let Some(simplified) = simplify_type(self.tcx, ty, TreatParams::AsRigid) {
+            self.facts.instantiated.insert(simplified);
+        }
+    }
+
+    /// Record the types that may be created by foreign code, including the trait objects of
+    /// foreign traits. Foreign code cannot create trait objects of local traits by itself.
+    fn instantiate_opaque(&mut self, ty: Ty<'tcx>) {
+        for ty in ty.walk().filter_map(|arg| arg.as_type()) {
+            self.instantiate(ty);
+            if let ty::Dynamic(predicates, ..) = ty.kind()
+                && let Some(trait_def_id) = predicates.principal_def_id()
+                && !trait_def_id.is_local()
+            {
+                self.facts.opaque.insert(trait_def_id);
+            }
+        }
+    }
+
+    fn coerce(&mut self, src: Ty<'tcx>, dst: Ty<'tcx>) {
+        let Some((self_ty, dyn_ty)) = unsize_tails(src, dst) else {
+            return;
+        };
+        let ty::Dynamic(predicates, ..) = dyn_ty.kind() else {
+            return;
+        };
+        let Some(trait_def_id) = predicates.principal_def_id() else {
+            return;
+        };
+        self.instantiate(self_ty);
+        if let Some(simplified) = simplify_type(self.tcx, self_ty, TreatParams::AsRigid) {
+            self.facts.coerced.insert((trait_def_id, simplified));
+        }
+    }
+
+    fn visit_operand(&mut self, operand: &Operand<'tcx>) {
+        // Unit structs and closures without captures are constants, and may be promoted
+        // behind references.
+        if let Operand::Constant(constant) = operand {
+            for ty in constant.const_.ty().walk().filter_map(|arg| arg.as_type()) {
+                self.instantiate(ty);
+            }
+        }
+    }
+
+    fn visit_body(&mut self, body: &Body<'tcx>) {
+        for data in body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                match rvalue {
+                    Rvalue::Aggregate(_, operands) => {
+                        self.instantiate(place.ty(body, self.tcx).ty);
+                        operands
+                            .iter()
+                            .for_each(|operand| self.visit_operand(operand));
+                    }
+                    Rvalue::Cast(
+                        CastKind::PointerCoercion(PointerCoercion::Unsize, _),
+                        operand,
+                        target_ty,
+                    ) => {
+                        self.visit_operand(operand);
+                        self.coerce(operand.ty(body, self.tcx), *target_ty);
+                    }
+                    Rvalue::Use(operand)
+                    | Rvalue::Repeat(operand, _)
+                    | Rvalue::Cast(_, operand, _) => self.visit_operand(operand),
+                    _ => {}
+                }
+            }
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            args.iter().for_each(|arg| self.visit_operand(&arg.node));
+            if let ty::FnDef(callee, _) = func.ty(body, self.tcx).kind()
+                && self.body(*callee).is_none()
+            {
+                self.instantiate_opaque(destination.ty(body, self.tcx).ty);
+            }
+        }
+    }
+
+    /// Whether the edge is kept, i.e., it is not a virtual call to an implementor whose self
+    /// type never reaches the call.
+    fn keeps(&self, callee: DefId, kind: CallKind) -> bool {
+        if kind != CallKind::Virtual {
+            return true;
+        }
+        let Some(impl_id) = self.tcx.impl_of_assoc(callee) else {
+            // The trait method itself.
+            return true;
+        };
+        let Some(trait_ref) = self.tcx.impl_opt_trait_ref(impl_id) else {
+            return true;
+        };
+        let trait_def_id = trait_ref.skip_binder().def_id;
+        let self_ty = self.tcx.type_of(impl_id).instantiate_identity();
+        // Blanket impls cannot be pruned.
+        let Some(self_ty) = simplify_type(self.tcx, self_ty, TreatParams::InstantiateWithInfer)
+        else {
+            return true;
+        };
+        let is_subtrait =
+            |sub: DefId| supertrait_def_ids(self.tcx, sub).any(|def_id| def_id == trait_def_id);
+        if self.facts.opaque.iter().any(|opaque| is_subtrait(*opaque)) {
+            return true;
+        }
+        let coerced = self
+            .facts
+            .coerced
+            .iter()
+            .any(|(sub, ty)| *ty == self_ty && is_subtrait(*sub));
+        match self.resolution {
+            VirtualCallResolution::Cha => true,
+            VirtualCallResolution::Rta => coerced || self.facts.instantiated.contains(&self_ty),
+            VirtualCallResolution::Vta => coerced,
+        }
+    }
+}
+
+/// The source and target tails of an unsizing coercion to a trait object, e.g., `Dog` and
+/// `dyn Animal` for `Box<Dog>` to `Box<dyn Animal>`.
+pub(super) fn unsize_tails<'tcx>(src: Ty<'tcx>, dst: Ty<'tcx>) -> Option<(Ty<'tcx>, Ty<'tcx>)> {
+    match (src.kind(), dst.kind()) {
+        (_, ty::Dynamic(..)) => Some((src, dst)),
+        (ty::Ref(_, src, _) | ty::RawPtr(src, _), ty::Ref(_, dst, _) | ty::RawPtr(dst, _)) => {
+            unsize_tails(*src, *dst)
+        }
+        (ty::Adt(src_def, src_args), ty::Adt(dst_def, dst_args)) if src_def == dst_def => src_args
+            .types()
+            .zip(dst_args.types())
+            .find(|(src, dst)| src != dst)
+            .and_then(|(src, dst)| unsize_tails(src, dst)),
+        _ => None,
+    }
+}
+
+impl<'tcx> CallGraph<'tcx> {
+    /// Remove the virtual call edges to implementors that cannot be the receiver, according to
+    /// the resolution mode. Nothing is removed with class hierarchy analysis.
+    pub fn prune_virtual_calls(&mut self, resolution: VirtualCallResolution) {
+        if resolution == VirtualCallResolution::Cha {
+            return;
+        }
+        let tcx = self.tcx;
+        let mut pruner = Pruner {
+            tcx,
+            resolution,
+            facts: TypeFacts::default(),
+        };
+
+        let callers_map = self.get_callers_map();
+        let entry = tcx.entry_fn(()).map(|(def_id, _)| def_id);
+        let mut worklist: Vec<DefId> = Vec::new();
+        for def_id in &self.functions {
+            let Some(local_def_id) = def_id.as_local() else {
+                continue;
+            };
+            let exported = tcx.effective_visibilities(()).is_reachable(local_def_id);
+            if exported && matches!(tcx.def_kind(*def_id), DefKind::Fn | DefKind::AssocFn) {
+                // Trait objects passed by the users of the crate may hold any implementor.
+                let inputs = tcx
+                    .fn_sig(*def_id)
+                    .instantiate_identity()
+                    .skip_binder()
+                    .inputs();
+                for ty in inputs {
+                    for ty in ty.walk().filter_map(|arg| arg.as_type()) {
+                        if let ty::Dynamic(predicates, ..) = ty.kind()
+                            && let Some(trait_def_id) = predicates.principal_def_id()
+                        {
+                            pruner.facts.opaque.insert(trait_def_id);
+                        }
+                    }
+                }
+            }
+            if Some(*def_id) == entry || exported || !callers_map.contains_key(def_id) {
+                worklist.push(*def_id);
+            }
+        }
+        worklist.sort_by_cached_key(|def_id| tcx.def_path_str(*def_id));
+
+        // Grow the reachable code until no more virtual call targets are kept.
+        let mut reachable: FxHashSet<DefId> = FxHashSet::default();
+        loop {
+            while let Some(def_id) = worklist.pop() {
+                if !reachable.insert(def_id) {
+                    continue;
+                }
+                if let Some(body) = pruner.body(def_id) {
+                    pruner.visit_body(body);
+                }
+                for (callee, _, kind) in self.fn_calls.get(&def_id).into_iter().flatten() {
+                    if *kind != CallKind::Virtual && !reachable.contains(callee) {
+                        worklist.push(*callee);
+                    }
+                }
+            }
+            for def_id in &reachable {
+                for (callee, _, kind) in self.fn_calls.get(def_id).into_iter().flatten() {
+                    if !reachable.contains(callee) && pruner.keeps(*callee, *kind) {
+                        worklist.push(*callee);
+                    }
+                }
+            }
+            if worklist.is_empty() {
+                break;
+            }
+        }
+
+        let mut pruned = 0;
+        for callees in self.fn_calls.values_mut() {
+            let before = callees.len();
+            callees.retain(|(callee, _, kind)| pruner.keeps(*callee, *kind));
+            pruned += before - callees.len();
+        }
+        rap_debug!(
+            "[Callgraph] {:?} prunes {} virtual call edges, {} functions are reachable.",
+            resolution,
+            pruned,
+            reachable.len()
+        );
+    }
+}
@@ -1,7 +1,10 @@
-use super::default::CallGraph;
+use super::{
+    CallKind,
+    default::{CallGraph, IndirectCall},
+};
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
-use rustc_middle::ty::{FnDef, Instance, InstanceKind, TyCtxt, TypingEnv};
+use rustc_middle::ty::{self, FnDef, Instance, InstanceKind, Ty, TyCtxt, TypingEnv};
 use std::collections::HashSet;
 
 pub struct CallGraphVisitor<'b, 'tcx> {
@@ -9,6 +12,7 @@ pub struct CallGraphVisitor<'b, 'tcx> {
     def_id: DefId,
     body: &'tcx mir::Body<'tcx>,
     call_graph_info: &'b mut CallGraph<'tcx>,
+    drop_glue: bool,
 }
 
 impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
@@ -23,29 +27,82 @@ impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
             def_id: def_id,
             body: body,
             call_graph_info: call_graph_info,
+            drop_glue: false,
         }
     }
 
-    fn add_fn_call(&mut self, callee_def_id: DefId, terminator: &'tcx mir::Terminator<'tcx>) {
+    /// Add drop glue edges for the drops of values with `Drop` impls.
+    pub fn with_drop_glue(mut self, drop_glue: bool) -> Self {
+        self.drop_glue = drop_glue;
+        self
+    }
+
+    fn add_fn_call(
+        &mut self,
+        callee_def_id: DefId,
+        terminator: &'tcx mir::Terminator<'tcx>,
+        kind: CallKind,
+    ) {
         self.call_graph_info.register_fn(callee_def_id);
         self.call_graph_info.add_funciton_call(
             self.def_id.clone(),
             callee_def_id,
             Some(terminator),
+            kind,
         );
     }
 
+    /// Record a call whose callee is a value, i.e., a function pointer or a closure, to be
+    /// resolved by the function values flowing to it.
+    fn add_indirect_call(
+        &mut self,
+        callee: mir::Place<'tcx>,
+        terminator: &'tcx mir::Terminator<'tcx>,
+    ) {
+        self.call_graph_info.record_indirect_call(IndirectCall {
+            caller: self.def_id,
+            callee,
+            terminator,
+        });
+    }
+
+    /// Whether the callee is a method of `Fn`, `FnMut` or `FnOnce` called on a value that is not
+    /// a known closure or fn item, e.g., a `dyn Fn` object, a function pointer or a generic
+    /// parameter.
+    fn is_indirect_fn_trait_call(
+        &self,
+        callee_def_id: DefId,
+        callee_substs: ty::GenericArgsRef<'tcx>,
+    ) -> bool {
+        let is_fn_trait = self
+            .tcx
+            .trait_of_assoc(callee_def_id)
+            .and_then(|trait_def_id| self.tcx.fn_trait_kind_from_def_id(trait_def_id))
+            .is_some();
+        let Some(mut self_ty) = callee_substs.first().and_then(|arg| arg.as_type()) else {
+            return false;
+        };
+        loop {
+            self_ty = match self_ty.kind() {
+                ty::Ref(_, inner, _) => *inner,
+                ty::Adt(adt_def, args) if adt_def.is_box() => args.type_at(0),
+                _ => break,
+            };
+        }
+        is_fn_trait && !matches!(self_ty.kind(), ty::Closure(..) | ty::FnDef(..))
+    }
+
     fn handle_fn_call(
         &mut self,
         callee_def_id: DefId,
-        is_virtual: bool,
+        kind: CallKind,
         terminator: &'tcx mir::Terminator<'tcx>,
     ) {
-        if is_virtual {
+        if kind == CallKind::Virtual {
             // Handle dynamic dispatch for trait objects
             self.handle_virtual_call(callee_def_id, terminator);
         } else {
-            self.add_fn_call(callee_def_id, terminator);
+            self.add_fn_call(callee_def_id, terminator, kind);
         }
     }
 
@@ -57,7 +114,7 @@ impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
         // Step 1: Add an edge from caller to the virtual function (stub);
         // If the DefId exists, we assume that stub has been analyzed.
         let visited = !self.call_graph_info.register_fn(stub_def_id);
-        self.add_fn_call(stub_def_id, terminator);
+        self.add_fn_call(stub_def_id, terminator, CallKind::Virtual);
 
         // If this function has already been analyzed, return;
         if visited {
@@ -87,7 +144,16 @@ impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
 
         // Step 3: For each implementor, add an edge from the stub to it.
         for candidate_def_id in candidates {
-            self.add_fn_call(candidate_def_id, terminator);
+            self.add_fn_call(candidate_def_id, terminator, CallKind::Virtual);
+        }
+    }
+
+    /// Add a drop glue edge to the `Drop` impl of the dropped type, if any.
+    fn visit_drop(&mut self, ty: Ty<'tcx>, terminator: &'tcx mir::Terminator<'tcx>) {
+        if let ty::Adt(adt_def, _) = ty.kind()
+            && let Some(destructor) = adt_def.destructor(self.tcx)
+        {
+            self.add_fn_call(destructor.did, terminator, CallKind::DropGlue);
         }
     }
 
@@ -95,55 +161,74 @@ impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
         self.call_graph_info.register_fn(self.def_id);
         for (_, data) in self.body.basic_blocks.iter().enumerate() {
             let terminator = data.terminator();
+            // Drops on the unwinding paths duplicate those on the normal paths.
+            if data.is_cleanup && matches!(terminator.kind, mir::TerminatorKind::Drop { .. }) {
+                continue;
+            }
             self.visit_terminator(&terminator);
         }
     }
 
     fn visit_terminator(&mut self, terminator: &'tcx mir::Terminator<'tcx>) {
-        if let mir::TerminatorKind::Call { func, .. } = &terminator.kind {
+        if let mir::TerminatorKind::Drop { place, .. } = &terminator.kind
+            && self.drop_glue
+        {
+            self.visit_drop(place.ty(self.body, self.tcx).ty, terminator);
+        }
+        if let mir::TerminatorKind::Call { func, args, .. } = &terminator.kind {
+            if let mir::Operand::Copy(callee) | mir::Operand::Move(callee) = func {
+                self.add_indirect_call(*callee, terminator);
+            }
             if let mir::Operand::Constant(constant) = func {
                 if let FnDef(callee_def_id, callee_substs) = constant.const_.ty().kind() {
+                    if let Some(receiver) = args.first().and_then(|arg| arg.node.place())
+                        && self.is_indirect_fn_trait_call(*callee_def_id, callee_substs)
+                    {
+                        self.add_indirect_call(receiver, terminator);
+                    }
                     let ty_env = TypingEnv::post_analysis(self.tcx, self.def_id);
                     if let Ok(Some(instance)) =
                         Instance::try_resolve(self.tcx, ty_env, *callee_def_id, callee_substs)
                     {
-                        let mut is_virtual = false;
                         // Try to analysis the specific type of callee.
-                        let instance_def_id = match instance.def {
-                            InstanceKind::Item(def_id) => Some(def_id),
-                            InstanceKind::Intrinsic(def_id) => Some(def_id),
-                            InstanceKind::VTableShim(def_id) => Some(def_id),
-                            InstanceKind::ReifyShim(def_id, _) => Some(def_id),
-                            InstanceKind::FnPtrShim(def_id, _) => Some(def_id),
-                            InstanceKind::Virtual(def_id, _) => {
-                                is_virtual = true;
-                                Some(def_id)
+                        let callee = match instance.def {
+                            InstanceKind::Item(def_id) => Some((def_id, CallKind::Static)),
+                            InstanceKind::Intrinsic(def_id) => Some((def_id, CallKind::Static)),
+                            InstanceKind::VTableShim(def_id) => Some((def_id, CallKind::Shim)),
+                            InstanceKind::ReifyShim(def_id, _) => Some((def_id, CallKind::Shim)),
+                            InstanceKind::FnPtrShim(def_id, _) => Some((def_id, CallKind::Shim)),
+                            InstanceKind::Virtual(def_id, _) => Some((def_id, CallKind::Virtual)),
+                            InstanceKind::ClosureOnceShim { call_once, .. } => {
+                                Some((call_once, CallKind::Shim))
                             }
-                            InstanceKind::ClosureOnceShim { call_once, .. } => Some(call_once),
                             InstanceKind::ConstructCoroutineInClosureShim {
                                 coroutine_closure_def_id,
                                 ..
-                            } => Some(coroutine_closure_def_id),
-                            InstanceKind::ThreadLocalShim(def_id) => Some(def_id),
-                            InstanceKind::DropGlue(def_id, _) => Some(def_id),
-                            InstanceKind::FnPtrAddrShim(def_id, _) => Some(def_id),
-                            InstanceKind::AsyncDropGlueCtorShim(def_id, _) => Some(def_id),
+                            } => Some((coroutine_closure_def_id, CallKind::Shim)),
+                            InstanceKind::ThreadLocalShim(def_id) => Some((def_id, CallKind::Shim)),
+                            InstanceKind::DropGlue(def_id, _) => Some((def_id, CallKind::DropGlue)),
+                            InstanceKind::FnPtrAddrShim(def_id, _) => {
+                                Some((def_id, CallKind::Shim))
+                            }
+                            InstanceKind::AsyncDropGlueCtorShim(def_id, _) => {
+                                Some((def_id, CallKind::DropGlue))
+                            }
                             InstanceKind::CloneShim(def_id, _) => {
                                 if !self.tcx.is_closure_like(def_id) {
                                     // Not a closure
-                                    Some(def_id)
+                                    Some((def_id, CallKind::Shim))
                                 } else {
                                     None
                                 }
                             }
                             _ => todo!(),
                         };
-                        if let Some(instance_def_id) = instance_def_id {
-                            self.handle_fn_call(instance_def_id, is_virtual, terminator);
+                        if let Some((instance_def_id, kind)) = callee {
+                            self.handle_fn_call(instance_def_id, kind, terminator);
                         }
                     } else {
                         // Although failing to get specific type, callee is still useful.
-                        self.handle_fn_call(*callee_def_id, false, terminator);
+                        self.handle_fn_call(*callee_def_id, CallKind::Static, terminator);
                     }
                 }
             }
@@ -1,21 +1,30 @@
 use super::graph::*;
-use crate::analysis::core::dataflow::*;
+use crate::analysis::{
+    core::dataflow::*,
+    utils::cache::{FnKeys, SummaryCache},
+};
 
 pub struct DataFlowAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub graphs: HashMap<DefId, Graph>,
+    /// Arg2Ret summaries restored from the cache, whose graphs are not built.
+    pub arg2ret: Arg2RetMap,
+    /// The summaries of the functions whose graphs are built.
+    pub summaries: FnSummaryMap,
     pub debug: bool,
 }
 
 impl<'tcx> DataFlowAnalysis for DataFlowAnalyzer<'tcx> {
     fn get_fn_dataflow(&self, def_id: DefId) -> Option<DataFlowGraph> {
-        self.graphs.get(&def_id).cloned().map(Into::into)
+        let mut dataflow: DataFlowGraph = self.graphs.get(&def_id).cloned()?.into();
+        dataflow.summary = self.get_fn_summary(def_id);
+        Some(dataflow)
     }
 
     fn get_all_dataflow(&self) -> DataFlowGraphMap {
         self.graphs
-            .iter()
-            .map(|(&def_id, graph)| (def_id, graph.clone().into()))
+            .keys()
+            .filter_map(|&def_id| Some((def_id, self.get_fn_dataflow(def_id)?)))
             .collect()
     }
 
@@ -30,18 +39,29 @@ impl<'tcx> DataFlowAnalysis for DataFlowAnalyzer<'tcx> {
     }
 
     fn get_fn_arg2ret(&self, def_id: DefId) -> Arg2Ret {
+        if let Some(deps) = self.arg2ret.get(&def_id) {
+            return deps.clone();
+        }
         let graph = self.graphs.get(&def_id).unwrap();
         graph.param_return_deps()
     }
 
     fn get_all_arg2ret(&self) -> Arg2RetMap {
-        let mut result = HashMap::new();
+        let mut result = self.arg2ret.clone();
         for (def_id, graph) in &self.graphs {
             let deps = graph.param_return_deps();
             result.insert(*def_id, deps);
         }
         result
     }
+
+    fn get_fn_summary(&self, def_id: DefId) -> FnSummary {
+        self.summaries.get(&def_id).cloned().unwrap_or_default()
+    }
+
+    fn get_all_summaries(&self) -> FnSummaryMap {
+        self.summaries.clone()
+    }
 }
 
 impl<'tcx> Analysis for DataFlowAnalyzer<'tcx> {
@@ -58,6 +78,8 @@ impl<'tcx> Analysis for DataFlowAnalyzer<'tcx> {
 
     fn reset(&mut self) {
         self.graphs.clear();
+        self.arg2ret.clear();
+        self.summaries.clear();
     }
 }
 
@@ -66,6 +88,8 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
         Self {
             tcx: tcx,
             graphs: HashMap::new(),
+            arg2ret: HashMap::new(),
+            summaries: HashMap::new(),
             debug,
         }
     }
@@ -87,6 +111,85 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
                 }
             }
         }
+        self.build_summaries();
+    }
+
+    /// Compute the summaries of the functions whose graphs are built. The summaries start empty
+    /// and grow with the summaries of the callees until a fixpoint is reached.
+    pub fn build_summaries(&mut self) {
+        for def_id in self.graphs.keys() {
+            self.summaries.entry(*def_id).or_default();
+        }
+        loop {
+            let mut changed = false;
+            for (def_id, graph) in &self.graphs {
+                let summary = graph.summarize(&self.summaries);
+                if self.summaries.get(def_id) != Some(&summary) {
+                    self.summaries.insert(*def_id, summary);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Compute the Arg2Ret summaries of all functions. Unlike `build_graphs`, the summaries of
+    /// unchanged functions are restored from the cache and their graphs are not built.
+    pub fn build_arg2ret(&mut self) {
+        let tcx = self.tcx;
+        let keys = FnKeys::get(tcx);
+        let mut cache: SummaryCache<Vec<bool>> = SummaryCache::load(tcx, "arg2ret", "");
+        let mut fn_keys = HashMap::new();
+        for local_def_id in tcx.iter_local_def_id() {
+            let def_kind = tcx.def_kind(local_def_id);
+            if !matches!(def_kind, DefKind::Fn | DefKind::AssocFn)
+                || tcx.hir_maybe_body_owned_by(local_def_id).is_none()
+            {
+                continue;
+            }
+            let def_id = local_def_id.to_def_id();
+            // Closures are summarized along with their enclosing function, so the key of the
+            // function also covers the closures inside.
+            let nested: Vec<DefId> = tcx
+                .nested_bodies_within(local_def_id)
+                .iter()
+                .map(|nested| nested.to_def_id())
+                .filter(|nested| tcx.is_closure_like(*nested))
+                .collect();
+            let key = nested.iter().fold(keys.own_key(def_id), |key, nested| {
+                Some(key?.combine(keys.own_key(*nested)?))
+            });
+            if let Some(key) = key
+                && let Some(deps) = cache.get(tcx, def_id, key)
+            {
+                self.arg2ret
+                    .insert(def_id, IndexVec::from_raw(deps.clone()));
+                for nested in nested {
+                    if let Some(key) = keys.own_key(nested)
+                        && let Some(deps) = cache.get(tcx, nested, key)
+                    {
+                        self.arg2ret
+                            .insert(nested, IndexVec::from_raw(deps.clone()));
+                    }
+                }
+                continue;
+            }
+            self.build_graph(def_id);
+            if let Some(key) = key {
+                fn_keys.insert(def_id, key);
+            }
+        }
+        if cache.is_enabled() {
+            for (def_id, graph) in &self.graphs {
+                if let Some(key) = fn_keys.get(def_id).copied().or(keys.own_key(*def_id)) {
+                    let deps = graph.param_return_deps().raw;
+                    cache.insert(tcx, *def_id, key, deps);
+                }
+            }
+            cache.save();
+        }
     }
 
     pub fn build_graph(&mut self, def_id: DefId) {
@@ -123,6 +226,11 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
             .output()
             .expect("Failed to create directory.");
 
+        // The dot files are still written if Graphviz is not installed.
+        let has_dot = Command::new("dot").arg("-V").output().is_ok();
+        if !has_dot {
+            rap_warn!("Graphviz dot is not found; the dataflow graphs are not rendered.");
+        }
         for (def_id, graph) in self.graphs.iter() {
             let name = self.tcx.def_path_str(def_id);
             let dot_file_name = format!("DataflowGraph/{}.dot", &name);
@@ -132,10 +240,12 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
             file.write_all(dot.as_bytes())
                 .expect("Unable to write data.");
 
-            Command::new("dot")
-                .args(&["-Tpng", &dot_file_name, "-o", &png_file_name])
-                .output()
-                .expect("Failed to execute Graphviz dot command.");
+            if has_dot {
+                Command::new("dot")
+                    .args(["-Tpng", &dot_file_name, "-o", &png_file_name])
+                    .output()
+                    .expect("Failed to execute Graphviz dot command.");
+            }
         }
     }
 }
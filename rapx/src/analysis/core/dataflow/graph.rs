@@ -1,14 +1,14 @@
 use std::cell::Cell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use rustc_hir::def_id::DefId;
 use rustc_index::IndexVec;
 use rustc_middle::{
     mir::{
-        AggregateKind, BorrowKind, Const, Local, Operand, Place, PlaceElem, Rvalue, Statement,
-        StatementKind, Terminator, TerminatorKind,
+        AggregateKind, BorrowKind, Const, Local, Operand, Place, PlaceElem, RETURN_PLACE, Rvalue,
+        Statement, StatementKind, Terminator, TerminatorKind,
     },
-    ty::TyKind,
+    ty::{self, TyKind},
 };
 use rustc_span::{DUMMY_SP, Span};
 
@@ -35,8 +35,11 @@ pub struct Graph {
     pub edges: GraphEdges,
     pub n_locals: usize,
     pub closures: HashSet<DefId>,
+    /// The nodes of the constants pointing to statics and the locals holding thread locals.
+    pub statics: HashMap<Local, DefId>,
 }
 
+// The summary is left empty, since it depends on the summaries of the callees.
 impl From<Graph> for DataFlowGraph {
     fn from(graph: Graph) -> Self {
         let param_ret_deps = graph.param_return_deps();
@@ -44,10 +47,17 @@ impl From<Graph> for DataFlowGraph {
             nodes: graph.nodes,
             edges: graph.edges,
             param_ret_deps: param_ret_deps,
+            summary: FnSummary::new(),
         }
     }
 }
 
+// The place written through a node, see `Graph::written_place`.
+enum WrittenPlace {
+    Summary(SummaryPlace),
+    Local(Local),
+}
+
 impl Graph {
     pub fn new(def_id: DefId, span: Span, argc: usize, n_locals: usize) -> Self {
         Self {
@@ -58,6 +68,7 @@ impl Graph {
             edges: GraphEdges::new(),
             n_locals,
             closures: HashSet::new(),
+            statics: HashMap::new(),
         }
     }
 
@@ -104,7 +115,13 @@ impl Graph {
                     Const::Unevaluated(_, ty) => ty.to_string(),
                     Const::Ty(ty, _) => ty.to_string(),
                 };
-                self.add_const_edge(src_desc, src_ty, dst, EdgeOp::Const);
+                let edge_idx = self.add_const_edge(src_desc, src_ty, dst, EdgeOp::Const);
+                if let Some(def_id) = ty::tls::with(|tcx| boxed_const_op.check_static_ptr(tcx)) {
+                    // Unlike other constants, statics are sources of the summary edges.
+                    let src = self.edges[edge_idx].src;
+                    self.nodes[src].out_edges.push(edge_idx);
+                    self.statics.insert(src, def_id);
+                }
             }
         }
     }
@@ -224,8 +241,9 @@ impl Graph {
                 Rvalue::NullaryOp(_) => {
                     self.nodes[dst].ops[seq] = NodeOp::NullaryOp;
                 }
-                Rvalue::ThreadLocalRef(_) => {
-                    //todo!()
+                Rvalue::ThreadLocalRef(def_id) => {
+                    self.statics.insert(dst, *def_id);
+                    self.nodes[dst].ops[seq] = NodeOp::ThreadLocalRef;
                 }
                 Rvalue::Discriminant(place) => {
                     let src = self.parse_place(place);
@@ -277,7 +295,7 @@ impl Graph {
                         }
                     }
                 }
-                Operand::Move(_) => {
+                Operand::Move(_) | Operand::Copy(_) => {
                     self.add_operand(func, dst); //the func is a place
                     for op in args.iter() {
                         //rustc version related
@@ -285,10 +303,6 @@ impl Graph {
                     }
                     self.nodes[dst].ops[seq] = NodeOp::CallOperand;
                 }
-                _ => {
-                    println!("{:?}", func);
-                    todo!();
-                }
             }
             self.nodes[dst].span = terminator.source_info.span;
             self.nodes[dst].seq = seq + 1;
@@ -601,6 +615,279 @@ impl Graph {
     pub fn is_marker(&self, idx: Local) -> bool {
         idx >= Local::from_usize(self.n_locals)
     }
+
+    /// The index of the argument passed by the edge into a call node.
+    pub fn call_arg_index(&self, edge_idx: EdgeIdx) -> usize {
+        let edge = &self.edges[edge_idx];
+        self.nodes[edge.dst]
+            .in_edges
+            .iter()
+            .filter(|idx| self.edges[**idx].seq == edge.seq)
+            .position(|idx| *idx == edge_idx)
+            .unwrap_or_default()
+    }
+
+    /// The node of the argument passed to the call of the given sequence number.
+    pub fn call_arg(&self, call: Local, seq: usize, arg: usize) -> Option<Local> {
+        self.nodes[call]
+            .in_edges
+            .iter()
+            .filter(|idx| self.edges[**idx].seq == seq)
+            .nth(arg)
+            .map(|idx| self.edges[*idx].src)
+    }
+
+    // The first in-edge of a marker projects the place of the marker from its source.
+    fn is_projection_edge(&self, edge_idx: EdgeIdx) -> bool {
+        let dst = self.edges[edge_idx].dst;
+        self.is_marker(dst) && self.nodes[dst].in_edges.first() == Some(&edge_idx)
+    }
+
+    // Whether the edge assigns a value to the place of a marker, e.g., `(*_2).0 = move _1`.
+    // The in-edges of a marker start with its projection, and the index of `Index`.
+    fn is_write_edge(&self, edge_idx: EdgeIdx) -> bool {
+        let dst = self.edges[edge_idx].dst;
+        if !self.is_marker(dst) {
+            return false;
+        }
+        let in_edges = &self.nodes[dst].in_edges;
+        let projections = match in_edges.first() {
+            Some(first) if matches!(self.edges[*first].op, EdgeOp::Index) => 2,
+            _ => 1,
+        };
+        in_edges[projections.min(in_edges.len())..].contains(&edge_idx)
+    }
+
+    // Resolve the place written through `(*node).fields` if `deref` is true, or `node.fields`
+    // otherwise. The node is a local or a marker, whose projections are walked up to its base.
+    // Temporary pointers are followed to the places they borrow.
+    fn written_place(&self, node: Local, mut deref: bool, mut fields: Vec<usize>) -> WrittenPlace {
+        let mut idx = node;
+        let mut seen = HashSet::new();
+        while seen.insert(idx) {
+            if let Some(def_id) = self.statics.get(&idx) {
+                return WrittenPlace::Summary(SummaryPlace::Static(*def_id));
+            }
+            if self.is_marker(idx) {
+                let Some(edge_idx) = self.nodes[idx].in_edges.first() else {
+                    break;
+                };
+                let edge = &self.edges[*edge_idx];
+                match edge.op {
+                    EdgeOp::Deref => deref = true,
+                    EdgeOp::Field(field) => fields.insert(0, field),
+                    EdgeOp::Downcast(_) | EdgeOp::Index | EdgeOp::ConstIndex | EdgeOp::SubSlice => {
+                    }
+                    _ => break,
+                }
+                idx = edge.src;
+            } else if idx.as_usize() <= self.argc {
+                if idx == RETURN_PLACE || deref {
+                    return WrittenPlace::Summary(SummaryPlace::Local {
+                        local: idx,
+                        deref,
+                        fields,
+                    });
+                }
+                return WrittenPlace::Local(idx);
+            } else if deref && let [edge_idx] = self.nodes[idx].in_edges.as_slice() {
+                let edge = &self.edges[*edge_idx];
+                match edge.op {
+                    // The reference cancels the dereference, e.g., `_3 = &mut (*_1)`.
+                    EdgeOp::Mut | EdgeOp::Immut => deref = false,
+                    EdgeOp::Move | EdgeOp::Copy | EdgeOp::Nop | EdgeOp::Const => {}
+                    _ => return WrittenPlace::Local(idx),
+                }
+                idx = edge.src;
+            } else {
+                return WrittenPlace::Local(idx);
+            }
+        }
+        WrittenPlace::Local(node)
+    }
+
+    /// The local written through the reference held by the node, e.g., `s` for `_3` with
+    /// `_2 = &mut s; _3 = &mut (*_2)`. It is the parameter if the reference comes from one.
+    pub fn referent_local(&self, node: Local) -> Option<Local> {
+        match self.written_place(node, true, Vec::new()) {
+            WrittenPlace::Local(local)
+            | WrittenPlace::Summary(SummaryPlace::Local { local, .. }) => Some(local),
+            WrittenPlace::Summary(SummaryPlace::Static(_)) => None,
+        }
+    }
+
+    // The places projected from the local through fields and dereferences, e.g., `(*_1).0`.
+    fn projection_tree(&self, local: Local) -> Vec<(Local, bool, Vec<usize>)> {
+        let mut tree = vec![(local, false, Vec::new())];
+        let mut idx = 0;
+        while idx < tree.len() {
+            let (node, deref, fields) = tree[idx].clone();
+            for edge_idx in &self.nodes[node].out_edges {
+                if !self.is_projection_edge(*edge_idx) {
+                    continue;
+                }
+                let edge = &self.edges[*edge_idx];
+                let mut deref = deref;
+                let mut fields = fields.clone();
+                match edge.op {
+                    EdgeOp::Deref => deref = true,
+                    EdgeOp::Field(field) => fields.push(field),
+                    _ => {}
+                }
+                tree.push((edge.dst, deref, fields));
+            }
+            idx += 1;
+        }
+        tree
+    }
+
+    /// Compute the summary edges of the function from its parameters and the statics it reads,
+    /// given the summaries of its callees. A call without the summary of its callee is assumed
+    /// to pass its arguments to its return value and to the places borrowed mutably by its other
+    /// arguments.
+    pub fn summarize(&self, summaries: &FnSummaryMap) -> FnSummary {
+        let mut summary = FnSummary::new();
+        let mut add = |src: &SummaryPlace, dsts: Vec<SummaryPlace>| {
+            for dst in dsts {
+                let edge = SummaryEdge {
+                    src: src.clone(),
+                    dst,
+                };
+                if edge.src != edge.dst && !summary.contains(&edge) {
+                    summary.push(edge);
+                }
+            }
+        };
+        for arg in 1..=self.argc {
+            let tree = self.projection_tree(Local::from_usize(arg));
+            let nodes: HashSet<Local> = tree.iter().map(|(node, ..)| *node).collect();
+            for (node, deref, fields) in tree {
+                let src = SummaryPlace::Local {
+                    local: Local::from_usize(arg),
+                    deref,
+                    fields,
+                };
+                add(&src, self.flow_targets(node, &nodes, summaries));
+            }
+        }
+        let mut statics: Vec<(Local, DefId)> = self
+            .statics
+            .iter()
+            .map(|(node, def_id)| (*node, *def_id))
+            .collect();
+        statics.sort_by_key(|(node, _)| *node);
+        for (node, def_id) in statics {
+            let targets = self.flow_targets(node, &HashSet::new(), summaries);
+            add(&SummaryPlace::Static(def_id), targets);
+        }
+        summary
+    }
+
+    // The places visible to the callers that the data of the source node flows into. The
+    // projections from the nodes in `tree` are not followed, since they are separate sources.
+    fn flow_targets(
+        &self,
+        source: Local,
+        tree: &HashSet<Local>,
+        summaries: &FnSummaryMap,
+    ) -> Vec<SummaryPlace> {
+        let mut targets = Vec::new();
+        let mut seen = HashSet::from([source]);
+        let mut worklist = VecDeque::from([source]);
+        while let Some(node) = worklist.pop_front() {
+            if node == RETURN_PLACE {
+                let ret = SummaryPlace::Local {
+                    local: RETURN_PLACE,
+                    deref: false,
+                    fields: Vec::new(),
+                };
+                if !targets.contains(&ret) {
+                    targets.push(ret);
+                }
+            }
+            for edge_idx in &self.nodes[node].out_edges {
+                if tree.contains(&node) && self.is_projection_edge(*edge_idx) {
+                    continue;
+                }
+                let edge = &self.edges[*edge_idx];
+                let dst = edge.dst;
+                let mut next = Vec::new();
+                let mut writes = Vec::new();
+                let op = self.nodes[dst].ops.get(edge.seq);
+                let callee_summary = match op {
+                    Some(NodeOp::Call(callee)) => summaries.get(callee),
+                    _ => None,
+                };
+                match (op, callee_summary) {
+                    (_, Some(summary)) => {
+                        let arg = self.call_arg_index(*edge_idx) + 1;
+                        for summary_edge in summary {
+                            if !matches!(summary_edge.src, SummaryPlace::Local { local, .. } if local.as_usize() == arg)
+                            {
+                                continue;
+                            }
+                            match &summary_edge.dst {
+                                SummaryPlace::Local { local, .. } if *local == RETURN_PLACE => {
+                                    next.push(dst)
+                                }
+                                SummaryPlace::Local {
+                                    local,
+                                    deref: true,
+                                    fields,
+                                } => {
+                                    if let Some(arg_node) =
+                                        self.call_arg(dst, edge.seq, local.as_usize() - 1)
+                                    {
+                                        writes.push((arg_node, true, fields.clone()));
+                                    }
+                                }
+                                SummaryPlace::Local { .. } => {}
+                                SummaryPlace::Static(_) => {
+                                    if !targets.contains(&summary_edge.dst) {
+                                        targets.push(summary_edge.dst.clone());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    (Some(NodeOp::Call(_)) | Some(NodeOp::CallOperand), None) => {
+                        next.push(dst);
+                        for idx in &self.nodes[dst].in_edges {
+                            let arg_edge = &self.edges[*idx];
+                            if arg_edge.seq != edge.seq || *idx == *edge_idx {
+                                continue;
+                            }
+                            let borrowed = self.nodes[arg_edge.src]
+                                .in_edges
+                                .iter()
+                                .any(|idx| matches!(self.edges[*idx].op, EdgeOp::Mut));
+                            if borrowed {
+                                writes.push((arg_edge.src, true, Vec::new()));
+                            }
+                        }
+                    }
+                    _ if self.is_write_edge(*edge_idx) => writes.push((dst, false, Vec::new())),
+                    _ => next.push(dst),
+                }
+                for (node, deref, fields) in writes {
+                    match self.written_place(node, deref, fields) {
+                        WrittenPlace::Summary(place) => {
+                            if !targets.contains(&place) {
+                                targets.push(place);
+                            }
+                        }
+                        WrittenPlace::Local(local) => next.push(local),
+                    }
+                }
+                for node in next {
+                    if seen.insert(node) {
+                        worklist.push_back(node);
+                    }
+                }
+            }
+        }
+        targets
+    }
 }
 
 impl Graph {
@@ -16,22 +16,49 @@ use rustc_hir::{def::DefKind, def_id::DefId};
 use rustc_index::IndexVec;
 use rustc_middle::{
     mir::{Body, Local},
-    ty::TyCtxt,
+    ty::{self, TyCtxt},
 };
 use rustc_span::Span;
 
 pub type Arg2Ret = IndexVec<Local, bool>;
 pub type Arg2RetMap = HashMap<DefId, IndexVec<Local, bool>>;
+pub type FnSummary = Vec<SummaryEdge>;
+pub type FnSummaryMap = HashMap<DefId, FnSummary>;
 #[derive(Clone)]
 pub struct DataFlowGraph {
     pub nodes: GraphNodes,
     pub edges: GraphEdges,
     pub param_ret_deps: Arg2Ret,
+    pub summary: FnSummary,
 }
 pub type DataFlowGraphMap = HashMap<DefId, DataFlowGraph>;
 
+/// A place of a function visible to its callers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SummaryPlace {
+    /// The return value `_0` or a parameter, with the indices of the fields projected from it.
+    /// `deref` tells whether the place is behind the reference or pointer held by the local,
+    /// e.g., `(*_1).0` for `self.0` in a `&mut self` method.
+    Local {
+        local: Local,
+        deref: bool,
+        fields: Vec<usize>,
+    },
+    /// A static item, including thread locals.
+    Static(DefId),
+}
+
+/// A dataflow from `src` to `dst` caused by calling the function, e.g., from a parameter to the
+/// place borrowed mutably by another parameter.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SummaryEdge {
+    pub src: SummaryPlace,
+    pub dst: SummaryPlace,
+}
+
 pub struct Arg2RetWrapper(pub Arg2Ret);
 pub struct Arg2RetMapWrapper(pub Arg2RetMap);
+pub struct FnSummaryWrapper(pub FnSummary);
 pub struct DataFlowGraphWrapper(pub DataFlowGraph);
 pub struct DataFlowGraphMapWrapper(pub HashMap<DefId, DataFlowGraph>);
 
@@ -76,6 +103,57 @@ pub trait DataFlowAnalysis: Analysis {
 
     /// The function returns the dataflow between the arguments and return value for all functions
     fn get_all_arg2ret(&self) -> Arg2RetMap;
+
+    /// The function returns the summary edges of the given function, i.e., the dataflows among
+    /// its parameters, return value, their fields, and statics, which go through its callees.
+    fn get_fn_summary(&self, def_id: DefId) -> FnSummary;
+
+    /// The function returns the summary edges for all functions.
+    fn get_all_summaries(&self) -> FnSummaryMap;
+}
+
+impl Display for SummaryPlace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SummaryPlace::Local {
+                local,
+                deref,
+                fields,
+            } => {
+                if *deref {
+                    write!(f, "(*{:?})", local)?;
+                } else {
+                    write!(f, "{:?}", local)?;
+                }
+                for field in fields {
+                    write!(f, ".{}", field)?;
+                }
+                Ok(())
+            }
+            SummaryPlace::Static(def_id) => {
+                write!(
+                    f,
+                    "static {}",
+                    ty::tls::with(|tcx| tcx.def_path_str(*def_id))
+                )
+            }
+        }
+    }
+}
+
+impl Display for SummaryEdge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ---> {}", self.src, self.dst)
+    }
+}
+
+impl Display for FnSummaryWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for edge in &self.0 {
+            writeln!(f, "{}", edge)?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for Arg2RetWrapper {
@@ -127,6 +205,13 @@ impl Display for DataFlowGraphWrapper {
                 }
             }
         }
+        if !graph.summary.is_empty() {
+            write!(
+                f,
+                "Summary edges: \n{}",
+                FnSummaryWrapper(graph.summary.clone())
+            )?;
+        }
 
         for (node_idx, node) in graph.nodes.iter_enumerated() {
             let node_adj: Vec<Local> = node
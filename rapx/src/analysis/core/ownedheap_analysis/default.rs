@@ -13,6 +13,10 @@ use rustc_span::def_id::DefId;
 use std::{collections::HashMap, ops::ControlFlow};
 
 use super::*;
+use crate::analysis::utils::{
+    cache::{SummaryCache, adt_keys},
+    sidecar::dep_summaries,
+};
 
 pub struct OwnedHeapAnalyzer<'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -169,13 +173,41 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
             }
         }
 
-        let dids: Vec<DefId> = self.adt_recorder.iter().map(|did| *did).collect();
+        // ADTs whose definitions are unchanged since the last run are restored from the cache.
+        let all_dids: Vec<DefId> = self.adt_recorder.iter().copied().collect();
+        let keys = adt_keys(tcx, &all_dids);
+        let mut cache: SummaryCache<Vec<(OwnedHeap, Vec<bool>)>> =
+            SummaryCache::load(tcx, "ownedheap", "");
+        let deps = &dep_summaries(tcx).ownedheap;
+        let mut dids = Vec::new();
+        for did in all_dids {
+            // ADTs of dependencies may have been analyzed by `-summarize-deps`.
+            if let Some(heap) = deps.get(&did) {
+                self.adt_heap.insert(did, heap.clone());
+                continue;
+            }
+            match cache.get(tcx, did, keys[&did]) {
+                Some(heap) => {
+                    self.adt_heap.insert(did, heap.clone());
+                }
+                None => dids.push(did),
+            }
+        }
 
         start_channel(|did| self.extract_raw_generic(did), &dids);
         start_channel(|did| self.extract_raw_generic_prop(did), &dids);
         start_channel(|did| self.extract_phantom_unit(did), &dids);
         start_channel(|did| self.extract_heap_prop(did), &dids);
 
+        if cache.is_enabled() {
+            for did in dids {
+                if let Some(heap) = self.adt_heap.get(&did) {
+                    cache.insert(tcx, did, keys[&did], heap.clone());
+                }
+            }
+            cache.save();
+        }
+
         show_heap(self);
     }
 
@@ -2,6 +2,7 @@ pub mod default;
 
 use rustc_middle::ty::{Ty, TyKind};
 use rustc_span::def_id::DefId;
+use serde::{Deserialize, Serialize};
 
 use std::{
     collections::{HashMap, HashSet},
@@ -12,7 +13,7 @@ use std::{
 use crate::{Analysis, utils::source::get_fn_name_byid};
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum OwnedHeap {
     False = 0,
     True = 1,
@@ -12,6 +12,7 @@ use crate::{
                 domain::{
                     ConstraintGraph::ConstraintGraph,
                     domain::{ConstConvert, IntervalArithmetic, VarNodes},
+                    relational::{Relation, RelationAnalysis},
                 },
             },
 
@@ -19,15 +20,19 @@ use crate::{
             ssa_transform::*,
         },
         graphs::scc::Scc,
+        utils::cache::{FnKeys, SummaryCache},
     },
     rap_debug, rap_info,
 };
 
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::{fingerprint::Fingerprint, fx::FxHashMap};
 use rustc_hir::{def::DefKind, def_id::DefId};
 use rustc_middle::{
-    mir::{Body, Place},
-    ty::TyCtxt,
+    mir::{
+        BasicBlock, Body, Local, Location, Place, PlaceElem, ProjectionElem, START_BLOCK,
+        visit::{PlaceContext, Visitor},
+    },
+    ty::{List, TyCtxt},
 };
 use std::{
     cell::RefCell,
@@ -39,7 +44,129 @@ use std::{
     rc::Rc,
 };
 
-use super::{PathConstraint, PathConstraintMap, RAResult, RAResultMap, RAVecResultMap};
+use super::{
+    PathConstraint, PathConstraintMap, RAResult, RAResultMap, RAVecResultMap, RangeType,
+    RelationMap,
+};
+use serde::{Deserialize, Serialize};
+
+/// A projection element of a cached place. The types of fields are left out, since the
+/// projections are resolved against the places of the original MIR.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum CachedElem {
+    Deref,
+    Field(u32),
+    Index(u32),
+    ConstantIndex {
+        offset: u64,
+        min_length: u64,
+        from_end: bool,
+    },
+    Subslice {
+        from: u64,
+        to: u64,
+        from_end: bool,
+    },
+    Downcast(u32),
+}
+
+impl CachedElem {
+    /// Encode a projection, or return None if it has an element that cannot be cached.
+    fn encode(projection: &[PlaceElem<'_>]) -> Option<Vec<CachedElem>> {
+        projection
+            .iter()
+            .map(|elem| {
+                Some(match *elem {
+                    ProjectionElem::Deref => CachedElem::Deref,
+                    ProjectionElem::Field(field, _) => CachedElem::Field(field.as_u32()),
+                    ProjectionElem::Index(local) => CachedElem::Index(local.as_u32()),
+                    ProjectionElem::ConstantIndex {
+                        offset,
+                        min_length,
+                        from_end,
+                    } => CachedElem::ConstantIndex {
+                        offset,
+                        min_length,
+                        from_end,
+                    },
+                    ProjectionElem::Subslice { from, to, from_end } => {
+                        CachedElem::Subslice { from, to, from_end }
+                    }
+                    ProjectionElem::Downcast(_, variant) => CachedElem::Downcast(variant.as_u32()),
+                    _ => return None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A place of the SSA form in the cache. SSA renames the local of an original place and keeps
+/// its projection, so the place is restored from the projection of the original place.
+#[derive(Clone, Serialize, Deserialize)]
+struct CachedPlace {
+    local: u32,
+    /// The local of the original place.
+    origin: u32,
+    projection: Vec<CachedElem>,
+}
+
+/// A range result in the cache.
+type CachedRanges<T> = Vec<(CachedPlace, RangeType, T, T)>;
+
+/// The projections of the places in the original MIR of a function, by their locals and
+/// encoded projections.
+struct OriginalPlaces<'tcx> {
+    projections: HashMap<(Local, Vec<CachedElem>), &'tcx List<PlaceElem<'tcx>>>,
+}
+
+impl<'tcx> OriginalPlaces<'tcx> {
+    fn new(body: &'tcx Body<'tcx>) -> Self {
+        let mut places = OriginalPlaces {
+            projections: HashMap::new(),
+        };
+        places.visit_body(body);
+        places
+    }
+
+    /// The original place and the SSA place of a cached place.
+    fn resolve(&self, place: &CachedPlace) -> Option<(Place<'tcx>, Place<'tcx>)> {
+        let origin = Local::from_u32(place.origin);
+        let projection = if place.projection.is_empty() {
+            List::empty()
+        } else {
+            *self.projections.get(&(origin, place.projection.clone()))?
+        };
+        Some((
+            Place {
+                local: origin,
+                projection,
+            },
+            Place {
+                local: Local::from_u32(place.local),
+                projection,
+            },
+        ))
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for OriginalPlaces<'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if let Some(elems) = CachedElem::encode(place.projection) {
+            self.projections
+                .insert((place.local, elems), place.projection);
+        }
+        self.super_place(place, context, location);
+    }
+}
+
+/// The cached range summary of a function, i.e., its `final_vars` and `final_vars_vec`, and the
+/// symbolic intervals reported when its constraint graph is built.
+#[derive(Clone, Serialize, Deserialize)]
+struct RangeSummary<T> {
+    ranges: CachedRanges<T>,
+    ranges_percall: Vec<CachedRanges<T>>,
+    symbexprs: Vec<String>,
+}
 
 /// RangeAnalyzer performs MIR-based interprocedural range analysis.
 /// It builds SSA/ESSA, constraint graphs, propagates intervals,
@@ -55,6 +182,8 @@ pub struct RangeAnalyzer<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
 
     // Mapping from original places to SSA-renamed places
     pub ssa_places_mapping: FxHashMap<DefId, HashMap<Place<'tcx>, HashSet<Place<'tcx>>>>,
+    // Mapping from original places to the places defined by phi and ESSA statements
+    pub ssa_locals_mapping: FxHashMap<DefId, HashMap<Place<'tcx>, HashSet<Place<'tcx>>>>,
 
     pub fn_constraintgraph_mapping: FxHashMap<DefId, ConstraintGraph<'tcx, T>>,
     pub callgraph: CallGraph<'tcx>,
@@ -67,6 +196,8 @@ pub struct RangeAnalyzer<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub final_vars_vec: RAVecResultMap<'tcx, T>, // Interval results per call
 
     pub path_constraints: PathConstraintMap<'tcx>, // Path-sensitive constraints
+
+    pub relations: RelationMap<'tcx>, // Differences bounded by the relational domain
 }
 
 impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> Analysis for RangeAnalyzer<'tcx, T>
@@ -82,11 +213,13 @@ where
         // self.start();
         self.only_caller_range_analysis();
         self.start_path_constraints_analysis();
+        self.start_relational_analysis();
     }
 
     fn reset(&mut self) {
         self.final_vars.clear();
         self.ssa_places_mapping.clear();
+        self.ssa_locals_mapping.clear();
     }
 }
 
@@ -126,6 +259,14 @@ where
     fn get_all_path_constraints(&self) -> PathConstraintMap<'tcx> {
         self.path_constraints.clone()
     }
+
+    fn get_relation(&self, def_id: DefId, a: Place<'tcx>, b: Place<'tcx>) -> Option<Relation> {
+        self.relations.get(&def_id)?.relation(a, b)
+    }
+
+    fn get_all_relations(&self) -> RelationMap<'tcx> {
+        self.relations.clone()
+    }
 }
 
 impl<'tcx, T> RangeAnalyzer<'tcx, T>
@@ -163,6 +304,7 @@ where
             essa_def_id: essa_id,
             final_vars: FxHashMap::default(),
             ssa_places_mapping: FxHashMap::default(),
+            ssa_locals_mapping: FxHashMap::default(),
             fn_constraintgraph_mapping: FxHashMap::default(),
             callgraph: CallGraph::new(tcx),
             body_map: FxHashMap::default(),
@@ -170,6 +312,7 @@ where
             vars_map: FxHashMap::default(),
             final_vars_vec: FxHashMap::default(),
             path_constraints: FxHashMap::default(),
+            relations: FxHashMap::default(),
         }
     }
 
@@ -210,6 +353,22 @@ where
     fn only_caller_range_analysis(&mut self) {
         let ssa_def_id = self.ssa_def_id.expect("SSA definition ID is not set");
         let essa_def_id = self.essa_def_id.expect("ESSA definition ID is not set");
+        // Ranges depend on both callers and callees, so the functions connected through calls
+        // are restored from the cache only if none of them has changed. The restored functions
+        // are neither transformed nor analyzed.
+        // The ranges of each numeric domain are cached in a file of their own.
+        let domain = std::any::type_name::<T>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        let mut cache: SummaryCache<RangeSummary<T>> =
+            SummaryCache::load(self.tcx, format!("range-{domain}"), domain);
+        let cached = if self.debug {
+            HashSet::new()
+        } else {
+            self.restore_cached_ranges(&mut cache)
+        };
+
         // ====================================================================
         // PHASE 1: Build all ConstraintGraphs and the complete CallGraph first.
         // ====================================================================
@@ -218,7 +377,7 @@ where
             if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
                 let def_id = local_def_id.to_def_id();
 
-                if self.tcx.is_mir_available(def_id) {
+                if self.tcx.is_mir_available(def_id) && !cached.contains(&def_id) {
                     rap_info!("Processing function: {}", self.tcx.def_path_str(def_id));
                     let mut body = self.tcx.optimized_mir(def_id).clone();
                     let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
@@ -234,6 +393,8 @@ where
 
                     self.ssa_places_mapping
                         .insert(def_id, passrunner.places_map.clone());
+                    self.ssa_locals_mapping
+                        .insert(def_id, passrunner.ssa_locals_map.clone());
                     // rap_debug!("ssa_places_mapping: {:?}", self.ssa_places_mapping);
                     // Build and store the constraint graph
                     self.build_constraintgraph(body_mut_ref, def_id);
@@ -251,13 +412,15 @@ where
         // PHASE 2: Analyze only the call chain start functions.
         // ====================================================================
         rap_debug!("PHASE 2: Finding and analyzing call chain start functions...");
-
         let mut call_chain_starts: Vec<DefId> = Vec::new();
 
         let callers_by_callee_id = self.callgraph.get_callers_map();
 
         for &def_id in &self.callgraph.functions {
-            if !callers_by_callee_id.contains_key(&def_id) && self.cg_map.contains_key(&def_id) {
+            if !callers_by_callee_id.contains_key(&def_id)
+                && self.cg_map.contains_key(&def_id)
+                && !cached.contains(&def_id)
+            {
                 call_chain_starts.push(def_id);
             }
         }
@@ -291,6 +454,9 @@ where
 
         let analysis_order = self.callgraph.get_reverse_post_order();
         for def_id in analysis_order {
+            if cached.contains(&def_id) {
+                continue;
+            }
             if let Some(cg_cell) = self.cg_map.get(&def_id) {
                 let mut cg = cg_cell.borrow_mut();
                 let (final_vars_for_fn, _) = cg.build_final_vars(&self.ssa_places_mapping[&def_id]);
@@ -320,8 +486,200 @@ where
             }
         }
 
+        if cache.is_enabled() && !self.debug {
+            self.save_cached_ranges(&mut cache, &cached);
+            cache.save();
+        }
         rap_debug!("PHASE 2 Complete. Interval analysis finished for call chain start functions.");
     }
+
+    /// The functions analyzed in phase 1, grouped by their component keys.
+    fn range_components(&self) -> FxHashMap<Fingerprint, Vec<DefId>> {
+        let keys = FnKeys::get(self.tcx);
+        let mut components: FxHashMap<Fingerprint, Vec<DefId>> = FxHashMap::default();
+        for local_def_id in self.tcx.iter_local_def_id() {
+            let def_id = local_def_id.to_def_id();
+            if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn)
+                && self.tcx.is_mir_available(def_id)
+                && let Some(key) = keys.component_key(def_id)
+            {
+                components.entry(key).or_default().push(def_id);
+            }
+        }
+        components
+    }
+
+    /// Restore the ranges of unchanged components from the cache and return their functions.
+    fn restore_cached_ranges(
+        &mut self,
+        cache: &mut SummaryCache<RangeSummary<T>>,
+    ) -> HashSet<DefId> {
+        let mut restored = HashSet::new();
+        'component: for (key, def_ids) in self.range_components() {
+            let summaries: Option<Vec<RangeSummary<T>>> = def_ids
+                .iter()
+                .map(|def_id| cache.get(self.tcx, *def_id, key).cloned())
+                .collect();
+            let Some(summaries) = summaries else {
+                continue;
+            };
+            let mut results = Vec::new();
+            for (def_id, summary) in def_ids.into_iter().zip(summaries) {
+                let places = OriginalPlaces::new(self.tcx.optimized_mir(def_id));
+                let mut places_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>> = HashMap::new();
+                let mut resolve = |ranges: &CachedRanges<T>| -> Option<RAResult<'tcx, T>> {
+                    ranges
+                        .iter()
+                        .map(|(place, rtype, lower, upper)| {
+                            let (origin, place) = places.resolve(place)?;
+                            places_map.entry(origin).or_default().insert(place);
+                            Some((place, Range::new(*lower, *upper, rtype.clone())))
+                        })
+                        .collect()
+                };
+                let Some(ranges) = resolve(&summary.ranges) else {
+                    continue 'component;
+                };
+                let Some(ranges_percall) =
+                    summary.ranges_percall.iter().map(&mut resolve).collect()
+                else {
+                    continue 'component;
+                };
+                results.push((
+                    def_id,
+                    ranges,
+                    ranges_percall,
+                    places_map,
+                    summary.symbexprs,
+                ));
+            }
+            for (def_id, ranges, ranges_percall, places_map, symbexprs) in results {
+                // The constraint graph of a restored function is not built, so its symbolic
+                // intervals are reported from the cache.
+                for line in symbexprs {
+                    rap_info!("{}", line);
+                }
+                self.final_vars.insert(def_id, ranges);
+                self.final_vars_vec.insert(def_id, ranges_percall);
+                self.ssa_places_mapping.insert(def_id, places_map);
+                restored.insert(def_id);
+            }
+        }
+        restored
+    }
+
+    /// Store the ranges of the components analyzed in this run.
+    fn save_cached_ranges(
+        &self,
+        cache: &mut SummaryCache<RangeSummary<T>>,
+        restored: &HashSet<DefId>,
+    ) {
+        'component: for (key, def_ids) in self.range_components() {
+            if def_ids.iter().any(|def_id| restored.contains(def_id)) {
+                continue;
+            }
+            let mut summaries = Vec::new();
+            for def_id in def_ids {
+                // The original local of each SSA place.
+                let origins: HashMap<Place<'tcx>, Local> = self
+                    .ssa_places_mapping
+                    .get(&def_id)
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|(origin, places)| places.iter().map(|place| (*place, origin.local)))
+                    .collect();
+                // Ranges with open bounds cannot be restored by `Range::new`.
+                let to_cache = |ranges: &RAResult<'tcx, T>| -> Option<CachedRanges<T>> {
+                    ranges
+                        .iter()
+                        .map(|(place, range)| {
+                            let place = CachedPlace {
+                                local: place.local.as_u32(),
+                                origin: origins.get(place)?.as_u32(),
+                                projection: CachedElem::encode(place.projection)?,
+                            };
+                            let lower = *range.range.lower()?;
+                            let upper = *range.range.upper()?;
+                            Some((place, range.rtype.clone(), lower, upper))
+                        })
+                        .collect()
+                };
+                let ranges = self
+                    .final_vars
+                    .get(&def_id)
+                    .map_or(Some(Vec::new()), to_cache);
+                let ranges_percall = self
+                    .final_vars_vec
+                    .get(&def_id)
+                    .map_or(Some(Vec::new()), |vec| vec.iter().map(to_cache).collect());
+                let (Some(ranges), Some(ranges_percall)) = (ranges, ranges_percall) else {
+                    continue 'component;
+                };
+                let symbexprs = self
+                    .cg_map
+                    .get(&def_id)
+                    .map(|cg| cg.borrow().symbexpr_lines())
+                    .unwrap_or_default();
+                summaries.push((
+                    def_id,
+                    RangeSummary {
+                        ranges,
+                        ranges_percall,
+                        symbexprs,
+                    },
+                ));
+            }
+            for (def_id, summary) in summaries {
+                cache.insert(self.tcx, def_id, key, summary);
+            }
+        }
+    }
+    /// The path constraints of the acyclic paths from the entry of the function to the block,
+    /// or `None` if there are more than `max_paths` of them. Since every path ends at the block,
+    /// the constraints of a path hold whenever the block is reached along it.
+    pub fn path_constraints_to(
+        &self,
+        def_id: DefId,
+        block: BasicBlock,
+        max_paths: usize,
+    ) -> Option<PathConstraint<'tcx>> {
+        let body = self.tcx.optimized_mir(def_id);
+        let successors = |bb: BasicBlock| -> Vec<BasicBlock> {
+            body[bb]
+                .terminator()
+                .successors()
+                .filter(|succ| !body[*succ].is_cleanup)
+                .collect()
+        };
+        let mut paths: Vec<Vec<usize>> = Vec::new();
+        if block == START_BLOCK {
+            paths.push(vec![START_BLOCK.as_usize()]);
+        }
+        let mut path = vec![START_BLOCK];
+        let mut stack = vec![successors(START_BLOCK)];
+        while let Some(next) = stack.last_mut().map(Vec::pop) {
+            let Some(next) = next else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            if path.contains(&next) {
+                continue;
+            }
+            if next == block {
+                paths.push(path.iter().chain([&next]).map(|bb| bb.as_usize()).collect());
+                if paths.len() > max_paths {
+                    return None;
+                }
+            } else {
+                path.push(next);
+                stack.push(successors(next));
+            }
+        }
+        let mut cg: ConstraintGraph<'tcx, T> =
+            ConstraintGraph::new_without_ssa(body, self.tcx, def_id);
+        Some(cg.start_analyze_path_constraints(body, &paths))
+    }
     pub fn start_path_constraints_analysis_for_defid(
         &mut self,
         def_id: DefId,
@@ -358,6 +716,66 @@ where
             None
         }
     }
+    /// Build the SSA form of the function if it has not been built.
+    fn transform_body(&mut self, def_id: DefId) {
+        if self.body_map.contains_key(&def_id) {
+            return;
+        }
+        let ssa_def_id = self.ssa_def_id.expect("SSA definition ID is not set");
+        let essa_def_id = self.essa_def_id.expect("ESSA definition ID is not set");
+        let mut body = self.tcx.optimized_mir(def_id).clone();
+        let mut passrunner = PassRunner::new(self.tcx);
+        passrunner.run_pass(&mut body, ssa_def_id, essa_def_id);
+        self.body_map.insert(def_id, body);
+        self.ssa_places_mapping
+            .insert(def_id, passrunner.places_map.clone());
+        self.ssa_locals_mapping
+            .insert(def_id, passrunner.ssa_locals_map.clone());
+    }
+
+    /// Build the SSA forms of the functions whose ranges are restored from the cache, for the
+    /// clients inspecting the SSA forms along with the ranges.
+    pub fn transform_restored_bodies(&mut self) {
+        let restored: Vec<DefId> = self
+            .final_vars
+            .keys()
+            .filter(|def_id| !self.body_map.contains_key(def_id))
+            .copied()
+            .collect();
+        for def_id in restored {
+            self.transform_body(def_id);
+        }
+    }
+
+    /// Bound the differences between the integer places of each function with the relational
+    /// domain, over the SSA forms built by the range analysis or built here if it has not run.
+    /// The relations are kept between the original places of the function.
+    pub fn start_relational_analysis(&mut self) {
+        let ssa_def_id = self.ssa_def_id.expect("SSA definition ID is not set");
+        let essa_def_id = self.essa_def_id.expect("ESSA definition ID is not set");
+        for local_def_id in self.tcx.iter_local_def_id() {
+            if !matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
+                continue;
+            }
+            let def_id = local_def_id.to_def_id();
+            if !self.tcx.is_mir_available(def_id) {
+                continue;
+            }
+            self.transform_body(def_id);
+            let body = &self.body_map[&def_id];
+            if let Some(relations) =
+                RelationAnalysis::new(self.tcx, body, ssa_def_id, essa_def_id).analyze()
+            {
+                let origins = self.ssa_places_mapping[&def_id]
+                    .iter()
+                    .chain(self.ssa_locals_mapping.get(&def_id).into_iter().flatten())
+                    .flat_map(|(origin, places)| places.iter().map(|place| (*place, *origin)))
+                    .collect();
+                self.relations
+                    .insert(def_id, relations.to_original(&origins));
+            }
+        }
+    }
     pub fn start_path_constraints_analysis(&mut self) {
         for local_def_id in self.tcx.iter_local_def_id() {
             if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
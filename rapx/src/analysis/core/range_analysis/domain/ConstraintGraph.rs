@@ -8,6 +8,7 @@
 
 use super::domain::*;
 use crate::analysis::core::range_analysis::{Range, RangeType};
+use crate::analysis::utils::budget::{self, Budget};
 
 use crate::analysis::core::range_analysis::domain::SymbolicExpr::*;
 use crate::rap_debug;
@@ -78,6 +79,8 @@ pub struct ConstraintGraph<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub const_func_place: HashMap<&'tcx Place<'tcx>, usize>,
     pub func_without_mir: HashMap<DefId, String>,
     pub unique_adt_path: HashMap<String, usize>,
+    // The budget of solving the intervals of the function.
+    pub budget: Budget,
 }
 
 impl<'tcx, T> ConstraintGraph<'tcx, T>
@@ -130,6 +133,7 @@ where
             const_func_place: HashMap::new(),
             func_without_mir: HashMap::new(),
             unique_adt_path: unique_adt_path,
+            budget: Budget::new(),
         }
     }
     pub fn new_without_ssa(body: &'tcx Body<'tcx>, tcx: TyCtxt<'tcx>, self_def_id: DefId) -> Self {
@@ -169,6 +173,7 @@ where
             const_func_place: HashMap::new(),
             func_without_mir: HashMap::new(),
             unique_adt_path: unique_adt_path,
+            budget: Budget::new(),
         }
     }
     pub fn to_dot(&self) -> String {
@@ -392,18 +397,26 @@ where
         }
     }
     fn print_symbexpr(&self) {
+        for line in self.symbexpr_lines() {
+            rap_info!("{}", line);
+        }
+    }
+    /// The symbolic intervals of the variables, as reported when the graph is built.
+    pub fn symbexpr_lines(&self) -> Vec<String> {
         let mut vars: Vec<_> = self.vars.iter().collect();
 
         vars.sort_by_key(|(local, _)| local.local.index());
 
-        for (&local, value) in vars {
-            rap_info!(
-                "Var: {:?}. [ {:?} , {:?} ]",
-                local,
-                value.interval.get_lower_expr(),
-                value.interval.get_upper_expr()
-            );
-        }
+        vars.into_iter()
+            .map(|(&local, value)| {
+                format!(
+                    "Var: {:?}. [ {:?} , {:?} ]",
+                    local,
+                    value.interval.get_lower_expr(),
+                    value.interval.get_upper_expr()
+                )
+            })
+            .collect()
     }
     // pub fn create_random_place(&mut self) -> Place<'tcx> {
     //     let mut rng = rand::rng();
@@ -989,7 +1002,7 @@ where
         match cmp_op {
             BinOp::Lt => {
                 if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant.sub(U::one()), RangeType::Unknown)
+                    Range::new(U::min_value(), constant.pred(), RangeType::Unknown)
                 } else {
                     Range::new(constant, U::max_value(), RangeType::Unknown)
                 }
@@ -999,7 +1012,7 @@ where
                 if is_true_branch ^ const_in_left {
                     Range::new(U::min_value(), constant, RangeType::Unknown)
                 } else {
-                    Range::new(constant.add(U::one()), U::max_value(), RangeType::Unknown)
+                    Range::new(constant.succ(), U::max_value(), RangeType::Unknown)
                 }
             }
 
@@ -1007,7 +1020,7 @@ where
                 if is_true_branch ^ const_in_left {
                     Range::new(U::min_value(), constant, RangeType::Unknown)
                 } else {
-                    Range::new(constant.add(U::one()), U::max_value(), RangeType::Unknown)
+                    Range::new(constant.succ(), U::max_value(), RangeType::Unknown)
                 }
             }
 
@@ -1015,7 +1028,7 @@ where
                 if is_true_branch ^ const_in_left {
                     Range::new(U::min_value(), constant, RangeType::Unknown)
                 } else {
-                    Range::new(constant, U::max_value().sub(U::one()), RangeType::Unknown)
+                    Range::new(constant, U::max_value().pred(), RangeType::Unknown)
                 }
             }
 
@@ -1901,6 +1914,10 @@ where
         let mut worklist: Vec<&'tcx Place<'tcx>> = entry_points.iter().cloned().collect();
 
         while let Some(place) = worklist.pop() {
+            if !self.budget.tick() {
+                rap_trace!("Budget exhausted, breaking out of pre_update\n");
+                break;
+            }
             if let Some(op_set) = comp_use_map.get(place) {
                 for &op in op_set {
                     if self.widen(op, cg_map, vars_map) {
@@ -1922,11 +1939,9 @@ where
         vars_map: &mut FxHashMap<DefId, Vec<RefCell<VarNodes<'tcx, T>>>>,
     ) {
         let mut worklist: Vec<&'tcx Place<'tcx>> = entry_points.iter().cloned().collect();
-        let mut iteration = 0;
         while let Some(place) = worklist.pop() {
-            iteration += 1;
-            if (iteration > 1000) {
-                rap_trace!("Iteration limit reached, breaking out of pos_update\n");
+            if !self.budget.tick() {
+                rap_trace!("Budget exhausted, breaking out of pos_update\n");
                 break;
             }
 
@@ -1942,7 +1957,6 @@ where
                 }
             }
         }
-        rap_trace!("pos_update finished after {} iterations\n", iteration);
     }
     fn generate_active_vars(
         &mut self,
@@ -2057,6 +2071,7 @@ where
         // let scc_list = Nuutila::new(&self.vars, &self.usemap, &self.symbmap,false,&self.oprs);
         // self.print_vars();
 
+        self.budget = Budget::new();
         self.solve_const_func_call(cg_map, vars_map);
         self.numSCCs = self.worklist.len();
         let mut seen = HashSet::new();
@@ -2116,6 +2131,7 @@ where
             self.propagate_to_next_scc(&component, cg_map, vars_map);
         }
         self.merge_return_places();
+        budget::record_incomplete(self.tcx, "range", self.self_def_id, &self.budget);
         let Some(varnodes_vec) = vars_map.get_mut(&self.self_def_id) else {
             rap_trace!(
                 "No variable map entry for this function {:?}, skipping Nuutila\n",
@@ -513,8 +513,7 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> SymbInterval<'tcx, T> {
 
             BinOp::Lt => {
                 if u != T::max_value() {
-                    let u_minus_1 = u.checked_sub(&T::one()).unwrap_or(u);
-                    Range::new(lower, u_minus_1, RangeType::Regular)
+                    Range::new(lower, u.pred(), RangeType::Regular)
                 } else {
                     Range::new(lower, u, RangeType::Regular)
                 }
@@ -524,8 +523,7 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> SymbInterval<'tcx, T> {
 
             BinOp::Gt => {
                 if l != T::min_value() {
-                    let l_plus_1 = l.checked_add(&T::one()).unwrap_or(l);
-                    Range::new(l_plus_1, upper, RangeType::Regular)
+                    Range::new(l.succ(), upper, RangeType::Regular)
                 } else {
                     Range::new(l, upper, RangeType::Regular)
                 }
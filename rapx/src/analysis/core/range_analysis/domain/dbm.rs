@@ -0,0 +1,132 @@
+//! A difference-bound matrix (DBM), the relational domain of constraints `x - y <= c`.
+//!
+//! The matrix is kept closed, i.e., each entry is the tightest bound implied by the others, by
+//! the incremental operations below, so that the bound of a pair can be read directly.
+
+/// The bound of an unconstrained difference.
+pub const INF: i64 = i64::MAX;
+
+/// `a + b`, where an infinite operand or an overflowing sum yields `INF`.
+pub fn bound_add(a: i64, b: i64) -> i64 {
+    if a == INF || b == INF {
+        INF
+    } else {
+        a.checked_add(b).unwrap_or(INF)
+    }
+}
+
+/// `-a` as the bound of the opposite difference.
+fn bound_neg(a: i64) -> i64 {
+    if a == INF {
+        INF
+    } else {
+        a.checked_neg().unwrap_or(INF)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dbm {
+    size: usize,
+    /// `bounds[i * size + j]` is the bound of `v_i - v_j`.
+    bounds: Vec<i64>,
+}
+
+impl Dbm {
+    /// A matrix of `size` unconstrained variables.
+    pub fn new(size: usize) -> Self {
+        let mut bounds = vec![INF; size * size];
+        for i in 0..size {
+            bounds[i * size + i] = 0;
+        }
+        Dbm { size, bounds }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The bound of `v_i - v_j`.
+    pub fn get(&self, i: usize, j: usize) -> i64 {
+        self.bounds[i * self.size + j]
+    }
+
+    fn set(&mut self, i: usize, j: usize, c: i64) {
+        self.bounds[i * self.size + j] = c;
+    }
+
+    /// Whether the constraints are unsatisfiable, i.e., some variable is less than itself.
+    pub fn is_empty(&self) -> bool {
+        (0..self.size).any(|i| self.get(i, i) < 0)
+    }
+
+    /// Drop all constraints on `v_x`.
+    pub fn forget(&mut self, x: usize) {
+        for k in 0..self.size {
+            if k != x {
+                self.set(x, k, INF);
+                self.set(k, x, INF);
+            }
+        }
+    }
+
+    /// `v_x := v_y + c`.
+    pub fn assign(&mut self, x: usize, y: usize, c: i64) {
+        if x == y {
+            // The old value is only known through the other variables.
+            for k in 0..self.size {
+                if k != x {
+                    let upper = bound_add(self.get(x, k), c);
+                    let lower = bound_add(self.get(k, x), bound_neg(c));
+                    self.set(x, k, upper);
+                    self.set(k, x, lower);
+                }
+            }
+            return;
+        }
+        self.forget(x);
+        for k in 0..self.size {
+            if k != x {
+                self.set(x, k, bound_add(self.get(y, k), c));
+                self.set(k, x, bound_add(self.get(k, y), bound_neg(c)));
+            }
+        }
+        self.set(x, y, c);
+        self.set(y, x, bound_neg(c));
+    }
+
+    /// Add the constraint `v_i - v_j <= c` and close the matrix.
+    pub fn constrain(&mut self, i: usize, j: usize, c: i64) {
+        if c >= self.get(i, j) {
+            return;
+        }
+        for a in 0..self.size {
+            let to_i = self.get(a, i);
+            if to_i == INF {
+                continue;
+            }
+            for b in 0..self.size {
+                let through = bound_add(bound_add(to_i, c), self.get(j, b));
+                if through < self.get(a, b) {
+                    self.set(a, b, through);
+                }
+            }
+        }
+    }
+
+    /// The least upper bound, i.e., the constraints satisfied by both matrices.
+    pub fn join(&mut self, other: &Dbm) {
+        for (bound, other) in self.bounds.iter_mut().zip(other.bounds.iter()) {
+            *bound = (*bound).max(*other);
+        }
+    }
+
+    /// Join with `other`, dropping every bound that `other` loosens so that the iteration
+    /// over a loop terminates.
+    pub fn widen(&mut self, other: &Dbm) {
+        for (bound, other) in self.bounds.iter_mut().zip(other.bounds.iter()) {
+            if *other > *bound {
+                *bound = INF;
+            }
+        }
+    }
+}
@@ -12,7 +12,7 @@ use crate::analysis::core::range_analysis::domain::SymbolicExpr::{
 };
 use crate::analysis::core::range_analysis::{Range, RangeType};
 use crate::{rap_debug, rap_trace};
-use num_traits::{Bounded, CheckedAdd, CheckedSub, One, ToPrimitive, Zero, ops};
+use num_traits::{Bounded, CheckedAdd, CheckedMul, CheckedSub, One, ToPrimitive, Zero, ops};
 use rustc_abi::Size;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
@@ -35,52 +35,6 @@ pub trait ConstConvert: Sized {
     fn from_const(c: &Const) -> Option<Self>;
 }
 
-impl ConstConvert for u32 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as u32)
-        } else {
-            None
-        }
-    }
-}
-impl ConstConvert for usize {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as usize)
-        } else {
-            None
-        }
-    }
-}
-impl ConstConvert for i32 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as i32)
-        } else {
-            None
-        }
-    }
-}
-
-impl ConstConvert for i64 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as i64)
-        } else {
-            None
-        }
-    }
-}
-impl ConstConvert for i128 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as i128)
-        } else {
-            None
-        }
-    }
-}
 pub trait IntervalArithmetic:
     PartialOrd
     + Clone
@@ -90,6 +44,7 @@ pub trait IntervalArithmetic:
     + One
     + CheckedAdd
     + CheckedSub
+    + CheckedMul
     + Add<Output = Self>
     + Sub<Output = Self>
     + Mul<Output = Self>
@@ -97,12 +52,19 @@ pub trait IntervalArithmetic:
     + PartialOrd
     + PartialEq
     + NothingBetween
+    + serde::Serialize
+    + serde::de::DeserializeOwned
 {
-}
+    /// The greatest value less than `self`, or `self` if there is none.
+    fn pred(&self) -> Self {
+        self.checked_sub(&Self::one()).unwrap_or(*self)
+    }
 
-impl IntervalArithmetic for i32 {}
-impl IntervalArithmetic for usize {}
-impl IntervalArithmetic for i64 {}
+    /// The least value greater than `self`, or `self` if there is none.
+    fn succ(&self) -> Self {
+        self.checked_add(&Self::one()).unwrap_or(*self)
+    }
+}
 use rustc_middle::ty::Ty;
 
 // Define the basic operation trait
@@ -420,8 +382,12 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
                 match self.args.last() {
                     Some(Operand::Copy(place)) | Some(Operand::Move(place)) => {
                         let range = caller_vars[place].get_range().clone();
-                        let len = range.get_upper().clone() - range.get_lower().clone();
-                        result = Range::new(len.clone(), len.clone(), RangeType::Regular);
+                        // Only the slices created from a range of indices have a known length.
+                        if range.is_regular()
+                            && let Some(len) = range.get_upper().checked_sub(&range.get_lower())
+                        {
+                            result = Range::new(len, len, RangeType::Regular);
+                        }
                     }
                     Some(Operand::Constant(c)) => {}
                     None => {}
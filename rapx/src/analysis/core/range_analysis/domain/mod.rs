@@ -1,4 +1,7 @@
 pub mod ConstraintGraph;
 pub mod SymbolicExpr;
+pub mod dbm;
 pub mod domain;
+pub mod numeric;
 pub mod range;
+pub mod relational;
@@ -0,0 +1,632 @@
+//! The numeric domains of the range analysis. Each primitive integer type is a domain of its
+//! own, `i128` is wrapped in [`Int128`] since `rust_intervals` does not support it, and `f32`
+//! and `f64` share the [`Float`] domain. The [`Number`] domain holds the values of all of them,
+//! so that the ranges of all places are computed in a single run and then converted to the
+//! domains of their types with [`FromNumber`].
+
+use super::domain::{ConstConvert, IntervalArithmetic};
+use num_traits::{Bounded, CheckedAdd, CheckedMul, CheckedSub, One, Zero};
+use rust_intervals::NothingBetween;
+use rustc_middle::{mir::Const, ty};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Add, Mul, Sub},
+};
+
+/// The value of an integer constant, sign-extended if its type is signed, and saturated to
+/// the bounds of `T`. Constants of other scalar types are converted from their bits.
+fn convert_int<T: TryFrom<i128> + TryFrom<u128> + Bounded>(c: &Const) -> Option<T> {
+    let scalar = c.try_to_scalar_int()?;
+    Some(if c.ty().is_signed() {
+        let value = scalar.to_int(scalar.size());
+        T::try_from(value).unwrap_or_else(|_| {
+            if value < 0 {
+                T::min_value()
+            } else {
+                T::max_value()
+            }
+        })
+    } else {
+        T::try_from(scalar.to_uint(scalar.size())).unwrap_or_else(|_| T::max_value())
+    })
+}
+
+macro_rules! int_domains {
+    ($($ty:ty),*) => {
+        $(
+            impl ConstConvert for $ty {
+                fn from_const(c: &Const) -> Option<Self> {
+                    convert_int(c)
+                }
+            }
+
+            impl IntervalArithmetic for $ty {}
+        )*
+    };
+}
+
+int_domains!(i8, i16, i32, i64, isize, u8, u16, u32, u64, u128, usize);
+
+impl ConstConvert for i128 {
+    fn from_const(c: &Const) -> Option<Self> {
+        convert_int(c)
+    }
+}
+
+/// The domain of `i128` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Int128(pub i128);
+
+impl fmt::Display for Int128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Bounded for Int128 {
+    fn min_value() -> Self {
+        Int128(i128::MIN)
+    }
+
+    fn max_value() -> Self {
+        Int128(i128::MAX)
+    }
+}
+
+impl Add for Int128 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Int128(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Int128 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Int128(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Int128 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Int128(self.0 * rhs.0)
+    }
+}
+
+impl Zero for Int128 {
+    fn zero() -> Self {
+        Int128(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl One for Int128 {
+    fn one() -> Self {
+        Int128(1)
+    }
+}
+
+impl CheckedAdd for Int128 {
+    fn checked_add(&self, v: &Self) -> Option<Self> {
+        self.0.checked_add(v.0).map(Int128)
+    }
+}
+
+impl CheckedSub for Int128 {
+    fn checked_sub(&self, v: &Self) -> Option<Self> {
+        self.0.checked_sub(v.0).map(Int128)
+    }
+}
+
+impl CheckedMul for Int128 {
+    fn checked_mul(&self, v: &Self) -> Option<Self> {
+        self.0.checked_mul(v.0).map(Int128)
+    }
+}
+
+impl NothingBetween for Int128 {
+    fn nothing_between(&self, other: &Self) -> bool {
+        other.0.checked_sub(self.0).is_some_and(|diff| diff <= 1)
+    }
+}
+
+impl ConstConvert for Int128 {
+    fn from_const(c: &Const) -> Option<Self> {
+        convert_int(c).map(Int128)
+    }
+}
+
+impl IntervalArithmetic for Int128 {}
+
+/// The domain of `f32` and `f64` values, which are both represented by `f64`.
+///
+/// The values are totally ordered with NaN above infinity, so that the largest range
+/// `[-inf, NaN]` covers every value, and a range may be NaN if and only if its upper bound is
+/// NaN. An operation yielding NaN from non-NaN operands, e.g., `inf - inf`, widens the bound
+/// it computes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub struct Float(f64);
+
+impl Float {
+    /// Create a value with a single NaN and a single zero.
+    pub fn new(value: f64) -> Self {
+        if value.is_nan() {
+            Float(f64::NAN)
+        } else if value == 0.0 {
+            Float(0.0)
+        } else {
+            Float(value)
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    pub fn is_nan(&self) -> bool {
+        self.0.is_nan()
+    }
+}
+
+impl From<u64> for Float {
+    fn from(bits: u64) -> Self {
+        Float::new(f64::from_bits(bits))
+    }
+}
+
+impl From<Float> for u64 {
+    fn from(value: Float) -> Self {
+        value.0.to_bits()
+    }
+}
+
+impl fmt::Display for Float {
+    /// The shortest representation that round-trips, e.g., `1e308` or `2.0`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Float {}
+
+impl PartialOrd for Float {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Float {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for Float {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl Bounded for Float {
+    fn min_value() -> Self {
+        Float(f64::NEG_INFINITY)
+    }
+
+    fn max_value() -> Self {
+        Float(f64::NAN)
+    }
+}
+
+impl Add for Float {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Float::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Float {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Float::new(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Float {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Float::new(self.0 * rhs.0)
+    }
+}
+
+impl Zero for Float {
+    fn zero() -> Self {
+        Float(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0.0
+    }
+}
+
+impl One for Float {
+    fn one() -> Self {
+        Float(1.0)
+    }
+}
+
+impl CheckedAdd for Float {
+    fn checked_add(&self, v: &Self) -> Option<Self> {
+        let sum = *self + *v;
+        (!sum.is_nan() || self.is_nan() || v.is_nan()).then_some(sum)
+    }
+}
+
+impl CheckedSub for Float {
+    fn checked_sub(&self, v: &Self) -> Option<Self> {
+        let difference = *self - *v;
+        (!difference.is_nan() || self.is_nan() || v.is_nan()).then_some(difference)
+    }
+}
+
+impl CheckedMul for Float {
+    /// A NaN product, e.g., `0 * inf`, is kept so that the upper bound of the products is NaN.
+    fn checked_mul(&self, v: &Self) -> Option<Self> {
+        Some(*self * *v)
+    }
+}
+
+impl NothingBetween for Float {
+    fn nothing_between(&self, other: &Self) -> bool {
+        self.succ() >= *other
+    }
+}
+
+impl ConstConvert for Float {
+    fn from_const(c: &Const) -> Option<Self> {
+        let scalar = c.try_to_scalar_int()?;
+        let value = match c.ty().kind() {
+            ty::Float(ty::FloatTy::F32) => f32::from_bits(scalar.to_u32()) as f64,
+            ty::Float(ty::FloatTy::F64) => f64::from_bits(scalar.to_u64()),
+            ty::Int(_) => scalar.to_int(scalar.size()) as f64,
+            _ => scalar.to_uint(scalar.size()) as f64,
+        };
+        Some(Float::new(value))
+    }
+}
+
+impl IntervalArithmetic for Float {
+    fn pred(&self) -> Self {
+        if self.is_nan() {
+            *self
+        } else {
+            Float::new(self.0.next_down())
+        }
+    }
+
+    fn succ(&self) -> Self {
+        if self.is_nan() {
+            *self
+        } else {
+            Float::new(self.0.next_up())
+        }
+    }
+}
+
+/// The domain of the values of all numeric types, either an `i128` or a [`Float`].
+///
+/// Integer constants are kept as `i128` and floating-point constants as [`Float`]. Integer
+/// operations yield an integer unless they overflow `i128`, in which case they are computed
+/// in floating point. Values are ordered numerically, with the bounds of [`Float`], and an
+/// operation yielding NaN widens the bound it computes, so that the unbounded range
+/// `[-inf, NaN]` is only reached by widening.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Number {
+    Int(i128),
+    Float(Float),
+}
+
+impl Number {
+    pub fn to_float(&self) -> Float {
+        match self {
+            Number::Int(value) => Float::new(*value as f64),
+            Number::Float(value) => *value,
+        }
+    }
+
+    /// The integer value, unless it is a floating-point value.
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            Number::Int(value) => Some(*value),
+            Number::Float(_) => None,
+        }
+    }
+
+    /// Apply an operation on `i128` values, or on floating-point values if an operand is a
+    /// floating-point value or the integer operation overflows.
+    fn checked_op(
+        &self,
+        other: &Self,
+        int_op: fn(i128, i128) -> Option<i128>,
+        float_op: fn(Float, Float) -> Float,
+    ) -> Option<Self> {
+        if let (Number::Int(a), Number::Int(b)) = (self, other)
+            && let Some(value) = int_op(*a, *b)
+        {
+            return Some(Number::Int(value));
+        }
+        let value = float_op(self.to_float(), other.to_float());
+        (!value.is_nan()).then_some(Number::Float(value))
+    }
+}
+
+/// Compare an integer with a floating-point value exactly.
+fn cmp_int_float(a: i128, b: Float) -> Ordering {
+    let b = b.value();
+    if b.is_nan() || b >= -(i128::MIN as f64) {
+        return Ordering::Less;
+    }
+    if b < i128::MIN as f64 {
+        return Ordering::Greater;
+    }
+    // `b` is in the range of `i128`, so its integer part is exact.
+    let whole = b.trunc();
+    a.cmp(&(whole as i128))
+        .then_with(|| 0f64.total_cmp(&(b - whole)))
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Int(value) => write!(f, "{}", value),
+            Number::Float(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a.cmp(b),
+            (Number::Float(a), Number::Float(b)) => a.cmp(b),
+            (Number::Int(a), Number::Float(b)) => cmp_int_float(*a, *b),
+            (Number::Float(a), Number::Int(b)) => cmp_int_float(*b, *a).reverse(),
+        }
+    }
+}
+
+impl Hash for Number {
+    /// Equal integer and floating-point values hash as the integer.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Number::Int(value) => value.hash(state),
+            Number::Float(value) => {
+                let int = value.value() as i128;
+                if Number::Int(int) == *self {
+                    int.hash(state)
+                } else {
+                    value.hash(state)
+                }
+            }
+        }
+    }
+}
+
+impl Bounded for Number {
+    fn min_value() -> Self {
+        Number::Float(Float::min_value())
+    }
+
+    fn max_value() -> Self {
+        Number::Float(Float::max_value())
+    }
+}
+
+impl Add for Number {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(&rhs)
+            .unwrap_or(Number::Float(self.to_float() + rhs.to_float()))
+    }
+}
+
+impl Sub for Number {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.checked_sub(&rhs)
+            .unwrap_or(Number::Float(self.to_float() - rhs.to_float()))
+    }
+}
+
+impl Mul for Number {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(&rhs)
+            .unwrap_or(Number::Float(self.to_float() * rhs.to_float()))
+    }
+}
+
+impl Zero for Number {
+    fn zero() -> Self {
+        Number::Int(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Number::Int(0)
+    }
+}
+
+impl One for Number {
+    fn one() -> Self {
+        Number::Int(1)
+    }
+}
+
+impl CheckedAdd for Number {
+    fn checked_add(&self, v: &Self) -> Option<Self> {
+        self.checked_op(v, i128::checked_add, |a, b| a + b)
+    }
+}
+
+impl CheckedSub for Number {
+    fn checked_sub(&self, v: &Self) -> Option<Self> {
+        self.checked_op(v, i128::checked_sub, |a, b| a - b)
+    }
+}
+
+impl CheckedMul for Number {
+    fn checked_mul(&self, v: &Self) -> Option<Self> {
+        self.checked_op(v, i128::checked_mul, |a, b| a * b)
+    }
+}
+
+impl NothingBetween for Number {
+    fn nothing_between(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => {
+                i128::checked_sub(*b, *a).is_some_and(|diff| diff <= 1)
+            }
+            _ => self.succ() >= *other,
+        }
+    }
+}
+
+impl ConstConvert for Number {
+    fn from_const(c: &Const) -> Option<Self> {
+        let scalar = c.try_to_scalar_int()?;
+        Some(match c.ty().kind() {
+            ty::Float(_) => Number::Float(Float::from_const(c)?),
+            ty::Int(_) => Number::Int(scalar.to_int(scalar.size())),
+            _ => {
+                let value = scalar.to_uint(scalar.size());
+                i128::try_from(value)
+                    .map(Number::Int)
+                    .unwrap_or(Number::Float(Float::new(value as f64)))
+            }
+        })
+    }
+}
+
+impl IntervalArithmetic for Number {
+    fn pred(&self) -> Self {
+        match self {
+            Number::Int(_) => self.checked_sub(&Number::one()).unwrap_or(*self),
+            Number::Float(value) => Number::Float(value.pred()),
+        }
+    }
+
+    fn succ(&self) -> Self {
+        match self {
+            Number::Int(_) => self.checked_add(&Number::one()).unwrap_or(*self),
+            Number::Float(value) => Number::Float(value.succ()),
+        }
+    }
+}
+
+/// The conversion of the bounds of ranges computed in the [`Number`] domain to the domain of
+/// the type of a place. Bounds out of the domain saturate to its minimum or maximum, and
+/// integer domains round lower bounds up and upper bounds down.
+pub trait FromNumber: Sized {
+    fn from_lower(value: &Number) -> Self;
+    fn from_upper(value: &Number) -> Self;
+}
+
+/// The integer value of a bound rounded with `round`, saturated to the bounds of `T`.
+fn saturate_int<T: TryFrom<i128> + Bounded>(value: &Number, round: fn(f64) -> f64) -> T {
+    let value = match value {
+        Number::Int(value) => *value,
+        Number::Float(value) if value.is_nan() => return T::max_value(),
+        Number::Float(value) => {
+            let rounded = round(value.value());
+            if rounded < i128::MIN as f64 {
+                return T::min_value();
+            }
+            if rounded >= -(i128::MIN as f64) {
+                return T::max_value();
+            }
+            rounded as i128
+        }
+    };
+    T::try_from(value).unwrap_or_else(|_| {
+        if value < 0 {
+            T::min_value()
+        } else {
+            T::max_value()
+        }
+    })
+}
+
+macro_rules! from_number {
+    ($($ty:ty),*) => {
+        $(
+            impl FromNumber for $ty {
+                fn from_lower(value: &Number) -> Self {
+                    saturate_int(value, f64::ceil)
+                }
+
+                fn from_upper(value: &Number) -> Self {
+                    saturate_int(value, f64::floor)
+                }
+            }
+        )*
+    };
+}
+
+from_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, u128, usize);
+
+impl FromNumber for Int128 {
+    fn from_lower(value: &Number) -> Self {
+        Int128(saturate_int(value, f64::ceil))
+    }
+
+    fn from_upper(value: &Number) -> Self {
+        Int128(saturate_int(value, f64::floor))
+    }
+}
+
+impl FromNumber for Float {
+    fn from_lower(value: &Number) -> Self {
+        value.to_float()
+    }
+
+    fn from_upper(value: &Number) -> Self {
+        value.to_float()
+    }
+}
@@ -136,7 +136,7 @@ where
             .get_lower()
             .clone()
             .checked_add(&other.get_lower().clone())
-            .unwrap_or(T::max_value());
+            .unwrap_or(T::min_value());
 
         let b = self
             .get_upper()
@@ -164,12 +164,19 @@ where
     }
 
     pub fn mul(&self, other: &Range<T>) -> Range<T> {
-        let candidates = vec![
-            self.get_lower().clone() * other.get_lower().clone(),
-            self.get_lower().clone() * other.get_upper().clone(),
-            self.get_upper().clone() * other.get_lower().clone(),
-            self.get_upper().clone() * other.get_upper().clone(),
-        ];
+        let candidates: Option<Vec<T>> = [
+            (self.get_lower(), other.get_lower()),
+            (self.get_lower(), other.get_upper()),
+            (self.get_upper(), other.get_lower()),
+            (self.get_upper(), other.get_upper()),
+        ]
+        .iter()
+        .map(|(a, b)| a.checked_mul(b))
+        .collect();
+        // Any product out of the domain may wrap around.
+        let Some(candidates) = candidates else {
+            return Range::new(T::min_value(), T::max_value(), RangeType::Regular);
+        };
         let min = candidates
             .iter()
             .cloned()
@@ -0,0 +1,823 @@
+//! The relational range analysis, which bounds the differences between the integer places of
+//! a function in a difference-bound matrix.
+//!
+//! The analysis runs over the SSA form built for the range analysis. The comparisons of a
+//! branch are recorded as ESSA statements in its targets, so a fact like `i < len` established
+//! by `while i < v.len()` is a constraint between the ESSA copies of `i` and `len` rather than
+//! between places that vary along the loop. The result of a function is the bound of each
+//! difference that holds at every point where both places are defined.
+
+use super::dbm::{Dbm, INF};
+use crate::analysis::utils::def_path::def_path_def_ids;
+use rustc_abi::FieldIdx;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_index::IndexVec;
+use rustc_middle::{
+    mir::{
+        AggregateKind, BasicBlock, BinOp, Body, BorrowKind, CastKind, Const, Local, Operand, Place,
+        ProjectionElem, RawPtrKind, Rvalue, START_BLOCK, Statement, StatementKind, TerminatorKind,
+        UnOp,
+    },
+    ty::{self, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::sym;
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+/// Functions with more variables are not analyzed, as each state is quadratic in their number.
+const MAX_VARS: usize = 128;
+
+/// The number of times a loop head is joined before its state is widened.
+const WIDEN_AFTER: usize = 2;
+
+/// The bounds of the difference `a - b` of two places, `None` for an unbounded side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Relation {
+    pub lower: Option<i64>,
+    pub upper: Option<i64>,
+}
+
+impl Relation {
+    pub fn is_bounded(&self) -> bool {
+        self.lower.is_some() || self.upper.is_some()
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lower = self
+            .lower
+            .map_or("Min".to_string(), |lower| lower.to_string());
+        let upper = self
+            .upper
+            .map_or("Max".to_string(), |upper| upper.to_string());
+        write!(f, "[{}, {}]", lower, upper)
+    }
+}
+
+/// A variable of the matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RelVar<'tcx> {
+    /// The constant zero, through which the constant bounds of the other variables are kept.
+    Zero,
+    Place(Place<'tcx>),
+    /// The length of the array, slice or vector that a local is or points to.
+    Len(Local),
+}
+
+const ZERO: usize = 0;
+
+/// The relations between the places of a function.
+#[derive(Debug, Clone)]
+pub struct FnRelations<'tcx> {
+    vars: Vec<RelVar<'tcx>>,
+    index: FxHashMap<RelVar<'tcx>, usize>,
+    /// The bound of `v_i - v_j` at `i * vars.len() + j`, or `i64::MIN` if the two variables
+    /// are never defined at the same point.
+    bounds: Vec<i64>,
+}
+
+impl<'tcx> FnRelations<'tcx> {
+    /// The bounds of `a - b`, or `None` if one of the places is not an integer place of the
+    /// function or if they are never defined at the same point.
+    pub fn relation(&self, a: Place<'tcx>, b: Place<'tcx>) -> Option<Relation> {
+        let i = *self.index.get(&RelVar::Place(a))?;
+        let j = *self.index.get(&RelVar::Place(b))?;
+        let size = self.vars.len();
+        let (upper, lower) = (self.bounds[i * size + j], self.bounds[j * size + i]);
+        if upper == i64::MIN {
+            return None;
+        }
+        Some(Relation {
+            lower: (lower != INF).then(|| -lower),
+            upper: (upper != INF).then_some(upper),
+        })
+    }
+
+    /// The relations between the original places of the function, given the original place
+    /// of each SSA place. The bounds of two original places are the hull of the bounds of their
+    /// SSA places, so they hold wherever two of these are defined.
+    pub fn to_original(&self, origins: &FxHashMap<Place<'tcx>, Place<'tcx>>) -> Self {
+        let origin = |var: &RelVar<'tcx>| match var {
+            RelVar::Zero => RelVar::Zero,
+            RelVar::Place(place) => RelVar::Place(origins.get(place).copied().unwrap_or(*place)),
+            RelVar::Len(local) => RelVar::Len(
+                origins
+                    .get(&Place::from(*local))
+                    .map_or(*local, |place| place.local),
+            ),
+        };
+        let mut vars = Vec::new();
+        let mut index = FxHashMap::default();
+        let renamed: Vec<usize> = self
+            .vars
+            .iter()
+            .map(|var| {
+                let var = origin(var);
+                *index.entry(var).or_insert_with(|| {
+                    vars.push(var);
+                    vars.len() - 1
+                })
+            })
+            .collect();
+        let (old_size, size) = (self.vars.len(), vars.len());
+        let mut bounds = vec![i64::MIN; size * size];
+        for i in 0..old_size {
+            for j in 0..old_size {
+                let bound = &mut bounds[renamed[i] * size + renamed[j]];
+                *bound = (*bound).max(self.bounds[i * old_size + j]);
+            }
+        }
+        FnRelations {
+            vars,
+            index,
+            bounds,
+        }
+    }
+
+    /// The bounded relations between pairs of places, each pair ordered as in the function.
+    pub fn bounded(&self) -> Vec<(Place<'tcx>, Place<'tcx>, Relation)> {
+        let places: Vec<Place<'tcx>> = self
+            .vars
+            .iter()
+            .filter_map(|var| match var {
+                RelVar::Place(place) => Some(*place),
+                _ => None,
+            })
+            .collect();
+        let mut relations = Vec::new();
+        for (n, a) in places.iter().enumerate() {
+            for b in &places[n + 1..] {
+                if let Some(relation) = self.relation(*a, *b)
+                    && relation.is_bounded()
+                {
+                    relations.push((*a, *b, relation));
+                }
+            }
+        }
+        relations
+    }
+}
+
+/// A matrix together with the variables defined on every path reaching the point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct State {
+    dbm: Dbm,
+    defined: Vec<bool>,
+}
+
+impl State {
+    fn join(&mut self, other: &State, widen: bool) {
+        if widen {
+            self.dbm.widen(&other.dbm);
+        } else {
+            self.dbm.join(&other.dbm);
+        }
+        for (defined, other) in self.defined.iter_mut().zip(other.defined.iter()) {
+            *defined &= *other;
+        }
+    }
+}
+
+/// `v + c` for a variable `v` and a constant `c`.
+type Term = (usize, i64);
+
+pub struct RelationAnalysis<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    ssa_def_id: DefId,
+    essa_def_id: DefId,
+    len_fns: FxHashSet<DefId>,
+    vars: Vec<RelVar<'tcx>>,
+    index: FxHashMap<RelVar<'tcx>, usize>,
+}
+
+impl<'a, 'tcx> RelationAnalysis<'a, 'tcx> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        body: &'a Body<'tcx>,
+        ssa_def_id: DefId,
+        essa_def_id: DefId,
+    ) -> Self {
+        let len_fns = [
+            &["slice", "len"][..],
+            &["str", "len"],
+            &["alloc", "vec", "Vec", "len"],
+        ]
+        .iter()
+        .flat_map(|path| def_path_def_ids(&tcx, path))
+        .collect();
+        let mut analysis = RelationAnalysis {
+            tcx,
+            body,
+            ssa_def_id,
+            essa_def_id,
+            len_fns,
+            vars: Vec::new(),
+            index: FxHashMap::default(),
+        };
+        analysis.collect_vars();
+        analysis
+    }
+
+    fn add_var(&mut self, var: RelVar<'tcx>) {
+        if !self.index.contains_key(&var) {
+            self.index.insert(var, self.vars.len());
+            self.vars.push(var);
+        }
+    }
+
+    /// The variables are the zero, the integer locals, the results of the checked operations,
+    /// and the lengths of the locals holding or pointing to arrays, slices or vectors.
+    fn collect_vars(&mut self) {
+        self.add_var(RelVar::Zero);
+        for (local, decl) in self.body.local_decls.iter_enumerated() {
+            if decl.ty.is_integral() {
+                self.add_var(RelVar::Place(Place::from(local)));
+            }
+        }
+        for data in self.body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                if let StatementKind::Assign(box (sink, Rvalue::BinaryOp(op, _))) = &stmt.kind
+                    && let Some(place) = self.overflow_result(sink, *op)
+                {
+                    self.add_var(RelVar::Place(place));
+                }
+            }
+        }
+        for (local, decl) in self.body.local_decls.iter_enumerated() {
+            if self.has_len(decl.ty) {
+                self.add_var(RelVar::Len(local));
+            }
+        }
+    }
+
+    /// The place of the result of a checked operation, i.e., the first field of its sink.
+    fn overflow_result(&self, sink: &Place<'tcx>, op: BinOp) -> Option<Place<'tcx>> {
+        if !matches!(
+            op,
+            BinOp::AddWithOverflow | BinOp::SubWithOverflow | BinOp::MulWithOverflow
+        ) {
+            return None;
+        }
+        let ty::Tuple(fields) = sink.ty(self.body, self.tcx).ty.kind() else {
+            return None;
+        };
+        let ty = *fields.first()?;
+        Some(self.tcx.mk_place_field(*sink, FieldIdx::ZERO, ty))
+    }
+
+    /// Whether the length of the values of `ty` is fixed, i.e., arrays, slices and strings
+    /// behind any pointer, and vectors or shared references to them.
+    fn has_len(&self, ty: Ty<'tcx>) -> bool {
+        let is_vec = |ty: Ty<'tcx>| match ty.kind() {
+            ty::Adt(adt, _) => self.tcx.is_diagnostic_item(sym::Vec, adt.did()),
+            _ => false,
+        };
+        match ty.kind() {
+            ty::Ref(_, inner, mutability) => match inner.kind() {
+                ty::Array(..) | ty::Slice(_) | ty::Str => true,
+                _ => mutability.is_not() && is_vec(*inner),
+            },
+            ty::RawPtr(inner, _) => matches!(inner.kind(), ty::Array(..) | ty::Slice(_)),
+            ty::Array(..) => true,
+            _ => is_vec(ty),
+        }
+    }
+
+    fn var(&self, var: RelVar<'tcx>) -> Option<usize> {
+        self.index.get(&var).copied()
+    }
+
+    /// The relations of the function, or `None` if it has too many variables.
+    pub fn analyze(&self) -> Option<FnRelations<'tcx>> {
+        let size = self.vars.len();
+        if size > MAX_VARS {
+            rap_debug!(
+                "Skip the relational analysis of a function with {} variables",
+                size
+            );
+            return None;
+        }
+        let body = self.body;
+        let mut entry = State {
+            dbm: Dbm::new(size),
+            defined: vec![false; size],
+        };
+        entry.defined[ZERO] = true;
+        for arg in body.args_iter() {
+            self.define_local(&mut entry, arg);
+            if let Some(x) = self.var(RelVar::Place(Place::from(arg))) {
+                self.define_unknown(&mut entry, x);
+            }
+        }
+
+        let rpo = body.basic_blocks.reverse_postorder();
+        let order: FxHashMap<BasicBlock, usize> =
+            rpo.iter().enumerate().map(|(n, bb)| (*bb, n)).collect();
+        let dominators = body.basic_blocks.dominators();
+        let loop_heads: FxHashSet<BasicBlock> = body
+            .basic_blocks
+            .iter_enumerated()
+            .flat_map(|(bb, data)| data.terminator().successors().map(move |succ| (bb, succ)))
+            .filter(|(bb, succ)| dominators.dominates(*succ, *bb))
+            .map(|(_, succ)| succ)
+            .collect();
+
+        let mut states: IndexVec<BasicBlock, Option<State>> =
+            IndexVec::from_elem(None, &body.basic_blocks);
+        let mut joins: IndexVec<BasicBlock, usize> = IndexVec::from_elem(0, &body.basic_blocks);
+        states[START_BLOCK] = Some(entry);
+        let mut worklist = BTreeSet::from([0]);
+        let mut steps = 0;
+        while let Some(n) = worklist.pop_first() {
+            steps += 1;
+            if steps > 64 * rpo.len() {
+                rap_debug!("The relational analysis does not converge");
+                return None;
+            }
+            let bb = rpo[n];
+            let Some(mut state) = states[bb].clone() else {
+                continue;
+            };
+            if !self.transfer_block(&mut state, bb, |_| {}) {
+                continue;
+            }
+            for succ in body[bb].terminator().successors() {
+                if body[succ].is_cleanup {
+                    continue;
+                }
+                let Some(edge) = self.transfer_phis(&state, succ) else {
+                    continue;
+                };
+                let changed = match &mut states[succ] {
+                    Some(old) => {
+                        joins[succ] += 1;
+                        let widen = loop_heads.contains(&succ) && joins[succ] > WIDEN_AFTER;
+                        let before = old.clone();
+                        old.join(&edge, widen);
+                        *old != before
+                    }
+                    slot => {
+                        *slot = Some(edge);
+                        true
+                    }
+                };
+                if changed && let Some(n) = order.get(&succ) {
+                    worklist.insert(*n);
+                }
+            }
+        }
+
+        // Fold the states of all points into the bounds of each pair of variables.
+        let mut bounds = vec![i64::MIN; size * size];
+        let mut fold = |state: &State| {
+            for i in (0..size).filter(|i| state.defined[*i]) {
+                for j in (0..size).filter(|j| state.defined[*j]) {
+                    let bound = &mut bounds[i * size + j];
+                    *bound = (*bound).max(state.dbm.get(i, j));
+                }
+            }
+        };
+        for bb in rpo {
+            if let Some(mut state) = states[*bb].clone() {
+                fold(&state);
+                self.transfer_block(&mut state, *bb, &mut fold);
+            }
+        }
+        Some(FnRelations {
+            vars: self.vars.clone(),
+            index: self.index.clone(),
+            bounds,
+        })
+    }
+
+    /// Apply the statements and the terminator of a block, calling `fold` after each of them.
+    /// Return false if the block cannot be left.
+    fn transfer_block(
+        &self,
+        state: &mut State,
+        bb: BasicBlock,
+        mut fold: impl FnMut(&State),
+    ) -> bool {
+        let data = &self.body[bb];
+        let mut essa: Option<(usize, u64, u64)> = None;
+        for stmt in &data.statements {
+            self.transfer_statement(state, stmt, &mut essa);
+            if state.dbm.is_empty() {
+                return false;
+            }
+            fold(state);
+        }
+        if let TerminatorKind::Call {
+            func,
+            args,
+            destination,
+            ..
+        } = &data.terminator().kind
+        {
+            self.define_local(state, destination.local);
+            let len = func
+                .const_fn_def()
+                .filter(|(def_id, _)| self.len_fns.contains(def_id))
+                .and_then(|_| args.first()?.node.place())
+                .and_then(|arg| self.var(RelVar::Len(arg.as_local()?)));
+            if let Some(x) = self.var(RelVar::Place(*destination)) {
+                match len {
+                    Some(len) => self.assign(state, x, (len, 0)),
+                    None => self.define_unknown(state, x),
+                }
+            }
+            fold(state);
+        }
+        true
+    }
+
+    /// The state on the edge to `succ`, where each phi statement of `succ` takes the value of
+    /// one of its operands defined on the edge.
+    fn transfer_phis(&self, state: &State, succ: BasicBlock) -> Option<State> {
+        let mut state = state.clone();
+        for stmt in &self.body[succ].statements {
+            let StatementKind::Assign(box (sink, rvalue @ Rvalue::Aggregate(_, operands))) =
+                &stmt.kind
+            else {
+                continue;
+            };
+            if !self.is_phi(rvalue) {
+                continue;
+            }
+            self.define_local(&mut state, sink.local);
+            let Some(x) = self.var(RelVar::Place(*sink)) else {
+                continue;
+            };
+            let mut joined: Option<State> = None;
+            for operand in operands {
+                let Some(y) = operand.place().and_then(|p| self.var(RelVar::Place(p))) else {
+                    continue;
+                };
+                if !state.defined[y] {
+                    continue;
+                }
+                let mut candidate = state.clone();
+                self.assign(&mut candidate, x, (y, 0));
+                match &mut joined {
+                    Some(joined) => joined.join(&candidate, false),
+                    None => joined = Some(candidate),
+                }
+            }
+            match joined {
+                Some(joined) => state = joined,
+                None => self.define_unknown(&mut state, x),
+            }
+        }
+        (!state.dbm.is_empty()).then_some(state)
+    }
+
+    /// The phi statements are applied on the edges to their blocks.
+    fn is_phi(&self, rvalue: &Rvalue<'tcx>) -> bool {
+        matches!(rvalue, Rvalue::Aggregate(kind, _)
+            if matches!(**kind, AggregateKind::Adt(def_id, ..) if def_id == self.ssa_def_id))
+    }
+
+    /// Apply a statement. `essa` is the last ESSA statement of the block comparing two places,
+    /// i.e., its variable, comparison and switch block, until its counterpart is met.
+    fn transfer_statement(
+        &self,
+        state: &mut State,
+        stmt: &Statement<'tcx>,
+        essa: &mut Option<(usize, u64, u64)>,
+    ) {
+        let StatementKind::Assign(box (sink, rvalue)) = &stmt.kind else {
+            return;
+        };
+        if self.is_phi(rvalue) {
+            return;
+        }
+        if sink.projection.is_empty() {
+            self.define_local(state, sink.local);
+        } else if let Some(x) = self.var(RelVar::Place(*sink)) {
+            state.dbm.forget(x);
+        }
+        let x = self.var(RelVar::Place(*sink));
+        match rvalue {
+            Rvalue::Use(operand) => {
+                if let Some(x) = x {
+                    match self.term(operand) {
+                        Some(term) => self.assign(state, x, term),
+                        None => self.define_unknown(state, x),
+                    }
+                } else if let Some(place) = operand.place() {
+                    self.copy_len(state, sink, &place);
+                }
+            }
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => {
+                let result = match self.overflow_result(sink, *op) {
+                    Some(place) => self.var(RelVar::Place(place)),
+                    None => x,
+                };
+                if let Some(result) = result {
+                    self.binary(state, result, *op, lhs, rhs);
+                }
+            }
+            Rvalue::UnaryOp(UnOp::PtrMetadata, operand) => {
+                let len = operand
+                    .place()
+                    .and_then(|place| self.var(RelVar::Len(place.as_local()?)));
+                if let Some(x) = x {
+                    match len {
+                        Some(len) => self.assign(state, x, (len, 0)),
+                        None => self.define_unknown(state, x),
+                    }
+                }
+            }
+            Rvalue::Cast(kind, operand, ty) => {
+                if let Some(x) = x {
+                    match self.term(operand) {
+                        Some(term)
+                            if *kind == CastKind::IntToInt
+                                && self.preserves_value(operand.ty(self.body, self.tcx), *ty) =>
+                        {
+                            self.assign(state, x, term)
+                        }
+                        _ => self.define_unknown(state, x),
+                    }
+                } else if let Some(place) = operand.place() {
+                    self.copy_len(state, sink, &place);
+                }
+            }
+            Rvalue::Ref(_, kind, place) => {
+                if matches!(kind, BorrowKind::Mut { .. }) {
+                    self.forget_len(state, place);
+                }
+                self.copy_len(state, sink, place);
+            }
+            Rvalue::RawPtr(kind, place) => {
+                if *kind == RawPtrKind::Mut {
+                    self.forget_len(state, place);
+                }
+                self.copy_len(state, sink, place);
+            }
+            Rvalue::Aggregate(kind, operands) => match **kind {
+                AggregateKind::Adt(def_id, ..) if def_id == self.essa_def_id => {
+                    if let Some(x) = x {
+                        self.transfer_essa(state, x, operands, essa);
+                    }
+                }
+                _ => {
+                    if let Some(x) = x {
+                        self.define_unknown(state, x);
+                    }
+                }
+            },
+            _ => {
+                if let Some(x) = x {
+                    self.define_unknown(state, x);
+                }
+            }
+        }
+    }
+
+    /// `x = ESSAstmt { op1, op2, cmp, switch_bb }` copies `op1`, which satisfies `op1 cmp op2`
+    /// in the block. When `op2` is a place, the ESSA statement of `op2` follows with the
+    /// reversed comparison, and `x` is related to its copy of `op2` instead.
+    fn transfer_essa(
+        &self,
+        state: &mut State,
+        x: usize,
+        operands: &IndexVec<FieldIdx, Operand<'tcx>>,
+        essa: &mut Option<(usize, u64, u64)>,
+    ) {
+        let code = |n: usize| -> Option<u64> {
+            let constant = operands.get(FieldIdx::from_usize(n))?.constant()?;
+            let scalar = constant.const_.try_to_scalar_int()?;
+            u64::try_from(scalar.to_uint(scalar.size())).ok()
+        };
+        let (Some(op1), Some(op2), Some(cmp), Some(switch_bb)) = (
+            operands.get(FieldIdx::ZERO).and_then(|op| self.term(op)),
+            operands.get(FieldIdx::from_usize(1)),
+            code(2),
+            code(3),
+        ) else {
+            self.define_unknown(state, x);
+            return;
+        };
+        self.assign(state, x, op1);
+        if op2.constant().is_some() {
+            if let Some(bound) = self.term(op2) {
+                self.compare(state, (x, 0), cmp, bound);
+            }
+            return;
+        }
+        match essa.take() {
+            Some((first, first_cmp, first_bb)) if first_bb == switch_bb => {
+                self.compare(state, (first, 0), first_cmp, (x, 0));
+            }
+            _ => *essa = Some((x, cmp, switch_bb)),
+        }
+    }
+
+    /// Constrain `a cmp b` for a comparison encoded as in the ESSA statements.
+    fn compare(&self, state: &mut State, (a, ca): Term, cmp: u64, (b, cb): Term) {
+        // a + ca <= b + cb - strict, i.e., a - b <= cb - ca - strict.
+        let mut at_most = |lhs: usize, rhs: usize, offset: i64, strict: i64| {
+            if let Some(c) = offset.checked_sub(strict) {
+                state.dbm.constrain(lhs, rhs, c);
+            }
+        };
+        let (ab, ba) = (cb.checked_sub(ca), ca.checked_sub(cb));
+        match (cmp, ab, ba) {
+            // Lt
+            (1, Some(ab), _) => at_most(a, b, ab, 1),
+            // Le
+            (2, Some(ab), _) => at_most(a, b, ab, 0),
+            // Ge
+            (3, _, Some(ba)) => at_most(b, a, ba, 0),
+            // Gt
+            (4, _, Some(ba)) => at_most(b, a, ba, 1),
+            // Eq
+            (5, Some(ab), Some(ba)) => {
+                at_most(a, b, ab, 0);
+                at_most(b, a, ba, 0);
+            }
+            _ => {}
+        }
+    }
+
+    fn binary(
+        &self,
+        state: &mut State,
+        x: usize,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+    ) {
+        let (Some(a), Some(b)) = (self.term(lhs), self.term(rhs)) else {
+            self.define_unknown(state, x);
+            return;
+        };
+        let constant = |(v, c): Term| (v == ZERO).then_some(c);
+        match op {
+            BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => {
+                match (constant(a), constant(b)) {
+                    (_, Some(c)) if c.checked_add(a.1).is_some() => {
+                        self.assign(state, x, (a.0, a.1 + c))
+                    }
+                    (Some(c), _) if c.checked_add(b.1).is_some() => {
+                        self.assign(state, x, (b.0, b.1 + c))
+                    }
+                    _ => {
+                        // x - a lies within the bounds of b, and x - b within those of a.
+                        self.define_unknown(state, x);
+                        self.offset_by_bounds(state, x, a.0, b.0, false);
+                        self.offset_by_bounds(state, x, b.0, a.0, false);
+                    }
+                }
+            }
+            BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => match constant(b) {
+                Some(c) if a.1.checked_sub(c).is_some() => self.assign(state, x, (a.0, a.1 - c)),
+                _ => {
+                    // x - a lies within the negated bounds of b.
+                    self.define_unknown(state, x);
+                    self.offset_by_bounds(state, x, a.0, b.0, true);
+                }
+            },
+            _ => self.define_unknown(state, x),
+        }
+    }
+
+    /// Constrain `x - base` by the constant bounds of `offset`, negated if `negate`.
+    fn offset_by_bounds(
+        &self,
+        state: &mut State,
+        x: usize,
+        base: usize,
+        offset: usize,
+        negate: bool,
+    ) {
+        let (upper, neg_lower) = (state.dbm.get(offset, ZERO), state.dbm.get(ZERO, offset));
+        let (upper, neg_lower) = if negate {
+            (neg_lower, upper)
+        } else {
+            (upper, neg_lower)
+        };
+        if upper != INF {
+            state.dbm.constrain(x, base, upper);
+        }
+        if neg_lower != INF {
+            state.dbm.constrain(base, x, neg_lower);
+        }
+    }
+
+    /// Whether every value of `from` is a value of `to`.
+    fn preserves_value(&self, from: Ty<'tcx>, to: Ty<'tcx>) -> bool {
+        let bits = |ty: Ty<'tcx>| ty.primitive_size(self.tcx).bits();
+        match (from.is_signed(), to.is_signed()) {
+            (false, false) | (true, true) => bits(to) >= bits(from),
+            (false, true) => bits(to) > bits(from),
+            (true, false) => false,
+        }
+    }
+
+    /// The operand as a variable plus a constant.
+    fn term(&self, operand: &Operand<'tcx>) -> Option<Term> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                Some((self.var(RelVar::Place(*place))?, 0))
+            }
+            Operand::Constant(constant) => {
+                let value = self.constant(&constant.const_)?;
+                Some((ZERO, value))
+            }
+        }
+    }
+
+    fn constant(&self, c: &Const<'tcx>) -> Option<i64> {
+        let ty = c.ty();
+        if !ty.is_integral() {
+            return None;
+        }
+        let typing_env = TypingEnv::post_analysis(self.tcx, self.body.source.def_id());
+        let scalar = c.try_eval_scalar_int(self.tcx, typing_env)?;
+        if ty.is_signed() {
+            i64::try_from(scalar.to_int(scalar.size())).ok()
+        } else {
+            i64::try_from(scalar.to_uint(scalar.size())).ok()
+        }
+    }
+
+    /// `x := y + c`, and `x` is defined.
+    fn assign(&self, state: &mut State, x: usize, (y, c): Term) {
+        state.dbm.assign(x, y, c);
+        state.defined[x] = true;
+    }
+
+    /// `x` takes an unknown value of its type.
+    fn define_unknown(&self, state: &mut State, x: usize) {
+        state.dbm.forget(x);
+        state.defined[x] = true;
+        let non_negative = match self.vars[x] {
+            RelVar::Place(place) => matches!(place.ty(self.body, self.tcx).ty.kind(), ty::Uint(_)),
+            _ => true,
+        };
+        if non_negative {
+            state.dbm.constrain(ZERO, x, 0);
+        }
+    }
+
+    /// A new value of `local` invalidates the variables derived from its old value.
+    fn define_local(&self, state: &mut State, local: Local) {
+        for (x, var) in self.vars.iter().enumerate() {
+            let derived = match var {
+                RelVar::Place(place) => place.local == local && !place.projection.is_empty(),
+                RelVar::Len(len) => *len == local,
+                RelVar::Zero => false,
+            };
+            if derived {
+                state.dbm.forget(x);
+                state.defined[x] = false;
+            }
+        }
+        if let Some(len) = self.var(RelVar::Len(local)) {
+            self.define_unknown(state, len);
+            if let ty::Array(_, n) = self.body.local_decls[local].ty.kind()
+                && let Some(n) = n.try_to_target_usize(self.tcx)
+                && let Ok(n) = i64::try_from(n)
+            {
+                self.assign(state, len, (ZERO, n));
+            }
+        }
+    }
+
+    /// The length of what `sink` points to is the one of `place`, which is a local, or a local
+    /// it points to.
+    fn copy_len(&self, state: &mut State, sink: &Place<'tcx>, place: &Place<'tcx>) {
+        let (Some(sink), Some(root)) = (sink.as_local(), self.len_root(place)) else {
+            return;
+        };
+        if let (Some(x), Some(y)) = (self.var(RelVar::Len(sink)), self.var(RelVar::Len(root)))
+            && state.defined[y]
+        {
+            self.assign(state, x, (y, 0));
+        }
+    }
+
+    /// A mutable borrow of a vector may change its length.
+    fn forget_len(&self, state: &mut State, place: &Place<'tcx>) {
+        if place.projection.is_empty()
+            && let Some(len) = self.var(RelVar::Len(place.local))
+        {
+            self.define_unknown(state, len);
+        }
+    }
+
+    /// The local whose length is the one of `place`, i.e., `p` for `p` and `*p`.
+    fn len_root(&self, place: &Place<'tcx>) -> Option<Local> {
+        match place.projection.as_slice() {
+            [] | [ProjectionElem::Deref] => Some(place.local),
+            _ => None,
+        }
+    }
+}
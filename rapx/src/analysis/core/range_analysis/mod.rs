@@ -3,10 +3,14 @@
 #![allow(dead_code)]
 pub mod default;
 pub mod domain;
+pub mod typed;
 use crate::{
     analysis::{
         Analysis,
-        core::range_analysis::domain::domain::{ConstConvert, IntervalArithmetic},
+        core::range_analysis::domain::{
+            domain::{ConstConvert, IntervalArithmetic},
+            relational::{FnRelations, Relation},
+        },
     },
     utils::source::get_fn_name_byid,
 };
@@ -16,6 +20,7 @@ use rust_intervals::Interval;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir::{BinOp, Place};
+use serde::{Deserialize, Serialize};
 
 use std;
 use std::{
@@ -29,11 +34,13 @@ pub type RAVecResultMap<'tcx, T> = FxHashMap<DefId, Vec<HashMap<Place<'tcx>, Ran
 pub type PathConstraint<'tcx> = HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>>;
 pub type PathConstraintMap<'tcx> =
     FxHashMap<DefId, HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>>>;
+pub type RelationMap<'tcx> = FxHashMap<DefId, FnRelations<'tcx>>;
 pub struct RAResultWrapper<'tcx, T: IntervalArithmetic>(pub RAResult<'tcx, T>);
 pub struct RAResultMapWrapper<'tcx, T: IntervalArithmetic>(pub RAResultMap<'tcx, T>);
 pub struct RAVecResultMapWrapper<'tcx, T: IntervalArithmetic>(pub RAVecResultMap<'tcx, T>);
 pub struct PathConstraintWrapper<'tcx>(pub PathConstraint<'tcx>);
 pub struct PathConstraintMapWrapper<'tcx>(pub PathConstraintMap<'tcx>);
+pub struct RelationMapWrapper<'tcx>(pub RelationMap<'tcx>);
 
 /// The core trait for performing range analysis over Rust MIR.
 ///
@@ -65,6 +72,16 @@ pub trait RangeAnalysis<'tcx, T: IntervalArithmetic + ConstConvert + Debug>: Ana
 
     /// The function returns path constraints for all functions in the crate.
     fn get_all_path_constraints(&self) -> PathConstraintMap<'tcx>;
+
+    /// The function returns the bounds of the difference `a - b` between two integer places
+    /// of a function, computed by the relational domain. The bounds hold wherever both places
+    /// are defined, e.g., `j - len <= -1` for `let j = i;` in the body of a loop guarded by
+    /// `i < len`. The places are those of the original body, and the bounds of a place
+    /// assigned several times cover all its assignments.
+    fn get_relation(&self, def_id: DefId, a: Place<'tcx>, b: Place<'tcx>) -> Option<Relation>;
+
+    /// The function returns the relations of all functions in the crate.
+    fn get_all_relations(&self) -> RelationMap<'tcx>;
 }
 
 impl<'tcx, T> Display for RAResultWrapper<'tcx, T>
@@ -165,7 +182,7 @@ where
         Ok(())
     }
 }
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub enum RangeType {
     Unknown,
     Regular,
@@ -181,6 +198,23 @@ impl fmt::Display for RangeType {
         write!(f, "{}", s)
     }
 }
+impl<'tcx> Display for RelationMapWrapper<'tcx>
+where
+    DefId: Debug,
+    Place<'tcx>: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Print relational range analysis results ===")?;
+        for (def_id, relations) in &self.0 {
+            let fn_name = get_fn_name_byid(def_id);
+            writeln!(f, "Function: {:?} =>", fn_name)?;
+            for (a, b, relation) in relations.bounded() {
+                writeln!(f, "  {:?} - {:?} => {}", a, b, relation)?;
+            }
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq)]
 
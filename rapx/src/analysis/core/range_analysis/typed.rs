@@ -0,0 +1,311 @@
+//! Range analysis with a numeric domain per place type.
+//!
+//! [`RangeAnalyzer`] computes the ranges of all places in a single domain `T`. The
+//! [`TypedRangeAnalyzer`] runs it once in the [`Number`] domain, which holds the values of all
+//! numeric types, and converts the range of every place to the domain of its type. Places of
+//! non-numeric types keep their ranges in the `i64` domain.
+
+use super::{
+    PathConstraint, PathConstraintMap, Range, RangeAnalysis, RangeType, RelationMap,
+    default::RangeAnalyzer,
+    domain::{
+        domain::{ConstConvert, IntervalArithmetic},
+        numeric::{Float, FromNumber, Int128, Number},
+        relational::Relation,
+    },
+};
+use crate::{analysis::Analysis, utils::source::get_fn_name_byid};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{BasicBlock, Body, Place},
+    ty::{self, Ty, TyCtxt},
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Display},
+};
+
+pub type TypedRAResult<'tcx> = HashMap<Place<'tcx>, TypedRange>;
+pub type TypedRAResultMap<'tcx> = FxHashMap<DefId, TypedRAResult<'tcx>>;
+pub struct TypedRAResultMapWrapper<'tcx>(pub TypedRAResultMap<'tcx>);
+
+macro_rules! numeric_domains {
+    ($($variant:ident($domain:ty, $name:literal)),* $(,)?) => {
+        /// The numeric domains of the place types.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum NumericDomain {
+            $($variant,)*
+        }
+
+        impl NumericDomain {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(NumericDomain::$variant => $name,)*
+                }
+            }
+        }
+
+        /// A range in the domain of the type of its place.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum TypedRange {
+            $($variant(Range<$domain>),)*
+        }
+
+        impl TypedRange {
+            /// Convert a range computed in the [`Number`] domain to the given domain.
+            pub fn new(domain: NumericDomain, range: &Range<Number>) -> Self {
+                let (lower, upper) = (range.get_lower(), range.get_upper());
+                match domain {
+                    $(NumericDomain::$variant => TypedRange::$variant(Range::new(
+                        <$domain>::from_lower(&lower),
+                        <$domain>::from_upper(&upper),
+                        range.rtype.clone(),
+                    )),)*
+                }
+            }
+
+            pub fn domain(&self) -> NumericDomain {
+                match self {
+                    $(TypedRange::$variant(_) => NumericDomain::$variant,)*
+                }
+            }
+        }
+
+        impl Display for TypedRange {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    TypedRange::Float(range) if range.range.upper().is_some_and(|u| u.is_nan()) => {
+                        write!(f, "{} (float, may be NaN)", range)
+                    }
+                    $(TypedRange::$variant(range) => write!(f, "{} ({})", range, $name),)*
+                }
+            }
+        }
+    };
+}
+
+numeric_domains!(
+    I8(i8, "i8"),
+    I16(i16, "i16"),
+    I32(i32, "i32"),
+    I64(i64, "i64"),
+    I128(Int128, "i128"),
+    Isize(isize, "isize"),
+    U8(u8, "u8"),
+    U16(u16, "u16"),
+    U32(u32, "u32"),
+    U64(u64, "u64"),
+    U128(u128, "u128"),
+    Usize(usize, "usize"),
+    Float(Float, "float"),
+);
+
+impl NumericDomain {
+    /// The domain of the values of type `ty`, if it is a primitive numeric type.
+    pub fn of(ty: Ty<'_>) -> Option<Self> {
+        Some(match ty.kind() {
+            ty::Int(ty::IntTy::I8) => NumericDomain::I8,
+            ty::Int(ty::IntTy::I16) => NumericDomain::I16,
+            ty::Int(ty::IntTy::I32) => NumericDomain::I32,
+            ty::Int(ty::IntTy::I64) => NumericDomain::I64,
+            ty::Int(ty::IntTy::I128) => NumericDomain::I128,
+            ty::Int(ty::IntTy::Isize) => NumericDomain::Isize,
+            ty::Uint(ty::UintTy::U8) => NumericDomain::U8,
+            ty::Uint(ty::UintTy::U16) => NumericDomain::U16,
+            ty::Uint(ty::UintTy::U32) => NumericDomain::U32,
+            ty::Uint(ty::UintTy::U64) => NumericDomain::U64,
+            ty::Uint(ty::UintTy::U128) => NumericDomain::U128,
+            ty::Uint(ty::UintTy::Usize) => NumericDomain::Usize,
+            ty::Float(ty::FloatTy::F32 | ty::FloatTy::F64) => NumericDomain::Float,
+            _ => return None,
+        })
+    }
+}
+
+/// The types of the SSA places of a function. The SSA form of a function whose ranges are
+/// restored from the cache is not built, so the types of its places are those of the original
+/// places they rename.
+enum PlaceTys<'a, 'tcx> {
+    Ssa(TyCtxt<'tcx>, &'a Body<'tcx>),
+    Origin(
+        TyCtxt<'tcx>,
+        &'tcx Body<'tcx>,
+        HashMap<Place<'tcx>, Place<'tcx>>,
+    ),
+}
+
+impl<'a, 'tcx> PlaceTys<'a, 'tcx> {
+    fn new<T>(analyzer: &'a RangeAnalyzer<'tcx, T>, def_id: DefId) -> Option<Self>
+    where
+        T: IntervalArithmetic + ConstConvert + Debug,
+    {
+        let tcx = analyzer.tcx;
+        if let Some(body) = analyzer.body_map.get(&def_id) {
+            return Some(PlaceTys::Ssa(tcx, body));
+        }
+        let origins = analyzer
+            .ssa_places_mapping
+            .get(&def_id)?
+            .iter()
+            .flat_map(|(origin, places)| places.iter().map(|place| (*place, *origin)))
+            .collect();
+        Some(PlaceTys::Origin(tcx, tcx.optimized_mir(def_id), origins))
+    }
+
+    fn get(&self, place: Place<'tcx>) -> Option<Ty<'tcx>> {
+        match self {
+            PlaceTys::Ssa(tcx, body) => Some(place.ty(*body, *tcx).ty),
+            PlaceTys::Origin(tcx, body, origins) => Some(origins.get(&place)?.ty(*body, *tcx).ty),
+        }
+    }
+}
+
+/// Computes the range of each place in the domain of its type.
+pub struct TypedRangeAnalyzer<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub debug: bool,
+    /// The analysis computing the ranges of all places in the [`Number`] domain, along with
+    /// the SSA forms and path constraints they are computed on.
+    pub analyzer: RangeAnalyzer<'tcx, Number>,
+    pub ranges: TypedRAResultMap<'tcx>,
+}
+
+impl<'tcx> Analysis for TypedRangeAnalyzer<'tcx> {
+    fn name(&self) -> &'static str {
+        "Typed Range Analysis"
+    }
+
+    fn run(&mut self) {
+        self.analyzer.run();
+        for (def_id, ranges) in &self.analyzer.final_vars {
+            let Some(place_tys) = PlaceTys::new(&self.analyzer, *def_id) else {
+                continue;
+            };
+            let typed = self.ranges.entry(*def_id).or_default();
+            for (place, range) in ranges {
+                let domain = place_tys
+                    .get(*place)
+                    .and_then(NumericDomain::of)
+                    .unwrap_or(NumericDomain::I64);
+                typed.insert(*place, TypedRange::new(domain, range));
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.analyzer.reset();
+        self.ranges.clear();
+    }
+}
+
+impl<'tcx> TypedRangeAnalyzer<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, debug: bool) -> Self {
+        Self {
+            tcx,
+            debug,
+            analyzer: RangeAnalyzer::new(tcx, debug),
+            ranges: FxHashMap::default(),
+        }
+    }
+
+    pub fn get_fn_range(&self, def_id: DefId) -> Option<TypedRAResult<'tcx>> {
+        self.ranges.get(&def_id).cloned()
+    }
+
+    pub fn get_all_fn_ranges(&self) -> TypedRAResultMap<'tcx> {
+        self.ranges.clone()
+    }
+
+    /// The bounds of an integer place, before they are converted to the domain of its type,
+    /// or `None` if its range is not regular or not bounded by integers, e.g., if a bound is
+    /// unknown.
+    pub fn get_bounds(&self, def_id: DefId, place: Place<'tcx>) -> Option<(i128, i128)> {
+        let range = self.analyzer.final_vars.get(&def_id)?.get(&place)?;
+        let lower = range.get_lower().as_int()?;
+        let upper = range.get_upper().as_int()?;
+        (range.rtype == RangeType::Regular && lower <= upper).then_some((lower, upper))
+    }
+
+    /// The hull of the bounds of all SSA versions of a place, or `None` if any of them is not
+    /// bounded.
+    pub fn get_hull(&self, def_id: DefId, place: Place<'tcx>) -> Option<(i128, i128)> {
+        let versions = self.analyzer.ssa_places_mapping.get(&def_id)?.get(&place)?;
+        let ranges = self.analyzer.final_vars.get(&def_id)?;
+        let mut hull: Option<(i128, i128)> = None;
+        for version in versions.iter().chain([&place]) {
+            if !ranges.contains_key(version) {
+                continue;
+            }
+            let (lo, hi) = self.get_bounds(def_id, *version)?;
+            hull = Some(match hull {
+                Some((hull_lo, hull_hi)) => (hull_lo.min(lo), hull_hi.max(hi)),
+                None => (lo, hi),
+            });
+        }
+        hull
+    }
+
+    pub fn get_fn_path_constraints(&self, def_id: DefId) -> Option<PathConstraint<'tcx>> {
+        self.analyzer.get_fn_path_constraints(def_id)
+    }
+
+    pub fn get_all_path_constraints(&self) -> PathConstraintMap<'tcx> {
+        self.analyzer.get_all_path_constraints()
+    }
+
+    pub fn get_relation(&self, def_id: DefId, a: Place<'tcx>, b: Place<'tcx>) -> Option<Relation> {
+        self.analyzer.get_relation(def_id, a, b)
+    }
+
+    pub fn get_all_relations(&self) -> RelationMap<'tcx> {
+        self.analyzer.get_all_relations()
+    }
+
+    /// See [`RangeAnalyzer::path_constraints_to`].
+    pub fn path_constraints_to(
+        &self,
+        def_id: DefId,
+        block: BasicBlock,
+        max_paths: usize,
+    ) -> Option<PathConstraint<'tcx>> {
+        self.analyzer.path_constraints_to(def_id, block, max_paths)
+    }
+
+    /// Compute the path constraints of a function without computing the ranges.
+    pub fn start_path_constraints_analysis_for_defid(
+        &mut self,
+        def_id: DefId,
+    ) -> Option<PathConstraint<'tcx>> {
+        self.analyzer
+            .start_path_constraints_analysis_for_defid(def_id)
+    }
+
+    /// Compute the path constraints of all functions without computing the ranges.
+    pub fn start_path_constraints_analysis(&mut self) {
+        self.analyzer.start_path_constraints_analysis();
+    }
+
+    /// See [`RangeAnalyzer::transform_restored_bodies`].
+    pub fn transform_restored_bodies(&mut self) {
+        self.analyzer.transform_restored_bodies();
+    }
+}
+
+impl<'tcx> Display for TypedRAResultMapWrapper<'tcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Print range analysis results ===")?;
+        for (def_id, ra_result) in &self.0 {
+            let fn_name = get_fn_name_byid(def_id);
+            writeln!(f, "Function: {:?} =>", fn_name)?;
+
+            let mut sorted: Vec<_> = ra_result.iter().collect();
+            sorted.sort_by_key(|(place, _)| place.local.as_usize());
+
+            for (place, range) in sorted {
+                writeln!(f, "  {:?} => {}", place, range)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -98,6 +98,8 @@ impl<'tcx> SSATrans<'tcx> {
 pub struct PassRunner<'tcx> {
     tcx: TyCtxt<'tcx>,
     pub places_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
+    /// The places defined by the phi and ESSA statements, by the original place they rename.
+    pub ssa_locals_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
 }
 pub fn lvalue_check(mir_string: &str) -> bool {
     let re = regex::Regex::new(r"_(\d+)\s*=").unwrap();
@@ -201,6 +203,7 @@ impl<'tcx> PassRunner<'tcx> {
         Self {
             tcx,
             places_map: HashMap::default(),
+            ssa_locals_map: HashMap::default(),
         }
     }
 
@@ -226,5 +229,6 @@ impl<'tcx> PassRunner<'tcx> {
         replacer.insert_essa_statement(body);
         replacer.rename_variables(body);
         self.places_map = replacer.ssatransformer.places_map.clone();
+        self.ssa_locals_map = replacer.ssatransformer.ssa_locals_map.clone();
     }
 }
@@ -1,11 +1,17 @@
 pub mod core;
 pub mod graphs;
+pub mod oob;
 pub mod opt;
+pub mod overflow;
+pub mod panic_safety;
 pub mod rcanary;
 pub mod safedrop;
 pub mod scan;
+pub mod sendsync;
 pub mod senryx;
+pub mod taint;
 pub mod test;
+pub mod uninit;
 pub mod upg;
 pub mod utils;
 
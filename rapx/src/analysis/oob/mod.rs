@@ -0,0 +1,836 @@
+use crate::{
+    analysis::{
+        Analysis,
+        core::range_analysis::{domain::SymbolicExpr::SymbExpr, typed::TypedRangeAnalyzer},
+        utils::def_path::def_path_def_ids,
+    },
+    report::{self, Finding, FindingKind},
+    utils::{
+        log::{
+            are_spans_in_same_file, get_variable_name, relative_pos_range, span_to_filename,
+            span_to_line_number, span_to_source_code,
+        },
+        source::get_name,
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, BinOp, BorrowKind, CastKind, Local, Location, Operand, Place, ProjectionElem,
+        RawPtrKind, Rvalue, StatementKind, Terminator, TerminatorKind, UnOp,
+        visit::{PlaceContext, Visitor},
+    },
+    ty::{self, Ty, TyCtxt, adjustment::PointerCoercion},
+};
+use rustc_span::{Span, Symbol, source_map::Spanned, sym};
+
+/// An access that is out of bounds, or an unchecked access that is proven in bounds.
+#[derive(Debug, Clone)]
+pub struct BoundsIssue {
+    pub kind: FindingKind,
+    pub message: String,
+    pub span: Span,
+}
+
+/// Out-of-bounds checker based on the range analysis.
+///
+/// The checker relates the index of every indexing expression, `get_unchecked` call and
+/// `ptr::add` call to the length of the indexed array, slice or vector. The index is compared
+/// with the length both numerically, using the intervals of the range analysis when the length
+/// is a constant, and symbolically, using the expressions the index is computed from, e.g.,
+/// `v[v.len()]`. Accesses that are out of bounds whenever they are reached are reported as
+/// errors. Calls to `get_unchecked` whose index is proven in bounds, either by its interval or
+/// by a comparison with the length on every path, are reported as notes suggesting safe
+/// indexing instead.
+pub struct OobCheck<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub issues: FxHashMap<DefId, Vec<BoundsIssue>>,
+}
+
+impl<'tcx> Analysis for OobCheck<'tcx> {
+    fn name(&self) -> &'static str {
+        "Out-of-bounds checker."
+    }
+
+    fn run(&mut self) {
+        let tcx = self.tcx;
+        let mut analyzer = TypedRangeAnalyzer::new(tcx, false);
+        analyzer.run();
+        analyzer.transform_restored_bodies();
+        let apis = BoundsApis::new(tcx);
+        let mut def_ids: Vec<DefId> = analyzer.analyzer.body_map.keys().copied().collect();
+        def_ids.sort_by_key(|def_id| tcx.def_path_str(*def_id));
+        for def_id in def_ids {
+            if report::is_all_allowed(tcx, def_id, FindingKind::OOB) {
+                continue;
+            }
+            let checker = BodyChecker::new(tcx, def_id, &analyzer, &apis);
+            let issues = checker.check();
+            if !issues.is_empty() {
+                self.report(def_id, &issues);
+                self.issues.insert(def_id, issues);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.issues.clear();
+    }
+}
+
+impl<'tcx> OobCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            issues: FxHashMap::default(),
+        }
+    }
+
+    fn report(&self, def_id: DefId, issues: &[BoundsIssue]) {
+        let tcx = self.tcx;
+        let fn_name = get_name(tcx, tcx.typeck_root_def_id(def_id))
+            .unwrap_or_else(|| Symbol::intern("no symbol available"));
+        let title = format!("Bounds issue detected in function {:?}", fn_name);
+        let body_span = tcx.optimized_mir(def_id).span;
+        let mut reported = false;
+        for issue in issues {
+            let finding = Finding::new(
+                issue.kind,
+                format!("{}: {}", title, issue.message),
+                issue.span,
+            )
+            .with_function(def_id);
+            if !report::record(finding) {
+                continue;
+            }
+            if !reported {
+                rap_warn!("{}", title);
+                reported = true;
+            }
+            rap_warn!("{}", issue.message);
+
+            let level = match issue.kind {
+                FindingKind::OutOfBounds => Level::Error,
+                _ => Level::Note,
+            };
+            let code_source = span_to_source_code(body_span);
+            let filename = span_to_filename(body_span);
+            let mut snippet = Snippet::source(&code_source)
+                .line_start(span_to_line_number(body_span))
+                .origin(&filename)
+                .fold(true);
+            if are_spans_in_same_file(body_span, issue.span) && body_span.contains(issue.span) {
+                snippet = snippet.annotation(
+                    level
+                        .span(relative_pos_range(body_span, issue.span))
+                        .label(&issue.message),
+                );
+            }
+            let message = level.title(issue.kind.description()).snippet(snippet);
+            let renderer = Renderer::styled();
+            report::print_snippet(renderer.render(message));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoundsApi {
+    /// `len` of slices and vectors.
+    Len,
+    /// `as_ptr` and `as_mut_ptr`, whose result points into the receiver.
+    AsPtr,
+    /// `get_unchecked` and `get_unchecked_mut` of slices.
+    GetUnchecked,
+    /// `add` of raw pointers.
+    PtrAdd,
+}
+
+struct BoundsApis {
+    apis: FxHashMap<DefId, BoundsApi>,
+}
+
+impl BoundsApis {
+    fn new(tcx: TyCtxt<'_>) -> Self {
+        let mut apis = FxHashMap::default();
+        for (path, api) in [
+            ("slice::len", BoundsApi::Len),
+            ("alloc::vec::Vec::len", BoundsApi::Len),
+            ("slice::as_ptr", BoundsApi::AsPtr),
+            ("slice::as_mut_ptr", BoundsApi::AsPtr),
+            ("alloc::vec::Vec::as_ptr", BoundsApi::AsPtr),
+            ("alloc::vec::Vec::as_mut_ptr", BoundsApi::AsPtr),
+            ("slice::get_unchecked", BoundsApi::GetUnchecked),
+            ("slice::get_unchecked_mut", BoundsApi::GetUnchecked),
+            ("const_ptr::add", BoundsApi::PtrAdd),
+            ("mut_ptr::add", BoundsApi::PtrAdd),
+        ] {
+            let path: Vec<&str> = path.split("::").collect();
+            for def_id in def_path_def_ids(&tcx, &path) {
+                apis.insert(def_id, api);
+            }
+        }
+        BoundsApis { apis }
+    }
+
+    fn get(&self, func: &Operand<'_>) -> Option<BoundsApi> {
+        let (def_id, _) = func.const_fn_def()?;
+        self.apis.get(&def_id).copied()
+    }
+}
+
+/// The maximum number of paths enumerated to find the comparisons guarding an access.
+const MAX_PATHS: usize = 64;
+
+/// The maximum number of definitions followed when building a symbolic expression.
+const MAX_DEPTH: usize = 16;
+
+/// A closed interval of integer values.
+type Interval = (i128, i128);
+
+fn fmt_interval((lo, hi): Interval) -> String {
+    format!("[{}, {}]", lo, hi)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccessKind {
+    Index,
+    GetUnchecked,
+    /// A pointer offset, and whether the resulting pointer is dereferenced.
+    PtrAdd(bool),
+}
+
+/// An access to an array, a slice or a vector at an index.
+struct Access<'tcx> {
+    kind: AccessKind,
+    block: BasicBlock,
+    span: Span,
+    /// The place whose length bounds the index, i.e., an array, a vector, or a reference or a
+    /// pointer to a slice.
+    object: Place<'tcx>,
+    index: Operand<'tcx>,
+}
+
+/// The definition of a local in the body.
+#[derive(Clone, Copy)]
+enum Def<'tcx> {
+    Assign(&'tcx Rvalue<'tcx>),
+    Call(BoundsApi, &'tcx [Spanned<Operand<'tcx>>]),
+    /// An argument, a call to another function, or a write through a projection or a mutable
+    /// borrow.
+    Opaque,
+}
+
+/// An expression normalized into an optional symbolic term plus a constant offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Linear<'tcx> {
+    term: Option<SymbExpr<'tcx>>,
+    offset: i128,
+}
+
+struct BodyChecker<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    /// The body before the SSA transformation, on which the accesses and the path constraints
+    /// are computed.
+    body: &'tcx rustc_middle::mir::Body<'tcx>,
+    analyzer: &'a TypedRangeAnalyzer<'tcx>,
+    apis: &'a BoundsApis,
+    defs: FxHashMap<Local, Vec<Def<'tcx>>>,
+}
+
+impl<'tcx, 'a> BodyChecker<'tcx, 'a> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        def_id: DefId,
+        analyzer: &'a TypedRangeAnalyzer<'tcx>,
+        apis: &'a BoundsApis,
+    ) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        let mut defs: FxHashMap<Local, Vec<Def<'tcx>>> = FxHashMap::default();
+        for arg in body.args_iter() {
+            defs.entry(arg).or_default().push(Def::Opaque);
+        }
+        for data in body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (dst, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                let def = if dst.projection.is_empty() {
+                    Def::Assign(rvalue)
+                } else {
+                    Def::Opaque
+                };
+                defs.entry(dst.local).or_default().push(def);
+                if let Rvalue::Ref(_, BorrowKind::Mut { .. }, place)
+                | Rvalue::RawPtr(RawPtrKind::Mut, place) = rvalue
+                {
+                    defs.entry(place.local).or_default().push(Def::Opaque);
+                }
+            }
+            if let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            {
+                let def = match apis.get(func) {
+                    Some(api) if destination.projection.is_empty() => Def::Call(api, args),
+                    _ => Def::Opaque,
+                };
+                defs.entry(destination.local).or_default().push(def);
+            }
+        }
+        Self {
+            tcx,
+            def_id,
+            body,
+            analyzer,
+            apis,
+            defs,
+        }
+    }
+
+    fn check(&self) -> Vec<BoundsIssue> {
+        let mut collector = AccessCollector {
+            checker: self,
+            accesses: Vec::new(),
+            derefs: FxHashSet::default(),
+        };
+        collector.visit_body(self.body);
+        let AccessCollector {
+            mut accesses,
+            derefs,
+            ..
+        } = collector;
+        for access in &mut accesses {
+            if access.kind == AccessKind::PtrAdd(false) {
+                let dst = self.body[access.block].terminator().kind.clone();
+                if let TerminatorKind::Call { destination, .. } = dst
+                    && derefs.contains(&destination.local)
+                {
+                    access.kind = AccessKind::PtrAdd(true);
+                }
+            }
+        }
+        accesses
+            .iter()
+            .filter_map(|access| self.check_access(access))
+            .collect()
+    }
+
+    fn check_access(&self, access: &Access<'tcx>) -> Option<BoundsIssue> {
+        let length = self.linear(&self.length(access.object))?;
+        let index = self.linear(&self.symbolic(&access.index, MAX_DEPTH));
+        let interval = match &index {
+            Some(Linear { term: None, offset }) => Some((*offset, *offset)),
+            _ => self.interval(&access.index),
+        };
+        let what = self.describe_access(access);
+        // An offset may point one past the end unless the resulting pointer is dereferenced.
+        let slack = match access.kind {
+            AccessKind::PtrAdd(false) => 1,
+            _ => 0,
+        };
+        let noun = match access.kind {
+            AccessKind::PtrAdd(_) => "offset",
+            _ => "index",
+        };
+
+        // Out of bounds with a constant length.
+        if let (
+            Linear {
+                term: None,
+                offset: len,
+            },
+            Some((lo, hi)),
+        ) = (&length, interval)
+            && lo >= len + slack
+        {
+            let interval = if lo == hi {
+                format!("{}", lo)
+            } else {
+                format!("in {}", fmt_interval((lo, hi)))
+            };
+            return Some(BoundsIssue {
+                kind: FindingKind::OutOfBounds,
+                message: format!(
+                    "{} is out of bounds: the {} is {} but the length is {}.",
+                    what, noun, interval, len
+                ),
+                span: access.span,
+            });
+        }
+        // Out of bounds with a symbolic length.
+        if let (
+            Linear {
+                term: Some(len), ..
+            },
+            Some(index),
+        ) = (&length, &index)
+            && index.term.as_ref() == Some(len)
+            && index.offset >= length.offset + slack
+        {
+            let (comparison, deref) = match access.kind {
+                AccessKind::PtrAdd(false) => ("greater than", ""),
+                AccessKind::PtrAdd(true) => (
+                    "not less than",
+                    ", and the resulting pointer is dereferenced",
+                ),
+                _ => ("not less than", ""),
+            };
+            return Some(BoundsIssue {
+                kind: FindingKind::OutOfBounds,
+                message: format!(
+                    "{} is out of bounds: the {} is `{}`, {} the length `{}`{}.",
+                    what,
+                    noun,
+                    self.describe_linear(index),
+                    comparison,
+                    self.describe_linear(&length),
+                    deref
+                ),
+                span: access.span,
+            });
+        }
+
+        if access.kind != AccessKind::GetUnchecked {
+            return None;
+        }
+        let reason = match (&length, interval) {
+            (
+                Linear {
+                    term: None,
+                    offset: len,
+                },
+                Some((lo, hi)),
+            ) if lo >= 0 && hi < *len => {
+                format!(
+                    "the index is in {} and the length is {}",
+                    fmt_interval((lo, hi)),
+                    len
+                )
+            }
+            _ => match index {
+                Some(index) if self.is_guarded(access.block, &index, &length) => format!(
+                    "the index `{}` is less than `{}` on every path",
+                    self.describe_linear(&index),
+                    self.describe_linear(&length)
+                ),
+                _ if self.is_related_below(access) => format!(
+                    "the index `{}` is less than `{}` wherever both are defined",
+                    self.describe_operand(&access.index),
+                    self.describe_linear(&length)
+                ),
+                _ => return None,
+            },
+        };
+        Some(BoundsIssue {
+            kind: FindingKind::UncheckedInBounds,
+            message: format!(
+                "{} is always in bounds: {}, so safe indexing can be used instead.",
+                what, reason
+            ),
+            span: access.span,
+        })
+    }
+
+    /// Test if a comparison ensuring that the index is less than the length holds on every
+    /// path reaching the block.
+    fn is_guarded(&self, block: BasicBlock, index: &Linear<'tcx>, length: &Linear<'tcx>) -> bool {
+        let Some(constraints) = self
+            .analyzer
+            .path_constraints_to(self.def_id, block, MAX_PATHS)
+        else {
+            return false;
+        };
+        let less_than = |lhs: &Place<'tcx>, rhs: &Place<'tcx>, strict: bool| {
+            let lhs = self.linear(&self.place_expr(*lhs, MAX_DEPTH));
+            let rhs = self.linear(&self.place_expr(*rhs, MAX_DEPTH));
+            match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => {
+                    lhs.term == index.term
+                        && rhs.term == length.term
+                        && index.offset - lhs.offset + rhs.offset + i128::from(!strict)
+                            <= length.offset
+                }
+                _ => false,
+            }
+        };
+        !constraints.is_empty()
+            && constraints.values().all(|constraints| {
+                constraints.iter().any(|(p1, p2, op)| match op {
+                    BinOp::Lt => less_than(p1, p2, true),
+                    BinOp::Le => less_than(p1, p2, false),
+                    BinOp::Gt => less_than(p2, p1, true),
+                    BinOp::Ge => less_than(p2, p1, false),
+                    _ => false,
+                })
+            })
+    }
+
+    /// Test if the relational domain bounds the index below a place holding the length of the
+    /// object, e.g., `let j = i;` in the body of a loop guarded by `i < v.len()`.
+    fn is_related_below(&self, access: &Access<'tcx>) -> bool {
+        let Some(index) = access.index.place() else {
+            return false;
+        };
+        self.length_places(access.object).into_iter().any(|length| {
+            self.analyzer
+                .get_relation(self.def_id, index, Place::from(length))
+                .and_then(|relation| relation.upper)
+                .is_some_and(|upper| upper < 0)
+        })
+    }
+
+    /// The locals assigned once with the length of an object.
+    fn length_places(&self, object: Place<'tcx>) -> Vec<Local> {
+        let mut locals: Vec<Local> = self
+            .defs
+            .iter()
+            .filter_map(|(local, defs)| {
+                let receiver = match defs.as_slice() {
+                    [Def::Call(BoundsApi::Len, args)] => args[0].node.place()?,
+                    [Def::Assign(Rvalue::UnaryOp(UnOp::PtrMetadata, operand))] => {
+                        operand.place()?
+                    }
+                    _ => return None,
+                };
+                (self.object(receiver) == object).then_some(*local)
+            })
+            .collect();
+        locals.sort();
+        locals
+    }
+
+    /// The bounded interval of an integer operand, which covers all SSA versions of a local.
+    fn interval(&self, operand: &Operand<'tcx>) -> Option<Interval> {
+        self.analyzer.get_hull(self.def_id, operand.place()?)
+    }
+
+    fn single_def(&self, local: Local) -> Option<Def<'tcx>> {
+        match self.defs.get(&local).map(Vec::as_slice) {
+            Some([def]) => Some(*def),
+            _ => None,
+        }
+    }
+
+    /// The array, vector or slice a place refers to, following references, pointers derived
+    /// from it and unsizing casts.
+    fn object(&self, place: Place<'tcx>) -> Place<'tcx> {
+        let mut place = place;
+        for _ in 0..MAX_DEPTH {
+            if let [ProjectionElem::Deref] = place.projection.as_slice() {
+                place = Place::from(place.local);
+                continue;
+            }
+            let Some(local) = place.as_local() else {
+                break;
+            };
+            let source = match self.single_def(local) {
+                Some(Def::Assign(
+                    Rvalue::Use(Operand::Copy(source) | Operand::Move(source))
+                    | Rvalue::Ref(_, _, source)
+                    | Rvalue::RawPtr(_, source)
+                    | Rvalue::Cast(
+                        CastKind::PtrToPtr | CastKind::PointerCoercion(PointerCoercion::Unsize, _),
+                        Operand::Copy(source) | Operand::Move(source),
+                        _,
+                    ),
+                )) => Some(*source),
+                Some(Def::Call(BoundsApi::AsPtr, args)) => args[0].node.place(),
+                _ => None,
+            };
+            match source {
+                Some(source) => place = source,
+                None => break,
+            }
+        }
+        place
+    }
+
+    /// The symbolic length of an object, which is a constant for arrays.
+    fn length(&self, object: Place<'tcx>) -> SymbExpr<'tcx> {
+        let mut ty = object.ty(self.body, self.tcx).ty;
+        while let Some(inner) = ty.builtin_deref(true) {
+            ty = inner;
+        }
+        if let ty::Array(_, len) = ty.kind()
+            && let Some(len) = len.try_to_target_usize(self.tcx)
+        {
+            return SymbExpr::Constant(rustc_middle::mir::Const::from_usize(self.tcx, len));
+        }
+        SymbExpr::Unary(
+            UnOp::PtrMetadata,
+            Box::new(SymbExpr::Place(self.tcx.arena.dropless.alloc(object))),
+        )
+    }
+
+    fn symbolic(&self, operand: &Operand<'tcx>, depth: usize) -> SymbExpr<'tcx> {
+        match operand {
+            Operand::Constant(constant) => SymbExpr::Constant(constant.const_),
+            Operand::Copy(place) | Operand::Move(place) => self.place_expr(*place, depth),
+        }
+    }
+
+    /// The symbolic expression of a place in terms of arguments, lengths and constants,
+    /// following the locals defined once. Locals defined more than once are unknown.
+    fn place_expr(&self, place: Place<'tcx>, depth: usize) -> SymbExpr<'tcx> {
+        let opaque = SymbExpr::Place(self.tcx.arena.dropless.alloc(place));
+        if depth == 0 {
+            return opaque;
+        }
+        let depth = depth - 1;
+        // The result of a checked operation, e.g., `(_5.0)` after `_5 = AddWithOverflow(..)`.
+        if let [ProjectionElem::Field(field, _)] = place.projection.as_slice()
+            && field.as_usize() == 0
+            && let Some(Def::Assign(Rvalue::BinaryOp(op, box (lhs, rhs)))) =
+                self.single_def(place.local)
+        {
+            return self.binary(*op, lhs, rhs, depth);
+        }
+        let Some(local) = place.as_local() else {
+            return opaque;
+        };
+        match self.defs.get(&local).map(Vec::as_slice) {
+            Some([Def::Assign(rvalue)]) => match rvalue {
+                Rvalue::Use(operand) => self.symbolic(operand, depth),
+                Rvalue::BinaryOp(op, box (lhs, rhs)) => self.binary(*op, lhs, rhs, depth),
+                Rvalue::UnaryOp(UnOp::PtrMetadata, operand) => match operand.place() {
+                    Some(source) => self.length(self.object(source)),
+                    None => opaque,
+                },
+                _ => opaque,
+            },
+            Some([Def::Call(BoundsApi::Len, args)]) => match args[0].node.place() {
+                Some(receiver) => self.length(self.object(receiver)),
+                None => opaque,
+            },
+            Some([_]) | None => opaque,
+            Some(_) => SymbExpr::Unknown,
+        }
+    }
+
+    fn binary(
+        &self,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        depth: usize,
+    ) -> SymbExpr<'tcx> {
+        let op = match op {
+            BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => BinOp::Add,
+            BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => BinOp::Sub,
+            _ => return SymbExpr::Unknown,
+        };
+        let mut expr = SymbExpr::Binary(
+            op,
+            Box::new(self.symbolic(lhs, depth)),
+            Box::new(self.symbolic(rhs, depth)),
+        );
+        expr.simplify();
+        expr
+    }
+
+    /// Normalize an expression with at most one non-constant term.
+    fn linear(&self, expr: &SymbExpr<'tcx>) -> Option<Linear<'tcx>> {
+        match expr {
+            SymbExpr::Constant(constant) => {
+                let scalar = constant.try_to_scalar_int()?;
+                let value = i128::try_from(scalar.to_uint(scalar.size())).ok()?;
+                Some(Linear {
+                    term: None,
+                    offset: value,
+                })
+            }
+            SymbExpr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.linear(lhs)?, self.linear(rhs)?);
+                match op {
+                    BinOp::Add => {
+                        if lhs.term.is_some() && rhs.term.is_some() {
+                            return None;
+                        }
+                        Some(Linear {
+                            term: lhs.term.or(rhs.term),
+                            offset: lhs.offset.checked_add(rhs.offset)?,
+                        })
+                    }
+                    BinOp::Sub => {
+                        let term = match (lhs.term, rhs.term) {
+                            (term, None) => term,
+                            (Some(lhs), Some(rhs)) if lhs == rhs => None,
+                            _ => return None,
+                        };
+                        Some(Linear {
+                            term,
+                            offset: lhs.offset.checked_sub(rhs.offset)?,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            SymbExpr::Place(_) | SymbExpr::Unary(UnOp::PtrMetadata, _) => Some(Linear {
+                term: Some(expr.clone()),
+                offset: 0,
+            }),
+            _ => None,
+        }
+    }
+
+    fn name(&self, place: &Place<'tcx>) -> String {
+        place
+            .as_local()
+            .and_then(|local| get_variable_name(self.body, local.as_usize()))
+            .unwrap_or_else(|| format!("{:?}", place))
+    }
+
+    /// The name of an operand, or of the variable it is a copy of if it is a temporary.
+    fn describe_operand(&self, operand: &Operand<'tcx>) -> String {
+        let Some(mut place) = operand.place() else {
+            return format!("{:?}", operand);
+        };
+        for _ in 0..MAX_DEPTH {
+            let Some(local) = place.as_local() else {
+                break;
+            };
+            if get_variable_name(self.body, local.as_usize()).is_some() {
+                break;
+            }
+            match self.single_def(local) {
+                Some(Def::Assign(Rvalue::Use(Operand::Copy(source) | Operand::Move(source)))) => {
+                    place = *source
+                }
+                _ => break,
+            }
+        }
+        self.name(&place)
+    }
+
+    fn describe_term(&self, term: &SymbExpr<'tcx>) -> String {
+        match term {
+            SymbExpr::Place(place) => self.name(place),
+            SymbExpr::Unary(UnOp::PtrMetadata, object) => match object.as_ref() {
+                SymbExpr::Place(place) => format!("{}.len()", self.name(place)),
+                _ => format!("{}", term),
+            },
+            _ => format!("{}", term),
+        }
+    }
+
+    fn describe_linear(&self, linear: &Linear<'tcx>) -> String {
+        match (&linear.term, linear.offset) {
+            (None, offset) => format!("{}", offset),
+            (Some(term), 0) => self.describe_term(term),
+            (Some(term), offset) if offset > 0 => {
+                format!("{} + {}", self.describe_term(term), offset)
+            }
+            (Some(term), offset) => format!("{} - {}", self.describe_term(term), -offset),
+        }
+    }
+
+    fn describe_access(&self, access: &Access<'tcx>) -> String {
+        let object = self.name(&access.object);
+        match access.kind {
+            AccessKind::Index => format!("Indexing `{}`", object),
+            AccessKind::GetUnchecked => format!("`get_unchecked` on `{}`", object),
+            AccessKind::PtrAdd(_) => format!("`add` on a pointer into `{}`", object),
+        }
+    }
+
+    fn is_usize(&self, operand: &Operand<'tcx>) -> bool {
+        operand.ty(self.body, self.tcx).is_usize()
+    }
+
+    fn is_indexable(&self, ty: Ty<'tcx>) -> bool {
+        let mut ty = ty;
+        while let Some(inner) = ty.builtin_deref(true) {
+            ty = inner;
+        }
+        match ty.kind() {
+            ty::Array(..) | ty::Slice(_) => true,
+            ty::Adt(adt, _) => self.tcx.is_diagnostic_item(sym::Vec, adt.did()),
+            _ => false,
+        }
+    }
+}
+
+/// Collects the accesses in a body, as well as the locals that are dereferenced.
+struct AccessCollector<'tcx, 'a, 'b> {
+    checker: &'b BodyChecker<'tcx, 'a>,
+    accesses: Vec<Access<'tcx>>,
+    derefs: FxHashSet<Local>,
+}
+
+impl<'tcx, 'a, 'b> Visitor<'tcx> for AccessCollector<'tcx, 'a, 'b> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        let tcx = self.checker.tcx;
+        if place.projection.first() == Some(&ProjectionElem::Deref) {
+            self.derefs.insert(place.local);
+        }
+        for (i, elem) in place.projection.iter().enumerate() {
+            let ProjectionElem::Index(index) = elem else {
+                continue;
+            };
+            let base = Place {
+                local: place.local,
+                projection: tcx.mk_place_elems(&place.projection[..i]),
+            };
+            let object = match base.projection.as_slice() {
+                [] | [ProjectionElem::Deref] => self.checker.object(base),
+                _ => base,
+            };
+            self.accesses.push(Access {
+                kind: AccessKind::Index,
+                block: location.block,
+                span: self.checker.body.source_info(location).span,
+                object,
+                index: Operand::Copy(Place::from(index)),
+            });
+        }
+        self.super_place(place, context, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        self.super_terminator(terminator, location);
+        let TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+            return;
+        };
+        if args.len() != 2 || !self.checker.is_usize(&args[1].node) {
+            return;
+        }
+        let tcx = self.checker.tcx;
+        let kind = match self.checker.apis.get(func) {
+            Some(BoundsApi::GetUnchecked) => AccessKind::GetUnchecked,
+            Some(BoundsApi::PtrAdd) => AccessKind::PtrAdd(false),
+            Some(_) => return,
+            None => {
+                // `Index::index` and `IndexMut::index_mut`, e.g., indexing vectors.
+                let Some((def_id, generic_args)) = func.const_fn_def() else {
+                    return;
+                };
+                let lang_items = tcx.lang_items();
+                let trait_id = tcx.trait_of_assoc(def_id);
+                if trait_id.is_none()
+                    || (trait_id != lang_items.index_trait()
+                        && trait_id != lang_items.index_mut_trait())
+                    || !self.checker.is_indexable(generic_args.type_at(0))
+                {
+                    return;
+                }
+                AccessKind::Index
+            }
+        };
+        let Some(receiver) = args[0].node.place() else {
+            return;
+        };
+        self.accesses.push(Access {
+            kind,
+            block: location.block,
+            span: terminator.source_info.span,
+            object: self.checker.object(receiver),
+            index: args[1].node.clone(),
+        });
+    }
+}
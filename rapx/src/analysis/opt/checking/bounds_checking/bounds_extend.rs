@@ -3,6 +3,7 @@ use once_cell::sync::OnceCell;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::Span;
 
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -82,6 +83,16 @@ impl OptCheck for BoundsExtendCheck {
 }
 
 fn report_extend_bug(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::BoundsExtend,
+            "Unnecessary bound checkings detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -98,5 +109,5 @@ fn report_extend_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Manipulate memory directly."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -7,6 +7,7 @@ use rustc_hir::{Expr, ExprKind, intravisit};
 use rustc_middle::{mir::Local, ty::TyCtxt};
 use rustc_span::Span;
 
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -285,6 +286,18 @@ fn find_downside_index_node(graph: &Graph, node_idx: Local) -> Vec<Local> {
 
 fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record: &Vec<Local>) {
     let upperbound_span = graph.nodes[upperbound_node_idx].span;
+    let mut finding = Finding::new(
+        FindingKind::BoundsLen,
+        "Unnecessary bounds checkings detected",
+        upperbound_span,
+    )
+    .with_function(graph.def_id);
+    for node_idx in index_record {
+        finding = finding.with_secondary(graph.nodes[*node_idx].span, "Checked here.");
+    }
+    if !report::record(finding) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(upperbound_span);
     let mut snippet = Snippet::source(&code_source)
@@ -309,5 +322,5 @@ fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs instead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -7,6 +7,7 @@ use rustc_span::Span;
 
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::utils::def_path::DefPath;
+use crate::report::{self, Finding, FindingKind};
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_first_line, span_to_line_number,
     span_to_source_code, span_to_trimmed_span,
@@ -109,9 +110,9 @@ impl OptCheck for BoundsLoopPushCheck {
         }
     }
 
-    fn report(&self, _: &Graph) {
+    fn report(&self, graph: &Graph) {
         for (loop_span, push_record) in self.record.iter() {
-            report_loop_push_bug(*loop_span, push_record);
+            report_loop_push_bug(graph, *loop_span, push_record);
         }
     }
 
@@ -120,7 +121,19 @@ impl OptCheck for BoundsLoopPushCheck {
     }
 }
 
-fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
+fn report_loop_push_bug(graph: &Graph, loop_span: Span, push_record: &Vec<Span>) {
+    let mut finding = Finding::new(
+        FindingKind::BoundsLoopPush,
+        "Unnecessary bounds checkings detected",
+        span_to_trimmed_span(span_to_first_line(loop_span)),
+    )
+    .with_function(graph.def_id);
+    for push_span in push_record {
+        finding = finding.with_secondary(*push_span, "Push happens here.");
+    }
+    if !report::record(finding) {
+        return;
+    }
     let code_source = span_to_source_code(loop_span);
     let filename = span_to_filename(loop_span);
     let mut snippet = Snippet::source(&code_source)
@@ -146,5 +159,5 @@ fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
         .title("Unnecessary bounds checkings detected")
         .snippet(snippet);
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
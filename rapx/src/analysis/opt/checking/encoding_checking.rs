@@ -5,6 +5,7 @@ pub mod vec_encoding;
 
 use std::collections::HashSet;
 
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -62,6 +63,16 @@ impl OptCheck for EncodingCheck {
 }
 
 fn report_encoding_bug(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::Encoding,
+            "Unnecessary encoding checkings detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -78,7 +89,7 @@ fn report_encoding_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
 
 // Warning: WE APPROXIMATELY VIEW CONST U8s AS SAFE INPUT
@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -84,6 +85,16 @@ impl OptCheck for StringLowercaseCheck {
 }
 
 fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::StringLowercase,
+            "Unnecessary encoding checkings detected",
+            contains_span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -100,5 +111,5 @@ fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use to_ascii_lowercase istead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
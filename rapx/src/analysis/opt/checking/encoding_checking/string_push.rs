@@ -6,6 +6,7 @@ use rustc_middle::{mir::Local, ty::TyCtxt};
 use rustc_span::Span;
 
 use super::value_is_from_const;
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -109,13 +110,29 @@ impl OptCheck for StringPushCheck {
 }
 
 fn report_string_push_bug(graph: &Graph, spans: &Vec<Span>) {
+    let spans: Vec<&Span> = spans
+        .iter()
+        .filter(|span| {
+            report::record(
+                Finding::new(
+                    FindingKind::StringPush,
+                    "Unnecessary encoding checkings detected",
+                    **span,
+                )
+                .with_function(graph.def_id),
+            )
+        })
+        .collect();
+    if spans.is_empty() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let mut snippet = Snippet::source(&code_source)
         .line_start(span_to_line_number(graph.span))
         .origin(&filename)
         .fold(true);
-    for span in spans.iter() {
+    for span in spans {
         snippet = snippet.annotation(
             Level::Error
                 .span(relative_pos_range(graph.span, *span))
@@ -127,5 +144,5 @@ fn report_string_push_bug(graph: &Graph, spans: &Vec<Span>) {
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs instead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -83,6 +84,16 @@ impl OptCheck for LocalSetCheck {
 }
 
 fn report_local_set(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::LocalSet,
+            "Unnecessary data collection initialization detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -101,5 +112,5 @@ fn report_local_set(graph: &Graph, span: Span) {
             Level::Help.title("Move it into parameter list and use hash table to save allocation."),
         );
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
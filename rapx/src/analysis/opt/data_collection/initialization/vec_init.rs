@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -67,6 +68,16 @@ impl OptCheck for VecInitCheck {
 }
 
 fn report_vec_init(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::VecInit,
+            "Unnecessary data collection initialization detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -83,5 +94,5 @@ fn report_vec_init(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs to skip initialization."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -2,6 +2,7 @@ use once_cell::sync::OnceCell;
 
 use rustc_middle::ty::TyCtxt;
 
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::Graph, *},
@@ -93,6 +94,16 @@ impl OptCheck for FlattenCollectCheck {
 }
 
 fn report_flatten_collect(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::FlattenCollect,
+            "Data collection inefficiency detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -110,5 +121,5 @@ fn report_flatten_collect(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use extend manually."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -11,6 +11,7 @@ use crate::{
 use once_cell::sync::OnceCell;
 use rustc_middle::{mir::Local, ty::TyCtxt};
 
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 use rustc_span::Span;
 
@@ -113,6 +114,17 @@ impl OptCheck for UnreservedHashCheck {
 }
 
 fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::UnreservedHash,
+            "Improper data collection detected",
+            hash_span,
+        )
+        .with_function(graph.def_id)
+        .with_secondary(insert_span, "Insertion happens here."),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(hash_span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -134,5 +146,5 @@ fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span)
         .snippet(snippet)
         .footer(Level::Help.title("Reserve enough space."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -11,6 +11,7 @@ use crate::{
 use once_cell::sync::OnceCell;
 use rustc_middle::{mir::Local, ty::TyCtxt};
 
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 use rustc_span::Span;
 
@@ -193,6 +194,16 @@ impl OptCheck for UnreservedVecCheck {
 }
 
 fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::UnreservedVec,
+            "Improper data collection detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -209,5 +220,5 @@ fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Reserve enough space."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
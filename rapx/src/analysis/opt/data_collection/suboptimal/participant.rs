@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -80,6 +81,16 @@ impl OptCheck for ParticipantCheck {
 }
 
 fn report_participant(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::Participant,
+            "Suboptimal data collection detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -96,5 +107,5 @@ fn report_participant(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use faster data collection or hash operators instead. Static container is also a choice"));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
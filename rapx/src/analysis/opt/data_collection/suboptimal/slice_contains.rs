@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -79,6 +80,16 @@ impl OptCheck for SliceContainsCheck {
 }
 
 fn report_slice_contains_bug(graph: &Graph, contains_span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::SliceContains,
+            "Improper data collection detected",
+            contains_span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -95,5 +106,5 @@ fn report_slice_contains_bug(graph: &Graph, contains_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use Set instead of Slice."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -88,6 +89,16 @@ impl OptCheck for VecRemoveCheck {
 }
 
 fn report_vec_remove_bug(graph: &Graph, span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::VecRemove,
+            "Improper data collection detected",
+            span,
+        )
+        .with_function(graph.def_id),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -104,5 +115,5 @@ fn report_vec_remove_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use VecQueue instead of Vec."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
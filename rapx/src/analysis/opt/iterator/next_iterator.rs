@@ -8,6 +8,7 @@ use rustc_span::Span;
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::opt::OptCheck;
 use crate::analysis::utils::def_path::DefPath;
+use crate::report::{self, Finding, FindingKind};
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code,
 };
@@ -91,7 +92,7 @@ impl OptCheck for NextIteratorCheck {
     }
 
     fn report(&self, graph: &Graph) {
-        report_next_iterator_bug(&self.next_record, &self.chain_record, graph.span);
+        report_next_iterator_bug(graph, &self.next_record, &self.chain_record);
     }
 
     fn cnt(&self) -> usize {
@@ -99,7 +100,20 @@ impl OptCheck for NextIteratorCheck {
     }
 }
 
-fn report_next_iterator_bug(next_record: &Vec<Span>, chain_record: &Vec<Span>, graph_span: Span) {
+fn report_next_iterator_bug(graph: &Graph, next_record: &Vec<Span>, chain_record: &Vec<Span>) {
+    let graph_span = graph.span;
+    let mut finding = Finding::new(
+        FindingKind::NextIterator,
+        "Inefficient iterators detected",
+        graph_span,
+    )
+    .with_function(graph.def_id);
+    for span in next_record.iter().chain(chain_record) {
+        finding = finding.with_secondary(*span, "Iterator used here.");
+    }
+    if !report::record(finding) {
+        return;
+    }
     let code_source = span_to_source_code(graph_span);
     let filename = span_to_filename(graph_span);
     let mut snippet = Snippet::source(&code_source)
@@ -117,5 +131,5 @@ fn report_next_iterator_bug(next_record: &Vec<Span>, chain_record: &Vec<Span>, g
         .snippet(snippet)
         .footer(Level::Help.title("Use chunk iterators."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -126,6 +127,17 @@ fn find_hash_new_node(graph: &Graph, node: &GraphNode) -> Option<Local> {
 }
 
 fn report_hash_key_cloning(graph: &Graph, clone_span: Span, insert_span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::HashKeyCloning,
+            "Unnecessary memory cloning detected",
+            clone_span,
+        )
+        .with_function(graph.def_id)
+        .with_secondary(insert_span, "Used here."),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(clone_span);
     let snippet = Snippet::source(&code_source)
@@ -147,7 +159,7 @@ fn report_hash_key_cloning(graph: &Graph, clone_span: Span, insert_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use borrowings as keys."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
 
 pub struct HashKeyCloningCheck {
@@ -1,3 +1,4 @@
+use crate::report::{self, Finding, FindingKind};
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -155,6 +156,17 @@ impl OptCheck for UsedAsImmutableCheck {
 }
 
 fn report_used_as_immutable(graph: &Graph, clone_span: Span, use_span: Span) {
+    if !report::record(
+        Finding::new(
+            FindingKind::UsedAsImmutable,
+            "Unnecessary memory cloning detected",
+            clone_span,
+        )
+        .with_function(graph.def_id)
+        .with_secondary(use_span, "Used here."),
+    ) {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(clone_span);
     let snippet = Snippet::source(&code_source)
@@ -176,5 +188,5 @@ fn report_used_as_immutable(graph: &Graph, clone_span: Span, use_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use borrowings instead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    report::print_snippet(renderer.render(message));
 }
@@ -5,7 +5,10 @@ pub mod memory_cloning;
 
 use rustc_middle::ty::TyCtxt;
 
-use crate::utils::log::span_to_source_code;
+use crate::{
+    report::{self, FindingKind},
+    utils::log::span_to_source_code,
+};
 
 use super::core::dataflow::{default::DataFlowAnalyzer, graph::Graph};
 use checking::bounds_checking::BoundsCheck;
@@ -67,6 +70,10 @@ impl<'tcx> Opt<'tcx> {
         let mut statistics = vec![0 as usize; 6];
 
         dataflow.graphs.iter().for_each(|(_, graph)| {
+            if report::is_all_allowed(self.tcx, graph.def_id, FindingKind::OPT) {
+                return;
+            }
+
             let mut bounds_check = BoundsCheck::new();
             bounds_check.check(graph, &self.tcx);
             statistics[0] += bounds_check.cnt();
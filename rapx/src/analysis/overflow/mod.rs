@@ -0,0 +1,439 @@
+use crate::{
+    analysis::{Analysis, core::range_analysis::typed::TypedRangeAnalyzer},
+    report::{self, Finding, FindingKind},
+    utils::{
+        log::{
+            are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
+            span_to_source_code,
+        },
+        source::get_name,
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, BinOp, Body, CastKind, Local, LocalKind, Operand, Place, Rvalue, StatementKind,
+    },
+    ty::{self, Ty, TyCtxt},
+};
+use rustc_span::{Span, Symbol};
+
+/// An integer operation whose operands may be out of the range it accepts.
+#[derive(Debug, Clone)]
+pub struct ArithIssue {
+    pub kind: FindingKind,
+    pub message: String,
+    pub span: Span,
+}
+
+/// Integer overflow checker based on the range analysis.
+///
+/// The checker evaluates the intervals of the operands of every integer operation and reports
+/// additions, subtractions and multiplications whose result may not fit in its type, which
+/// panic in debug builds but wrap silently in release builds, `as` casts that may truncate,
+/// divisions and remainders by a divisor that may be zero, and shifts by an amount that may
+/// exceed the bit width. Operands without a bounded interval are not reported. A subtraction
+/// guarded by a comparison of its operands on every path, e.g., `if a > b { a - b }`, is not
+/// reported either.
+pub struct OverflowCheck<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub issues: FxHashMap<DefId, Vec<ArithIssue>>,
+}
+
+impl<'tcx> Analysis for OverflowCheck<'tcx> {
+    fn name(&self) -> &'static str {
+        "Integer overflow checker."
+    }
+
+    fn run(&mut self) {
+        let tcx = self.tcx;
+        let mut analyzer = TypedRangeAnalyzer::new(tcx, false);
+        analyzer.run();
+        analyzer.transform_restored_bodies();
+        let mut def_ids: Vec<DefId> = analyzer.analyzer.body_map.keys().copied().collect();
+        def_ids.sort_by_key(|def_id| tcx.def_path_str(*def_id));
+        for def_id in def_ids {
+            if report::is_all_allowed(tcx, def_id, FindingKind::OVERFLOW) {
+                continue;
+            }
+            if !analyzer.analyzer.final_vars.contains_key(&def_id) {
+                continue;
+            }
+            let checker = BodyChecker {
+                tcx,
+                body: &analyzer.analyzer.body_map[&def_id],
+                origin: tcx.optimized_mir(def_id),
+                def_id,
+                analyzer: &analyzer,
+            };
+            let issues = checker.check();
+            if !issues.is_empty() {
+                self.report(def_id, &issues);
+                self.issues.insert(def_id, issues);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.issues.clear();
+    }
+}
+
+impl<'tcx> OverflowCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            issues: FxHashMap::default(),
+        }
+    }
+
+    fn report(&self, def_id: DefId, issues: &[ArithIssue]) {
+        let tcx = self.tcx;
+        let fn_name = get_name(tcx, tcx.typeck_root_def_id(def_id))
+            .unwrap_or_else(|| Symbol::intern("no symbol available"));
+        let title = format!(
+            "Integer arithmetic issue detected in function {:?}",
+            fn_name
+        );
+        let body_span = tcx.optimized_mir(def_id).span;
+        let mut reported = false;
+        for issue in issues {
+            let finding = Finding::new(
+                issue.kind,
+                format!("{}: {}", title, issue.message),
+                issue.span,
+            )
+            .with_function(def_id);
+            if !report::record(finding) {
+                continue;
+            }
+            if !reported {
+                rap_warn!("{}", title);
+                reported = true;
+            }
+            rap_warn!("{}", issue.message);
+
+            let code_source = span_to_source_code(body_span);
+            let filename = span_to_filename(body_span);
+            let mut snippet = Snippet::source(&code_source)
+                .line_start(span_to_line_number(body_span))
+                .origin(&filename)
+                .fold(true);
+            if are_spans_in_same_file(body_span, issue.span) && body_span.contains(issue.span) {
+                snippet = snippet.annotation(
+                    Level::Warning
+                        .span(relative_pos_range(body_span, issue.span))
+                        .label(&issue.message),
+                );
+            }
+            let message = Level::Warning
+                .title(issue.kind.description())
+                .snippet(snippet);
+            let renderer = Renderer::styled();
+            report::print_snippet(renderer.render(message));
+        }
+    }
+}
+
+/// The maximum number of paths enumerated to find the comparisons guarding a subtraction.
+const MAX_PATHS: usize = 64;
+
+/// A closed interval of integer values.
+type Interval = (i128, i128);
+
+fn fmt_interval((lo, hi): Interval) -> String {
+    format!("[{}, {}]", lo, hi)
+}
+
+/// The values of an integer type, or `None` for other types.
+fn type_bounds<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Interval> {
+    if !matches!(ty.kind(), ty::Int(_) | ty::Uint(_)) {
+        return None;
+    }
+    let (size, signed) = ty.int_size_and_signed(tcx);
+    let bits = size.bits();
+    Some(match (signed, bits) {
+        (true, 128) => (i128::MIN, i128::MAX),
+        (false, 128) => (0, i128::MAX),
+        (true, _) => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+        (false, _) => (0, (1 << bits) - 1),
+    })
+}
+
+fn contains((lo, hi): Interval, (inner_lo, inner_hi): Interval) -> bool {
+    lo <= inner_lo && inner_hi <= hi
+}
+
+struct BodyChecker<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    /// The body after the SSA transformation, on which the ranges are computed.
+    body: &'a Body<'tcx>,
+    /// The body before the SSA transformation, on which the path constraints are computed.
+    origin: &'tcx Body<'tcx>,
+    def_id: DefId,
+    analyzer: &'a TypedRangeAnalyzer<'tcx>,
+}
+
+impl<'tcx, 'a> BodyChecker<'tcx, 'a> {
+    fn check(&self) -> Vec<ArithIssue> {
+        let mut issues = Vec::new();
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            let mut subs = 0;
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (_, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                let span = stmt.source_info.span;
+                let issue = match rvalue {
+                    Rvalue::BinaryOp(op, box (lhs, rhs)) => match op {
+                        BinOp::Add | BinOp::AddWithOverflow => {
+                            self.check_arith("+", lhs, rhs, |a, b| {
+                                (a.0.saturating_add(b.0), a.1.saturating_add(b.1))
+                            })
+                        }
+                        BinOp::Sub | BinOp::SubWithOverflow => {
+                            subs += 1;
+                            if self.is_guarded_sub(bb, subs - 1) {
+                                None
+                            } else {
+                                self.check_arith("-", lhs, rhs, |a, b| {
+                                    (a.0.saturating_sub(b.1), a.1.saturating_sub(b.0))
+                                })
+                            }
+                        }
+                        BinOp::Mul | BinOp::MulWithOverflow => {
+                            self.check_arith("*", lhs, rhs, |a, b| {
+                                let products = [
+                                    a.0.saturating_mul(b.0),
+                                    a.0.saturating_mul(b.1),
+                                    a.1.saturating_mul(b.0),
+                                    a.1.saturating_mul(b.1),
+                                ];
+                                (
+                                    *products.iter().min().unwrap(),
+                                    *products.iter().max().unwrap(),
+                                )
+                            })
+                        }
+                        BinOp::Div => self.check_divisor("/", rhs),
+                        BinOp::Rem => self.check_divisor("%", rhs),
+                        BinOp::Shl | BinOp::ShlUnchecked => self.check_shift("<<", lhs, rhs),
+                        BinOp::Shr | BinOp::ShrUnchecked => self.check_shift(">>", lhs, rhs),
+                        _ => None,
+                    },
+                    Rvalue::Cast(CastKind::IntToInt, operand, target) => {
+                        self.check_cast(operand, *target)
+                    }
+                    _ => None,
+                };
+                if let Some((kind, message)) = issue {
+                    issues.push(ArithIssue {
+                        kind,
+                        message,
+                        span,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        operand.ty(&self.body.local_decls, self.tcx)
+    }
+
+    /// The bounded interval of an integer operand. Places whose range is unbounded or does not
+    /// fit in their type are ignored.
+    fn interval(&self, operand: &Operand<'tcx>) -> Option<Interval> {
+        let bounds = type_bounds(self.tcx, self.operand_ty(operand))?;
+        let interval = match operand {
+            Operand::Constant(constant) => {
+                let scalar = constant.const_.try_to_scalar_int()?;
+                let value = if bounds.0 < 0 {
+                    scalar.to_int(scalar.size())
+                } else {
+                    i128::try_from(scalar.to_uint(scalar.size())).ok()?
+                };
+                (value, value)
+            }
+            Operand::Copy(place) | Operand::Move(place) => {
+                self.analyzer.get_bounds(self.def_id, *place)?
+            }
+        };
+        contains(bounds, interval).then_some(interval)
+    }
+
+    fn check_arith(
+        &self,
+        symbol: &str,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        eval: impl Fn(Interval, Interval) -> Interval,
+    ) -> Option<(FindingKind, String)> {
+        let ty = self.operand_ty(lhs);
+        let bounds = type_bounds(self.tcx, ty)?;
+        let (a, b) = (self.interval(lhs)?, self.interval(rhs)?);
+        let result = eval(a, b);
+        if contains(bounds, result) {
+            return None;
+        }
+        Some((
+            FindingKind::ArithmeticOverflow,
+            format!(
+                "`{}` on `{}` may overflow and wrap silently in release builds: {} {} {} gives {}, outside {}.",
+                symbol,
+                ty,
+                fmt_interval(a),
+                symbol,
+                fmt_interval(b),
+                fmt_interval(result),
+                fmt_interval(bounds)
+            ),
+        ))
+    }
+
+    fn check_cast(
+        &self,
+        operand: &Operand<'tcx>,
+        target: Ty<'tcx>,
+    ) -> Option<(FindingKind, String)> {
+        let bounds = type_bounds(self.tcx, target)?;
+        let value = self.interval(operand)?;
+        if contains(bounds, value) {
+            return None;
+        }
+        Some((
+            FindingKind::TruncatingCast,
+            format!(
+                "Cast from `{}` to `{}` may truncate: the value is in {}, outside {}.",
+                self.operand_ty(operand),
+                target,
+                fmt_interval(value),
+                fmt_interval(bounds)
+            ),
+        ))
+    }
+
+    fn check_divisor(
+        &self,
+        symbol: &str,
+        divisor: &Operand<'tcx>,
+    ) -> Option<(FindingKind, String)> {
+        let value = self.interval(divisor)?;
+        if !contains(value, (0, 0)) {
+            return None;
+        }
+        Some((
+            FindingKind::DivisionByZero,
+            format!(
+                "The divisor of `{}` may be zero: it is in {}.",
+                symbol,
+                fmt_interval(value)
+            ),
+        ))
+    }
+
+    fn check_shift(
+        &self,
+        symbol: &str,
+        lhs: &Operand<'tcx>,
+        amount: &Operand<'tcx>,
+    ) -> Option<(FindingKind, String)> {
+        let ty = self.operand_ty(lhs);
+        type_bounds(self.tcx, ty)?;
+        let bits = ty.primitive_size(self.tcx).bits() as i128;
+        let value = self.interval(amount)?;
+        if contains((0, bits - 1), value) {
+            return None;
+        }
+        Some((
+            FindingKind::ShiftOverflow,
+            format!(
+                "`{}` on `{}` may shift by the bit width or more: the amount is in {}, outside {}.",
+                symbol,
+                ty,
+                fmt_interval(value),
+                fmt_interval((0, bits - 1))
+            ),
+        ))
+    }
+
+    /// Test if the `nth` subtraction in the block is guarded by a comparison ensuring that the
+    /// minuend is not less than the subtrahend on every path reaching the block. The path
+    /// constraints are computed on the body before the SSA transformation, which keeps the
+    /// blocks and the order of the statements in them.
+    fn is_guarded_sub(&self, bb: BasicBlock, nth: usize) -> bool {
+        if self.origin.basic_blocks.len() != self.body.basic_blocks.len() {
+            return false;
+        }
+        let Some((lhs, rhs)) = self.origin[bb]
+            .statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StatementKind::Assign(box (
+                    _,
+                    Rvalue::BinaryOp(BinOp::Sub | BinOp::SubWithOverflow, box (lhs, rhs)),
+                )) => Some((lhs, rhs)),
+                _ => None,
+            })
+            .nth(nth)
+        else {
+            return false;
+        };
+        let (Some(lhs), Some(rhs)) = (lhs.place(), rhs.place()) else {
+            return false;
+        };
+        let Some(constraints) = self
+            .analyzer
+            .path_constraints_to(self.def_id, bb, MAX_PATHS)
+        else {
+            return false;
+        };
+        let (lhs, rhs) = (self.root(lhs), self.root(rhs));
+        !constraints.is_empty()
+            && constraints.values().all(|constraints| {
+                constraints.iter().any(|(p1, p2, op)| {
+                    let (p1, p2) = (self.root(*p1), self.root(*p2));
+                    (p1 == lhs && p2 == rhs && matches!(op, BinOp::Gt | BinOp::Ge))
+                        || (p1 == rhs && p2 == lhs && matches!(op, BinOp::Lt | BinOp::Le))
+                })
+            })
+    }
+
+    /// Follow the copies and moves between locals in the body before the SSA transformation.
+    fn root(&self, place: Place<'tcx>) -> Place<'tcx> {
+        let mut place = place;
+        for _ in 0..self.origin.local_decls.len() {
+            let Some(local) = place.as_local() else {
+                break;
+            };
+            match self.unique_use(local) {
+                Some(source) => place = source,
+                None => break,
+            }
+        }
+        place
+    }
+
+    /// The source of a local assigned once by a copy or a move of another place.
+    fn unique_use(&self, local: Local) -> Option<Place<'tcx>> {
+        let mut sources = self.origin.basic_blocks.iter().flat_map(|data| {
+            data.statements.iter().filter_map(|stmt| match &stmt.kind {
+                StatementKind::Assign(box (dst, rvalue)) if dst.as_local() == Some(local) => {
+                    Some(match rvalue {
+                        Rvalue::Use(Operand::Copy(source) | Operand::Move(source)) => Some(*source),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            })
+        });
+        let source = sources.next()??;
+        if sources.next().is_some() || self.origin.local_kind(local) != LocalKind::Temp {
+            return None;
+        }
+        Some(source)
+    }
+}
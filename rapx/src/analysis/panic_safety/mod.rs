@@ -0,0 +1,383 @@
+use crate::{
+    analysis::{Analysis, utils::def_path::def_path_def_ids},
+    report::{self, Finding, FindingKind},
+    utils::{
+        log::{
+            are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
+            span_to_source_code,
+        },
+        source::get_name,
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, CastKind, Const, Local, Operand, Rvalue, StatementKind, TerminatorKind,
+    },
+    ty::{self, Instance, InstanceKind, TyCtxt, TypingEnv},
+};
+use rustc_span::{Span, Symbol};
+use std::collections::VecDeque;
+
+/// Operations that bypass the ownership or initialization invariants of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BypassKind {
+    /// `ptr::read` duplicates the ownership of the value behind the pointer.
+    Read,
+    /// `ptr::copy` and `ptr::copy_nonoverlapping` duplicate the ownership of the values.
+    Copy,
+    /// `Vec::set_len` exposes uninitialized or duplicated elements.
+    SetLen,
+    /// `Vec::from_raw_parts` creates another owner of the buffer.
+    FromRawParts,
+}
+
+impl BypassKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            BypassKind::Read => "the value is duplicated by `ptr::read`",
+            BypassKind::Copy => "the values are duplicated by `ptr::copy`",
+            BypassKind::SetLen => "the length is changed by `Vec::set_len`",
+            BypassKind::FromRawParts => "the buffer is owned again by `Vec::from_raw_parts`",
+        }
+    }
+}
+
+/// A lifetime bypass followed by a call to caller-provided code that may panic before the
+/// invariant is restored.
+#[derive(Debug, Clone)]
+pub struct PanicHazard {
+    pub kind: BypassKind,
+    pub bypass_span: Span,
+    pub panic_span: Span,
+}
+
+/// The well-known APIs involved in panic safety, resolved once per crate.
+struct PanicSafetyApis {
+    bypass: FxHashMap<DefId, BypassKind>,
+    restore: FxHashSet<DefId>,
+}
+
+impl PanicSafetyApis {
+    fn new(tcx: TyCtxt<'_>) -> Self {
+        let resolve = |path: &str| {
+            let path: Vec<&str> = path.split("::").collect();
+            def_path_def_ids(&tcx, &path).collect::<Vec<_>>()
+        };
+        let mut bypass = FxHashMap::default();
+        for (path, kind) in [
+            ("core::ptr::read", BypassKind::Read),
+            ("core::ptr::read_unaligned", BypassKind::Read),
+            ("const_ptr::read", BypassKind::Read),
+            ("const_ptr::read_unaligned", BypassKind::Read),
+            ("mut_ptr::read", BypassKind::Read),
+            ("mut_ptr::read_unaligned", BypassKind::Read),
+            ("core::ptr::copy", BypassKind::Copy),
+            ("core::ptr::copy_nonoverlapping", BypassKind::Copy),
+            ("alloc::vec::Vec::set_len", BypassKind::SetLen),
+            ("alloc::vec::Vec::from_raw_parts", BypassKind::FromRawParts),
+        ] {
+            for def_id in resolve(path) {
+                bypass.insert(def_id, kind);
+            }
+        }
+        let restore = [
+            "core::ptr::write",
+            "core::ptr::write_unaligned",
+            "mut_ptr::write",
+            "mut_ptr::write_unaligned",
+            "core::mem::forget",
+            "alloc::vec::Vec::set_len",
+        ]
+        .into_iter()
+        .flat_map(resolve)
+        .collect();
+        PanicSafetyApis { bypass, restore }
+    }
+}
+
+/// Panic-safety checker for unsafe code.
+///
+/// Similar to the panic-safety checker of Rudra, the checker reports lifetime bypasses, e.g.,
+/// `ptr::read` and `Vec::set_len`, that reach a call to caller-provided code, i.e., a generic
+/// function that cannot be resolved, a trait object, or a function pointer, on the normal path
+/// without an intervening restoration like `ptr::write`, `mem::forget` or another `set_len` on a
+/// place aliasing the bypassed one.
+/// If the caller-provided code panics, the broken invariant is observed during unwinding, e.g.,
+/// the duplicated value is dropped twice.
+pub struct PanicSafetyCheck<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub hazards: FxHashMap<DefId, Vec<PanicHazard>>,
+}
+
+impl<'tcx> Analysis for PanicSafetyCheck<'tcx> {
+    fn name(&self) -> &'static str {
+        "Panic-safety checker."
+    }
+
+    fn run(&mut self) {
+        let tcx = self.tcx;
+        let apis = PanicSafetyApis::new(tcx);
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(
+                tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            ) || tcx.hir_body_const_context(*local_def_id).is_some()
+                || !tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            if report::is_all_allowed(tcx, def_id, &[FindingKind::PanicSafety]) {
+                continue;
+            }
+            let hazards = self.check_body(def_id, &apis);
+            if !hazards.is_empty() {
+                self.report(def_id, &hazards);
+                self.hazards.insert(def_id, hazards);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.hazards.clear();
+    }
+}
+
+impl<'tcx> PanicSafetyCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            hazards: FxHashMap::default(),
+        }
+    }
+
+    fn check_body(&self, def_id: DefId, apis: &PanicSafetyApis) -> Vec<PanicHazard> {
+        let body = self.tcx.optimized_mir(def_id);
+        let aliases = LocalAliases::new(body);
+        let mut hazards = Vec::new();
+        for (bb, data) in body.basic_blocks.iter_enumerated() {
+            if data.is_cleanup {
+                continue;
+            }
+            let terminator = data.terminator();
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                target: Some(target),
+                ..
+            } = &terminator.kind
+            else {
+                continue;
+            };
+            let Some((callee, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let Some(&kind) = apis.bypass.get(&callee) else {
+                continue;
+            };
+            // `set_len(0)` only leaks the elements if the callback panics.
+            if kind == BypassKind::SetLen && args.get(1).is_some_and(|arg| is_zero(&arg.node)) {
+                continue;
+            }
+            rap_debug!("Lifetime bypass {:?} at {:?} of {:?}", kind, bb, def_id);
+            // The pointers and vectors passed to the bypass, and the duplicated value or the
+            // new owner returned by it.
+            let bypassed: FxHashSet<Local> = args
+                .iter()
+                .filter_map(|arg| arg.node.place())
+                .map(|place| place.local)
+                .chain([destination.local])
+                .map(|local| aliases.find(local))
+                .collect();
+            if let Some(panic_span) =
+                self.find_callback(def_id, body, *target, apis, &aliases, &bypassed)
+            {
+                hazards.push(PanicHazard {
+                    kind,
+                    bypass_span: terminator.source_info.span,
+                    panic_span,
+                });
+            }
+        }
+        hazards
+    }
+
+    /// Search the normal paths from the block for the first call to caller-provided code,
+    /// stopping at restorations of the bypassed places.
+    fn find_callback(
+        &self,
+        def_id: DefId,
+        body: &Body<'tcx>,
+        start: BasicBlock,
+        apis: &PanicSafetyApis,
+        aliases: &LocalAliases,
+        bypassed: &FxHashSet<Local>,
+    ) -> Option<Span> {
+        let mut visited = FxHashSet::default();
+        let mut worklist = VecDeque::from([start]);
+        while let Some(bb) = worklist.pop_front() {
+            if !visited.insert(bb) || body.basic_blocks[bb].is_cleanup {
+                continue;
+            }
+            let terminator = body.basic_blocks[bb].terminator();
+            if let TerminatorKind::Call { func, args, .. } = &terminator.kind {
+                if func
+                    .const_fn_def()
+                    .is_some_and(|(callee, _)| apis.restore.contains(&callee))
+                    && args
+                        .first()
+                        .and_then(|arg| arg.node.place())
+                        .is_some_and(|place| bypassed.contains(&aliases.find(place.local)))
+                {
+                    continue;
+                }
+                if self.is_callback(def_id, body, func) {
+                    return Some(terminator.source_info.span);
+                }
+            }
+            worklist.extend(terminator.successors());
+        }
+        None
+    }
+
+    /// Whether the callee is provided by the caller, so that it may panic.
+    fn is_callback(&self, def_id: DefId, body: &Body<'tcx>, func: &Operand<'tcx>) -> bool {
+        let tcx = self.tcx;
+        match func.ty(body, tcx).kind() {
+            ty::FnDef(callee, args) => {
+                // Only trait methods could be dispatched to the code of the caller.
+                if tcx.trait_of_assoc(*callee).is_none() {
+                    return false;
+                }
+                let typing_env = TypingEnv::post_analysis(tcx, def_id);
+                match Instance::try_resolve(tcx, typing_env, *callee, args) {
+                    Ok(Some(instance)) => matches!(instance.def, InstanceKind::Virtual(..)),
+                    _ => true,
+                }
+            }
+            ty::FnPtr(..) => true,
+            _ => false,
+        }
+    }
+
+    fn report(&self, def_id: DefId, hazards: &[PanicHazard]) {
+        let tcx = self.tcx;
+        let fn_name = get_name(tcx, tcx.typeck_root_def_id(def_id))
+            .unwrap_or_else(|| Symbol::intern("no symbol available"));
+        let title = format!("Panic safety issue detected in function {:?}", fn_name);
+        let body_span = tcx.optimized_mir(def_id).span;
+        let mut reported = false;
+        for hazard in hazards {
+            let finding = Finding::new(
+                FindingKind::PanicSafety,
+                format!("{}: {}", title, hazard.kind.description()),
+                hazard.bypass_span,
+            )
+            .with_function(def_id)
+            .with_secondary(
+                hazard.panic_span,
+                "Caller-provided code may panic before the invariant is restored.",
+            );
+            if !report::record(finding) {
+                continue;
+            }
+            if !reported {
+                rap_warn!("{}", title);
+                reported = true;
+            }
+
+            let code_source = span_to_source_code(body_span);
+            let filename = span_to_filename(body_span);
+            let mut snippet = Snippet::source(&code_source)
+                .line_start(span_to_line_number(body_span))
+                .origin(&filename)
+                .fold(true);
+            let bypass_label = format!("Lifetime bypass: {}.", hazard.kind.description());
+            for (sub_span, label) in [
+                (hazard.bypass_span, bypass_label.as_str()),
+                (hazard.panic_span, "Caller-provided code may panic here."),
+            ] {
+                if are_spans_in_same_file(body_span, sub_span) {
+                    snippet = snippet.annotation(
+                        Level::Warning
+                            .span(relative_pos_range(body_span, sub_span))
+                            .label(label),
+                    );
+                }
+            }
+            let message = Level::Warning
+                .title("Panic safety issue detected.")
+                .snippet(snippet)
+                .footer(Level::Help.title(
+                    "Restore the invariant before calling the code, or use a guard that restores it on drop.",
+                ));
+            let renderer = Renderer::styled();
+            report::print_snippet(renderer.render(message));
+        }
+    }
+}
+
+/// Flow-insensitive aliases between the locals of a body, i.e., the locals copied, moved, cast or
+/// borrowed from each other, or from the places behind them.
+struct LocalAliases {
+    parents: Vec<usize>,
+}
+
+impl LocalAliases {
+    fn new(body: &Body<'_>) -> Self {
+        let mut aliases = LocalAliases {
+            parents: (0..body.local_decls.len()).collect(),
+        };
+        for data in body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                let source = match rvalue {
+                    Rvalue::Use(operand) | Rvalue::Cast(CastKind::PtrToPtr, operand, _) => {
+                        operand.place()
+                    }
+                    Rvalue::Ref(_, _, source)
+                    | Rvalue::RawPtr(_, source)
+                    | Rvalue::CopyForDeref(source) => Some(*source),
+                    _ => None,
+                };
+                if let Some(source) = source {
+                    aliases.union(place.local, source.local);
+                }
+            }
+        }
+        aliases
+    }
+
+    fn find(&self, local: Local) -> Local {
+        let mut idx = local.as_usize();
+        while self.parents[idx] != idx {
+            idx = self.parents[idx];
+        }
+        Local::from_usize(idx)
+    }
+
+    fn union(&mut self, a: Local, b: Local) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.as_usize()] = b.as_usize();
+        }
+    }
+}
+
+fn is_zero(operand: &Operand<'_>) -> bool {
+    let Operand::Constant(constant) = operand else {
+        return false;
+    };
+    match constant.const_ {
+        Const::Val(value, _) => value
+            .try_to_scalar_int()
+            .is_some_and(|scalar| scalar.is_null()),
+        _ => false,
+    }
+}
@@ -10,7 +10,10 @@ use rustc_middle::{
 use rustc_span::def_id::DefId;
 
 use super::{IcxMut, IcxSliceMut, Rcx, RcxMut, rCanary};
-use crate::analysis::core::ownedheap_analysis::{OHAResultMap, OwnedHeap, default::TyWithIndex};
+use crate::analysis::{
+    core::ownedheap_analysis::{OHAResultMap, OwnedHeap, default::TyWithIndex},
+    utils::budget::Budget,
+};
 use ownership::{IntraVar, Taint};
 
 use std::{
@@ -170,12 +173,14 @@ struct IntraFlowAnalysis<'tcx, 'ctx, 'a> {
     pub graph: &'a Graph,
     taint_flag: bool,
     taint_source: Vec<Terminator<'tcx>>,
+    budget: Budget,
 }
 
 impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
     pub fn new(
         rcx: &'a rCanary<'tcx>,
         def_id: DefId,
+        budget: Budget,
         //unique: &'a mut HashSet<DefId>,
     ) -> Self {
         let body = rcx.tcx.instance_mir(Item(def_id));
@@ -192,6 +197,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             graph,
             taint_flag: false,
             taint_source: Vec::default(),
+            budget,
         }
     }
 
@@ -19,7 +19,11 @@ use super::is_z3_goal_verbose;
 use super::ownership::IntraVar;
 use super::{FlowAnalysis, IcxSliceFroBlock, IntraFlowAnalysis};
 use crate::{
-    analysis::core::ownedheap_analysis::{default::*, *},
+    analysis::{
+        core::ownedheap_analysis::{default::*, *},
+        utils::budget::{self, Budget, Exhaustion},
+    },
+    report::{self, Finding, FindingKind},
     utils::{
         log::{
             are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
@@ -48,6 +52,9 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
 
         for each_mir in mir_keys {
             let def_id = each_mir.to_def_id();
+            if report::is_all_allowed(tcx, def_id, &[FindingKind::MemoryLeak]) {
+                continue;
+            }
             let body = tcx.instance_mir(Item(def_id));
             if graph::is_cyclic(&body.basic_blocks) {
                 continue;
@@ -56,14 +63,14 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
                 continue;
             }
 
+            let budget = Budget::new();
             let mut cfg = z3::Config::new();
             cfg.set_model_generation(true);
-            cfg.set_timeout_msec(1000);
             let ctx = z3::Context::new(&cfg);
             let goal = z3::Goal::new(&ctx, true, false, false);
             let solver = z3::Solver::new(&ctx);
 
-            let mut intra_visitor = IntraFlowAnalysis::new(self.rcx, def_id);
+            let mut intra_visitor = IntraFlowAnalysis::new(self.rcx, def_id, budget);
             intra_visitor.visit_body(&ctx, &goal, &solver, body);
         }
     }
@@ -79,6 +86,11 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
     ) {
         let topo: Vec<usize> = self.graph.get_topo().iter().map(|id| *id).collect();
         for bidx in topo {
+            // Each block is visited once, so that only the wall time is bounded.
+            if !self.budget.check_time() {
+                budget::record_incomplete(self.tcx(), "rcanary", self.def_id, &self.budget);
+                break;
+            }
             let data = &body.basic_blocks[BasicBlock::from(bidx)];
             self.visit_block_data(ctx, goal, solver, data, bidx);
         }
@@ -2453,6 +2465,13 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             }
         }
 
+        // The query is bounded by one second as well as the budget left for the function.
+        let mut params = z3::Params::new(ctx);
+        params.set_u32(
+            "timeout",
+            self.budget.remaining_time().as_millis().clamp(1, 1000) as u32,
+        );
+        solver.set_params(&params);
         let result = solver.check();
         let model = solver.get_model();
 
@@ -2464,6 +2483,10 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             }
         }
 
+        if result == z3::SatResult::Unknown {
+            budget::record_incomplete_with(self.tcx(), "rcanary", self.def_id, Exhaustion::Solver);
+        }
+
         // rap_debug!("{}", self.body.local_decls.display());
         // rap_debug!("{}", self.body.basic_blocks.display());
         // let g = format!("{}", goal);
@@ -2473,7 +2496,21 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             let fn_name = get_name(self.tcx(), self.def_id)
                 .unwrap_or_else(|| Symbol::intern("no symbol available"));
 
+            let mut finding = Finding::new(
+                FindingKind::MemoryLeak,
+                format!("Memory Leak detected in function {:}", fn_name),
+                self.body.span,
+            )
+            .with_function(self.def_id);
+            for source in self.taint_source.iter() {
+                finding =
+                    finding.with_secondary(source.source_info.span, "Memory Leak Candidates.");
+            }
+            if !report::record(finding) {
+                return;
+            }
             rap_warn!("Memory Leak detected in function {:}", fn_name);
+
             let source = span_to_source_code(self.body.span);
             let file = span_to_filename(self.body.span);
             let mut snippet = Snippet::source(&source)
@@ -2502,7 +2539,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
                 .title("Memory Leak detected.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            report::print_snippet(renderer.render(message));
         }
     }
 
@@ -1,6 +1,6 @@
 use rustc_middle::{
-    mir::{Operand, Place, ProjectionElem, TerminatorKind},
-    ty::{self},
+    mir::{Operand, Place, TerminatorKind},
+    ty::{self, Ty},
 };
 
 use super::{drop::*, graph::*};
@@ -75,7 +75,10 @@ impl<'tcx> SafeDropGraph<'tcx> {
                         if is_no_alias_intrinsic(*target_id) {
                             return;
                         }
-                        if self.mop_graph.tcx.is_mir_available(*target_id) {
+                        // Foreign functions without MIR may have summaries from sidecar files.
+                        if self.mop_graph.tcx.is_mir_available(*target_id)
+                            || fn_map.contains_key(target_id)
+                        {
                             rap_debug!("fn_map: {:?}", fn_map);
                             if fn_map.contains_key(&target_id) {
                                 let fn_aliases = fn_map.get(&target_id).unwrap();
@@ -258,51 +261,44 @@ impl<'tcx> SafeDropGraph<'tcx> {
      *
      */
     pub fn projection(&mut self, place: Place<'tcx>) -> usize {
-        let local = place.local.as_usize();
-        let mut value_idx = local;
         // Projections are leveled
         // Case 1: (*6).1 involves two projections: a Deref and a Field.
         // Case 2: (6.0).1 involves two field projections.
         // We should recursively parse the projection.
-        for proj in place.projection {
-            let new_value_idx = self.mop_graph.values.len();
-            match proj {
-                ProjectionElem::Deref => {}
-                ProjectionElem::Field(field, ty) => {
-                    let field_idx = field.as_usize();
-                    if !self.mop_graph.values[value_idx]
-                        .fields
-                        .contains_key(&field_idx)
-                    {
-                        let ty_env =
-                            ty::TypingEnv::post_analysis(self.mop_graph.tcx, self.mop_graph.def_id);
-                        let need_drop = ty.needs_drop(self.mop_graph.tcx, ty_env);
-                        let may_drop = !is_not_drop(self.mop_graph.tcx, ty);
-                        let mut node =
-                            Value::new(new_value_idx, local, need_drop, need_drop || may_drop);
-                        node.kind = kind(ty);
-                        node.father = Some(FatherInfo::new(value_idx, field_idx));
-                        self.mop_graph.values[value_idx]
-                            .fields
-                            .insert(field_idx, node.index);
-                        self.mop_graph.values.push(node);
-                        // The drop status is the same as its father.
-                        self.drop_record.push(DropRecord::from(
-                            new_value_idx,
-                            &self.drop_record[value_idx],
-                        ));
-                    }
-                    value_idx = *self.mop_graph.values[value_idx]
-                        .fields
-                        .get(&field_idx)
-                        .unwrap();
-                }
-                _ => {}
-            }
+        let (mut value_idx, path) = self.mop_graph.field_path(place);
+        for (field_idx, ty) in path {
+            value_idx = self.project_field(value_idx, field_idx, ty);
         }
         value_idx
     }
 
+    /// Return the value of a field, which is created if it does not exist.
+    pub fn project_field(&mut self, value_idx: usize, field_idx: usize, ty: Ty<'tcx>) -> usize {
+        if !self.mop_graph.values[value_idx]
+            .fields
+            .contains_key(&field_idx)
+        {
+            let new_value_idx = self.mop_graph.values.len();
+            let local = self.mop_graph.values[value_idx].local;
+            let ty_env = ty::TypingEnv::post_analysis(self.mop_graph.tcx, self.mop_graph.def_id);
+            let need_drop = ty.needs_drop(self.mop_graph.tcx, ty_env);
+            let may_drop = !is_not_drop(self.mop_graph.tcx, ty);
+            let mut node = Value::new(new_value_idx, local, need_drop, need_drop || may_drop);
+            node.kind = kind(ty);
+            node.father = Some(FatherInfo::new(value_idx, field_idx));
+            self.mop_graph.values[value_idx]
+                .fields
+                .insert(field_idx, node.index);
+            self.mop_graph.values.push(node);
+            // The drop status is the same as its father.
+            self.drop_record.push(DropRecord::from(
+                new_value_idx,
+                &self.drop_record[value_idx],
+            ));
+        }
+        self.mop_graph.values[value_idx].fields[&field_idx]
+    }
+
     //inter-procedure instruction to merge alias.
     pub fn handle_fn_alias(&mut self, fn_alias: &MopAliasPair, arg_vec: &Vec<usize>) {
         rap_debug!("ret_alias: {:?}", fn_alias);
@@ -2,6 +2,7 @@ use annotate_snippets::{Level, Renderer, Snippet};
 use rustc_data_structures::fx::FxHashMap;
 use rustc_span::{Span, symbol::Symbol};
 
+use crate::report::{self, Finding, FindingKind};
 use crate::utils::log::{
     are_spans_in_same_file, get_basic_block_span, get_variable_name, relative_pos_range,
     span_to_filename, span_to_line_number, span_to_source_code,
@@ -53,6 +54,7 @@ impl BugRecords {
     pub fn df_bugs_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
         self.emit_bug_reports(
             body, &self.df_bugs, fn_name, span,
+            FindingKind::DoubleFree,
             "Double free detected",
             "Double free detected.",
             |bug, drop_local, trigger_local, drop_bb, trigger_bb| {
@@ -70,6 +72,7 @@ impl BugRecords {
 
         self.emit_bug_reports(
             body, &self.df_bugs_unwind, fn_name, span,
+            FindingKind::DoubleFreeUnwind,
             "Double free detected",
             "Double free detected during unwinding.",
             |bug, drop_local, trigger_local, drop_bb, trigger_bb| {
@@ -89,6 +92,7 @@ impl BugRecords {
     pub fn uaf_bugs_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
         self.emit_bug_reports(
             body, &self.uaf_bugs, fn_name, span,
+            FindingKind::UseAfterFree,
             "Use-after-free detected",
             "Use-after-free detected.",
             |bug, drop_local, trigger_local, drop_bb, trigger_bb| {
@@ -108,6 +112,7 @@ impl BugRecords {
     pub fn dp_bug_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
         self.emit_bug_reports(
             body, &self.dp_bugs, fn_name, span,
+            FindingKind::DanglingPointer,
             "Dangling pointer detected",
             "Dangling pointer detected.",
             |bug, drop_local, trigger_local, drop_bb, _trigger_bb| {
@@ -125,6 +130,7 @@ impl BugRecords {
 
         self.emit_bug_reports(
             body, &self.dp_bugs_unwind, fn_name, span,
+            FindingKind::DanglingPointerUnwind,
             "Dangling pointer detected during unwinding",
             "Dangling pointer detected during unwinding.",
             |bug, drop_local, trigger_local, drop_bb, _trigger_bb| {
@@ -147,6 +153,7 @@ impl BugRecords {
         bugs: &FxHashMap<usize, TyBug>,
         fn_name: Symbol,
         span: Span,
+        kind: FindingKind,
         log_msg: &str,
         title: &str,
         detail_formatter: F,
@@ -157,11 +164,10 @@ impl BugRecords {
             return;
         }
 
-        rap_warn!("{} in function {:?}", log_msg, fn_name);
-
         let code_source = span_to_source_code(span);
         let filename = span_to_filename(span);
         let renderer = Renderer::styled();
+        let mut reported = false;
 
         for bug in bugs.values() {
             if are_spans_in_same_file(span, bug.span) {
@@ -217,6 +223,42 @@ impl BugRecords {
                 let detail =
                     detail_formatter(bug, &drop_local, &trigger_local, &drop_bb, &trigger_bb);
 
+                let mut finding = Finding::new(
+                    kind,
+                    format!("{} in function {:?}", log_msg, fn_name),
+                    bug.span,
+                )
+                .with_confidence(bug.confidence)
+                .with_function(body.source.def_id());
+                for local in [bug.drop_spot.local, bug.trigger_info.local]
+                    .into_iter()
+                    .flatten()
+                {
+                    finding = finding.with_local(local);
+                }
+                if let Some(bb) = bug.drop_spot.bb {
+                    finding = finding.with_basic_block(bb).with_secondary(
+                        get_basic_block_span(body, bb),
+                        format!("MIR detail: {} is dropped at {}.", drop_local, drop_bb),
+                    );
+                }
+                if let Some(bb) = bug.trigger_info.bb {
+                    finding = finding.with_basic_block(bb).with_secondary(
+                        get_basic_block_span(body, bb),
+                        format!(
+                            "MIR detail: {} is triggered at {}.",
+                            trigger_local, trigger_bb
+                        ),
+                    );
+                }
+                if !report::record(finding) {
+                    continue;
+                }
+                if !reported {
+                    rap_warn!("{} in function {:?}", log_msg, fn_name);
+                    reported = true;
+                }
+
                 let mut snippet = Snippet::source(&code_source)
                     .line_start(span_to_line_number(span))
                     .origin(&filename)
@@ -230,7 +272,7 @@ impl BugRecords {
 
                 let message = Level::Warning.title(title).snippet(snippet);
 
-                println!("{}", renderer.render(message));
+                report::print_snippet(renderer.render(message));
             }
         }
     }
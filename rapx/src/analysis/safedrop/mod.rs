@@ -15,11 +15,12 @@ use crate::{
             ownedheap_analysis::{OHAResultMap, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
         },
         graphs::scc::Scc,
+        utils::budget,
     },
+    report::{self, FindingKind},
     utils::source::get_fn_name,
 };
 use graph::SafeDropGraph;
-use safedrop::*;
 
 use super::Analysis;
 
@@ -44,6 +45,9 @@ impl<'tcx> SafeDrop<'tcx> {
 
         let mir_keys = self.tcx.mir_keys(());
         for local_def_id in mir_keys {
+            if report::is_all_allowed(self.tcx, local_def_id.to_def_id(), FindingKind::SAFEDROP) {
+                continue;
+            }
             query_safedrop(
                 self.tcx,
                 &fn_map,
@@ -75,8 +79,8 @@ pub fn query_safedrop(tcx: TyCtxt, fn_map: &MopFnAliasMap, def_id: DefId, adt_ow
         safedrop_graph.mop_graph.find_scc();
         rap_debug!("safedrop graph (scc): {}", safedrop_graph);
         safedrop_graph.check(0, fn_map);
-        if safedrop_graph.mop_graph.visit_times <= VISIT_LIMIT {
-            safedrop_graph.report_bugs();
-        }
+        // The bugs found on the explored paths are reported even if the budget is exhausted.
+        budget::record_incomplete(tcx, "safedrop", def_id, &safedrop_graph.mop_graph.budget);
+        safedrop_graph.report_bugs();
     }
 }
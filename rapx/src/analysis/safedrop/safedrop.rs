@@ -1,6 +1,9 @@
 use super::{bug_records::*, corner_case::*, drop::*, graph::*};
 use crate::{
-    analysis::core::alias_analysis::default::{MopFnAliasMap, block::Term, types::ValueKind},
+    analysis::{
+        core::alias_analysis::default::{MopFnAliasMap, block::Term, types::ValueKind},
+        graphs::scc::SccExit,
+    },
     utils::source::{get_filename, get_name},
 };
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
@@ -13,8 +16,6 @@ use rustc_middle::{
 };
 use rustc_span::{Span, Symbol};
 
-pub const VISIT_LIMIT: usize = 1000;
-
 impl<'tcx> SafeDropGraph<'tcx> {
     // analyze the drop statement and update the liveness for nodes.
     pub fn drop_check(&mut self, bb_idx: usize) {
@@ -125,8 +126,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
 
     // the core function of the safedrop.
     pub fn check(&mut self, bb_idx: usize, fn_map: &MopFnAliasMap) {
-        self.mop_graph.visit_times += 1;
-        if self.mop_graph.visit_times > VISIT_LIMIT {
+        if !self.mop_graph.budget.tick() {
             return;
         }
         let scc_idx = self.mop_graph.blocks[bb_idx].scc.enter;
@@ -160,24 +160,30 @@ impl<'tcx> SafeDropGraph<'tcx> {
         let backup_constant = self.mop_graph.constants.clone();
         let backup_alias_sets = self.mop_graph.alias_sets.clone();
         let backup_drop_record = self.drop_record.clone();
+        // SCC exits are stored on the SCC enter node.
+        let scc_exits = cur_block.scc.exits.clone();
         for raw_path in &paths_in_scc {
             let path = &raw_path.0;
             let path_constants = &raw_path.1;
 
-            if !path.is_empty() {
-                for idx in &path[..path.len() - 1] {
-                    self.alias_bb(*idx);
-                    self.alias_bbcall(*idx, fn_map);
-                    self.drop_check(*idx);
+            if self.mop_graph.coroutine.is_some() {
+                self.check_coroutine_scc_path(path, path_constants, &scc_exits, fn_map);
+            } else {
+                if !path.is_empty() {
+                    for idx in &path[..path.len() - 1] {
+                        self.alias_bb(*idx);
+                        self.alias_bbcall(*idx, fn_map);
+                        self.drop_check(*idx);
+                    }
                 }
-            }
-            // The last node is already ouside the scc.
-            if let Some(&last_node) = path.last() {
-                if self.mop_graph.blocks[last_node].scc.nodes.is_empty() {
-                    self.check_single_node(last_node, fn_map);
-                    self.handle_nexts(last_node, fn_map, None, Some(path_constants));
-                } else {
-                    // TODO
+                // The last node is already ouside the scc.
+                if let Some(&last_node) = path.last() {
+                    if self.mop_graph.blocks[last_node].scc.nodes.is_empty() {
+                        self.check_single_node(last_node, fn_map);
+                        self.handle_nexts(last_node, fn_map, None, Some(path_constants));
+                    } else {
+                        // TODO
+                    }
                 }
             }
             self.mop_graph.alias_sets = backup_alias_sets.clone();
@@ -187,12 +193,78 @@ impl<'tcx> SafeDropGraph<'tcx> {
         }
     }
 
+    // A path in the SCC of a coroutine body, e.g., the polling loop of an async fn, ends at an
+    // SCC-exit node inside the SCC, whose suspension points are checked as well. The path leaves
+    // the SCC only via the exit edges of the node that are consistent with the path constraints.
+    fn check_coroutine_scc_path(
+        &mut self,
+        path: &[usize],
+        path_constants: &FxHashMap<usize, usize>,
+        scc_exits: &FxHashSet<SccExit>,
+        fn_map: &MopFnAliasMap,
+    ) {
+        for idx in path {
+            self.alias_bb(*idx);
+            self.alias_bbcall(*idx, fn_map);
+            self.drop_check(*idx);
+            self.suspension_drop_check(*idx);
+        }
+        let Some(&exit_node) = path.last() else {
+            return;
+        };
+        self.mop_graph.constants.extend(path_constants);
+        let allowed_targets = self.allowed_switch_targets(exit_node);
+        for e in scc_exits {
+            if e.exit != exit_node
+                || allowed_targets
+                    .as_ref()
+                    .is_some_and(|allowed| !allowed.contains(&e.to))
+            {
+                continue;
+            }
+            self.split_check(e.to, fn_map);
+        }
+    }
+
+    // The targets of a SwitchInt terminator that are reachable under the current constants.
+    fn allowed_switch_targets(&mut self, bb_idx: usize) -> Option<FxHashSet<usize>> {
+        let Term::Switch(switch) = self.mop_graph.blocks[bb_idx].terminator.clone() else {
+            return None;
+        };
+        let TerminatorKind::SwitchInt {
+            discr: Copy(place) | Move(place),
+            targets,
+        } = switch.kind
+        else {
+            return None;
+        };
+        let value_idx = self.projection(place);
+        let discr_local = self
+            .mop_graph
+            .discriminants
+            .get(&self.mop_graph.values[value_idx].local)
+            .cloned()
+            .unwrap_or(value_idx);
+        let allowed = match self.mop_graph.constants.get(&discr_local) {
+            Some(&value) => [targets.target_for_value(value as u128).as_usize()]
+                .into_iter()
+                .collect(),
+            None => targets
+                .all_targets()
+                .iter()
+                .map(|bb| bb.as_usize())
+                .collect(),
+        };
+        Some(allowed)
+    }
+
     pub fn check_single_node(&mut self, bb_idx: usize, fn_map: &MopFnAliasMap) {
         let cur_block = self.mop_graph.blocks[bb_idx].clone();
         rap_debug!("check {:?} as a node", bb_idx);
         self.alias_bb(bb_idx);
         self.alias_bbcall(bb_idx, fn_map);
         self.drop_check(bb_idx);
+        self.suspension_drop_check(bb_idx);
 
         // For dangling pointer check;
         // Since a node within an SCC cannot be an exit, we only check for non-scc nodes;
@@ -318,7 +390,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
             // Other cases in switchInt terminators
             if let Some(targets) = sw_targets {
                 for iter in targets.iter() {
-                    if self.mop_graph.visit_times > VISIT_LIMIT {
+                    if self.mop_graph.budget.is_exhausted() {
                         continue;
                     }
                     let next = iter.1.as_usize();
@@ -340,7 +412,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 self.split_check_with_cond(next_idx, path_discr_id, path_discr_val, fn_map);
             } else {
                 for next in &cur_block.next {
-                    if self.mop_graph.visit_times > VISIT_LIMIT {
+                    if self.mop_graph.budget.is_exhausted() {
                         continue;
                     }
 
@@ -375,7 +447,15 @@ impl<'tcx> SafeDropGraph<'tcx> {
         if self.bug_records.is_bug_free() {
             return;
         }
-        let fn_name = match get_name(self.mop_graph.tcx, self.mop_graph.def_id) {
+        let tcx = self.mop_graph.tcx;
+        let def_id = self.mop_graph.def_id;
+        // A coroutine body, e.g., of an async fn, is reported with the name of its function.
+        let name = if tcx.is_coroutine(def_id) {
+            get_name(tcx, tcx.typeck_root_def_id(def_id))
+        } else {
+            get_name(tcx, def_id)
+        };
+        let fn_name = match name {
             Some(name) => name,
             None => Symbol::intern("no symbol available"),
         };
@@ -504,33 +584,14 @@ impl<'tcx> SafeDropGraph<'tcx> {
     pub fn dp_check(&mut self, flag_cleanup: bool) {
         rap_debug!("dangling pointer check");
         rap_debug!("current alias sets: {:?}", self.mop_graph.alias_sets);
+        // The arguments of a coroutine body, e.g., an async fn, are moved from its state.
+        let coroutine_args = self.coroutine_arg_values();
         if flag_cleanup {
             for arg_idx in 1..self.mop_graph.arg_size + 1 {
-                if !self.mop_graph.values[arg_idx].is_ptr() {
-                    continue;
-                }
-                self.fetch_drop_info(arg_idx);
-                let mut fully_dropped = true;
-                if !self.drop_record[arg_idx].is_dropped {
-                    fully_dropped = false;
-                    if !self.drop_record[arg_idx].has_dropped_field {
-                        continue;
-                    }
-                }
-                let kind = self.mop_graph.values[arg_idx].kind;
-                let confidence = Self::rate_confidence(kind, fully_dropped);
-                let bug = TyBug {
-                    drop_spot: self.drop_record[arg_idx].drop_spot,
-                    trigger_info: LocalSpot::from_local(arg_idx),
-                    prop_chain: self.drop_record[arg_idx].prop_chain.clone(),
-                    span: self.mop_graph.span.clone(),
-                    confidence,
-                };
-                self.bug_records.dp_bugs_unwind.insert(arg_idx, bug);
-                rap_info!(
-                    "Find a dangling pointer {} during unwinding; add to record.",
-                    arg_idx
-                );
+                self.dp_check_value(arg_idx, true, false);
+            }
+            for value_idx in coroutine_args {
+                self.dp_check_value(value_idx, true, true);
             }
         } else {
             if self.mop_graph.values[0].may_drop
@@ -555,30 +616,98 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 rap_info!("Find a dangling pointer 0; add to record.");
             } else {
                 for arg_idx in 0..self.mop_graph.arg_size + 1 {
-                    if !self.mop_graph.values[arg_idx].is_ptr() {
-                        continue;
-                    }
-                    self.fetch_drop_info(arg_idx);
-                    let mut fully_dropped = true;
-                    if !self.drop_record[arg_idx].is_dropped {
-                        fully_dropped = false;
-                        if !self.drop_record[arg_idx].has_dropped_field {
-                            continue;
-                        }
-                    }
-                    let kind = self.mop_graph.values[arg_idx].kind;
-                    let confidence = Self::rate_confidence(kind, fully_dropped);
-                    let bug = TyBug {
-                        drop_spot: self.drop_record[arg_idx].drop_spot,
-                        trigger_info: LocalSpot::from_local(arg_idx),
-                        prop_chain: self.drop_record[arg_idx].prop_chain.clone(),
-                        span: self.mop_graph.span.clone(),
-                        confidence,
-                    };
-                    self.bug_records.dp_bugs.insert(arg_idx, bug);
-                    rap_info!("Find a dangling pointer {}; add to record.", arg_idx);
+                    self.dp_check_value(arg_idx, false, false);
                 }
+                for value_idx in coroutine_args {
+                    self.dp_check_value(value_idx, false, true);
+                }
+            }
+        }
+    }
+
+    // Check whether a pointer that outlives the function points to dropped values. The bug of a
+    // coroutine argument is only recorded for the first path that reaches it.
+    pub fn dp_check_value(&mut self, value_idx: usize, flag_cleanup: bool, keep_first: bool) {
+        if !self.mop_graph.values[value_idx].is_ptr() {
+            return;
+        }
+        self.fetch_drop_info(value_idx);
+        let mut fully_dropped = true;
+        if !self.drop_record[value_idx].is_dropped {
+            fully_dropped = false;
+            if !self.drop_record[value_idx].has_dropped_field {
+                return;
+            }
+        }
+        let kind = self.mop_graph.values[value_idx].kind;
+        let confidence = Self::rate_confidence(kind, fully_dropped);
+        let bug = TyBug {
+            drop_spot: self.drop_record[value_idx].drop_spot,
+            trigger_info: LocalSpot::from_local(self.mop_graph.values[value_idx].local),
+            prop_chain: self.drop_record[value_idx].prop_chain.clone(),
+            span: self.mop_graph.span,
+            confidence,
+        };
+        let (records, unwinding) = if flag_cleanup {
+            (&mut self.bug_records.dp_bugs_unwind, " during unwinding")
+        } else {
+            (&mut self.bug_records.dp_bugs, "")
+        };
+        if keep_first && records.contains_key(&value_idx) {
+            return;
+        }
+        records.insert(value_idx, bug);
+        rap_info!(
+            "Find a dangling pointer {}{}; add to record.",
+            value_idx,
+            unwinding
+        );
+    }
+
+    // The values of the arguments of a coroutine body after they are moved from the state.
+    pub fn coroutine_arg_values(&mut self) -> Vec<usize> {
+        let Some(state) = &self.mop_graph.coroutine else {
+            return Vec::new();
+        };
+        let arg_places = state.arg_places.clone();
+        arg_places
+            .into_iter()
+            .map(|place| self.projection(place))
+            .collect()
+    }
+
+    // A suspended coroutine may be dropped instead of being resumed, which drops the saved locals
+    // at the suspension point. The pointers escaped through its arguments then become dangling.
+    pub fn suspension_drop_check(&mut self, bb_idx: usize) {
+        let Some(state) = &self.mop_graph.coroutine else {
+            return;
+        };
+        let state_value = state.value;
+        let saved = state.saved_at(bb_idx);
+        if saved.is_empty() || !should_check(self.mop_graph.def_id) {
+            return;
+        }
+        rap_debug!("suspension drop check bb: {}", bb_idx);
+        let backup_values = self.mop_graph.values.clone();
+        let backup_alias_sets = self.mop_graph.alias_sets.clone();
+        let backup_drop_record = self.drop_record.clone();
+        for (field_idx, ty) in saved {
+            let value_idx = self.project_field(state_value, field_idx, ty);
+            self.fetch_drop_info(value_idx);
+            // Moved or dropped values are not dropped again by the drop of the coroutine.
+            if !self.mop_graph.values[value_idx].need_drop || self.drop_record[value_idx].is_dropped
+            {
+                continue;
             }
+            let drop_spot = LocalSpot::new(bb_idx, self.mop_graph.values[value_idx].local);
+            self.drop_record[value_idx] = DropRecord::new(value_idx, true, drop_spot);
+            self.push_drop_info(value_idx, drop_spot);
+        }
+        for value_idx in self.coroutine_arg_values() {
+            self.dp_check_value(value_idx, false, true);
         }
+        self.mop_graph.values = backup_values;
+        self.mop_graph.alias_sets = backup_alias_sets;
+        self.drop_record = backup_drop_record;
     }
 }
@@ -0,0 +1,426 @@
+use crate::{
+    analysis::{
+        Analysis,
+        core::{
+            api_dependency::{ApiDependencyGraph, DepNode, is_def_id_public},
+            ownedheap_analysis::{OHAResultMap, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
+        },
+    },
+    report::{self, Finding, FindingKind},
+    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{Safety, def::DefKind, def_id::DefId};
+use rustc_infer::infer::TyCtxtInferExt;
+use rustc_middle::ty::{self, ImplPolarity, Ty, TyCtxt, TypingMode};
+use rustc_span::{Span, sym};
+use rustc_trait_selection::traits::type_known_to_meet_bound_modulo_regions;
+use std::fmt::{self, Display};
+
+/// The marker trait implemented by an `unsafe impl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Send,
+    Sync,
+}
+
+impl Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Marker::Send => write!(f, "Send"),
+            Marker::Sync => write!(f, "Sync"),
+        }
+    }
+}
+
+/// How a generic parameter is used by the fields or the APIs of a type.
+#[derive(Debug, Clone, Copy, Default)]
+struct ParamUsage {
+    /// The parameter is owned or passed by value (or by `&mut`), so it can be moved to another
+    /// thread.
+    moved: bool,
+    /// The parameter is only reachable through shared references.
+    shared: bool,
+}
+
+/// A missing bound on a generic parameter of an `unsafe impl Send/Sync`.
+#[derive(Debug, Clone)]
+pub struct MissingBound {
+    pub param: String,
+    pub bound: Marker,
+    /// Why the bound is required, with the span of the field or API causing it.
+    pub reasons: Vec<(Span, String)>,
+}
+
+/// Thread-safety checker for `unsafe impl Send/Sync`.
+///
+/// Similar to the Send/Sync variance checker of Rudra, the checker infers how each generic
+/// parameter of the implementing type is moved or shared across threads:
+/// - for `unsafe impl Send for Foo<T>`, `T` must be `Send` if `Foo<T>` owns `T` or its APIs
+///   move `T` in or out; otherwise, `T` must be `Sync` if `&T` is reachable from `Foo<T>`;
+/// - for `unsafe impl Sync for Foo<T>`, only the APIs callable with `&self` matter: `T` must be
+///   `Sync` if they expose `&T`, and `Send` if they move `T` in or out.
+///
+/// Ownership is obtained from the owned-heap analysis, and the APIs are the public methods of
+/// the type in the API dependency graph.
+pub struct SendSyncCheck<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    adt_owner: OHAResultMap,
+    api_graph: ApiDependencyGraph<'tcx>,
+    /// The local impls of each ADT, except the Send/Sync impls.
+    adt_impls: FxHashMap<DefId, Vec<DefId>>,
+    pub missing_bounds: FxHashMap<DefId, Vec<MissingBound>>,
+}
+
+impl<'tcx> Analysis for SendSyncCheck<'tcx> {
+    fn name(&self) -> &'static str {
+        "Send/Sync implementation checker."
+    }
+
+    fn run(&mut self) {
+        let tcx = self.tcx;
+        let mut heap = OwnedHeapAnalyzer::new(tcx);
+        heap.run();
+        self.adt_owner = heap.get_all_items();
+
+        let (Some(send), Some(sync)) = (
+            tcx.get_diagnostic_item(sym::Send),
+            tcx.lang_items().sync_trait(),
+        ) else {
+            return;
+        };
+        let trait_impls = tcx.all_local_trait_impls(());
+        for (trait_did, impls) in trait_impls.iter() {
+            if *trait_did == send || *trait_did == sync {
+                continue;
+            }
+            for impl_id in impls {
+                self.add_adt_impl(impl_id.to_def_id());
+            }
+        }
+        for impl_id in tcx.hir_crate_items(()).definitions() {
+            if tcx.def_kind(impl_id) == (DefKind::Impl { of_trait: false }) {
+                self.add_adt_impl(impl_id.to_def_id());
+            }
+        }
+
+        for (trait_did, marker) in [(send, Marker::Send), (sync, Marker::Sync)] {
+            let Some(impls) = trait_impls.get(&trait_did) else {
+                continue;
+            };
+            for impl_id in impls {
+                let impl_id = impl_id.to_def_id();
+                let header = tcx.impl_trait_header(impl_id);
+                if header.safety != Safety::Unsafe || header.polarity != ImplPolarity::Positive {
+                    continue;
+                }
+                let missing = self.check_impl(impl_id, marker, send, sync);
+                if !missing.is_empty() {
+                    self.report(impl_id, marker, &missing);
+                    self.missing_bounds.insert(impl_id, missing);
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.adt_owner.clear();
+        self.adt_impls.clear();
+        self.missing_bounds.clear();
+    }
+}
+
+impl<'tcx> SendSyncCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            adt_owner: OHAResultMap::default(),
+            api_graph: ApiDependencyGraph::new(tcx),
+            adt_impls: FxHashMap::default(),
+            missing_bounds: FxHashMap::default(),
+        }
+    }
+
+    fn add_adt_impl(&mut self, impl_id: DefId) {
+        let self_ty = self.tcx.type_of(impl_id).instantiate_identity();
+        if let ty::Adt(adt_def, _) = self_ty.kind() {
+            self.adt_impls
+                .entry(adt_def.did())
+                .or_default()
+                .push(impl_id);
+        }
+    }
+
+    /// Check the bounds of each generic parameter of the implementing type.
+    fn check_impl(
+        &mut self,
+        impl_id: DefId,
+        marker: Marker,
+        send: DefId,
+        sync: DefId,
+    ) -> Vec<MissingBound> {
+        let tcx = self.tcx;
+        let self_ty = tcx.type_of(impl_id).instantiate_identity();
+        let ty::Adt(adt_def, args) = self_ty.kind() else {
+            return Vec::new();
+        };
+        let param_env = tcx.param_env(impl_id);
+        let infcx = tcx.infer_ctxt().build(TypingMode::non_body_analysis());
+        let mut missing = Vec::new();
+
+        for (pos, arg) in args.iter().enumerate() {
+            let Some(param_ty) = arg.as_type() else {
+                continue;
+            };
+            if !matches!(param_ty.kind(), ty::Param(_)) {
+                continue;
+            }
+            let (owned, field_usage, field_span) = self.field_usage(adt_def.did(), pos);
+            let mut moved_by = Vec::new();
+            let mut shared_by = Vec::new();
+            for (api, usage) in self.api_usage(adt_def.did(), pos, marker) {
+                let span = tcx.def_span(api);
+                let api_name = tcx.def_path_str(api);
+                if usage.moved {
+                    moved_by.push((span, format!("`{param_ty}` is moved by `{api_name}`")));
+                }
+                if usage.shared {
+                    shared_by.push((span, format!("`&{param_ty}` is exposed by `{api_name}`")));
+                }
+            }
+
+            let (needs_send, needs_sync) = match marker {
+                Marker::Send => {
+                    let mut send_reasons = moved_by;
+                    if owned || field_usage.moved {
+                        send_reasons.insert(
+                            0,
+                            (field_span, format!("`{param_ty}` is owned by `{self_ty}`")),
+                        );
+                    }
+                    let sync_reasons = if !send_reasons.is_empty() {
+                        Vec::new()
+                    } else if field_usage.shared {
+                        vec![(
+                            field_span,
+                            format!("`&{param_ty}` is reachable from `{self_ty}`"),
+                        )]
+                    } else {
+                        shared_by
+                    };
+                    (send_reasons, sync_reasons)
+                }
+                Marker::Sync => (moved_by, shared_by),
+            };
+
+            for (bound, bound_did, reasons) in [
+                (Marker::Send, send, needs_send),
+                (Marker::Sync, sync, needs_sync),
+            ] {
+                if reasons.is_empty()
+                    || type_known_to_meet_bound_modulo_regions(
+                        &infcx, param_env, param_ty, bound_did,
+                    )
+                {
+                    continue;
+                }
+                missing.push(MissingBound {
+                    param: param_ty.to_string(),
+                    bound,
+                    reasons,
+                });
+            }
+        }
+        missing
+    }
+
+    /// Whether the parameter at the position of the ADT's generic arguments is owned by the
+    /// ADT, and how it is used by the fields. The span is the first field using the parameter.
+    fn field_usage(&self, adt_did: DefId, pos: usize) -> (bool, ParamUsage, Span) {
+        let tcx = self.tcx;
+        let owned = self
+            .adt_owner
+            .get(&adt_did)
+            .is_some_and(|variants| variants.iter().any(|(_, params)| params[pos]));
+        let ty::Adt(adt_def, args) = tcx.type_of(adt_did).instantiate_identity().kind() else {
+            return (owned, ParamUsage::default(), tcx.def_span(adt_did));
+        };
+        let Some(ty::Param(param)) = args[pos].as_type().map(|ty| *ty.kind()) else {
+            return (owned, ParamUsage::default(), tcx.def_span(adt_did));
+        };
+        let mut usage = ParamUsage::default();
+        let mut span = None;
+        for field in adt_def.all_fields() {
+            let mut field_usage = ParamUsage::default();
+            collect_usage(
+                tcx.type_of(field.did).instantiate_identity(),
+                param.index,
+                adt_did,
+                false,
+                &mut field_usage,
+            );
+            if field_usage.moved || field_usage.shared {
+                span.get_or_insert(tcx.def_span(field.did));
+            }
+            usage.moved |= field_usage.moved;
+            usage.shared |= field_usage.shared;
+        }
+        (owned, usage, span.unwrap_or(tcx.def_span(adt_did)))
+    }
+
+    /// How the APIs of the ADT use the parameter at the position of the ADT's generic
+    /// arguments. For Sync, only the APIs taking `&self` are considered.
+    fn api_usage(
+        &mut self,
+        adt_did: DefId,
+        pos: usize,
+        marker: Marker,
+    ) -> Vec<(DefId, ParamUsage)> {
+        let tcx = self.tcx;
+        let mut result = Vec::new();
+        let impls = self.adt_impls.get(&adt_did).cloned().unwrap_or_default();
+        for impl_id in impls {
+            // The parameter of the impl standing for the parameter of the ADT.
+            let impl_self_ty = tcx.type_of(impl_id).instantiate_identity();
+            let ty::Adt(_, impl_args) = impl_self_ty.kind() else {
+                continue;
+            };
+            let Some(ty::Param(param)) = impl_args[pos].as_type().map(|ty| *ty.kind()) else {
+                continue;
+            };
+            let of_trait = tcx.def_kind(impl_id) == (DefKind::Impl { of_trait: true });
+            for &api in tcx.associated_item_def_ids(impl_id) {
+                let item = tcx.associated_item(api);
+                if !item.is_fn() || (!of_trait && !is_def_id_public(api, tcx)) {
+                    continue;
+                }
+                self.api_graph.add_generic_api(api);
+                let args = ty::GenericArgs::identity_for_item(tcx, api);
+                let Some((inputs, output)) = self.api_graph.api_signature(DepNode::api(api, args))
+                else {
+                    continue;
+                };
+                let by_shared_self = item.is_method()
+                    && inputs
+                        .first()
+                        .is_some_and(|ty| is_shared_ref_to(*ty, adt_did));
+                if marker == Marker::Sync && !by_shared_self {
+                    continue;
+                }
+                let mut usage = ParamUsage::default();
+                for ty in inputs.iter().chain([&output]) {
+                    collect_usage(*ty, param.index, adt_did, false, &mut usage);
+                }
+                if usage.moved || usage.shared {
+                    result.push((api, usage));
+                }
+            }
+        }
+        result
+    }
+
+    fn report(&self, impl_id: DefId, marker: Marker, missing: &[MissingBound]) {
+        let tcx = self.tcx;
+        let self_ty = tcx.type_of(impl_id).instantiate_identity();
+        // Group the missing bounds by parameters, e.g., `T: Send + Sync`.
+        let mut params: Vec<(&str, Vec<String>)> = Vec::new();
+        for bound in missing {
+            match params.iter_mut().find(|(param, _)| *param == bound.param) {
+                Some((_, markers)) => markers.push(bound.bound.to_string()),
+                None => params.push((&bound.param, vec![bound.bound.to_string()])),
+            }
+        }
+        let bounds = params
+            .iter()
+            .map(|(param, markers)| format!("{}: {}", param, markers.join(" + ")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let title = format!(
+            "Unsafe impl {} for {} lacks bounds: {}",
+            marker, self_ty, bounds
+        );
+        let span = tcx.def_span(impl_id);
+        let mut finding =
+            Finding::new(FindingKind::UnsoundSendSync, &title, span).with_function(impl_id);
+        for bound in missing {
+            for (reason_span, reason) in &bound.reasons {
+                finding = finding.with_secondary(*reason_span, reason);
+            }
+        }
+        if !report::record(finding) {
+            return;
+        }
+        rap_warn!("{}", title);
+
+        let code_source = span_to_source_code(span);
+        let filename = span_to_filename(span);
+        let snippet = Snippet::source(&code_source)
+            .line_start(span_to_line_number(span))
+            .origin(&filename)
+            .fold(false)
+            .annotation(
+                Level::Warning
+                    .span(relative_pos_range(span, span))
+                    .label("The bounds are missing here."),
+            );
+        let notes = missing
+            .iter()
+            .flat_map(|bound| bound.reasons.iter().map(|(_, reason)| reason.as_str()))
+            .collect::<Vec<_>>();
+        let help = format!("Consider adding `where {}` to the impl.", bounds);
+        let mut message = Level::Warning
+            .title("Unsound Send/Sync implementation detected.")
+            .snippet(snippet);
+        for note in &notes {
+            message = message.footer(Level::Note.title(note));
+        }
+        message = message.footer(Level::Help.title(&help));
+        let renderer = Renderer::styled();
+        report::print_snippet(renderer.render(message));
+    }
+}
+
+/// Whether the type is `&Adt<..>`, i.e., the receiver `&self` of the ADT.
+fn is_shared_ref_to(ty: Ty<'_>, adt_did: DefId) -> bool {
+    match ty.kind() {
+        ty::Ref(_, inner, ty::Mutability::Not) => {
+            matches!(inner.kind(), ty::Adt(adt_def, _) if adt_def.did() == adt_did)
+        }
+        _ => false,
+    }
+}
+
+/// Collect how the parameter is used in the type. The ADT itself is skipped, e.g., the receiver
+/// `&self`, and so are raw pointers and function pointers that cannot move the parameter
+/// without unsafe code.
+fn collect_usage(ty: Ty<'_>, param: u32, adt_did: DefId, shared: bool, usage: &mut ParamUsage) {
+    match ty.kind() {
+        ty::Param(p) if p.index == param => {
+            if shared {
+                usage.shared = true;
+            } else {
+                usage.moved = true;
+            }
+        }
+        ty::Ref(_, inner, ty::Mutability::Not) => {
+            collect_usage(*inner, param, adt_did, true, usage)
+        }
+        ty::Ref(_, inner, ty::Mutability::Mut) => {
+            collect_usage(*inner, param, adt_did, shared, usage)
+        }
+        ty::Adt(adt_def, _) if adt_def.did() == adt_did => {}
+        ty::Adt(_, args) => {
+            for ty in args.types() {
+                collect_usage(ty, param, adt_did, shared, usage);
+            }
+        }
+        ty::Tuple(tys) => {
+            for ty in tys.iter() {
+                collect_usage(ty, param, adt_did, shared, usage);
+            }
+        }
+        ty::Array(ty, _) | ty::Slice(ty) => collect_usage(*ty, param, adt_did, shared, usage),
+        _ => {}
+    }
+}
@@ -0,0 +1,145 @@
+use super::property::PropertyContract;
+use crate::{
+    analysis::utils::fn_info::ContractEntry, rap_info, rap_warn, utils::log::rap_error_and_exit,
+};
+use lazy_static::lazy_static;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// The directory in the workspace searched for contract files if it exists.
+pub const CONTRACTS_DIR: &str = "rapx-contracts";
+
+lazy_static! {
+    /// Contracts of the APIs outside std, keyed by the cleaned def paths. They are merged with
+    /// the built-in std contracts, and take precedence over them.
+    static ref EXTRA_CONTRACTS: Mutex<BTreeMap<String, Vec<ContractEntry>>> =
+        Mutex::new(BTreeMap::new());
+}
+
+/// Load the contract files from `rapx-contracts/*.json` and the paths given by
+/// `-contracts=<path>`, where a path may be either a json file or a directory of json files.
+/// Each file has the same schema as `std_sps_args.json`, and is keyed by the cleaned def paths
+/// printed by `-verify`, which omit the crate names outside std, e.g.,
+///
+/// ```json
+/// {
+///     "unix::strlen": [
+///         { "tag": "ValidCStr", "args": ["0"] }
+///     ],
+///     "buf::buf_mut::BufMut::advance_mut": [
+///         { "tag": "ValidNum", "args": ["1", "cnt <= 4096"] }
+///     ]
+/// }
+/// ```
+///
+/// The entries are validated on loading, and RAPx exits on any malformed entry.
+pub fn load_contracts(paths: &[PathBuf]) {
+    let mut files = Vec::new();
+    let default_dir = Path::new(CONTRACTS_DIR);
+    if default_dir.is_dir() {
+        files.extend(json_files(default_dir));
+    }
+    for path in paths {
+        if path.is_dir() {
+            files.extend(json_files(path));
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut db = EXTRA_CONTRACTS.lock().unwrap();
+    for file in files {
+        for (api, entries) in parse_contract_file(&file) {
+            if db.insert(api.clone(), entries).is_some() {
+                rap_warn!(
+                    "Contracts of {api} in {} override the ones loaded before.",
+                    file.display()
+                );
+            }
+        }
+        rap_info!("Load contracts from {}.", file.display());
+    }
+}
+
+/// Return all the loaded contracts outside std.
+pub fn extra_contracts() -> BTreeMap<String, Vec<ContractEntry>> {
+    EXTRA_CONTRACTS.lock().unwrap().clone()
+}
+
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let entries = fs::read_dir(dir).unwrap_or_else(|e| {
+        rap_error_and_exit(format!(
+            "Failed to read contract directory {}: {e}",
+            dir.display()
+        ))
+    });
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn parse_contract_file(path: &Path) -> BTreeMap<String, Vec<ContractEntry>> {
+    let content = fs::read_to_string(path).unwrap_or_else(|e| {
+        rap_error_and_exit(format!(
+            "Failed to read contract file {}: {e}",
+            path.display()
+        ))
+    });
+    let contracts: BTreeMap<String, Vec<ContractEntry>> = serde_json::from_str(&content)
+        .unwrap_or_else(|e| {
+            rap_error_and_exit(format!(
+                "Failed to parse contract file {}: {e}",
+                path.display()
+            ))
+        });
+    for (api, entries) in &contracts {
+        for entry in entries {
+            if let Err(e) = validate_entry(entry) {
+                rap_error_and_exit(format!(
+                    "Invalid contract {} of {api} in {}: {e}",
+                    entry.tag,
+                    path.display()
+                ));
+            }
+        }
+    }
+    contracts
+}
+
+/// Check the entry in the same way as it is instantiated by `PropertyContract::new`, so that
+/// a malformed entry is rejected before the analysis rather than at the call sites.
+fn validate_entry(entry: &ContractEntry) -> Result<(), String> {
+    let Some(counts) = PropertyContract::arg_counts(&entry.tag) else {
+        return Err("unknown tag".to_string());
+    };
+    if !counts.contains(&entry.args.len()) {
+        return Err(format!(
+            "expected {} args, got {}",
+            counts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" or "),
+            entry.args.len()
+        ));
+    }
+    if entry.args[0].parse::<usize>().is_err() {
+        return Err(format!(
+            "the first arg must be an argument index, got `{}`",
+            entry.args[0]
+        ));
+    }
+    for arg in &entry.args {
+        if syn::parse_str::<syn::Expr>(arg).is_err() {
+            return Err(format!("`{arg}` is not a valid expression"));
+        }
+    }
+    Ok(())
+}
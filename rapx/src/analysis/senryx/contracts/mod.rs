@@ -1,3 +1,4 @@
 pub mod abstract_state;
+pub mod database;
 pub mod property;
 // pub mod state_lattice;
@@ -9,7 +9,10 @@ use rustc_hir::def_id::DefId;
 use rustc_middle::mir::BinOp;
 use rustc_middle::ty::Ty;
 use rustc_middle::ty::TyCtxt;
-use safety_parser::{safety::TagNameType, syn::Expr};
+use safety_parser::{
+    safety::TagNameType,
+    syn::{self, Expr},
+};
 
 #[derive(Clone, Debug)]
 pub enum CisRangeItem {
@@ -110,6 +113,14 @@ impl<'tcx> PropertyContract<'tcx> {
             }
             "NonOverlap" => Self::NonOverlap,
             "ValidNum" => {
+                // ValidNum(x, x <op> bound) restricts the value of x by a comparison, which is
+                // written as ValidNum(x <op> bound) in annotations.
+                if exprs.len() == 2 {
+                    return Self::ValidNum(Self::parse_comparison(tcx, def_id, &exprs[1]));
+                }
+                if let Some(Expr::Binary(_)) = exprs.first() {
+                    return Self::ValidNum(Self::parse_comparison(tcx, def_id, &exprs[0]));
+                }
                 Self::check_arg_length(exprs.len(), 1, "ValidNum");
                 let bin_op = BinOp::Ne;
                 let length = Self::parse_length(tcx, def_id, &exprs[0], "ValidNum");
@@ -150,6 +161,20 @@ impl<'tcx> PropertyContract<'tcx> {
         }
     }
 
+    /// The numbers of args accepted by the tag in a contract entry, where the first arg is the
+    /// index of the checked argument. Return None if the tag is unknown.
+    pub fn arg_counts(name: &str) -> Option<&'static [usize]> {
+        match name {
+            "Align" | "Typed" => Some(&[2]),
+            "Allocated" | "InBound" | "Init" | "ValidPtr" => Some(&[3]),
+            "ValidNum" => Some(&[1, 2]),
+            "Size" | "NoPadding" | "NonNull" | "NonOverlap" | "ValidString" | "ValidCStr"
+            | "Unwrap" | "Owning" | "Alias" | "Alive" | "Pinned" | "NonVolatile" | "Opened"
+            | "Trait" | "Unreachable" | "Deref" | "Ptr2Ref" | "Layout" => Some(&[1]),
+            _ => None,
+        }
+    }
+
     pub fn new_patial_order(p: usize, op: BinOp) -> Self {
         Self::ValidNum(CisRange::new(op, CisRangeItem::Var(p, Vec::new())))
     }
@@ -198,6 +223,30 @@ impl<'tcx> PropertyContract<'tcx> {
         }
     }
 
+    // -------- comparison parser ----------
+    fn parse_comparison(tcx: TyCtxt<'tcx>, def_id: DefId, expr: &Expr) -> CisRange {
+        if let Expr::Binary(binary) = expr {
+            let bin_op = match binary.op {
+                syn::BinOp::Lt(_) => Some(BinOp::Lt),
+                syn::BinOp::Le(_) => Some(BinOp::Le),
+                syn::BinOp::Gt(_) => Some(BinOp::Gt),
+                syn::BinOp::Ge(_) => Some(BinOp::Ge),
+                syn::BinOp::Eq(_) => Some(BinOp::Eq),
+                syn::BinOp::Ne(_) => Some(BinOp::Ne),
+                _ => None,
+            };
+            if let Some(bin_op) = bin_op {
+                let bound = Self::parse_length(tcx, def_id, &binary.right, "ValidNum");
+                return CisRange::new(bin_op, bound);
+            }
+        }
+        rap_error!(
+            "Range comparison error in \"ValidNum\" Tag! Unknown anntation:\n{:?}",
+            expr
+        );
+        CisRange::new(BinOp::Ne, CisRangeItem::Unknown)
+    }
+
     fn parse_arg_length(expr: &Expr) -> Option<CisRangeItem> {
         if let Expr::Path(expr_path) = expr {
             if let Some(ident) = expr_path.path.get_ident() {
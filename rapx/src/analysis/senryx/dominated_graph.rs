@@ -3,11 +3,14 @@ use crate::{
         senryx::{
             contracts::{
                 abstract_state::AlignState,
-                property::{CisRangeItem, ContractualInvariantState, PropertyContract},
+                property::{CisRange, CisRangeItem, ContractualInvariantState, PropertyContract},
             },
             symbolic_analysis::{AnaOperand, SymbolicDef},
         },
-        utils::fn_info::{display_hashmap, get_pointee, is_ptr, is_ref, is_slice, reverse_op},
+        utils::fn_info::{
+            FnKind, display_hashmap, generate_invariant_from_annotation, get_pointee, get_type,
+            is_ptr, is_ref, is_slice, reverse_op,
+        },
     },
     rap_debug, rap_warn,
 };
@@ -24,6 +27,8 @@ use std::fmt::Write;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct States<'tcx> {
     pub nonnull: bool,
+    /// The memory is known to exist: a local, the referent of a reference, or an allocation.
+    pub allocated: bool,
     pub allocator_consistency: bool,
     pub init: bool,
     pub align: AlignState<'tcx>,
@@ -36,18 +41,31 @@ impl<'tcx> States<'tcx> {
     pub fn new(ty: Ty<'tcx>) -> Self {
         Self {
             nonnull: true,
-            allocator_consistency: true,
+            allocated: true,
+            allocator_consistency: false,
             init: true,
             align: AlignState::Aligned(ty),
-            valid_string: true,
-            valid_cstr: true,
+            valid_string: Self::is_utf8_ty(ty),
+            valid_cstr: Self::is_cstr_ty(ty),
         }
     }
 
+    /// Values of `str` and `String` are always valid UTF-8.
+    fn is_utf8_ty(ty: Ty<'tcx>) -> bool {
+        ty.is_str() || ty.to_string().ends_with("string::String")
+    }
+
+    /// Values of `CStr` and `CString` are always NUL-terminated.
+    fn is_cstr_ty(ty: Ty<'tcx>) -> bool {
+        let name = ty.to_string();
+        name.ends_with("ffi::CStr") || name.ends_with("ffi::CString")
+    }
+
     /// Create a new States instance with all fields set to false/unknown.
     pub fn new_unknown() -> Self {
         Self {
             nonnull: false,
+            allocated: false,
             allocator_consistency: false,
             init: false,
             align: AlignState::Unknown,
@@ -59,6 +77,7 @@ impl<'tcx> States<'tcx> {
     /// Merge the states of this instance with another.
     pub fn merge_states(&mut self, other: &States<'tcx>) {
         self.nonnull &= other.nonnull;
+        self.allocated &= other.allocated;
         self.allocator_consistency &= other.allocator_consistency;
         self.init &= other.init;
         self.align.merge(&other.align);
@@ -338,6 +357,41 @@ impl<'tcx> DominatedGraph<'tcx> {
                 self.insert_cis_for_arg(field_node, contract);
             }
         }
+        // A method may assume the invariants of its type on entry.
+        if get_type(self.tcx, self.def_id) == FnKind::Method {
+            for (base, fields, contract) in
+                generate_invariant_from_annotation(self.tcx, self.def_id)
+            {
+                let fields = fields.into_iter().map(|(field, _)| field).collect();
+                let node = self.find_var_id_with_fields_seq(base, &fields);
+                let contract = self.resolve_contract_vars(contract);
+                self.insert_cis_for_arg(node, contract);
+            }
+        }
+    }
+
+    /// Replace the locals with fields in the lengths and bounds of the contract by the ids of
+    /// their nodes, e.g., `self.cap` in `ValidNum(self.len <= self.cap)`.
+    pub fn resolve_contract_vars(
+        &mut self,
+        contract: PropertyContract<'tcx>,
+    ) -> PropertyContract<'tcx> {
+        let mut resolve = |item| match item {
+            CisRangeItem::Var(local, fields) => {
+                CisRangeItem::new_var(self.find_var_id_with_fields_seq(local, &fields))
+            }
+            item => item,
+        };
+        match contract {
+            PropertyContract::Allocated(ty, len) => PropertyContract::Allocated(ty, resolve(len)),
+            PropertyContract::InBound(ty, len) => PropertyContract::InBound(ty, resolve(len)),
+            PropertyContract::Init(ty, len) => PropertyContract::Init(ty, resolve(len)),
+            PropertyContract::ValidPtr(ty, len) => PropertyContract::ValidPtr(ty, resolve(len)),
+            PropertyContract::ValidNum(range) => {
+                PropertyContract::ValidNum(CisRange::new(range.bin_op, resolve(range.range)))
+            }
+            contract => contract,
+        }
     }
 
     /// Insert a contract into the CIS of an argument.
@@ -488,8 +542,9 @@ impl<'tcx> DominatedGraph<'tcx> {
         for field in fields.clone() {
             let mut cur_node = self.get_var_node(cur).unwrap();
             if let TyKind::Ref(_, ty, _) = cur_node.ty.unwrap().kind() {
-                let point_to = self.get_point_to_id(cur);
-                cur_node = self.get_var_node(point_to).unwrap();
+                // The fields belong to the referent, as `(*_1).0` in MIR.
+                cur = self.get_point_to_id(cur);
+                cur_node = self.get_var_node(cur).unwrap();
             }
             // If there exist a field node, then get it as cur node
             if cur_node.field.get(&field).is_some() {
@@ -0,0 +1,286 @@
+use super::{
+    SenryxCheck, contracts::property::CisRangeItem, contracts::property::PropertyContract,
+};
+use crate::{
+    analysis::{core::alias_analysis::FnAliasPairs, utils::fn_info::*},
+    report::update_locked,
+    utils::log::{span_to_filename, span_to_line_number},
+};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{mir::BinOp, ty::TyCtxt};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fs,
+    path::{Component, Path},
+};
+
+/// Lines of context around the inserted annotations in the patch.
+const PATCH_CONTEXT: usize = 3;
+
+impl<'tcx> SenryxCheck<'tcx> {
+    /// Infer the preconditions of the unsafe functions of this crate from the unmet contracts
+    /// of their unsafe callees, and suggest them as `#[rapx::inner(...)]` annotations.
+    ///
+    /// The callees are analyzed before their callers along the unsafe call chains, so that the
+    /// preconditions inferred for a callee are checked, and propagated further, in its callers.
+    pub fn infer_preconditions(
+        &mut self,
+        targets: Vec<DefId>,
+        fn_map: &FxHashMap<DefId, FnAliasPairs>,
+    ) {
+        let tcx = self.tcx;
+        let mut suggestions = Vec::new();
+        for def_id in Self::order_by_unsafe_chains(tcx, targets) {
+            let body_visitor = self.visit_body(def_id, fn_map);
+            let (names, _) = parse_signature(tcx, def_id);
+            let mut annotated: HashSet<String> =
+                generate_contract_from_annotation_without_field_types(tcx, def_id)
+                    .into_iter()
+                    .filter(|(_, fields, _)| fields.is_empty())
+                    .filter_map(|(param, _, contract)| render_contract(&contract, param, &names))
+                    .collect();
+            let mut inferred = Vec::new();
+            let mut annotations = Vec::new();
+            for (param, contract) in body_visitor.unmet_param_contracts {
+                let Some(property) = render_contract(&contract, param, &names) else {
+                    continue;
+                };
+                if annotated.insert(property.clone()) {
+                    annotations.push(format!(
+                        "#[rapx::inner(property = {property}, kind = \"precond\")]"
+                    ));
+                    inferred.push((param, Vec::new(), contract));
+                }
+            }
+            if inferred.is_empty() {
+                continue;
+            }
+            Self::show_inferred_annotations(tcx, def_id, &annotations);
+            self.inferred_contracts.insert(def_id, inferred);
+            suggestions.push((def_id, annotations));
+        }
+        if let Some(path) = self.infer_patch.clone()
+            && !suggestions.is_empty()
+        {
+            write_patch(tcx, &path, &suggestions);
+        }
+    }
+
+    /// Order the functions such that the ones reachable by the unsafe call chains of a
+    /// function come before it. The functions on a cycle are kept in the given order.
+    fn order_by_unsafe_chains(tcx: TyCtxt<'tcx>, targets: Vec<DefId>) -> Vec<DefId> {
+        let names: HashMap<String, DefId> = targets
+            .iter()
+            .map(|def_id| (get_cleaned_def_path_name(tcx, *def_id), *def_id))
+            .collect();
+        let callees: HashMap<DefId, HashSet<DefId>> = targets
+            .iter()
+            .map(|def_id| {
+                let reachable = get_all_std_unsafe_chains(tcx, *def_id)
+                    .iter()
+                    .flat_map(|chain| chain.iter().skip(1))
+                    .filter_map(|name| names.get(name).copied())
+                    .filter(|callee| callee != def_id)
+                    .collect();
+                (*def_id, reachable)
+            })
+            .collect();
+
+        let mut order = Vec::new();
+        let mut pending = targets;
+        while !pending.is_empty() {
+            let done: HashSet<DefId> = order.iter().copied().collect();
+            let next = pending
+                .iter()
+                .position(|def_id| callees[def_id].iter().all(|callee| done.contains(callee)))
+                .unwrap_or(0);
+            order.push(pending.remove(next));
+        }
+        order
+    }
+
+    /// Show the annotations suggested for an unsafe function.
+    pub fn show_inferred_annotations(tcx: TyCtxt<'tcx>, def_id: DefId, annotations: &[String]) {
+        rap_info!(
+            "--------In unsafe function {:?}---------",
+            get_cleaned_def_path_name(tcx, def_id)
+        );
+        rap_warn!("Lack safety annotations:");
+        for annotation in annotations {
+            rap_warn!("      {}", annotation);
+        }
+    }
+}
+
+/// Write the contract on the param as the property of an annotation, e.g.,
+/// `ValidPtr(ptr, u32, 1)` or `ValidNum(idx < 4)`. Return None if it cannot be written down.
+pub fn render_contract(
+    contract: &PropertyContract<'_>,
+    param: usize,
+    names: &[String],
+) -> Option<String> {
+    let name = |local: usize| names.get(local.checked_sub(1)?).cloned();
+    let item = |item: &CisRangeItem| match item {
+        CisRangeItem::Var(local, fields) if fields.is_empty() => name(*local),
+        CisRangeItem::Value(value) => Some(value.to_string()),
+        _ => None,
+    };
+    let target = name(param)?;
+    let property = match contract {
+        PropertyContract::Align(ty) => format!("Align({target}, {ty})"),
+        PropertyContract::Typed(ty) => format!("Typed({target}, {ty})"),
+        PropertyContract::Allocated(ty, len) => {
+            format!("Allocated({target}, {ty}, {})", item(len)?)
+        }
+        PropertyContract::InBound(ty, len) => format!("InBound({target}, {ty}, {})", item(len)?),
+        PropertyContract::Init(ty, len) => format!("Init({target}, {ty}, {})", item(len)?),
+        PropertyContract::ValidPtr(ty, len) => format!("ValidPtr({target}, {ty}, {})", item(len)?),
+        PropertyContract::ValidNum(range) => {
+            let op = match range.bin_op {
+                BinOp::Lt => "<",
+                BinOp::Le => "<=",
+                BinOp::Gt => ">",
+                BinOp::Ge => ">=",
+                BinOp::Eq => "==",
+                BinOp::Ne => "!=",
+                _ => return None,
+            };
+            format!("ValidNum({target} {op} {})", item(&range.range)?)
+        }
+        PropertyContract::Unknown => return None,
+        // The tags without args other than the checked one, e.g., `NonNull(ptr)`.
+        contract => {
+            let tag = format!("{contract:?}");
+            format!("{}({target})", tag.trim_end_matches("()"))
+        }
+    };
+    Some(property)
+}
+
+/// Write the suggested annotations as a unified diff, which inserts them right above the
+/// signatures of the functions and can be applied by `git apply` or `patch -p1` in the
+/// workspace root. The files outside the workspace root are left out.
+///
+/// The patch is shared by the crates of a run, so the sections of the files annotated here
+/// replace their previous sections and the other sections are kept.
+fn write_patch(tcx: TyCtxt<'_>, path: &Path, suggestions: &[(DefId, Vec<String>)]) {
+    // The annotations to insert before each line, 1-based, of each file.
+    let mut insertions: BTreeMap<String, BTreeMap<usize, Vec<String>>> = BTreeMap::new();
+    for (def_id, annotations) in suggestions {
+        let span = tcx.def_span(*def_id);
+        insertions
+            .entry(span_to_filename(span))
+            .or_default()
+            .entry(span_to_line_number(span))
+            .or_default()
+            .extend(annotations.iter().cloned());
+    }
+
+    let mut sections = BTreeMap::new();
+    for (file, lines) in insertions {
+        let Some(name) = workspace_path(&file) else {
+            rap_warn!("{file} is outside the workspace root, skip its annotations in the patch.");
+            continue;
+        };
+        let Ok(source) = fs::read_to_string(&file) else {
+            rap_warn!("Cannot read {file}, skip its annotations in the patch.");
+            continue;
+        };
+        let mut section = format!("--- a/{name}\n+++ b/{name}\n");
+        section.push_str(&file_hunks(&source.lines().collect::<Vec<_>>(), &lines));
+        sections.insert(name, section);
+    }
+    if sections.is_empty() {
+        return;
+    }
+    let result = update_locked(path, |content| {
+        let mut merged = patch_sections(content);
+        merged.extend(sections);
+        merged.into_values().collect()
+    });
+    match result {
+        Ok(()) => rap_info!("Write the suggested annotations to {}.", path.display()),
+        Err(e) => rap_warn!("Failed to write {}: {e}", path.display()),
+    }
+}
+
+/// The path of a source file relative to the workspace root, i.e., the current folder of the
+/// compilation, or `None` if the file is outside of it.
+fn workspace_path(file: &str) -> Option<String> {
+    let path = Path::new(file);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(env::current_dir().ok()?).ok()?
+    } else {
+        path
+    };
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Split a patch into the sections of its files, keyed by the file names.
+fn patch_sections(patch: &str) -> BTreeMap<String, String> {
+    let mut sections: BTreeMap<String, String> = BTreeMap::new();
+    let mut current = None;
+    for line in patch.split_inclusive('\n') {
+        if let Some(file) = line.strip_prefix("--- a/") {
+            current = Some(file.trim_end().to_string());
+        }
+        if let Some(file) = &current {
+            sections.entry(file.clone()).or_default().push_str(line);
+        }
+    }
+    sections
+}
+
+/// Build the hunks of a file, where `insertions` maps the 1-based lines to the annotations
+/// inserted before them. The hunks whose contexts overlap are merged.
+fn file_hunks(source: &[&str], insertions: &BTreeMap<usize, Vec<String>>) -> String {
+    // Group the insertions into hunks, as ranges of 0-based line indices [start, end).
+    let mut groups: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+    for &line in insertions.keys() {
+        let at = line - 1;
+        let start = at.saturating_sub(PATCH_CONTEXT);
+        let end = (at + PATCH_CONTEXT).min(source.len());
+        match groups.last_mut() {
+            Some((_, last_end, lines)) if start <= *last_end => {
+                *last_end = end.max(*last_end);
+                lines.push(line);
+            }
+            _ => groups.push((start, end, vec![line])),
+        }
+    }
+
+    let mut hunks = String::new();
+    let mut added = 0;
+    for (start, end, lines) in groups {
+        let mut body = String::new();
+        let mut hunk_added = 0;
+        for (idx, text) in source.iter().enumerate().take(end).skip(start) {
+            if let Some(annotations) = lines.contains(&(idx + 1)).then(|| &insertions[&(idx + 1)]) {
+                let indent: String = text.chars().take_while(|c| c.is_whitespace()).collect();
+                for annotation in annotations {
+                    body.push_str(&format!("+{indent}{annotation}\n"));
+                }
+                hunk_added += annotations.len();
+            }
+            body.push_str(&format!(" {text}\n"));
+        }
+        let len = end - start;
+        hunks.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            start + 1,
+            len,
+            start + 1 + added,
+            len + hunk_added
+        ));
+        hunks.push_str(&body);
+        added += hunk_added;
+    }
+    hunks
+}
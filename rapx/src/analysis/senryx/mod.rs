@@ -3,6 +3,7 @@ pub mod contracts;
 #[allow(unused)]
 pub mod dominated_graph;
 pub mod generic_check;
+pub mod infer;
 // pub mod inter_record;
 pub mod matcher;
 pub mod symbolic_analysis;
@@ -10,22 +11,31 @@ pub mod symbolic_analysis;
 pub mod visitor;
 #[allow(unused)]
 pub mod visitor_check;
+use contracts::property::PropertyContract;
 use dominated_graph::InterResultNode;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::{Safety, def_id::DefId};
 use rustc_middle::{
     mir::{BasicBlock, Operand, TerminatorKind},
-    ty::{self, TyCtxt},
+    ty::{self, Ty, TyCtxt, TypeVisitableExt},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
 };
-use std::collections::HashSet;
 use visitor::{BodyVisitor, CheckResult};
 
 use crate::analysis::{
     Analysis,
-    core::alias_analysis::{AliasAnalysis, FnAliasPairs, default::AliasAnalyzer},
+    core::{
+        alias_analysis::{AliasAnalysis, FnAliasPairs, default::AliasAnalyzer},
+        callgraph::{CallGraphAnalysis, instance::InstanceCallGraphAnalyzer},
+        range_analysis::typed::TypedRangeAnalyzer,
+    },
     upg::{fn_collector::FnCollector, hir_visitor::ContainsUnsafe},
     utils::fn_info::*,
 };
+use crate::report::{self, Finding, FindingKind};
 
 macro_rules! cond_print {
     ($cond:expr, $($t:tt)*) => {if $cond {rap_warn!($($t)*)} else {rap_info!($($t)*)}};
@@ -40,6 +50,17 @@ pub enum CheckLevel {
 pub struct SenryxCheck<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub threshhold: usize,
+    /// Bounded intervals of the integer locals of each function, from the range analysis.
+    pub local_ranges: FxHashMap<DefId, HashMap<usize, (i128, i128)>>,
+    /// Preconditions inferred for the unsafe functions of this crate, which are checked at
+    /// their call sites in the functions analyzed later.
+    pub inferred_contracts: HashMap<DefId, Vec<(usize, Vec<usize>, PropertyContract<'tcx>)>>,
+    /// The file to write the inferred annotations to as a patch.
+    pub infer_patch: Option<PathBuf>,
+    /// The concrete types the generic parameters of each function are instantiated with, from
+    /// the instance-level call graph. Parameters of instances that remain generic, e.g., those
+    /// of public generic functions, are left out.
+    pub instantiations: HashMap<DefId, HashMap<String, HashSet<Ty<'tcx>>>>,
 }
 
 impl<'tcx> SenryxCheck<'tcx> {
@@ -49,7 +70,14 @@ impl<'tcx> SenryxCheck<'tcx> {
     /// - `tcx`: compiler TyCtxt for querying types/definitions.
     /// - `threshhold`: a numeric threshold used by checks.
     pub fn new(tcx: TyCtxt<'tcx>, threshhold: usize) -> Self {
-        Self { tcx, threshhold }
+        Self {
+            tcx,
+            threshhold,
+            local_ranges: FxHashMap::default(),
+            inferred_contracts: HashMap::new(),
+            infer_patch: None,
+            instantiations: HashMap::new(),
+        }
     }
 
     /// Start the checking pass over the collected functions.
@@ -62,6 +90,9 @@ impl<'tcx> SenryxCheck<'tcx> {
         let mut analyzer = AliasAnalyzer::new(self.tcx);
         analyzer.run(); // populate alias results
         let fn_map = &analyzer.get_all_fn_alias();
+        self.collect_local_ranges();
+        self.collect_instantiations();
+        let mut infer_targets = Vec::new();
 
         // Collect functions of interest (e.g. from UPG/collector)
         let related_items = FnCollector::collect(tcx);
@@ -81,17 +112,93 @@ impl<'tcx> SenryxCheck<'tcx> {
                     continue;
                 }
 
-                // If the body-level contains unsafe ops and we are verifying, run soundness checks
-                if block_unsafe && is_verify && !std_unsafe_callee.is_empty() {
+                // If the body-level contains unsafe ops and we are verifying, run soundness checks.
+                // Functions with postconditions or invariants to prove are checked as well.
+                if is_verify
+                    && ((block_unsafe && !std_unsafe_callee.is_empty())
+                        || !generate_return_obligations(tcx, def_id).is_empty())
+                {
                     self.check_soundness(def_id, fn_map);
                 }
 
-                // In non-verify mode we might annotate or produce diagnostics (disabled here)
-                if function_unsafe && !is_verify && !std_unsafe_callee.is_empty() {
-                    // annotation or other non-verification actions can be placed here
+                // In non-verify mode, infer the preconditions of the unsafe functions that call
+                // other unsafe functions.
+                if function_unsafe
+                    && !is_verify
+                    && get_all_std_unsafe_chains(tcx, def_id)
+                        .iter()
+                        .any(|chain| chain.len() > 1)
+                {
+                    infer_targets.push(def_id);
+                }
+            }
+        }
+        if !is_verify {
+            self.infer_preconditions(infer_targets, fn_map);
+        }
+    }
+
+    /// Run the range analysis and keep, for each local, the hull of the intervals of all its
+    /// SSA versions. Locals with an unbounded version are left out.
+    fn collect_local_ranges(&mut self) {
+        let mut analyzer = TypedRangeAnalyzer::new(self.tcx, false);
+        analyzer.run();
+        for (def_id, places_map) in &analyzer.analyzer.ssa_places_mapping {
+            if !analyzer.analyzer.final_vars.contains_key(def_id) {
+                continue;
+            }
+            let fn_ranges = places_map
+                .keys()
+                .filter(|place| place.projection.is_empty())
+                .filter_map(|place| {
+                    let hull = analyzer.get_hull(*def_id, *place)?;
+                    Some((place.local.as_usize(), hull))
+                })
+                .collect();
+            self.local_ranges.insert(*def_id, fn_ranges);
+        }
+    }
+
+    /// Collect the types each generic parameter of the local functions is instantiated with.
+    fn collect_instantiations(&mut self) {
+        let tcx = self.tcx;
+        let mut analyzer = InstanceCallGraphAnalyzer::new(tcx);
+        analyzer.run();
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            let generics = tcx.generics_of(def_id);
+            let mut types: HashMap<String, HashSet<Ty<'tcx>>> = HashMap::new();
+            let mut open = HashSet::new();
+            for instance in analyzer.get_instances(def_id) {
+                for (idx, arg) in instance.args.iter().enumerate() {
+                    let Some(ty) = arg.as_type() else {
+                        continue;
+                    };
+                    let name = generics.param_at(idx, tcx).name.to_string();
+                    if ty.has_param() {
+                        open.insert(name);
+                    } else {
+                        types.entry(name).or_default().insert(ty);
+                    }
                 }
             }
+            types.retain(|name, _| !open.contains(name));
+            if !types.is_empty() {
+                self.instantiations.insert(def_id, types);
+            }
+        }
+    }
+
+    /// Create a body visitor, whose generic parameters are narrowed to the types they are
+    /// instantiated with if known.
+    fn new_body_visitor(&self, def_id: DefId) -> BodyVisitor<'tcx> {
+        let mut body_visitor = BodyVisitor::new(self.tcx, def_id, 0);
+        if let Some(types) = self.instantiations.get(&def_id) {
+            for (name, tys) in types {
+                body_visitor.generic_map.insert(name.clone(), tys.clone());
+            }
         }
+        body_visitor
     }
 
     /// Iterate standard library `alloc` functions and run verification for those
@@ -220,24 +327,41 @@ impl<'tcx> SenryxCheck<'tcx> {
     /// a list of `CheckResult`s summarizing passed/failed contracts.
     ///
     /// If the function is a method, constructor results are merged into the
-    /// method's initial state before analyzing the method body.
+    /// method's initial state before analyzing the method body, unless its type
+    /// has invariants, which are assumed on entry instead.
     pub fn body_visit_and_check(
         &mut self,
         def_id: DefId,
         fn_map: &FxHashMap<DefId, FnAliasPairs>,
     ) -> Vec<CheckResult> {
+        self.visit_body(def_id, fn_map).check_results
+    }
+
+    /// Run the path-sensitive checks of `body_visit_and_check`, and return the visitor with
+    /// the states it collected.
+    fn visit_body(
+        &mut self,
+        def_id: DefId,
+        fn_map: &FxHashMap<DefId, FnAliasPairs>,
+    ) -> BodyVisitor<'tcx> {
         // Create a body visitor for the target function
-        let mut body_visitor = BodyVisitor::new(self.tcx, def_id, 0);
+        let mut body_visitor = self.new_body_visitor(def_id);
+        if let Some(ranges) = self.local_ranges.get(&def_id) {
+            body_visitor.local_ranges = ranges.clone();
+        }
+        body_visitor.inferred_contracts = self.inferred_contracts.clone();
         let target_name = get_cleaned_def_path_name(self.tcx, def_id);
         rap_info!("Begin verification process for: {:?}", target_name);
 
         // If this is a method, gather constructor-derived state first
-        if get_type(self.tcx, def_id) == FnKind::Method {
+        if get_type(self.tcx, def_id) == FnKind::Method
+            && generate_invariant_from_annotation(self.tcx, def_id).is_empty()
+        {
             let cons = get_cons(self.tcx, def_id);
             // Start with a default inter-result node for ADT fields
             let mut base_inter_result = InterResultNode::new_default(get_adt_ty(self.tcx, def_id));
             for con in cons {
-                let mut cons_body_visitor = BodyVisitor::new(self.tcx, con, 0);
+                let mut cons_body_visitor = self.new_body_visitor(con);
                 // Analyze constructor and merge its field states
                 let cons_fields_result = cons_body_visitor.path_forward_check(fn_map);
                 // cache and merge fields' states
@@ -264,7 +388,7 @@ impl<'tcx> SenryxCheck<'tcx> {
             // Non-method functions: just analyze body directly
             body_visitor.path_forward_check(fn_map);
         }
-        body_visitor.check_results
+        body_visitor
     }
 
     /// Variant of `body_visit_and_check` used for UI-guided annotation flows.
@@ -347,10 +471,19 @@ impl<'tcx> SenryxCheck<'tcx> {
                 }
             }
 
-            // Print the API name with conditional coloring
+            // Print the API name, or the function or type whose contracts are proved at the
+            // return, with conditional coloring
+            let (subject, finding_kind) = match check_result.kind {
+                ContractKind::Precond => ("Use unsafe api", FindingKind::UnsafePrecondition),
+                ContractKind::Postcond => {
+                    ("Ensure postcondition of", FindingKind::UnprovenContract)
+                }
+                ContractKind::Invariant => ("Keep invariant of", FindingKind::UnprovenContract),
+            };
             cond_print!(
                 !all_failed.is_empty(),
-                "  Use unsafe api {:?}.",
+                "  {} {:?}.",
+                subject,
                 check_result.func_name
             );
 
@@ -359,6 +492,33 @@ impl<'tcx> SenryxCheck<'tcx> {
                 let mut failed_sorted: Vec<&String> = all_failed.into_iter().collect();
                 failed_sorted.sort();
                 cond_print!(true, "      Failed: {:?}", failed_sorted);
+                let message = match check_result.kind {
+                    ContractKind::Precond => format!(
+                        "Unsafe api {:?} used in function {:?} fails to satisfy: {:?}",
+                        check_result.func_name,
+                        get_cleaned_def_path_name(tcx, def_id),
+                        failed_sorted
+                    ),
+                    ContractKind::Postcond => format!(
+                        "Function {:?} may return without satisfying its postconditions: {:?}",
+                        check_result.func_name, failed_sorted
+                    ),
+                    ContractKind::Invariant => format!(
+                        "Function {:?} may return without keeping the invariants of {:?}: {:?}",
+                        get_cleaned_def_path_name(tcx, def_id),
+                        check_result.func_name,
+                        failed_sorted
+                    ),
+                };
+                let mut finding = Finding::new(finding_kind, message, check_result.func_span)
+                    .with_function(def_id);
+                let mut failed_locals: Vec<usize> =
+                    check_result.failed_contracts.keys().copied().collect();
+                failed_locals.sort();
+                for local in failed_locals {
+                    finding = finding.with_local(local);
+                }
+                report::record(finding);
             }
 
             // Print aggregated Passed set
@@ -76,6 +76,10 @@ where
 
         if let Some(ref def) = domain.def {
             match def {
+                SymbolicDef::Constant(val) => {
+                    rap_debug!("  [Def] _{} == {}", local_idx, val);
+                    solver.assert(&current_var._eq(&BV::from_u64(&ctx, *val as u64, 64)));
+                }
                 SymbolicDef::Cast(src_idx, _ty) => {
                     if let Some(src_var) = z3_vars.get(src_idx) {
                         rap_debug!("  [Def] _{} == _{} (Cast)", local_idx, src_idx);
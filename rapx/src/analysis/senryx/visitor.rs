@@ -2,9 +2,8 @@ use crate::{
     analysis::{
         Analysis,
         core::{
-            alias_analysis::FnAliasPairs,
-            ownedheap_analysis::OHAResultMap,
-            range_analysis::{RangeAnalysis, default::RangeAnalyzer},
+            alias_analysis::FnAliasPairs, ownedheap_analysis::OHAResultMap,
+            range_analysis::typed::TypedRangeAnalyzer,
         },
         graphs::scc::Scc,
         safedrop::graph::SafeDropGraph,
@@ -31,6 +30,7 @@ use syn::Constraint;
 
 use super::dominated_graph::DominatedGraph;
 use super::dominated_graph::InterResultNode;
+use super::dominated_graph::VariableNode;
 use super::generic_check::GenericChecker;
 use super::matcher::UnsafeApi;
 use super::matcher::{get_arg_place, parse_unsafe_api};
@@ -39,7 +39,7 @@ use rustc_hir::def_id::DefId;
 use rustc_middle::{
     mir::{
         self, AggregateKind, BasicBlock, BasicBlockData, BinOp, CastKind, Local, Operand, Place,
-        ProjectionElem, Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+        ProjectionElem, Rvalue, Statement, StatementKind, Terminator, TerminatorKind, UnOp,
     },
     ty::{self, GenericArgKind, PseudoCanonicalInput, Ty, TyCtxt, TyKind},
 };
@@ -49,6 +49,9 @@ use rustc_span::{Span, source_map::Spanned};
 pub struct CheckResult {
     pub func_name: String,
     pub func_span: Span,
+    /// Preconditions are checked at the calls to `func_name`, and postconditions or invariants
+    /// of `func_name` are checked at the returns.
+    pub kind: ContractKind,
     pub failed_contracts: HashMap<usize, HashSet<String>>,
     pub passed_contracts: HashMap<usize, HashSet<String>>,
 }
@@ -60,6 +63,7 @@ impl CheckResult {
         Self {
             func_name: func_name.to_string(),
             func_span,
+            kind: ContractKind::Precond,
             failed_contracts: HashMap::new(),
             passed_contracts: HashMap::new(),
         }
@@ -112,6 +116,18 @@ pub struct BodyVisitor<'tcx> {
     pub chains: DominatedGraph<'tcx>,
     pub value_domains: HashMap<usize, ValueDomain<'tcx>>,
     pub path_constraints: Vec<SymbolicDef<'tcx>>,
+    /// Bounded intervals of integer locals over the whole body, from the range analysis.
+    pub local_ranges: HashMap<usize, (i128, i128)>,
+    /// Postconditions and invariants proved at the returns of the top-level analysis.
+    pub return_obligations: Vec<(ContractKind, AnnotatedContracts<'tcx>)>,
+    /// The kind of the contracts being checked, recorded in the check results.
+    pub checking_kind: ContractKind,
+    /// Preconditions inferred by `-infer` for the unsafe functions of this crate, in the same
+    /// form as the annotated ones, which are checked at the call sites as well.
+    pub inferred_contracts: HashMap<DefId, Vec<(usize, Vec<usize>, PropertyContract<'tcx>)>>,
+    /// Preconditions of the callees that fail and only depend on the params, as pairs of the
+    /// param and the contract on it.
+    pub unmet_param_contracts: Vec<(usize, PropertyContract<'tcx>)>,
 }
 
 // === Partition: Initialization & state ===
@@ -127,6 +143,11 @@ impl<'tcx> BodyVisitor<'tcx> {
             GenericChecker::new(tcx, param_env).get_satisfied_ty_map();
         let mut chains = DominatedGraph::new(tcx, def_id);
         chains.init_arg();
+        let return_obligations = if visit_time == 0 {
+            generate_return_obligations(tcx, def_id)
+        } else {
+            Vec::new()
+        };
         Self {
             tcx,
             def_id,
@@ -139,6 +160,11 @@ impl<'tcx> BodyVisitor<'tcx> {
             chains,
             value_domains: HashMap::new(),
             path_constraints: Vec::new(),
+            local_ranges: HashMap::new(),
+            return_obligations,
+            checking_kind: ContractKind::Precond,
+            inferred_contracts: HashMap::new(),
+            unmet_param_contracts: Vec::new(),
         }
     }
 }
@@ -181,6 +207,7 @@ impl<'tcx> BodyVisitor<'tcx> {
             }
             self.path_constraints = Vec::new();
             self.chains = tmp_chain.clone();
+            self.assume_entry_contracts();
             self.set_constraint(constraint);
             for (i, block_index) in path.iter().enumerate() {
                 if block_index >= &body.basic_blocks.len() {
@@ -251,7 +278,7 @@ impl<'tcx> BodyVisitor<'tcx> {
     /// Retrieve all paths and optional range-based constraints for this function.
     /// Falls back to safedrop graph paths if range analysis did not produce constraints.
     pub fn get_all_paths(&mut self) -> HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>> {
-        let mut range_analyzer = RangeAnalyzer::<i64>::new(self.tcx, false);
+        let mut range_analyzer = TypedRangeAnalyzer::new(self.tcx, false);
         let path_constraints_option =
             range_analyzer.start_path_constraints_analysis_for_defid(self.def_id); // if def_id does not exist, this will break down
         let mut path_constraints: HashMap<Vec<usize>, Vec<(_, _, _)>> =
@@ -266,8 +293,9 @@ impl<'tcx> BodyVisitor<'tcx> {
                 path_constraints_option.unwrap()
             };
         self.safedrop_graph.mop_graph.find_scc();
-        // If this is the top-level analysis, keep only paths that contain unsafe calls.
-        if self.visit_time == 0 {
+        // If this is the top-level analysis, keep only paths that contain unsafe calls, unless
+        // the contracts at the returns have to be proved on every path.
+        if self.visit_time == 0 && self.return_obligations.is_empty() {
             let contains_unsafe_blocks = get_all_std_unsafe_callees_block_id(self.tcx, self.def_id);
             path_constraints.retain(|path, cons| {
                 path.iter()
@@ -386,6 +414,16 @@ impl<'tcx> BodyVisitor<'tcx> {
                             return;
                         }
                     }
+                    // The checked ops give the pair of the wrapped result and the overflow
+                    // flag, and the result is read from the first field.
+                    let lpjc_local = if bin_op.overflowing_to_wrapping().is_some() {
+                        let body = self.tcx.optimized_mir(self.def_id);
+                        let ty = op1.ty(body, self.tcx);
+                        self.chains.get_field_node_id(lpjc_local, 0, Some(ty))
+                    } else {
+                        lpjc_local
+                    };
+                    let bin_op = &bin_op.overflowing_to_wrapping().unwrap_or(*bin_op);
                     // Handle other binary operations
                     let def = match (ana_op1.clone(), ana_op2) {
                         (AnaOperand::Local(l), rhs) => Some(SymbolicDef::Binary(*bin_op, l, rhs)),
@@ -425,6 +463,12 @@ impl<'tcx> BodyVisitor<'tcx> {
             Rvalue::UnaryOp(un_op, op) => {
                 // Unary op: record unary operation on LHS (operand value not stored here).
                 self.record_value_def(lpjc_local, SymbolicDef::UnOp(*un_op));
+                // The metadata of a slice pointer is its length.
+                if *un_op == UnOp::PtrMetadata
+                    && let Some(AnaOperand::Local(ptr_local)) = self.lift_operand(op)
+                {
+                    self.bind_obj_len(ptr_local, lpjc_local);
+                }
             }
             // Discriminant: x = discriminant(y); read enum tag
             Rvalue::Discriminant(_place) => {
@@ -437,11 +481,12 @@ impl<'tcx> BodyVisitor<'tcx> {
                     for (idx, op) in op_vec.into_iter().enumerate() {
                         let (is_const, val) = get_arg_place(op);
                         if is_const {
-                            self.chains.insert_field_node(
+                            let field_node = self.chains.insert_field_node(
                                 lpjc_local,
                                 idx,
                                 Some(Ty::new_uint(self.tcx, rustc_middle::ty::UintTy::Usize)),
                             );
+                            self.record_value_def(field_node, SymbolicDef::Constant(val as u128));
                         } else {
                             let node = self.chains.get_var_node_mut(lpjc_local).unwrap();
                             node.field.insert(idx, val);
@@ -551,6 +596,11 @@ impl<'tcx> BodyVisitor<'tcx> {
                     self.handle_switch_int(discr, targets, next_bb);
                 }
             }
+            TerminatorKind::Return => {
+                if self.visit_time == 0 {
+                    self.check_return_obligations(terminator.source_info.span);
+                }
+            }
             _ => {}
         }
     }
@@ -620,11 +670,8 @@ impl<'tcx> BodyVisitor<'tcx> {
         }
         self.record_value_def(dst_local, SymbolicDef::Call(func_name, call_arg_indices));
 
-        if !self.tcx.is_mir_available(def_id) {
-            return;
-        }
-
-        // Find std unsafe API call, then check the contracts.
+        // Find unsafe API call with known contracts, then check the contracts. The callee may
+        // be a foreign function without MIR, e.g., those of libc.
         if let Some(fn_result) =
             parse_unsafe_api(get_cleaned_def_path_name(self.tcx, *def_id).as_str())
         {
@@ -636,8 +683,16 @@ impl<'tcx> BodyVisitor<'tcx> {
                 fn_map,
                 fn_span,
                 fn_result,
-                generic_mapping,
+                generic_mapping.clone(),
             );
+        } else if is_annotated_unsafe_fn(self.tcx, *def_id)
+            || self.inferred_contracts.contains_key(def_id)
+        {
+            self.handle_annotated_unsafe_call(def_id, args, fn_span, generic_mapping);
+        }
+
+        if !self.tcx.is_mir_available(def_id) {
+            return;
         }
 
         self.handle_offset_call(dst_place, def_id, args);
@@ -646,6 +701,28 @@ impl<'tcx> BodyVisitor<'tcx> {
 
         // merge alias results
         self.handle_ret_alias(dst_place, def_id, fn_map, args);
+
+        self.set_alloc_state(def_id, dst_place);
+    }
+
+    /// Memory returned by the global allocator is allocated, and owned by that allocator.
+    fn set_alloc_state(&mut self, def_id: &DefId, dst_place: &Place<'tcx>) {
+        let func_name = get_cleaned_def_path_name(self.tcx, *def_id);
+        if !matches!(
+            func_name.as_str(),
+            "alloc::alloc::alloc" | "alloc::alloc::alloc_zeroed" | "alloc::alloc::realloc"
+        ) {
+            return;
+        }
+        let d_local = self.handle_proj(false, *dst_place);
+        let mem_local = self.chains.get_point_to_id(d_local);
+        if mem_local == d_local {
+            return;
+        }
+        if let Some(mem_var) = self.chains.get_var_node_mut(mem_local) {
+            mem_var.ots.allocated = true;
+            mem_var.ots.allocator_consistency = true;
+        }
     }
 
     /// For certain library calls (e.g. `slice::len`), bind computed values into object contracts.
@@ -660,11 +737,18 @@ impl<'tcx> BodyVisitor<'tcx> {
         }
         let d_local = self.handle_proj(false, dst_place.clone());
         let ptr_local = get_arg_place(&args[0].node).1;
+        self.bind_obj_len(ptr_local, d_local);
+    }
+
+    /// Record `len_local` as the number of elements of the object pointed to by `ptr_local`.
+    fn bind_obj_len(&mut self, ptr_local: usize, len_local: usize) {
         let mem_local = self.chains.get_point_to_id(ptr_local);
-        let mem_var = self.chains.get_var_node_mut(mem_local).unwrap();
+        let Some(mem_var) = self.chains.get_var_node_mut(mem_local) else {
+            return;
+        };
         for cis in &mut mem_var.cis.contracts {
             if let PropertyContract::InBound(cis_ty, len) = cis {
-                *len = CisRangeItem::new_var(d_local);
+                *len = CisRangeItem::new_var(len_local);
             }
         }
     }
@@ -924,7 +1008,12 @@ impl<'tcx> BodyVisitor<'tcx> {
         };
 
         // Create a symbolic definition for the pointer offset operation.
-        let summary_def = SymbolicDef::PtrOffset(bin_op, 1, AnaOperand::Local(2), place_ty);
+        // A constant offset is kept as is, since it has no caller local to be resolved to.
+        let offset = match self.lift_operand(&args[1].node) {
+            Some(AnaOperand::Const(val)) => AnaOperand::Const(val),
+            _ => AnaOperand::Local(2),
+        };
+        let summary_def = SymbolicDef::PtrOffset(bin_op, 1, offset, place_ty);
         let summary = FunctionSummary::new(Some(summary_def));
 
         // Apply the function summary to the destination local.
@@ -1316,6 +1405,7 @@ impl<'tcx> BodyVisitor<'tcx> {
 
     /// Record or overwrite the symbolic definition for a local in value_domains.
     fn record_value_def(&mut self, local_idx: usize, def: SymbolicDef<'tcx>) {
+        self.retire_value(local_idx);
         self.value_domains
             .entry(local_idx)
             .and_modify(|d| d.def = Some(def.clone()))
@@ -1325,6 +1415,58 @@ impl<'tcx> BodyVisitor<'tcx> {
             });
     }
 
+    /// The defs and path constraints recorded so far refer to the current value of `local`,
+    /// e.g., `self.len` in `self.len = self.len + 1`. Keep that value under a new node before
+    /// `local` is redefined, so that the new def does not constrain the old value.
+    fn retire_value(&mut self, local: usize) {
+        let refers_to = |def: &SymbolicDef<'tcx>| match def {
+            SymbolicDef::Use(src) | SymbolicDef::Cast(src, _) => *src == local,
+            SymbolicDef::Binary(_, lhs, rhs) | SymbolicDef::PtrOffset(_, lhs, rhs, _) => {
+                *lhs == local || *rhs == AnaOperand::Local(local)
+            }
+            _ => false,
+        };
+        let is_used = self
+            .value_domains
+            .values()
+            .filter_map(|domain| domain.def.as_ref())
+            .chain(self.path_constraints.iter())
+            .any(refers_to);
+        if !is_used {
+            return;
+        }
+
+        let old_value = self.chains.generate_node_id();
+        let ty = self.chains.get_var_node(local).and_then(|node| node.ty);
+        self.chains
+            .variables
+            .insert(old_value, VariableNode::new_default(old_value, ty));
+        let domain = self.value_domains.remove(&local).unwrap_or_default();
+        self.value_domains.insert(old_value, domain);
+        let rename = |id: &mut usize| {
+            if *id == local {
+                *id = old_value;
+            }
+        };
+        for def in self
+            .value_domains
+            .values_mut()
+            .filter_map(|domain| domain.def.as_mut())
+            .chain(self.path_constraints.iter_mut())
+        {
+            match def {
+                SymbolicDef::Use(src) | SymbolicDef::Cast(src, _) => rename(src),
+                SymbolicDef::Binary(_, lhs, rhs) | SymbolicDef::PtrOffset(_, lhs, rhs, _) => {
+                    rename(lhs);
+                    if let AnaOperand::Local(rhs) = rhs {
+                        rename(rhs);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Convert a MIR Operand into an AnaOperand (local node id or constant) when possible.
     fn lift_operand(&mut self, op: &Operand<'tcx>) -> Option<AnaOperand> {
         match op {
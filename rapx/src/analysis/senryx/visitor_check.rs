@@ -1,7 +1,8 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use super::{
     contracts::abstract_state::AlignState,
+    dominated_graph::{States, VariableNode},
     matcher::{UnsafeApi, get_arg_place},
     visitor::{BodyVisitor, CheckResult, PlaceTy},
 };
@@ -13,12 +14,13 @@ use crate::{
         },
         senryx::{
             contracts::property::{CisRange, CisRangeItem, PropertyContract},
-            symbolic_analysis::{AnaOperand, SymbolicDef, verify_with_z3},
+            symbolic_analysis::{AnaOperand, SymbolicDef, ValueDomain, verify_with_z3},
         },
         utils::fn_info::{
-            display_hashmap, generate_contract_from_annotation_without_field_types,
-            generate_contract_from_std_annotation_json, get_cleaned_def_path_name, get_pointee,
-            is_ptr, is_ref, is_strict_ty_convert, reflect_generic,
+            ContractKind, display_hashmap, generate_contract_from_annotation_without_field_types,
+            generate_contract_from_std_annotation_json, get_adt_def_id_by_adt_method,
+            get_cleaned_def_path_name, get_pointee, is_ptr, is_ref, is_strict_ty_convert,
+            reflect_generic,
         },
     },
     rap_debug, rap_error, rap_info, rap_warn,
@@ -28,11 +30,36 @@ use rustc_hir::def_id::DefId;
 use rustc_middle::mir::BinOp;
 use rustc_middle::mir::Operand;
 use rustc_middle::mir::Place;
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_middle::mir::{BasicBlock, Body, Local, Location, TerminatorKind};
 use rustc_middle::ty::Ty;
+use rustc_middle::ty::TyKind;
 use rustc_span::Span;
 use rustc_span::source_map::Spanned;
 use z3::ast::Ast;
 use z3::ast::BV;
+use z3::ast::Bool;
+
+/// Calls that give a view of the bytes of their receiver, keeping them valid UTF-8.
+const UTF8_VIEWS: &[&str] = &[
+    "as_bytes",
+    "as_bytes_mut",
+    "into_bytes",
+    "as_str",
+    "as_mut_str",
+    "as_ptr",
+    "as_mut_ptr",
+    "deref",
+];
+
+/// Calls that give a pointer to, or a view of, their receiver, keeping it NUL-terminated.
+const CSTR_VIEWS: &[&str] = &[
+    "as_ptr",
+    "as_c_str",
+    "as_bytes_with_nul",
+    "into_raw",
+    "deref",
+];
 
 impl<'tcx> BodyVisitor<'tcx> {
     /// Entry point for handling standard library unsafe API calls and verifying their contracts.
@@ -48,24 +75,158 @@ impl<'tcx> BodyVisitor<'tcx> {
         generic_mapping: FxHashMap<String, Ty<'tcx>>,
     ) {
         let func_name = get_cleaned_def_path_name(self.tcx, *def_id);
-
-        // If the target API has contract annotation in signature,
-        // this fn-call could be replaced with 'generate_contract_from_annotation_without_field_types(self.tcx, *def_id);'
         let args_with_contracts = generate_contract_from_std_annotation_json(self.tcx, *def_id);
+        self.check_call_contracts(
+            def_id,
+            args,
+            args_with_contracts,
+            &generic_mapping,
+            func_name,
+            fn_span,
+        );
+    }
+
+    /// Entry point for handling calls to the unsafe functions of this crate, whose preconditions
+    /// are annotated by `#[rapx::inner(property = ..., kind = "precond")]`.
+    pub fn handle_annotated_unsafe_call(
+        &mut self,
+        def_id: &DefId,
+        args: &[Spanned<Operand>],
+        fn_span: Span,
+        generic_mapping: FxHashMap<String, Ty<'tcx>>,
+    ) {
+        let func_name = get_cleaned_def_path_name(self.tcx, *def_id);
+        // The annotations refer to the params by their MIR locals, which begin from 1, while
+        // the std contracts refer to the args of the call.
+        let inferred = self
+            .inferred_contracts
+            .get(def_id)
+            .cloned()
+            .unwrap_or_default();
+        let args_with_contracts =
+            generate_contract_from_annotation_without_field_types(self.tcx, *def_id)
+                .into_iter()
+                .chain(inferred)
+                .filter(|(base, _, _)| *base > 0)
+                .map(|(base, fields, contract)| (base - 1, fields, param_to_arg_contract(contract)))
+                .collect();
+        self.check_call_contracts(
+            def_id,
+            args,
+            args_with_contracts,
+            &generic_mapping,
+            func_name,
+            fn_span,
+        );
+    }
+
+    /// Prove the postconditions of the function, and the invariants of its type if it is a
+    /// constructor or a `&mut self` method, at a return of the function.
+    pub fn check_return_obligations(&mut self, return_span: Span) {
+        for (kind, contracts) in self.return_obligations.clone() {
+            let def_id = match kind {
+                ContractKind::Invariant => {
+                    get_adt_def_id_by_adt_method(self.tcx, self.def_id).unwrap_or(self.def_id)
+                }
+                _ => self.def_id,
+            };
+            let name = get_cleaned_def_path_name(self.tcx, def_id);
+            self.checking_kind = kind;
+            for (idx, (base, fields, contract)) in contracts.into_iter().enumerate() {
+                let fields = fields.into_iter().map(|(field, _)| field).collect();
+                let node = self.chains.find_var_id_with_fields_seq(base, &fields);
+                let contract = self.chains.resolve_contract_vars(contract);
+                self.declare_values(node, &contract);
+                self.check_contract(
+                    node,
+                    &[],
+                    contract,
+                    &FxHashMap::default(),
+                    name.clone(),
+                    return_span,
+                    idx,
+                );
+            }
+        }
+        self.checking_kind = ContractKind::Precond;
+    }
+
+    /// Assume the numeric contracts annotated on the params, i.e., the preconditions and the
+    /// invariants of the receiver, by adding them to the path constraints.
+    pub fn assume_entry_contracts(&mut self) {
+        let mut assumptions = Vec::new();
+        for node in self.chains.variables.values() {
+            for cis in &node.cis.contracts {
+                let PropertyContract::ValidNum(range) = cis else {
+                    continue;
+                };
+                let bound = match &range.range {
+                    CisRangeItem::Var(bound, fields) if fields.is_empty() => {
+                        AnaOperand::Local(*bound)
+                    }
+                    CisRangeItem::Value(value) => AnaOperand::Const(*value as u128),
+                    _ => continue,
+                };
+                assumptions.push((node.id, cis.clone(), bound, range.bin_op));
+            }
+        }
+        for (node, cis, bound, bin_op) in assumptions {
+            self.declare_values(node, &cis);
+            self.path_constraints
+                .push(SymbolicDef::Binary(bin_op, node, bound));
+        }
+    }
+
+    /// Give the values without defs, e.g., the fields of the params, a free symbolic value,
+    /// so that the contract on `node` can be encoded.
+    fn declare_values(&mut self, node: usize, contract: &PropertyContract<'tcx>) {
+        let mut values = vec![node];
+        if let PropertyContract::ValidNum(CisRange {
+            range: CisRangeItem::Var(bound, _),
+            ..
+        }) = contract
+        {
+            values.push(*bound);
+        }
+        for value in values {
+            self.value_domains
+                .entry(value)
+                .or_insert_with(|| ValueDomain {
+                    def: Some(SymbolicDef::Param(value)),
+                    value_constraint: None,
+                });
+        }
+    }
 
-        for (idx, (base, fields, contract)) in args_with_contracts.iter().enumerate() {
+    /// Instantiate the contracts of the callee with the args of the call, and check them.
+    fn check_call_contracts(
+        &mut self,
+        def_id: &DefId,
+        args: &[Spanned<Operand>],
+        args_with_contracts: Vec<(usize, Vec<usize>, PropertyContract<'tcx>)>,
+        generic_mapping: &FxHashMap<String, Ty<'tcx>>,
+        func_name: String,
+        fn_span: Span,
+    ) {
+        for (idx, (base, fields, contract)) in args_with_contracts.into_iter().enumerate() {
             rap_debug!("Find contract for {:?}, {base}: {:?}", def_id, contract);
-            let arg_tuple = get_arg_place(&args[*base].node);
+            let Some(arg) = args.get(base) else {
+                continue;
+            };
+            let arg_tuple = get_arg_place(&arg.node);
             // if this arg is a constant
             if arg_tuple.0 {
                 continue; //TODO: check the constant value
             } else {
-                let arg_place = self.chains.find_var_id_with_fields_seq(arg_tuple.1, fields);
+                let arg_place = self
+                    .chains
+                    .find_var_id_with_fields_seq(arg_tuple.1, &fields);
+                let contract = self.resolve_contract_args(contract, args);
                 self.check_contract(
                     arg_place,
                     args,
-                    contract.clone(),
-                    &generic_mapping,
+                    contract,
+                    generic_mapping,
                     func_name.clone(),
                     fn_span,
                     idx,
@@ -74,6 +235,52 @@ impl<'tcx> BodyVisitor<'tcx> {
         }
     }
 
+    /// Lengths and bounds written as `Arg_N` in the std contracts refer to the N-th argument of
+    /// the call. Replace them with the local or the constant passed at this call site.
+    fn resolve_contract_args(
+        &mut self,
+        contract: PropertyContract<'tcx>,
+        args: &[Spanned<Operand>],
+    ) -> PropertyContract<'tcx> {
+        match contract {
+            PropertyContract::Allocated(ty, len) => {
+                PropertyContract::Allocated(ty, self.resolve_arg_item(len, args))
+            }
+            PropertyContract::InBound(ty, len) => {
+                PropertyContract::InBound(ty, self.resolve_arg_item(len, args))
+            }
+            PropertyContract::Init(ty, len) => {
+                PropertyContract::Init(ty, self.resolve_arg_item(len, args))
+            }
+            PropertyContract::ValidPtr(ty, len) => {
+                PropertyContract::ValidPtr(ty, self.resolve_arg_item(len, args))
+            }
+            PropertyContract::ValidNum(range) => PropertyContract::ValidNum(CisRange::new(
+                range.bin_op,
+                self.resolve_arg_item(range.range, args),
+            )),
+            contract => contract,
+        }
+    }
+
+    fn resolve_arg_item(&mut self, item: CisRangeItem, args: &[Spanned<Operand>]) -> CisRangeItem {
+        match item {
+            CisRangeItem::Var(idx, fields) => {
+                let Some(arg) = args.get(idx) else {
+                    return CisRangeItem::Unknown;
+                };
+                let (is_const, value) = get_arg_place(&arg.node);
+                if is_const {
+                    CisRangeItem::Value(value)
+                } else {
+                    let local = self.chains.find_var_id_with_fields_seq(value, &fields);
+                    CisRangeItem::new_var(local)
+                }
+            }
+            item => item,
+        }
+    }
+
     /// Dispatcher function that validates a specific contract type.
     pub fn check_contract(
         &mut self,
@@ -86,6 +293,7 @@ impl<'tcx> BodyVisitor<'tcx> {
         idx: usize,
     ) -> bool {
         rap_debug!("Check contract {:?} for {:?}.", contract, func_name);
+        let checked_contract = contract.clone();
         let (sp_name, check_result) = match contract {
             PropertyContract::Align(ty) => {
                 let contract_required_ty = reflect_generic(generic_mapping, &func_name, ty);
@@ -110,13 +318,129 @@ impl<'tcx> BodyVisitor<'tcx> {
                 let check_result = self.check_valid_ptr(arg, contract_len, contract_required_ty);
                 ("ValidPtr", check_result)
             }
+            PropertyContract::Allocated(ty, contract_len) => {
+                let contract_required_ty = reflect_generic(generic_mapping, &func_name, ty);
+                let mut check_result = self.check_allocated(arg)
+                    && self.check_inbound(arg, contract_len, contract_required_ty);
+                // Memory handed over to the alloc crate is freed by the global allocator.
+                if func_name.starts_with("alloc::") {
+                    check_result &= self.check_allocator_consistency(func_name.clone(), arg);
+                }
+                ("Allocated", check_result)
+            }
+            PropertyContract::ValidNum(range) => {
+                let check_result = self.check_valid_num(arg, &range);
+                ("ValidNum", check_result)
+            }
+            PropertyContract::ValidString => {
+                let check_result = self.check_valid_string(arg);
+                ("ValidString", check_result)
+            }
+            PropertyContract::ValidCStr => {
+                let check_result = self.check_valid_cstr(arg);
+                ("ValidCStr", check_result)
+            }
+            PropertyContract::Alias => {
+                let check_result = self.check_alias(arg, fn_span);
+                ("Alias", check_result)
+            }
+            PropertyContract::Deref => {
+                let check_result = match self.chains.get_obj_ty_through_chain(arg) {
+                    Some(ty) => self.check_deref(arg, CisRangeItem::new_value(1), ty),
+                    None => false,
+                };
+                ("Deref", check_result)
+            }
+            PropertyContract::Ptr2Ref => {
+                let check_result = match self.chains.get_obj_ty_through_chain(arg) {
+                    Some(ty) => self.check_ref_to_ptr(arg, CisRangeItem::new_value(1), ty, fn_span),
+                    None => false,
+                };
+                ("Ptr2Ref", check_result)
+            }
             _ => ("Unknown", false),
         };
 
+        if !check_result && self.checking_kind == ContractKind::Precond {
+            self.record_unmet_contract(arg, checked_contract, generic_mapping, &func_name);
+        }
         self.insert_checking_result(sp_name, check_result, func_name, fn_span, idx);
         true
     }
 
+    /// Keep the failed precondition of a callee if it can be required on the params instead,
+    /// i.e., the checked value and the lengths or bounds are the params passed down unchanged.
+    fn record_unmet_contract(
+        &mut self,
+        arg: usize,
+        contract: PropertyContract<'tcx>,
+        generic_mapping: &FxHashMap<String, Ty<'tcx>>,
+        func_name: &str,
+    ) {
+        let Some(param) = self.param_of(arg) else {
+            return;
+        };
+        let item_of = |item: CisRangeItem| match item {
+            CisRangeItem::Var(node, fields) if fields.is_empty() => {
+                self.param_of(node).map(CisRangeItem::new_var)
+            }
+            CisRangeItem::Value(value) => Some(CisRangeItem::Value(value)),
+            _ => None,
+        };
+        let ty_of = |ty| reflect_generic(generic_mapping, func_name, ty);
+        let contract = match contract {
+            PropertyContract::Align(ty) => PropertyContract::Align(ty_of(ty)),
+            PropertyContract::Typed(ty) => PropertyContract::Typed(ty_of(ty)),
+            PropertyContract::Allocated(ty, len) => {
+                let Some(len) = item_of(len) else { return };
+                PropertyContract::Allocated(ty_of(ty), len)
+            }
+            PropertyContract::InBound(ty, len) => {
+                let Some(len) = item_of(len) else { return };
+                PropertyContract::InBound(ty_of(ty), len)
+            }
+            PropertyContract::Init(ty, len) => {
+                let Some(len) = item_of(len) else { return };
+                PropertyContract::Init(ty_of(ty), len)
+            }
+            PropertyContract::ValidPtr(ty, len) => {
+                let Some(len) = item_of(len) else { return };
+                PropertyContract::ValidPtr(ty_of(ty), len)
+            }
+            PropertyContract::ValidNum(range) => {
+                let Some(bound) = item_of(range.range) else {
+                    return;
+                };
+                PropertyContract::ValidNum(CisRange::new(range.bin_op, bound))
+            }
+            PropertyContract::Unknown => return,
+            contract => contract,
+        };
+        self.unmet_param_contracts.push((param, contract));
+    }
+
+    /// The param whose value reaches `node` through copies and casts only.
+    fn param_of(&self, node: usize) -> Option<usize> {
+        let mut cur = node;
+        let mut visited = HashSet::new();
+        while visited.insert(cur) {
+            match self
+                .value_domains
+                .get(&cur)
+                .and_then(|domain| domain.def.as_ref())
+            {
+                Some(SymbolicDef::Param(param)) => {
+                    // Values without defs are declared as params too, e.g., the fields.
+                    let arg_count = self.tcx.optimized_mir(self.def_id).arg_count;
+                    return (1..=arg_count).contains(param).then_some(*param);
+                }
+                Some(SymbolicDef::Use(src)) | Some(SymbolicDef::Cast(src, _)) => cur = *src,
+                _ => return None,
+            }
+        }
+        None
+    }
+
     // ---------------------- Sp checking functions --------------------------
 
     /// Taint Analysis: Check if the base pointer comes from a determined/aligned source.
@@ -168,20 +492,13 @@ impl<'tcx> BodyVisitor<'tcx> {
             self.show_error_info(arg);
         }
         let ori_ty = self.visit_ty_and_get_layout(obj_ty.unwrap());
+        // Only a type known to be zero-sized is reported as ZST.
         match ori_ty {
-            PlaceTy::Ty(_align, size) => size == 0,
+            PlaceTy::Ty(_align, size) => size != 0,
             PlaceTy::GenericTy(_, _, tys) => {
-                if tys.is_empty() {
-                    return false;
-                }
-                for (_, size) in tys {
-                    if size != 0 {
-                        return false;
-                    }
-                }
-                true
+                tys.is_empty() || tys.iter().any(|(_, size)| *size != 0)
             }
-            _ => false,
+            _ => true,
         }
     }
 
@@ -221,36 +538,218 @@ impl<'tcx> BodyVisitor<'tcx> {
         }
     }
 
-    pub fn check_allocator_consistency(&self, _func_name: String, _arg: usize) -> bool {
-        true
+    /// The pointee of `arg` must be owned by the global allocator, i.e. returned by it.
+    pub fn check_allocator_consistency(&self, func_name: String, arg: usize) -> bool {
+        let Some(obj) = self.get_pointee_node(arg) else {
+            rap_debug!("{func_name}: _{arg} does not point to a known object");
+            return false;
+        };
+        !obj.is_dropped && obj.ots.allocator_consistency
     }
 
-    pub fn check_allocated(&self, _arg: usize) -> bool {
-        true
+    /// The pointee of `arg` must be memory that is known to exist and not yet dropped: a place
+    /// of this body, the referent of a reference or an allocation.
+    pub fn check_allocated(&self, arg: usize) -> bool {
+        self.get_pointee_node(arg)
+            .is_some_and(|obj| !obj.is_dropped && obj.ots.allocated)
     }
 
+    /// `length_arg` values of `contract_ty` starting at `arg` must lie in the object `arg`
+    /// points to. The offsets `arg` is derived by are summed up and compared with the size of
+    /// the object, which is a single value or the bound recorded for a slice, under the path
+    /// constraints.
     pub fn check_inbound(
         &self,
         arg: usize,
         length_arg: CisRangeItem,
         contract_ty: Ty<'tcx>,
     ) -> bool {
-        false
+        let Some(obj) = self.get_pointee_node(arg) else {
+            return false;
+        };
+        let obj_bound = obj.cis.contracts.iter().find_map(|cis| match cis {
+            PropertyContract::InBound(ty, len) => Some((*ty, len.clone())),
+            _ => None,
+        });
+        let (elem_ty, obj_len) = match (obj_bound, obj.ty) {
+            (Some(bound), _) => bound,
+            (None, Some(ty)) => (ty, CisRangeItem::new_value(1)),
+            (None, None) => return false,
+        };
+        let elem_layout = self.visit_ty_and_get_layout(elem_ty);
+        let access_layout = self.visit_ty_and_get_layout(contract_ty);
+        let offsets = self.collect_ptr_offsets(arg);
+
+        // Count in elements if everything shares one layout, which also covers generic types,
+        // and in bytes otherwise.
+        let same_unit = elem_layout == access_layout
+            && offsets.iter().all(|(_, _, stride)| *stride == elem_layout);
+        let unit = |layout: &PlaceTy<'tcx>| {
+            if same_unit {
+                Some(1)
+            } else {
+                Self::concrete_size(layout)
+            }
+        };
+        let (Some(elem_size), Some(access_size)) = (unit(&elem_layout), unit(&access_layout))
+        else {
+            return false;
+        };
+        let mut byte_offsets = Vec::new();
+        for (op, offset, stride) in offsets {
+            let Some(stride_size) = unit(&stride) else {
+                return false;
+            };
+            byte_offsets.push((op, offset, stride_size));
+        }
+
+        self.prove_with_facts(|ctx, vars| {
+            // 128 bits hold the products of 64-bit values and sizes without wrapping.
+            fn wide(bv: BV<'_>, signed: bool) -> BV<'_> {
+                if signed {
+                    bv.sign_ext(64)
+                } else {
+                    bv.zero_ext(64)
+                }
+            }
+            let mut start = BV::from_u64(ctx, 0, 128);
+            for (op, offset, stride) in &byte_offsets {
+                let offset = match offset {
+                    AnaOperand::Local(idx) => vars.get(idx)?.clone(),
+                    AnaOperand::Const(val) => BV::from_u64(ctx, *val as u64, 64),
+                };
+                // `offset` takes an isize, the others a usize.
+                let bytes =
+                    wide(offset, *op == BinOp::Offset).bvmul(&BV::from_u64(ctx, *stride, 128));
+                start = if *op == BinOp::Sub {
+                    start.bvsub(&bytes)
+                } else {
+                    start.bvadd(&bytes)
+                };
+            }
+            let item = |item: &CisRangeItem| match item {
+                CisRangeItem::Var(idx, _) => vars.get(idx).map(|bv| wide(bv.clone(), false)),
+                CisRangeItem::Value(val) => Some(BV::from_u64(ctx, *val as u64, 128)),
+                CisRangeItem::Unknown => None,
+            };
+            let need = item(&length_arg)?.bvmul(&BV::from_u64(ctx, access_size, 128));
+            let size = item(&obj_len)?.bvmul(&BV::from_u64(ctx, elem_size, 128));
+            let zero = BV::from_u64(ctx, 0, 128);
+            Some(Bool::and(
+                ctx,
+                &[&start.bvsge(&zero), &start.bvadd(&need).bvsle(&size)],
+            ))
+        })
     }
 
-    pub fn check_valid_string(&self, _arg: usize) -> bool {
-        true
+    /// The bytes behind `arg` must be valid UTF-8: they belong to a `str` or `String`, or are
+    /// a byte view taken from one.
+    pub fn check_valid_string(&self, arg: usize) -> bool {
+        self.is_text_source(arg, &|ots| ots.valid_string, UTF8_VIEWS, 0)
     }
 
-    pub fn check_valid_cstr(&self, _arg: usize) -> bool {
-        true
+    /// The pointee of `arg` must be NUL-terminated: it belongs to a `CStr` or `CString`, or is
+    /// a pointer taken from one.
+    pub fn check_valid_cstr(&self, arg: usize) -> bool {
+        self.is_text_source(arg, &|ots| ots.valid_cstr, CSTR_VIEWS, 0)
     }
 
-    pub fn check_valid_num(&self, _arg: usize) -> bool {
-        true
+    /// The value of `arg` must satisfy `arg <op> bound`. The interval from the range analysis
+    /// is tried first, then the path constraints, compared as signed or unsigned integers
+    /// according to the type of `arg`.
+    pub fn check_valid_num(&self, arg: usize, range: &CisRange) -> bool {
+        if let (Some(&(lo, hi)), CisRangeItem::Value(bound)) =
+            (self.local_ranges.get(&arg), &range.range)
+        {
+            let bound = *bound as i128;
+            let holds = match range.bin_op {
+                BinOp::Lt => hi < bound,
+                BinOp::Le => hi <= bound,
+                BinOp::Gt => lo > bound,
+                BinOp::Ge => lo >= bound,
+                BinOp::Eq => lo == bound && hi == bound,
+                BinOp::Ne => bound < lo || bound > hi,
+                _ => false,
+            };
+            if holds {
+                return true;
+            }
+        }
+        let signed = self
+            .tcx
+            .optimized_mir(self.def_id)
+            .local_decls
+            .get(Local::from_usize(arg))
+            .is_some_and(|decl| decl.ty.is_signed());
+        self.prove_with_facts(|ctx, vars| {
+            let value = vars.get(&arg)?;
+            let bound = match &range.range {
+                CisRangeItem::Var(idx, _) => vars.get(idx)?.clone(),
+                CisRangeItem::Value(val) if signed => BV::from_i64(ctx, *val as i64, 64),
+                CisRangeItem::Value(val) => BV::from_u64(ctx, *val as u64, 64),
+                CisRangeItem::Unknown => return None,
+            };
+            match (range.bin_op, signed) {
+                (BinOp::Lt, true) => Some(value.bvslt(&bound)),
+                (BinOp::Lt, false) => Some(value.bvult(&bound)),
+                (BinOp::Le, true) => Some(value.bvsle(&bound)),
+                (BinOp::Le, false) => Some(value.bvule(&bound)),
+                (BinOp::Gt, true) => Some(value.bvsgt(&bound)),
+                (BinOp::Gt, false) => Some(value.bvugt(&bound)),
+                (BinOp::Ge, true) => Some(value.bvsge(&bound)),
+                (BinOp::Ge, false) => Some(value.bvuge(&bound)),
+                (BinOp::Eq, _) => Some(value._eq(&bound)),
+                (BinOp::Ne, _) => Some(value._eq(&bound).not()),
+                _ => None,
+            }
+        })
     }
 
-    pub fn check_alias(&self, _arg: usize) -> bool {
+    /// The reference created from `arg` must not overlap another reference that is still used
+    /// after the call: any such reference if the new one is mutable, a mutable one otherwise.
+    /// The references to the pointee are looked up in the alias graph.
+    pub fn check_alias(&self, arg: usize, fn_span: Span) -> bool {
+        let (Some(obj), Some(arg_node)) =
+            (self.get_pointee_node(arg), self.chains.get_var_node(arg))
+        else {
+            return false;
+        };
+        let Some(call_bb) = self.find_call_block(fn_span) else {
+            return false;
+        };
+        let body = self.tcx.optimized_mir(self.def_id);
+        let TerminatorKind::Call { func, .. } = &body[call_bb].terminator().kind else {
+            return false;
+        };
+        let Some((callee, _)) = func.const_fn_def() else {
+            return false;
+        };
+        // The created reference is mutable if the callee returns a mutable reference, e.g.,
+        // `&'a mut T` or `Option<&'a mut T>`.
+        let output = self.tcx.fn_sig(callee).skip_binder().output().skip_binder();
+        let is_mut = output
+            .walk()
+            .filter_map(|arg| arg.as_type())
+            .any(|ty| matches!(ty.kind(), TyKind::Ref(_, _, mutbl) if mutbl.is_mut()));
+        for node in self.chains.variables.values() {
+            if node.id == arg_node.id
+                || !node.points_to.is_some_and(|to| obj.alias_set.contains(&to))
+            {
+                continue;
+            }
+            let Some(TyKind::Ref(_, _, mutbl)) = node.ty.map(|ty| ty.kind()) else {
+                continue;
+            };
+            if !is_mut && !mutbl.is_mut() {
+                continue;
+            }
+            if node.alias_set.iter().any(|local| {
+                self.chains.is_local(*local) && Self::is_used_after(body, call_bb, *local)
+            }) {
+                rap_debug!("_{arg} aliases the live reference _{}", node.id);
+                return false;
+            }
+        }
         true
     }
 
@@ -262,8 +761,7 @@ impl<'tcx> BodyVisitor<'tcx> {
         length_arg: CisRangeItem,
         contract_ty: Ty<'tcx>,
     ) -> bool {
-        !self.check_non_zst(arg)
-            || (self.check_non_zst(arg) && self.check_deref(arg, length_arg, contract_ty))
+        !self.check_non_zst(arg) || self.check_deref(arg, length_arg, contract_ty)
     }
 
     pub fn check_deref(&self, arg: usize, length_arg: CisRangeItem, contract_ty: Ty<'tcx>) -> bool {
@@ -275,11 +773,12 @@ impl<'tcx> BodyVisitor<'tcx> {
         arg: usize,
         length_arg: CisRangeItem,
         contract_ty: Ty<'tcx>,
+        fn_span: Span,
     ) -> bool {
         self.check_deref(arg, length_arg, contract_ty)
             && self.check_init(arg)
             && self.check_align(arg, contract_ty)
-            && self.check_alias(arg)
+            && self.check_alias(arg, fn_span)
     }
 
     // -------------------------- helper functions: insert checking results --------------------------
@@ -311,11 +810,10 @@ impl<'tcx> BodyVisitor<'tcx> {
         idx: usize,
         sp: &str,
     ) {
-        if let Some(existing) = self
-            .check_results
-            .iter_mut()
-            .find(|result| result.func_name == func_name && result.func_span == fn_span)
-        {
+        let kind = self.checking_kind;
+        if let Some(existing) = self.check_results.iter_mut().find(|result| {
+            result.func_name == func_name && result.func_span == fn_span && result.kind == kind
+        }) {
             if let Some(passed_set) = existing.passed_contracts.get_mut(&idx) {
                 passed_set.remove(sp);
                 if passed_set.is_empty() {
@@ -335,6 +833,7 @@ impl<'tcx> BodyVisitor<'tcx> {
                 });
         } else {
             let mut new_result = CheckResult::new(&func_name, fn_span);
+            new_result.kind = kind;
             new_result
                 .failed_contracts
                 .insert(idx, HashSet::from([sp.to_string()]));
@@ -350,11 +849,10 @@ impl<'tcx> BodyVisitor<'tcx> {
         idx: usize,
         sp: &str,
     ) {
-        if let Some(existing) = self
-            .check_results
-            .iter_mut()
-            .find(|result| result.func_name == func_name && result.func_span == fn_span)
-        {
+        let kind = self.checking_kind;
+        if let Some(existing) = self.check_results.iter_mut().find(|result| {
+            result.func_name == func_name && result.func_span == fn_span && result.kind == kind
+        }) {
             if let Some(failed_set) = existing.failed_contracts.get_mut(&idx) {
                 if failed_set.contains(sp) {
                     return;
@@ -370,6 +868,7 @@ impl<'tcx> BodyVisitor<'tcx> {
                 .or_insert_with(|| HashSet::from([sp.to_string()]));
         } else {
             let mut new_result = CheckResult::new(&func_name, fn_span);
+            new_result.kind = kind;
             new_result
                 .passed_contracts
                 .insert(idx, HashSet::from([sp.to_string()]));
@@ -386,6 +885,199 @@ impl<'tcx> BodyVisitor<'tcx> {
     }
 }
 
+/// Helpers shared by the checks above.
+impl<'tcx> BodyVisitor<'tcx> {
+    /// The node of the object `arg` points to, if it points to one.
+    fn get_pointee_node(&self, arg: usize) -> Option<&VariableNode<'tcx>> {
+        let obj = self.chains.get_var_node(arg)?.points_to?;
+        self.chains.get_var_node(obj)
+    }
+
+    /// The offsets `arg` was derived by from the pointer it is based on, as
+    /// `(op, offset, stride)`.
+    fn collect_ptr_offsets(&self, arg: usize) -> Vec<(BinOp, AnaOperand, PlaceTy<'tcx>)> {
+        let mut offsets = Vec::new();
+        let mut curr = arg;
+        while let Some((op, base, offset, stride)) = self.get_ptr_offset_info(curr) {
+            if base == curr || offsets.len() >= 16 {
+                break;
+            }
+            offsets.push((op, offset, stride));
+            curr = base;
+        }
+        offsets
+    }
+
+    fn concrete_size(layout: &PlaceTy<'tcx>) -> Option<u64> {
+        match layout {
+            PlaceTy::Ty(_, size) => Some(*size as u64),
+            PlaceTy::GenericTy(_, _, tys) if tys.len() == 1 => {
+                tys.iter().next().map(|(_, size)| *size as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Prove the property built by `target` under the path constraints and the facts that hold
+    /// on every path. A property that cannot be built is not proved.
+    fn prove_with_facts<F>(&self, target: F) -> bool
+    where
+        F: for<'ctx> FnOnce(&'ctx z3::Context, &HashMap<usize, BV<'ctx>>) -> Option<Bool<'ctx>>,
+    {
+        verify_with_z3(
+            self.value_domains.clone(),
+            self.path_constraints.clone(),
+            |ctx, vars| {
+                let Some(property) = target(ctx, vars) else {
+                    return Bool::from_bool(ctx, false);
+                };
+                let facts = self.known_facts(ctx, vars);
+                let facts: Vec<&Bool> = facts.iter().collect();
+                Bool::and(ctx, &facts).implies(&property)
+            },
+        )
+    }
+
+    /// The intervals of the range analysis, and the bound of `isize::MAX` bytes on the size of
+    /// every object whose length is a local.
+    fn known_facts<'ctx>(
+        &self,
+        ctx: &'ctx z3::Context,
+        vars: &HashMap<usize, BV<'ctx>>,
+    ) -> Vec<Bool<'ctx>> {
+        let mut facts = Vec::new();
+        for (local, (lo, hi)) in &self.local_ranges {
+            let Some(var) = vars.get(local) else {
+                continue;
+            };
+            let (lo_bv, hi_bv) = (
+                BV::from_i64(ctx, *lo as i64, 64),
+                BV::from_i64(ctx, *hi as i64, 64),
+            );
+            if *lo >= 0 {
+                facts.push(var.bvuge(&lo_bv));
+                facts.push(var.bvule(&hi_bv));
+            } else {
+                facts.push(var.bvsge(&lo_bv));
+                facts.push(var.bvsle(&hi_bv));
+            }
+        }
+        for node in self.chains.variables.values() {
+            for cis in &node.cis.contracts {
+                let PropertyContract::InBound(ty, CisRangeItem::Var(len, _)) = cis else {
+                    continue;
+                };
+                let (Some(var), Some(size)) = (
+                    vars.get(len),
+                    Self::concrete_size(&self.visit_ty_and_get_layout(*ty)),
+                ) else {
+                    continue;
+                };
+                if size > 0 {
+                    let max_len = isize::MAX as u64 / size;
+                    facts.push(var.bvule(&BV::from_u64(ctx, max_len, 64)));
+                }
+            }
+        }
+        facts
+    }
+
+    /// Whether the state picked by `is_valid` holds for `local` or its pointee, or for the
+    /// receiver of a view call in `views` that `local` was returned by.
+    fn is_text_source(
+        &self,
+        local: usize,
+        is_valid: &dyn Fn(&States<'tcx>) -> bool,
+        views: &[&str],
+        depth: usize,
+    ) -> bool {
+        if depth > 10 {
+            return false;
+        }
+        if let Some(node) = self.chains.get_var_node(local)
+            && is_valid(&node.ots)
+        {
+            return true;
+        }
+        if let Some(obj) = self.get_pointee_node(local)
+            && is_valid(&obj.ots)
+        {
+            return true;
+        }
+        let Some(def) = self.value_domains.get(&local).and_then(|d| d.def.as_ref()) else {
+            return false;
+        };
+        match def {
+            SymbolicDef::Use(src) | SymbolicDef::Cast(src, _) | SymbolicDef::Ref(src) => {
+                self.is_text_source(*src, is_valid, views, depth + 1)
+            }
+            SymbolicDef::Call(func_name, args) => {
+                let is_view = views
+                    .iter()
+                    .any(|view| func_name.ends_with(&format!("::{view}")));
+                match args.first() {
+                    Some(AnaOperand::Local(receiver)) if is_view => {
+                        self.is_text_source(*receiver, is_valid, views, depth + 1)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// The block whose terminator is the call at `fn_span`.
+    fn find_call_block(&self, fn_span: Span) -> Option<BasicBlock> {
+        let body = self.tcx.optimized_mir(self.def_id);
+        body.basic_blocks
+            .iter_enumerated()
+            .find_map(|(bb, data)| match &data.terminator().kind {
+                TerminatorKind::Call { fn_span: span, .. } if *span == fn_span => Some(bb),
+                _ => None,
+            })
+    }
+
+    /// Whether `local` is used in a block reachable from the successors of `bb`.
+    fn is_used_after(body: &Body<'tcx>, bb: BasicBlock, local: usize) -> bool {
+        let mut finder = LocalUseFinder {
+            local: Local::from_usize(local),
+            found: false,
+        };
+        let mut visited = HashSet::new();
+        let mut worklist: Vec<BasicBlock> =
+            body.basic_blocks[bb].terminator().successors().collect();
+        while let Some(curr) = worklist.pop() {
+            if !visited.insert(curr) {
+                continue;
+            }
+            let data = &body.basic_blocks[curr];
+            if data.is_cleanup {
+                continue;
+            }
+            finder.visit_basic_block_data(curr, data);
+            if finder.found {
+                return true;
+            }
+            worklist.extend(data.terminator().successors());
+        }
+        false
+    }
+}
+
+/// Finds a use of a local, ignoring storage markers.
+struct LocalUseFinder {
+    local: Local,
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalUseFinder {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, _location: Location) {
+        if local == self.local && !matches!(context, PlaceContext::NonUse(_)) {
+            self.found = true;
+        }
+    }
+}
+
 /// Impl block for Align check
 /// Align checking functions
 impl<'tcx> BodyVisitor<'tcx> {
@@ -849,3 +1541,23 @@ impl<'tcx> BodyVisitor<'tcx> {
         }
     }
 }
+
+/// Make the lengths and bounds in an annotated contract refer to the args of the call rather
+/// than the MIR locals of the params.
+fn param_to_arg_contract(contract: PropertyContract<'_>) -> PropertyContract<'_> {
+    let param_to_arg = |item: CisRangeItem| match item {
+        CisRangeItem::Var(local, fields) if local > 0 => CisRangeItem::Var(local - 1, fields),
+        CisRangeItem::Var(..) => CisRangeItem::Unknown,
+        item => item,
+    };
+    match contract {
+        PropertyContract::Allocated(ty, len) => PropertyContract::Allocated(ty, param_to_arg(len)),
+        PropertyContract::InBound(ty, len) => PropertyContract::InBound(ty, param_to_arg(len)),
+        PropertyContract::ValidPtr(ty, len) => PropertyContract::ValidPtr(ty, param_to_arg(len)),
+        PropertyContract::Init(ty, len) => PropertyContract::Init(ty, param_to_arg(len)),
+        PropertyContract::ValidNum(range) => {
+            PropertyContract::ValidNum(CisRange::new(range.bin_op, param_to_arg(range.range)))
+        }
+        contract => contract,
+    }
+}
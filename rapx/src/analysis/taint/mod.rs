@@ -0,0 +1,432 @@
+pub mod spec;
+
+use crate::{
+    analysis::{
+        Analysis,
+        core::dataflow::{
+            Arg2RetMap, DataFlowAnalysis, EdgeOp, FnSummaryMap, GraphEdge, NodeOp, SummaryPlace,
+            default::DataFlowAnalyzer, graph::Graph,
+        },
+    },
+    report::{self, Finding, FindingKind},
+    utils::{
+        log::{
+            are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
+            span_to_source_code,
+        },
+        source::get_name,
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Local, RETURN_PLACE},
+    ty::TyCtxt,
+};
+use rustc_span::{Span, Symbol};
+use spec::{ResolvedSpec, TaintSpec};
+use std::{
+    collections::{HashMap, VecDeque, hash_map::Entry},
+    path::{Path, PathBuf},
+};
+
+/// One step of a flow of tainted data, e.g., a call the data passes through.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A flow of tainted data from a source, or from a parameter of a function.
+#[derive(Debug, Clone, Default)]
+pub struct Flow {
+    pub source: Option<DefId>,
+    pub steps: Vec<Step>,
+}
+
+impl Flow {
+    fn then(&self, span: Span, message: String) -> Flow {
+        let mut flow = self.clone();
+        flow.steps.push(Step { span, message });
+        flow
+    }
+}
+
+/// A tainted flow from a source to a sink reported in a function.
+#[derive(Debug, Clone)]
+pub struct TaintFlow {
+    pub def_id: DefId,
+    pub sink: DefId,
+    pub flow: Flow,
+}
+
+/// The results of propagating tainted data in a function.
+#[derive(Default)]
+struct Propagation {
+    /// The flow to the return value, if any.
+    returned: Option<Flow>,
+    /// The flows to sinks, ending with the step at the sink.
+    sinks: Vec<(DefId, Flow)>,
+}
+
+/// Taint analysis on the dataflow graphs.
+///
+/// The sources, sanitizers and sinks are given by a TOML specification (see `TaintSpec`).
+/// Tainted data are propagated forward along the edges of the dataflow graph of each function.
+/// A call propagates the tainted arguments to its return value if the callee has no dataflow
+/// graph in the crate, or if the Arg2Ret summary of the callee says so. The tainted arguments
+/// also taint the values borrowed mutably by the other arguments, e.g., `s` in
+/// `s.push_str(tainted)`, if the callee is foreign or its summary writes through them.
+///
+/// Across functions, the check computes which functions may return tainted data from the
+/// sources inside them, and which parameters flow to sinks inside the functions, until a
+/// fixpoint is reached. A flow is reported in the function where its source and sink meet,
+/// with the full path of the flow.
+pub struct TaintCheck<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    spec_path: PathBuf,
+    spec: ResolvedSpec,
+    graphs: HashMap<DefId, Graph>,
+    arg2ret: Arg2RetMap,
+    summaries: FnSummaryMap,
+    /// The functions that may return tainted data, with the flows to their return values.
+    tainted_returns: FxHashMap<DefId, Flow>,
+    /// The parameters flowing to sinks, with the sinks and the flows from the parameters.
+    param_sinks: FxHashMap<(DefId, usize), (DefId, Flow)>,
+    pub flows: Vec<TaintFlow>,
+}
+
+impl<'tcx> Analysis for TaintCheck<'tcx> {
+    fn name(&self) -> &'static str {
+        "Taint analysis."
+    }
+
+    fn run(&mut self) {
+        let tcx = self.tcx;
+        self.spec = ResolvedSpec::new(tcx, &TaintSpec::load(&self.spec_path));
+        if self.spec.sources.is_empty() || self.spec.sinks.is_empty() {
+            rap_warn!("The taint specification has no resolved sources or sinks.");
+            return;
+        }
+        let mut analyzer = DataFlowAnalyzer::new(tcx, false);
+        analyzer.build_graphs();
+        self.arg2ret = analyzer.get_all_arg2ret();
+        self.summaries = analyzer.get_all_summaries();
+        self.graphs = analyzer.graphs;
+
+        let mut fns: Vec<DefId> = self.graphs.keys().copied().collect();
+        fns.sort_by_cached_key(|def_id| tcx.def_path_str(*def_id));
+        self.summarize(&fns);
+
+        for def_id in fns {
+            let mut reported = FxHashSet::default();
+            let mut flows = Vec::new();
+            for (seed, flow) in self.source_seeds(def_id) {
+                for (sink, flow) in self.propagate(def_id, seed, flow).sinks {
+                    let spans: Vec<Span> = flow.steps.iter().map(|step| step.span).collect();
+                    if reported.insert((flow.source, sink, spans)) {
+                        flows.push(TaintFlow { def_id, sink, flow });
+                    }
+                }
+            }
+            if !flows.is_empty() {
+                self.report(def_id, &flows);
+                self.flows.extend(flows);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.graphs.clear();
+        self.arg2ret.clear();
+        self.summaries.clear();
+        self.tainted_returns.clear();
+        self.param_sinks.clear();
+        self.flows.clear();
+    }
+}
+
+impl<'tcx> TaintCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, spec_path: &Path) -> Self {
+        Self {
+            tcx,
+            spec_path: spec_path.to_path_buf(),
+            spec: ResolvedSpec::default(),
+            graphs: HashMap::new(),
+            arg2ret: Arg2RetMap::new(),
+            summaries: FnSummaryMap::new(),
+            tainted_returns: FxHashMap::default(),
+            param_sinks: FxHashMap::default(),
+            flows: Vec::new(),
+        }
+    }
+
+    /// Compute the functions returning tainted data and the parameters flowing to sinks.
+    fn summarize(&mut self, fns: &[DefId]) {
+        loop {
+            let mut changed = false;
+            for def_id in fns {
+                if !self.tainted_returns.contains_key(def_id) {
+                    let returned = self
+                        .source_seeds(*def_id)
+                        .into_iter()
+                        .find_map(|(seed, flow)| self.propagate(*def_id, seed, flow).returned);
+                    if let Some(flow) = returned {
+                        self.tainted_returns.insert(*def_id, flow);
+                        changed = true;
+                    }
+                }
+                for arg in 0..self.graphs[def_id].argc {
+                    if self.param_sinks.contains_key(&(*def_id, arg)) {
+                        continue;
+                    }
+                    let param = Local::from_usize(arg + 1);
+                    let propagation = self.propagate(*def_id, param, Flow::default());
+                    if let Some(sink) = propagation.sinks.into_iter().next() {
+                        self.param_sinks.insert((*def_id, arg), sink);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn fn_name(&self, def_id: DefId) -> String {
+        self.tcx.def_path_str(def_id)
+    }
+
+    /// The call nodes of sources and of the functions returning tainted data.
+    fn source_seeds(&self, def_id: DefId) -> Vec<(Local, Flow)> {
+        let graph = &self.graphs[&def_id];
+        let mut seeds = Vec::new();
+        for (local, node) in graph.nodes.iter_enumerated() {
+            for op in &node.ops {
+                let NodeOp::Call(callee) = op else {
+                    continue;
+                };
+                let name = self.fn_name(*callee);
+                if self.spec.sources.contains(callee) {
+                    let flow = Flow {
+                        source: Some(*callee),
+                        steps: Vec::new(),
+                    };
+                    seeds.push((local, flow.then(node.span, format!("tainted by `{name}`"))));
+                } else if let Some(flow) = self.tainted_returns.get(callee) {
+                    seeds.push((
+                        local,
+                        flow.then(node.span, format!("returned from `{name}`")),
+                    ));
+                }
+            }
+        }
+        seeds
+    }
+
+    /// Propagate the tainted data from the seed node in the function.
+    fn propagate(&self, def_id: DefId, seed: Local, flow: Flow) -> Propagation {
+        let graph = &self.graphs[&def_id];
+        let mut result = Propagation::default();
+        let mut flows: FxHashMap<Local, Flow> = FxHashMap::default();
+        let mut worklist = VecDeque::new();
+        flows.insert(seed, flow);
+        worklist.push_back(seed);
+        while let Some(node) = worklist.pop_front() {
+            let flow = flows[&node].clone();
+            if node == RETURN_PLACE && result.returned.is_none() {
+                result.returned = Some(flow.clone());
+            }
+            let mut targets = Vec::new();
+            for edge_idx in &graph.nodes[node].out_edges {
+                let edge = &graph.edges[*edge_idx];
+                match graph.nodes[edge.dst].ops.get(edge.seq) {
+                    Some(NodeOp::Call(callee)) => {
+                        let arg = graph.call_arg_index(*edge_idx);
+                        self.propagate_call(
+                            graph,
+                            edge,
+                            *callee,
+                            arg,
+                            &flow,
+                            &mut targets,
+                            &mut result,
+                        );
+                    }
+                    // The callee is a function pointer or a closure, which is the first operand.
+                    Some(NodeOp::CallOperand) => {
+                        let span = graph.nodes[edge.dst].span;
+                        targets.push((
+                            edge.dst,
+                            flow.then(span, "passed through an indirect call".to_owned()),
+                        ));
+                    }
+                    _ => targets.push((edge.dst, flow.clone())),
+                }
+            }
+            for (target, flow) in targets {
+                if let Entry::Vacant(entry) = flows.entry(target) {
+                    entry.insert(flow);
+                    worklist.push_back(target);
+                }
+            }
+        }
+        result
+    }
+
+    /// Propagate the tainted argument of a call to the sinks, to the return value and to the
+    /// values borrowed mutably by the other arguments.
+    #[allow(clippy::too_many_arguments)]
+    fn propagate_call(
+        &self,
+        graph: &Graph,
+        edge: &GraphEdge,
+        callee: DefId,
+        arg: usize,
+        flow: &Flow,
+        targets: &mut Vec<(Local, Flow)>,
+        result: &mut Propagation,
+    ) {
+        let node = edge.dst;
+        let span = graph.nodes[node].span;
+        let name = self.fn_name(callee);
+        if self.spec.is_sink_arg(callee, arg) {
+            result
+                .sinks
+                .push((callee, flow.then(span, format!("flows into `{name}`"))));
+        }
+        if let Some((sink, callee_flow)) = self.param_sinks.get(&(callee, arg)) {
+            let mut flow = flow.then(span, format!("passed to `{name}` as argument {arg}"));
+            flow.steps.extend(callee_flow.steps.iter().cloned());
+            result.sinks.push((*sink, flow));
+        }
+        if self.spec.sanitizers.contains(&callee) {
+            return;
+        }
+        let to_return = match self.arg2ret.get(&callee) {
+            Some(deps) => deps
+                .get(Local::from_usize(arg + 1))
+                .copied()
+                .unwrap_or(true),
+            None => true,
+        };
+        if to_return {
+            targets.push((node, flow.then(span, format!("passed through `{name}`"))));
+        }
+        // The summary of a local callee tells which arguments it writes through.
+        let summary = self.summaries.get(&callee);
+        let writes = |other: usize| {
+            summary.is_none_or(|summary| {
+                summary.iter().any(|summary_edge| {
+                    matches!(summary_edge.src, SummaryPlace::Local { local, .. } if local.as_usize() == arg + 1)
+                        && matches!(summary_edge.dst, SummaryPlace::Local { local, deref: true, .. } if local.as_usize() == other + 1)
+                })
+            })
+        };
+        let args = graph.nodes[node]
+            .in_edges
+            .iter()
+            .filter(|idx| graph.edges[**idx].seq == edge.seq);
+        for (other, idx) in args.enumerate() {
+            let src = graph.edges[*idx].src;
+            if src == edge.src || !writes(other) {
+                continue;
+            }
+            let borrowed = graph.nodes[src]
+                .in_edges
+                .iter()
+                .any(|borrow| matches!(graph.edges[*borrow].op, EdgeOp::Mut));
+            if borrowed && let Some(local) = graph.referent_local(src) {
+                targets.push((local, flow.then(span, format!("written by `{name}`"))));
+            }
+        }
+    }
+
+    fn report(&self, def_id: DefId, flows: &[TaintFlow]) {
+        let tcx = self.tcx;
+        let fn_name = get_name(tcx, tcx.typeck_root_def_id(def_id))
+            .unwrap_or_else(|| Symbol::intern("no symbol available"));
+        let title = format!("Tainted data flow detected in function {:?}", fn_name);
+        let body_span = tcx.optimized_mir(def_id).span;
+        let mut reported = false;
+        for taint_flow in flows {
+            let Some((sink_step, path)) = taint_flow.flow.steps.split_last() else {
+                continue;
+            };
+            let source = taint_flow
+                .flow
+                .source
+                .map(|source| self.fn_name(source))
+                .unwrap_or_default();
+            let message = format!(
+                "Tainted data from `{}` flows into `{}`.",
+                source,
+                self.fn_name(taint_flow.sink)
+            );
+            let mut finding = Finding::new(
+                FindingKind::TaintFlow,
+                format!("{}: {}", title, message),
+                sink_step.span,
+            )
+            .with_function(def_id);
+            for step in path {
+                finding = finding.with_secondary(step.span, &step.message);
+            }
+            if !report::record(finding) {
+                continue;
+            }
+            if !reported {
+                rap_warn!("{}", title);
+                reported = true;
+            }
+            rap_warn!("{}", message);
+
+            let code_source = span_to_source_code(body_span);
+            let filename = span_to_filename(body_span);
+            let labels: Vec<String> = taint_flow
+                .flow
+                .steps
+                .iter()
+                .enumerate()
+                .map(|(idx, step)| format!("{}: {}", idx + 1, step.message))
+                .collect();
+            let mut snippet = Snippet::source(&code_source)
+                .line_start(span_to_line_number(body_span))
+                .origin(&filename)
+                .fold(true);
+            for (step, label) in taint_flow.flow.steps.iter().zip(&labels) {
+                if are_spans_in_same_file(body_span, step.span) && body_span.contains(step.span) {
+                    snippet = snippet.annotation(
+                        Level::Warning
+                            .span(relative_pos_range(body_span, step.span))
+                            .label(label),
+                    );
+                }
+            }
+            let path: Vec<String> = taint_flow
+                .flow
+                .steps
+                .iter()
+                .enumerate()
+                .map(|(idx, step)| {
+                    format!(
+                        "{}: {} at {}:{}",
+                        idx + 1,
+                        step.message,
+                        span_to_filename(step.span),
+                        span_to_line_number(step.span)
+                    )
+                })
+                .collect();
+            let path = path.join("\n");
+            let message = Level::Warning
+                .title(&message)
+                .snippet(snippet)
+                .footer(Level::Note.title(&path));
+            let renderer = Renderer::styled();
+            report::print_snippet(renderer.render(message));
+        }
+    }
+}
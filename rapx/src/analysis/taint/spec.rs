@@ -0,0 +1,101 @@
+use crate::{analysis::utils::def_path::def_path_def_ids, utils::log::rap_error_and_exit};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// The taint specification given by `-taint=<spec.toml>`. Functions are described by their
+/// def paths, e.g.,
+///
+/// ```toml
+/// [[source]]
+/// path = "std::env::var"
+///
+/// [[sanitizer]]
+/// path = "my_crate::shell_escape"
+///
+/// [[sink]]
+/// path = "std::process::Command::arg"
+/// args = [1]
+/// ```
+///
+/// The return values of sources are tainted, and the return values of sanitizers are not
+/// tainted whatever their arguments are. The arguments of sinks must not be tainted; `args`
+/// selects the checked arguments by their indices, where the receiver of a method is 0, and
+/// all arguments are checked if it is omitted.
+#[derive(Debug, Default, Deserialize)]
+pub struct TaintSpec {
+    #[serde(default, rename = "source")]
+    pub sources: Vec<SpecItem>,
+    #[serde(default, rename = "sanitizer")]
+    pub sanitizers: Vec<SpecItem>,
+    #[serde(default, rename = "sink")]
+    pub sinks: Vec<SpecItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpecItem {
+    pub path: String,
+    #[serde(default)]
+    pub args: Option<Vec<usize>>,
+}
+
+impl TaintSpec {
+    pub fn load(path: &Path) -> Self {
+        let content = fs::read_to_string(path).unwrap_or_else(|e| {
+            rap_error_and_exit(format!(
+                "Failed to read taint specification {}: {e}",
+                path.display()
+            ))
+        });
+        toml::from_str(&content).unwrap_or_else(|e| {
+            rap_error_and_exit(format!(
+                "Failed to parse taint specification {}: {e}",
+                path.display()
+            ))
+        })
+    }
+}
+
+/// The specification with the def paths resolved in the current crate.
+#[derive(Debug, Default)]
+pub struct ResolvedSpec {
+    pub sources: FxHashSet<DefId>,
+    pub sanitizers: FxHashSet<DefId>,
+    /// The sinks with the indices of the checked arguments, or `None` for all arguments.
+    pub sinks: FxHashMap<DefId, Option<Vec<usize>>>,
+}
+
+impl ResolvedSpec {
+    pub fn new(tcx: TyCtxt<'_>, spec: &TaintSpec) -> Self {
+        let resolve = |item: &SpecItem| {
+            let path: Vec<&str> = item.path.split("::").collect();
+            let def_ids: Vec<DefId> = def_path_def_ids(&tcx, &path).collect();
+            if def_ids.is_empty() {
+                rap_warn!("Cannot resolve `{}` in the taint specification.", item.path);
+            }
+            def_ids
+        };
+        let mut resolved = ResolvedSpec::default();
+        for item in &spec.sources {
+            resolved.sources.extend(resolve(item));
+        }
+        for item in &spec.sanitizers {
+            resolved.sanitizers.extend(resolve(item));
+        }
+        for item in &spec.sinks {
+            for def_id in resolve(item) {
+                resolved.sinks.insert(def_id, item.args.clone());
+            }
+        }
+        resolved
+    }
+
+    /// Whether the argument of the call to the function is checked by a sink.
+    pub fn is_sink_arg(&self, def_id: DefId, arg: usize) -> bool {
+        self.sinks
+            .get(&def_id)
+            .is_some_and(|args| args.as_ref().is_none_or(|args| args.contains(&arg)))
+    }
+}
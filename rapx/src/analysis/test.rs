@@ -4,7 +4,7 @@ use crate::analysis::{
         alias_analysis::{AliasAnalysis, FnAliasMapWrapper, default::AliasAnalyzer},
         dataflow::{Arg2RetMapWrapper, DataFlowAnalysis, default::DataFlowAnalyzer},
         ownedheap_analysis::{OHAResultMapWrapper, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
-        range_analysis::{PathConstraintMapWrapper, RangeAnalysis, default::RangeAnalyzer},
+        range_analysis::{PathConstraintMapWrapper, typed::TypedRangeAnalyzer},
     },
 };
 
@@ -35,7 +35,7 @@ impl<'tcx> Test<'tcx> {
         let result = heap_analysis.get_all_items();
         rap_info!("{}", OHAResultMapWrapper(result));
 
-        let mut range_analysis = RangeAnalyzer::<i64>::new(self.tcx, false);
+        let mut range_analysis = TypedRangeAnalyzer::new(self.tcx, false);
         range_analysis.run();
         let path_constraint = range_analysis.get_all_path_constraints();
         rap_info!("{}", PathConstraintMapWrapper(path_constraint));
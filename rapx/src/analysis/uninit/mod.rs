@@ -0,0 +1,739 @@
+use crate::{
+    analysis::{Analysis, utils::def_path::def_path_def_ids},
+    report::{self, Finding, FindingKind},
+    utils::{
+        log::{
+            are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
+            span_to_source_code,
+        },
+        source::get_name,
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, CastKind, Local, Operand, Place, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{Span, Symbol};
+use std::collections::{BTreeSet, VecDeque};
+
+/// The well-known APIs creating, writing, and exposing uninitialized memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UninitApi {
+    /// `MaybeUninit::uninit`.
+    MaybeUninit,
+    /// `alloc::alloc`.
+    Alloc,
+    /// `Vec::with_capacity`, whose spare capacity is uninitialized.
+    WithCapacity,
+    /// `mem::uninitialized`, which is undefined behavior for most types.
+    Uninitialized,
+    /// `ptr::write` and its variants, which write one element at the pointer.
+    Write,
+    /// `ptr::write_bytes`, `ptr::copy` and `MaybeUninit::write`, which are considered to
+    /// initialize the whole memory. The operand is the index of the destination argument.
+    Fill(usize),
+    /// `ptr::add` and `ptr::offset`, where the second argument is the offset.
+    Offset,
+    /// Methods returning a pointer into the memory, e.g., `as_mut_ptr`.
+    Derive,
+    /// `ptr::read` and its variants.
+    Read,
+    /// `MaybeUninit::assume_init` and its variants.
+    AssumeInit,
+    /// `Vec::set_len`.
+    SetLen,
+    /// `slice::from_raw_parts` and `Vec::from_raw_parts`.
+    FromRawParts,
+}
+
+struct UninitApis {
+    apis: FxHashMap<DefId, UninitApi>,
+}
+
+impl UninitApis {
+    fn new(tcx: TyCtxt<'_>) -> Self {
+        let mut apis = FxHashMap::default();
+        for (path, api) in [
+            ("core::mem::MaybeUninit::uninit", UninitApi::MaybeUninit),
+            ("alloc::alloc::alloc", UninitApi::Alloc),
+            ("alloc::vec::Vec::with_capacity", UninitApi::WithCapacity),
+            ("core::mem::uninitialized", UninitApi::Uninitialized),
+            ("core::ptr::write", UninitApi::Write),
+            ("core::ptr::write_unaligned", UninitApi::Write),
+            ("core::ptr::write_volatile", UninitApi::Write),
+            ("mut_ptr::write", UninitApi::Write),
+            ("mut_ptr::write_unaligned", UninitApi::Write),
+            ("mut_ptr::write_volatile", UninitApi::Write),
+            ("core::ptr::write_bytes", UninitApi::Fill(0)),
+            ("mut_ptr::write_bytes", UninitApi::Fill(0)),
+            ("core::mem::MaybeUninit::write", UninitApi::Fill(0)),
+            ("core::ptr::copy", UninitApi::Fill(1)),
+            ("core::ptr::copy_nonoverlapping", UninitApi::Fill(1)),
+            ("mut_ptr::add", UninitApi::Offset),
+            ("mut_ptr::offset", UninitApi::Offset),
+            ("const_ptr::add", UninitApi::Offset),
+            ("const_ptr::offset", UninitApi::Offset),
+            ("mut_ptr::cast", UninitApi::Derive),
+            ("const_ptr::cast", UninitApi::Derive),
+            ("core::mem::MaybeUninit::as_ptr", UninitApi::Derive),
+            ("core::mem::MaybeUninit::as_mut_ptr", UninitApi::Derive),
+            ("alloc::vec::Vec::as_ptr", UninitApi::Derive),
+            ("alloc::vec::Vec::as_mut_ptr", UninitApi::Derive),
+            ("core::ptr::read", UninitApi::Read),
+            ("core::ptr::read_unaligned", UninitApi::Read),
+            ("core::ptr::read_volatile", UninitApi::Read),
+            ("const_ptr::read", UninitApi::Read),
+            ("mut_ptr::read", UninitApi::Read),
+            ("core::mem::MaybeUninit::assume_init", UninitApi::AssumeInit),
+            (
+                "core::mem::MaybeUninit::assume_init_ref",
+                UninitApi::AssumeInit,
+            ),
+            (
+                "core::mem::MaybeUninit::assume_init_mut",
+                UninitApi::AssumeInit,
+            ),
+            (
+                "core::mem::MaybeUninit::assume_init_read",
+                UninitApi::AssumeInit,
+            ),
+            ("alloc::vec::Vec::set_len", UninitApi::SetLen),
+            ("core::slice::from_raw_parts", UninitApi::FromRawParts),
+            ("core::slice::from_raw_parts_mut", UninitApi::FromRawParts),
+            ("alloc::vec::Vec::from_raw_parts", UninitApi::FromRawParts),
+        ] {
+            let path: Vec<&str> = path.split("::").collect();
+            for def_id in def_path_def_ids(&tcx, &path) {
+                apis.insert(def_id, api);
+            }
+        }
+        UninitApis { apis }
+    }
+
+    fn get(&self, func: &Operand<'_>) -> Option<UninitApi> {
+        let (def_id, _) = func.const_fn_def()?;
+        self.apis.get(&def_id).copied()
+    }
+}
+
+/// A piece of memory that is uninitialized when created.
+#[derive(Debug, Clone)]
+struct Origin<'tcx> {
+    span: Span,
+    /// The number of elements if the memory is `MaybeUninit<[T; N]>`, so that the element-wise
+    /// initialization can be tracked; otherwise, any write initializes the whole memory.
+    len: Option<u64>,
+    /// The element type of `MaybeUninit<[T; N]>`.
+    elem_ty: Option<Ty<'tcx>>,
+}
+
+/// A pointer (or the owner) of an origin, with the element offset if known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pointer {
+    origin: usize,
+    offset: Option<u64>,
+}
+
+/// The initialization state of an origin, which is a may-analysis: the memory is considered
+/// initialized if it is written along any path, so that only memory that is never written
+/// is reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InitState {
+    /// The offsets of the written elements; the memory is uninitialized if the set is empty.
+    Written(BTreeSet<u64>),
+    Init,
+}
+
+impl InitState {
+    fn join(&mut self, other: &InitState) -> bool {
+        match (&mut *self, other) {
+            (InitState::Init, _) => false,
+            (_, InitState::Init) => {
+                *self = InitState::Init;
+                true
+            }
+            (InitState::Written(this), InitState::Written(that)) => {
+                let len = this.len();
+                this.extend(that.iter().copied());
+                this.len() != len
+            }
+        }
+    }
+}
+
+type State = FxHashMap<usize, InitState>;
+
+/// An exposure of uninitialized memory.
+#[derive(Debug, Clone)]
+pub struct UninitExposure {
+    pub message: String,
+    pub span: Span,
+    /// Where the uninitialized memory is created.
+    pub origin_span: Span,
+}
+
+/// Uninitialized-memory exposure checker.
+///
+/// The checker tracks the memory created by `MaybeUninit::uninit`, `alloc` and the spare
+/// capacity of `Vec::with_capacity` through the pointers derived from them, and reports
+/// `assume_init`, `set_len`, `from_raw_parts` and reads on the memory that is never written
+/// along any path. Calls to `mem::uninitialized` are always reported.
+pub struct UninitCheck<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub exposures: FxHashMap<DefId, Vec<UninitExposure>>,
+}
+
+impl<'tcx> Analysis for UninitCheck<'tcx> {
+    fn name(&self) -> &'static str {
+        "Uninitialized-memory exposure checker."
+    }
+
+    fn run(&mut self) {
+        let tcx = self.tcx;
+        let apis = UninitApis::new(tcx);
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(
+                tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            ) || tcx.hir_body_const_context(*local_def_id).is_some()
+                || !tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            if report::is_all_allowed(tcx, def_id, &[FindingKind::UninitExposure]) {
+                continue;
+            }
+            let body = tcx.optimized_mir(def_id);
+            let mut checker = BodyChecker::new(tcx, def_id, body, &apis);
+            checker.collect_pointers();
+            checker.check();
+            if !checker.exposures.is_empty() {
+                self.report(def_id, &checker.exposures);
+                self.exposures.insert(def_id, checker.exposures);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.exposures.clear();
+    }
+}
+
+impl<'tcx> UninitCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            exposures: FxHashMap::default(),
+        }
+    }
+
+    fn report(&self, def_id: DefId, exposures: &[UninitExposure]) {
+        let tcx = self.tcx;
+        let fn_name = get_name(tcx, tcx.typeck_root_def_id(def_id))
+            .unwrap_or_else(|| Symbol::intern("no symbol available"));
+        let title = format!(
+            "Uninitialized memory exposure detected in function {:?}",
+            fn_name
+        );
+        let body_span = tcx.optimized_mir(def_id).span;
+        let mut reported = false;
+        for exposure in exposures {
+            let finding = Finding::new(
+                FindingKind::UninitExposure,
+                format!("{}: {}", title, exposure.message),
+                exposure.span,
+            )
+            .with_function(def_id)
+            .with_secondary(
+                exposure.origin_span,
+                "The uninitialized memory is created here.",
+            );
+            if !report::record(finding) {
+                continue;
+            }
+            if !reported {
+                rap_warn!("{}", title);
+                reported = true;
+            }
+
+            let code_source = span_to_source_code(body_span);
+            let filename = span_to_filename(body_span);
+            let mut snippet = Snippet::source(&code_source)
+                .line_start(span_to_line_number(body_span))
+                .origin(&filename)
+                .fold(true);
+            if exposure.origin_span != exposure.span
+                && are_spans_in_same_file(body_span, exposure.origin_span)
+            {
+                snippet = snippet.annotation(
+                    Level::Warning
+                        .span(relative_pos_range(body_span, exposure.origin_span))
+                        .label("The uninitialized memory is created here."),
+                );
+            }
+            if are_spans_in_same_file(body_span, exposure.span) {
+                snippet = snippet.annotation(
+                    Level::Warning
+                        .span(relative_pos_range(body_span, exposure.span))
+                        .label(&exposure.message),
+                );
+            }
+            let message = Level::Warning
+                .title("Uninitialized memory exposure detected.")
+                .snippet(snippet);
+            let renderer = Renderer::styled();
+            report::print_snippet(renderer.render(message));
+        }
+    }
+}
+
+struct BodyChecker<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    typing_env: TypingEnv<'tcx>,
+    apis: &'a UninitApis,
+    origins: Vec<Origin<'tcx>>,
+    /// The origin created by the call terminating each block.
+    created: FxHashMap<BasicBlock, usize>,
+    pointers: FxHashMap<Local, Pointer>,
+    /// Locals assigned with a constant once, e.g., array indices.
+    constants: FxHashMap<Local, Option<u64>>,
+    exposures: Vec<UninitExposure>,
+    reported: FxHashSet<Span>,
+}
+
+impl<'tcx, 'a> BodyChecker<'tcx, 'a> {
+    fn new(tcx: TyCtxt<'tcx>, def_id: DefId, body: &'a Body<'tcx>, apis: &'a UninitApis) -> Self {
+        BodyChecker {
+            tcx,
+            body,
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            apis,
+            origins: Vec::new(),
+            created: FxHashMap::default(),
+            pointers: FxHashMap::default(),
+            constants: FxHashMap::default(),
+            exposures: Vec::new(),
+            reported: FxHashSet::default(),
+        }
+    }
+
+    fn constant(&self, operand: &Operand<'tcx>) -> Option<u64> {
+        match operand {
+            Operand::Constant(constant) => constant
+                .const_
+                .try_eval_target_usize(self.tcx, self.typing_env),
+            Operand::Copy(place) | Operand::Move(place) => {
+                self.constants.get(&place.as_local()?).copied().flatten()
+            }
+        }
+    }
+
+    fn pointer_of(&self, operand: &Operand<'tcx>) -> Option<Pointer> {
+        let place = operand.place()?;
+        self.pointer_of_place(place)
+    }
+
+    /// The pointer of a place that is a tracked local, or a reference to it, e.g., `&mut _2`.
+    fn pointer_of_place(&self, place: Place<'tcx>) -> Option<Pointer> {
+        if !place.projection.is_empty() {
+            return None;
+        }
+        self.pointers.get(&place.local).copied()
+    }
+
+    fn set_pointer(&mut self, local: Local, pointer: Pointer) -> bool {
+        self.pointers.insert(local, pointer) != Some(pointer)
+    }
+
+    /// Collect the origins and the pointers derived from them, flow-insensitively.
+    fn collect_pointers(&mut self) {
+        let tcx = self.tcx;
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            for stmt in &data.statements {
+                if let StatementKind::Assign(box (place, Rvalue::Use(operand))) = &stmt.kind
+                    && let Some(local) = place.as_local()
+                    && let Operand::Constant(constant) = operand
+                {
+                    let value = constant.const_.try_eval_target_usize(tcx, self.typing_env);
+                    self.constants
+                        .entry(local)
+                        .and_modify(|old| *old = None)
+                        .or_insert(value);
+                }
+            }
+            let terminator = data.terminator();
+            if let TerminatorKind::Call {
+                func, destination, ..
+            } = &terminator.kind
+                && let Some(
+                    api @ (UninitApi::MaybeUninit | UninitApi::Alloc | UninitApi::WithCapacity),
+                ) = self.apis.get(func)
+                && let Some(local) = destination.as_local()
+            {
+                let (len, elem_ty) = match api {
+                    UninitApi::MaybeUninit => {
+                        let ty = self.body.local_decls[local].ty;
+                        match ty.kind() {
+                            ty::Adt(_, args) => match args.type_at(0).kind() {
+                                ty::Array(elem_ty, len) => {
+                                    (len.try_to_target_usize(tcx), Some(*elem_ty))
+                                }
+                                _ => (None, None),
+                            },
+                            _ => (None, None),
+                        }
+                    }
+                    _ => (None, None),
+                };
+                self.origins.push(Origin {
+                    span: terminator.source_info.span,
+                    len,
+                    elem_ty: elem_ty.filter(|_| len.is_some()),
+                });
+                let origin = self.origins.len() - 1;
+                self.created.insert(bb, origin);
+                self.set_pointer(
+                    local,
+                    Pointer {
+                        origin,
+                        offset: Some(0),
+                    },
+                );
+            }
+        }
+        if self.origins.is_empty() {
+            return;
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for data in self.body.basic_blocks.iter() {
+                for stmt in &data.statements {
+                    let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                        continue;
+                    };
+                    let Some(local) = place.as_local() else {
+                        continue;
+                    };
+                    let pointer = match rvalue {
+                        Rvalue::Use(operand) | Rvalue::Cast(CastKind::PtrToPtr, operand, _) => {
+                            self.pointer_of(operand)
+                        }
+                        Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => {
+                            self.pointer_of_place(*place)
+                        }
+                        _ => None,
+                    };
+                    if let Some(pointer) = pointer {
+                        changed |= self.set_pointer(local, pointer);
+                    }
+                }
+                let terminator = data.terminator();
+                let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &terminator.kind
+                else {
+                    continue;
+                };
+                let Some(local) = destination.as_local() else {
+                    continue;
+                };
+                let Some(base) = args.first().and_then(|arg| self.pointer_of(&arg.node)) else {
+                    continue;
+                };
+                let pointer = match self.apis.get(func) {
+                    Some(UninitApi::Derive) => Some(base),
+                    Some(UninitApi::Offset) => {
+                        let offset = args.get(1).and_then(|arg| self.constant(&arg.node));
+                        Some(Pointer {
+                            origin: base.origin,
+                            offset: base.offset.zip(offset).map(|(base, offset)| base + offset),
+                        })
+                    }
+                    _ => None,
+                };
+                if let Some(pointer) = pointer {
+                    changed |= self.set_pointer(local, pointer);
+                }
+            }
+        }
+    }
+
+    /// Propagate the initialization states along the normal paths, and check the exposures.
+    fn check(&mut self) {
+        if self.origins.is_empty() && !self.has_uninitialized() {
+            return;
+        }
+        let blocks = &self.body.basic_blocks;
+        let mut entry_states: FxHashMap<BasicBlock, State> = FxHashMap::default();
+        let mut worklist = VecDeque::from([BasicBlock::from_usize(0)]);
+        entry_states.insert(BasicBlock::from_usize(0), State::default());
+        // The exposures are only checked once the states reach the fixed point.
+        let mut checking = false;
+        loop {
+            while let Some(bb) = worklist.pop_front() {
+                let mut state = entry_states[&bb].clone();
+                self.transfer(bb, &mut state, checking);
+                for succ in blocks[bb].terminator().successors() {
+                    if blocks[succ].is_cleanup {
+                        continue;
+                    }
+                    let changed = match entry_states.get_mut(&succ) {
+                        Some(old) => join_state(old, &state),
+                        None => {
+                            entry_states.insert(succ, state.clone());
+                            true
+                        }
+                    };
+                    if changed && !checking {
+                        worklist.push_back(succ);
+                    }
+                }
+            }
+            if checking {
+                break;
+            }
+            checking = true;
+            let mut reached: Vec<_> = entry_states.keys().copied().collect();
+            reached.sort();
+            worklist.extend(reached);
+        }
+    }
+
+    fn has_uninitialized(&self) -> bool {
+        self.body.basic_blocks.iter().any(|data| {
+            matches!(&data.terminator().kind, TerminatorKind::Call { func, .. }
+                if self.apis.get(func) == Some(UninitApi::Uninitialized))
+        })
+    }
+
+    /// Mark the element at the pointer as written.
+    fn write(&self, state: &mut State, pointer: Pointer, pointee: Option<Ty<'tcx>>) {
+        let origin = &self.origins[pointer.origin];
+        let element_write = origin.elem_ty.is_some() && pointee == origin.elem_ty;
+        match (state.get_mut(&pointer.origin), pointer.offset) {
+            (Some(InitState::Written(offsets)), Some(offset)) if element_write => {
+                offsets.insert(offset);
+                if Some(offsets.len() as u64) >= origin.len {
+                    state.insert(pointer.origin, InitState::Init);
+                }
+            }
+            (Some(_), _) => {
+                state.insert(pointer.origin, InitState::Init);
+            }
+            (None, _) => {}
+        }
+    }
+
+    /// Whether the memory at the pointer is uninitialized in the state.
+    fn is_uninit(&self, state: &State, pointer: Pointer, whole: bool) -> bool {
+        let origin = &self.origins[pointer.origin];
+        match state.get(&pointer.origin) {
+            Some(InitState::Written(offsets)) => {
+                if offsets.is_empty() {
+                    return true;
+                }
+                match (whole, pointer.offset) {
+                    (true, _) => origin.len.is_some_and(|len| (offsets.len() as u64) < len),
+                    (false, Some(offset)) => origin.elem_ty.is_some() && !offsets.contains(&offset),
+                    (false, None) => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn expose(&mut self, span: Span, pointer: Option<Pointer>, message: String) {
+        if !self.reported.insert(span) {
+            return;
+        }
+        let origin_span = pointer.map_or(span, |pointer| self.origins[pointer.origin].span);
+        rap_debug!("Uninitialized memory exposure at {:?}: {}", span, message);
+        self.exposures.push(UninitExposure {
+            message,
+            span,
+            origin_span,
+        });
+    }
+
+    fn transfer(&mut self, bb: BasicBlock, state: &mut State, checking: bool) {
+        let data = &self.body.basic_blocks[bb];
+        for stmt in &data.statements {
+            let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                continue;
+            };
+            // A write through the pointer, e.g., `(*_3) = move _4` or `(*_3)[_5] = move _4`.
+            if let Some(ProjectionElem::Deref) = place.projection.first()
+                && let Some(pointer) = self.pointers.get(&place.local).copied()
+            {
+                let pointee = place.ty(self.body, self.tcx).ty;
+                let element = match place.projection.get(1) {
+                    None => Some(pointer),
+                    Some(ProjectionElem::Index(index)) if place.projection.len() == 2 => {
+                        let index = self.constants.get(index).copied().flatten();
+                        Some(Pointer {
+                            origin: pointer.origin,
+                            offset: pointer.offset.zip(index).map(|(base, index)| base + index),
+                        })
+                    }
+                    _ => None,
+                };
+                match element {
+                    Some(element) => self.write(state, element, Some(pointee)),
+                    None => {
+                        state.insert(pointer.origin, InitState::Init);
+                    }
+                }
+            } else if place.projection.is_empty()
+                && let Some(pointer) = self.pointers.get(&place.local).copied()
+                && pointer.offset == Some(0)
+                && matches!(rvalue, Rvalue::Aggregate(..))
+            {
+                // The owner is overwritten by an initialized value.
+                state.insert(pointer.origin, InitState::Init);
+            }
+
+            // A read through the pointer, e.g., `_4 = copy (*_3)`.
+            if !checking {
+                continue;
+            }
+            if let Rvalue::Use(Operand::Copy(read) | Operand::Move(read)) = rvalue
+                && read.projection.len() == 1
+                && read.projection[0] == ProjectionElem::Deref
+                && let Some(pointer) = self.pointers.get(&read.local).copied()
+                && self.is_uninit(state, pointer, false)
+            {
+                self.expose(
+                    stmt.source_info.span,
+                    Some(pointer),
+                    "Uninitialized memory is read.".to_string(),
+                );
+            }
+        }
+
+        let terminator = data.terminator();
+        let TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+            return;
+        };
+        let span = terminator.source_info.span;
+        if let Some(&origin) = self.created.get(&bb) {
+            state.insert(origin, InitState::Written(BTreeSet::new()));
+            return;
+        }
+        let api = self.apis.get(func);
+        let pointer = |idx: usize| args.get(idx).and_then(|arg| self.pointer_of(&arg.node));
+        match api {
+            Some(UninitApi::Uninitialized) => {
+                if checking {
+                    self.expose(
+                        span,
+                        None,
+                        "`mem::uninitialized` creates an uninitialized value.".to_string(),
+                    );
+                }
+            }
+            Some(UninitApi::Write) => {
+                if let Some(p) = pointer(0) {
+                    let pointee = args[0].node.ty(self.body, self.tcx).builtin_deref(true);
+                    self.write(state, p, pointee);
+                }
+            }
+            Some(UninitApi::Fill(dst)) => {
+                if let Some(p) = pointer(dst) {
+                    state.insert(p.origin, InitState::Init);
+                }
+            }
+            Some(UninitApi::Read) => {
+                if checking
+                    && let Some(p) = pointer(0)
+                    && self.is_uninit(state, p, false)
+                {
+                    self.expose(span, Some(p), "Uninitialized memory is read.".to_string());
+                }
+            }
+            Some(UninitApi::AssumeInit) => {
+                if checking
+                    && let Some(p) = pointer(0)
+                    && self.is_uninit(state, p, true)
+                {
+                    self.expose(
+                        span,
+                        Some(p),
+                        "`assume_init` is called on uninitialized memory.".to_string(),
+                    );
+                }
+            }
+            Some(UninitApi::SetLen) => {
+                if checking
+                    && let Some(p) = pointer(0)
+                    && args.get(1).and_then(|arg| self.constant(&arg.node)) != Some(0)
+                    && self.is_uninit(state, p, true)
+                {
+                    self.expose(
+                        span,
+                        Some(p),
+                        "`set_len` exposes uninitialized elements.".to_string(),
+                    );
+                }
+            }
+            Some(UninitApi::FromRawParts) => {
+                if checking
+                    && let Some(p) = pointer(0)
+                    && args.get(1).and_then(|arg| self.constant(&arg.node)) != Some(0)
+                    && self.is_uninit(state, p, true)
+                {
+                    self.expose(
+                        span,
+                        Some(p),
+                        "`from_raw_parts` exposes uninitialized memory to safe code.".to_string(),
+                    );
+                }
+            }
+            Some(UninitApi::Derive | UninitApi::Offset) => {}
+            Some(UninitApi::MaybeUninit | UninitApi::Alloc | UninitApi::WithCapacity) | None => {
+                // Unknown callees may initialize the memory through mutable pointers.
+                for arg in args.iter() {
+                    if let Some(p) = self.pointer_of(&arg.node)
+                        && is_mutable(arg.node.ty(self.body, self.tcx))
+                    {
+                        state.insert(p.origin, InitState::Init);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn join_state(old: &mut State, new: &State) -> bool {
+    let mut changed = false;
+    for (origin, init) in new {
+        match old.get_mut(origin) {
+            Some(old_init) => changed |= old_init.join(init),
+            None => {
+                old.insert(*origin, init.clone());
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Whether the memory can be written through the value, e.g., `&mut T`, `*mut T` or an owner.
+fn is_mutable(ty: Ty<'_>) -> bool {
+    match ty.kind() {
+        ty::Ref(_, _, mutability) | ty::RawPtr(_, mutability) => mutability.is_mut(),
+        _ => true,
+    }
+}
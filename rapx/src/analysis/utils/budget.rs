@@ -0,0 +1,203 @@
+//! Per-function resource budgets of the path-sensitive and fixed-point analyses.
+//!
+//! Each function analyzed by SafeDrop, MOP alias analysis, rCanary, or range analysis gets a
+//! fresh [`Budget`] bounding the wall time and the number of visits (visited paths or blocks,
+//! or worklist iterations) spent on it. An analysis exceeding the budget stops exploring the
+//! function, keeps what it has found so far, and records the function as incomplete with
+//! [`record_incomplete`]. The incomplete functions are summarized at the end of the run.
+//!
+//! The limits are set by `-fn-visits=<n>` and `-fn-timeout=<milliseconds>`.
+
+use lazy_static::lazy_static;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The default number of visits per function, which was the former path limit of SafeDrop and
+/// MOP alias analysis.
+pub const DEFAULT_VISIT_LIMIT: usize = 1000;
+
+/// The default wall time per function.
+pub const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BudgetLimits {
+    pub visits: usize,
+    pub time: Duration,
+}
+
+impl Default for BudgetLimits {
+    fn default() -> Self {
+        BudgetLimits {
+            visits: DEFAULT_VISIT_LIMIT,
+            time: DEFAULT_TIME_LIMIT,
+        }
+    }
+}
+
+/// The reason why a budget is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exhaustion {
+    Visits(usize),
+    Time(Duration),
+    /// An external solver gives up, e.g., due to its timeout.
+    Solver,
+}
+
+impl fmt::Display for Exhaustion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exhaustion::Visits(limit) => write!(f, "more than {} visits", limit),
+            Exhaustion::Time(limit) => write!(f, "more than {} ms", limit.as_millis()),
+            Exhaustion::Solver => write!(f, "the solver gives up"),
+        }
+    }
+}
+
+/// An incomplete function of an analysis.
+#[derive(Debug, Clone)]
+struct Incomplete {
+    function: String,
+    reason: Exhaustion,
+}
+
+lazy_static! {
+    static ref LIMITS: Mutex<BudgetLimits> = Mutex::new(BudgetLimits::default());
+    /// Incomplete functions grouped by the analysis, recorded once per function.
+    static ref INCOMPLETE: Mutex<BTreeMap<&'static str, FxHashMap<DefId, Incomplete>>> =
+        Mutex::new(BTreeMap::new());
+}
+
+/// Set the limits of the budgets created afterwards.
+pub fn set_limits(limits: BudgetLimits) {
+    *LIMITS.lock().unwrap() = limits;
+}
+
+pub fn limits() -> BudgetLimits {
+    *LIMITS.lock().unwrap()
+}
+
+/// The resources spent on a function.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    limits: BudgetLimits,
+    start: Instant,
+    visits: usize,
+    exhausted: Option<Exhaustion>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget::new()
+    }
+}
+
+impl Budget {
+    /// Start a budget with the limits set by the command line.
+    pub fn new() -> Self {
+        Budget {
+            limits: limits(),
+            start: Instant::now(),
+            visits: 0,
+            exhausted: None,
+        }
+    }
+
+    /// Count a visit, and return false if the budget is exhausted.
+    pub fn tick(&mut self) -> bool {
+        if self.exhausted.is_some() {
+            return false;
+        }
+        self.visits += 1;
+        if self.visits > self.limits.visits {
+            self.exhausted = Some(Exhaustion::Visits(self.limits.visits));
+        } else if self.start.elapsed() > self.limits.time {
+            self.exhausted = Some(Exhaustion::Time(self.limits.time));
+        }
+        self.exhausted.is_none()
+    }
+
+    /// Check the wall time only, for analyses visiting each block once, and return false if
+    /// the budget is exhausted.
+    pub fn check_time(&mut self) -> bool {
+        if self.exhausted.is_none() && self.start.elapsed() > self.limits.time {
+            self.exhausted = Some(Exhaustion::Time(self.limits.time));
+        }
+        self.exhausted.is_none()
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.is_some()
+    }
+
+    pub fn exhaustion(&self) -> Option<Exhaustion> {
+        self.exhausted
+    }
+
+    pub fn visits(&self) -> usize {
+        self.visits
+    }
+
+    /// The wall time left, which bounds external solvers.
+    pub fn remaining_time(&self) -> Duration {
+        self.limits.time.saturating_sub(self.start.elapsed())
+    }
+}
+
+/// Record that the analysis of the function is incomplete, and print a note for it.
+/// Nothing is recorded if the budget is not exhausted.
+pub fn record_incomplete(tcx: TyCtxt<'_>, analysis: &'static str, def_id: DefId, budget: &Budget) {
+    if let Some(reason) = budget.exhaustion() {
+        record_incomplete_with(tcx, analysis, def_id, reason);
+    }
+}
+
+/// Record that the analysis of the function is incomplete for the given reason, e.g., a solver
+/// timeout, and print a note for it.
+pub fn record_incomplete_with(
+    tcx: TyCtxt<'_>,
+    analysis: &'static str,
+    def_id: DefId,
+    reason: Exhaustion,
+) {
+    let mut incomplete = INCOMPLETE.lock().unwrap();
+    let functions = incomplete.entry(analysis).or_default();
+    if functions.contains_key(&def_id) {
+        return;
+    }
+    let function = tcx.def_path_str(def_id);
+    rap_info!(
+        "Analysis incomplete: {} of function {} exceeds the budget ({}); the results of the function are partial.",
+        analysis,
+        function,
+        reason
+    );
+    functions.insert(def_id, Incomplete { function, reason });
+}
+
+/// Summarize the incomplete functions of the run.
+pub fn summarize() {
+    let incomplete = std::mem::take(&mut *INCOMPLETE.lock().unwrap());
+    if incomplete.is_empty() {
+        return;
+    }
+    let total: usize = incomplete.values().map(FxHashMap::len).sum();
+    rap_info!(
+        "{} function analyses are incomplete due to the per-function budget (-fn-visits, -fn-timeout):",
+        total
+    );
+    for (analysis, functions) in incomplete {
+        let mut names: Vec<_> = functions
+            .values()
+            .map(|incomplete| format!("{} ({})", incomplete.function, incomplete.reason))
+            .collect();
+        names.sort();
+        rap_info!("    {}: {}", analysis, names.join(", "));
+    }
+}
@@ -0,0 +1,469 @@
+//! A persistent cache of per-function analysis summaries under `target/rapx/`.
+//!
+//! Each summary is stored with a key that covers everything the summary depends on, i.e., the
+//! MIR of the function and the keys of its callees. Functions whose keys are unchanged since the
+//! last run are loaded from the cache instead of being analyzed again.
+//!
+//! The cache is enabled by cargo-rapx, which sets `RAP_CACHE_DIR` to `<target dir>/rapx`.
+
+use once_cell::sync::OnceCell;
+use petgraph::{algo::tarjan_scc, graph::DiGraph, unionfind::UnionFind};
+use rustc_data_structures::{
+    fingerprint::Fingerprint,
+    fx::{FxHashMap, FxHashSet},
+    stable_hasher::{HashStable, StableHasher},
+};
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::{
+    mir::{AggregateKind, ConstOperand, Location, Rvalue, visit::Visitor},
+    ty::{self, GenericArgKind, TyCtxt},
+};
+use rustc_query_system::ich::StableHashingContext;
+use rustc_span::def_id::DefPathHash;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable holding the cache folder.
+pub const RAP_CACHE_DIR: &str = "RAP_CACHE_DIR";
+
+/// Cache files written by another version of RAPx are discarded.
+const CACHE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The folder storing the cache files of the crate being compiled, if the cache is enabled.
+///
+/// The stable crate id distinguishes different targets of the same package, e.g., the lib and
+/// the test harness.
+pub fn crate_cache_dir(tcx: TyCtxt<'_>) -> Option<PathBuf> {
+    let root = env::var_os(RAP_CACHE_DIR)?;
+    let crate_dir = format!(
+        "{}-{:016x}",
+        tcx.crate_name(LOCAL_CRATE),
+        tcx.stable_crate_id(LOCAL_CRATE).as_u64()
+    );
+    Some(Path::new(&root).join(crate_dir))
+}
+
+/// The identity of a definition that is stable across compilation sessions.
+pub fn stable_def_id(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    to_hex(tcx.def_path_hash(def_id).0)
+}
+
+/// The inverse of `stable_def_id`, which fails if the definition no longer exists.
+pub fn resolve_stable_def_id(tcx: TyCtxt<'_>, id: &str) -> Option<DefId> {
+    let hash = u128::from_str_radix(id, 16).ok()?;
+    let fingerprint = Fingerprint::from_le_bytes(hash.to_le_bytes());
+    tcx.def_path_hash_to_def_id(DefPathHash(fingerprint))
+}
+
+fn to_hex(fingerprint: Fingerprint) -> String {
+    format!("{:032x}", u128::from_le_bytes(fingerprint.to_le_bytes()))
+}
+
+fn stable_hash<T: for<'a> HashStable<StableHashingContext<'a>>>(
+    tcx: TyCtxt<'_>,
+    value: &T,
+) -> Fingerprint {
+    tcx.with_stable_hashing_context(|mut hcx| {
+        let mut hasher = StableHasher::new();
+        // Spans are ignored, so that moving a function around does not invalidate its summary.
+        hcx.while_hashing_spans(false, |hcx| value.hash_stable(hcx, &mut hasher));
+        hasher.finish()
+    })
+}
+
+/// The stable hash of the MIR of a function.
+pub fn mir_hash(tcx: TyCtxt<'_>, def_id: DefId) -> Fingerprint {
+    stable_hash(tcx, tcx.optimized_mir(def_id))
+}
+
+/// The key of a definition that is not analyzed in this session, e.g., a function of a
+/// dependency. Dependencies are compiled separately, so their crate hash tells whether they
+/// changed.
+pub fn external_key(tcx: TyCtxt<'_>, def_id: DefId) -> Fingerprint {
+    let def_path_hash = tcx.def_path_hash(def_id).0;
+    if def_id.is_local() {
+        def_path_hash
+    } else {
+        let crate_hash = tcx.crate_hash(def_id.krate).as_u128();
+        def_path_hash.combine(Fingerprint::new(
+            crate_hash as u64,
+            (crate_hash >> 64) as u64,
+        ))
+    }
+}
+
+fn combine_sorted(mut fingerprints: Vec<Fingerprint>) -> Fingerprint {
+    fingerprints.sort();
+    fingerprints
+        .into_iter()
+        .fold(Fingerprint::ZERO, |acc, fingerprint| {
+            acc.combine(fingerprint)
+        })
+}
+
+/// Compute the keys of the given nodes, such that the key of a node covers its own hash and the
+/// keys of all nodes it depends on. Nodes in the same strongly connected component share a key.
+/// Dependencies that are not in `nodes` are covered by their `external_key`.
+pub fn combined_keys(
+    tcx: TyCtxt<'_>,
+    nodes: &[DefId],
+    own_hash: impl Fn(DefId) -> Fingerprint,
+    deps: &FxHashMap<DefId, FxHashSet<DefId>>,
+) -> FxHashMap<DefId, Fingerprint> {
+    let mut graph = DiGraph::<DefId, ()>::new();
+    let indices: FxHashMap<DefId, _> = nodes
+        .iter()
+        .map(|def_id| (*def_id, graph.add_node(*def_id)))
+        .collect();
+    for (def_id, targets) in deps {
+        let Some(&src) = indices.get(def_id) else {
+            continue;
+        };
+        for target in targets {
+            if let Some(&dst) = indices.get(target) {
+                graph.add_edge(src, dst, ());
+            }
+        }
+    }
+
+    // SCCs are returned in post order, so the keys of the successors are always ready.
+    let mut keys = FxHashMap::default();
+    for scc in tarjan_scc(&graph) {
+        let members: FxHashSet<DefId> = scc.iter().map(|idx| graph[*idx]).collect();
+        let mut parts: Vec<Fingerprint> = members.iter().map(|def_id| own_hash(*def_id)).collect();
+        for member in &members {
+            for target in deps.get(member).into_iter().flatten() {
+                if members.contains(target) {
+                    continue;
+                }
+                match keys.get(target) {
+                    Some(key) => parts.push(*key),
+                    None => parts.push(external_key(tcx, *target)),
+                }
+            }
+        }
+        let key = combine_sorted(parts);
+        for member in members {
+            keys.insert(member, key);
+        }
+    }
+    keys
+}
+
+/// Collect the functions and closures referred to by a MIR body, which include all direct callees.
+struct CalleeCollector {
+    callees: FxHashSet<DefId>,
+}
+
+impl<'tcx> Visitor<'tcx> for CalleeCollector {
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, _location: Location) {
+        if let ty::FnDef(def_id, args) = constant.ty().kind() {
+            self.callees.insert(*def_id);
+            // Closures passed as generic arguments, e.g., `Option::map(x, |..| ..)`.
+            for arg in args.iter() {
+                if let GenericArgKind::Type(ty) = arg.kind()
+                    && let ty::Closure(closure_id, _) = ty.kind()
+                {
+                    self.callees.insert(*closure_id);
+                }
+            }
+        }
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Aggregate(kind, _) = rvalue {
+            match **kind {
+                AggregateKind::Closure(def_id, _)
+                | AggregateKind::Coroutine(def_id, _)
+                | AggregateKind::CoroutineClosure(def_id, _) => {
+                    self.callees.insert(def_id);
+                }
+                _ => {}
+            }
+        }
+        self.super_rvalue(rvalue, location);
+    }
+}
+
+/// The cache keys of all local functions with MIR.
+pub struct FnKeys {
+    /// The MIR hash of each function.
+    own: FxHashMap<DefId, Fingerprint>,
+    /// The key covering the function and all its transitive callees, for bottom-up summaries.
+    callees: FxHashMap<DefId, Fingerprint>,
+    /// The key covering all functions connected with the function through calls in either
+    /// direction, for summaries that also depend on the calling contexts.
+    component: FxHashMap<DefId, Fingerprint>,
+}
+
+static FN_KEYS: OnceCell<FnKeys> = OnceCell::new();
+
+impl FnKeys {
+    /// Return the keys of the local crate, which are computed once per compilation.
+    pub fn get(tcx: TyCtxt<'_>) -> &'static FnKeys {
+        FN_KEYS.get_or_init(|| FnKeys::new(tcx))
+    }
+
+    fn new(tcx: TyCtxt<'_>) -> Self {
+        let mut own = FxHashMap::default();
+        let mut deps: FxHashMap<DefId, FxHashSet<DefId>> = FxHashMap::default();
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if tcx.hir_body_const_context(*local_def_id).is_some() || !tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            let body = tcx.optimized_mir(def_id);
+            let mut collector = CalleeCollector {
+                callees: FxHashSet::default(),
+            };
+            collector.visit_body(body);
+            collector.callees.remove(&def_id);
+            own.insert(def_id, mir_hash(tcx, def_id));
+            deps.insert(def_id, collector.callees);
+        }
+
+        let mut nodes: Vec<DefId> = own.keys().copied().collect();
+        nodes.sort_by_key(|def_id| def_id.index);
+        let callees = combined_keys(tcx, &nodes, |def_id| own[&def_id], &deps);
+
+        // Functions connected through local calls share the same component key.
+        let index: FxHashMap<DefId, usize> =
+            nodes.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut union = UnionFind::<usize>::new(nodes.len());
+        for (def_id, targets) in &deps {
+            for target in targets {
+                if let Some(&j) = index.get(target) {
+                    union.union(index[def_id], j);
+                }
+            }
+        }
+        let mut parts: FxHashMap<usize, Vec<Fingerprint>> = FxHashMap::default();
+        for (i, def_id) in nodes.iter().enumerate() {
+            let part = parts.entry(union.find(i)).or_default();
+            part.push(own[def_id]);
+            for target in &deps[def_id] {
+                if !index.contains_key(target) {
+                    part.push(external_key(tcx, *target));
+                }
+            }
+        }
+        let component_keys: FxHashMap<usize, Fingerprint> = parts
+            .into_iter()
+            .map(|(root, part)| (root, combine_sorted(part)))
+            .collect();
+        let component = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, def_id)| (*def_id, component_keys[&union.find(i)]))
+            .collect();
+
+        FnKeys {
+            own,
+            callees,
+            component,
+        }
+    }
+
+    /// The MIR hash of the function, for intra-procedural summaries.
+    pub fn own_key(&self, def_id: DefId) -> Option<Fingerprint> {
+        self.own.get(&def_id).copied()
+    }
+
+    /// The key for bottom-up summaries, which depend on the function and its callees.
+    pub fn callee_key(&self, def_id: DefId) -> Option<Fingerprint> {
+        self.callees.get(&def_id).copied()
+    }
+
+    /// The key for summaries that depend on both the callers and callees of the function.
+    pub fn component_key(&self, def_id: DefId) -> Option<Fingerprint> {
+        self.component.get(&def_id).copied()
+    }
+}
+
+/// The cache keys of the given ADTs, covering their definitions and the ADTs of their fields.
+pub fn adt_keys(tcx: TyCtxt<'_>, dids: &[DefId]) -> FxHashMap<DefId, Fingerprint> {
+    let mut own = FxHashMap::default();
+    let mut deps: FxHashMap<DefId, FxHashSet<DefId>> = FxHashMap::default();
+    for did in dids.iter().filter(|did| did.is_local()) {
+        let adt_def = tcx.adt_def(*did);
+        let mut parts = vec![stable_hash(tcx, &adt_def)];
+        let mut field_adts = FxHashSet::default();
+        for field in adt_def.all_fields() {
+            let field_ty = tcx.type_of(field.did).skip_binder();
+            parts.push(stable_hash(tcx, &field_ty));
+            for arg in field_ty.walk() {
+                if let GenericArgKind::Type(ty) = arg.kind()
+                    && let ty::Adt(field_adt, _) = ty.kind()
+                    && field_adt.did() != *did
+                {
+                    field_adts.insert(field_adt.did());
+                }
+            }
+        }
+        // The order of fields matters, so the parts are not sorted.
+        let hash = parts
+            .into_iter()
+            .fold(Fingerprint::ZERO, |acc, part| acc.combine(part));
+        own.insert(*did, hash);
+        deps.insert(*did, field_adts);
+    }
+    let nodes: Vec<DefId> = own.keys().copied().collect();
+    let mut keys = combined_keys(tcx, &nodes, |did| own[&did], &deps);
+    for did in dids.iter().filter(|did| !did.is_local()) {
+        keys.insert(*did, external_key(tcx, *did));
+    }
+    keys
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<S> {
+    key: String,
+    summary: S,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<S> {
+    version: String,
+    /// The options of the analysis that affect the summaries, e.g., the field depth of MOP.
+    config: String,
+    entries: HashMap<String, CacheEntry<S>>,
+}
+
+/// The cached summaries of one analysis, stored in `<crate cache dir>/<name>.json`.
+pub struct SummaryCache<S> {
+    name: String,
+    path: Option<PathBuf>,
+    config: String,
+    entries: HashMap<String, CacheEntry<S>>,
+    /// Entries that are looked up or updated in this session; stale ones are dropped on saving.
+    live: FxHashSet<String>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<S: Serialize + DeserializeOwned> SummaryCache<S> {
+    /// Load the cache of the analysis. An empty cache is returned if the cache is disabled,
+    /// missing, or written with another version or config.
+    pub fn load(tcx: TyCtxt<'_>, name: impl ToString, config: impl ToString) -> Self {
+        let name = name.to_string();
+        let config = config.to_string();
+        let path = crate_cache_dir(tcx).map(|dir| dir.join(format!("{name}.json")));
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<CacheFile<S>>(&content).ok())
+            .filter(|file| file.version == CACHE_VERSION && file.config == config)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+        SummaryCache {
+            name,
+            path,
+            config,
+            entries,
+            live: FxHashSet::default(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Return the cached summary of the definition if its key is unchanged.
+    pub fn get(&mut self, tcx: TyCtxt<'_>, def_id: DefId, key: Fingerprint) -> Option<&S> {
+        let id = stable_def_id(tcx, def_id);
+        let hit = self
+            .entries
+            .get(&id)
+            .is_some_and(|entry| entry.key == to_hex(key));
+        if hit {
+            self.hits += 1;
+            self.live.insert(id.clone());
+            self.entries.get(&id).map(|entry| &entry.summary)
+        } else {
+            self.misses += 1;
+            None
+        }
+    }
+
+    /// Iterate over all cached entries whose keys are unchanged, according to `key_of`.
+    /// This is used to restore summaries of definitions that are analyzed on demand, e.g.,
+    /// callees in dependencies.
+    pub fn drain_valid(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        key_of: impl Fn(DefId) -> Option<Fingerprint>,
+    ) -> Vec<(DefId, S)> {
+        let mut restored = Vec::new();
+        let mut kept = HashMap::new();
+        for (id, entry) in std::mem::take(&mut self.entries) {
+            let def_id = resolve_stable_def_id(tcx, &id);
+            match def_id.and_then(|def_id| key_of(def_id).map(|key| (def_id, key))) {
+                Some((def_id, key)) if to_hex(key) == entry.key => {
+                    self.hits += 1;
+                    self.live.insert(id.clone());
+                    restored.push((def_id, entry.summary));
+                    kept.insert(id, None);
+                }
+                _ => {
+                    kept.insert(id, Some(entry));
+                }
+            }
+        }
+        self.entries = kept
+            .into_iter()
+            .filter_map(|(id, entry)| entry.map(|entry| (id, entry)))
+            .collect();
+        restored
+    }
+
+    /// Store the summary of the definition with its current key.
+    pub fn insert(&mut self, tcx: TyCtxt<'_>, def_id: DefId, key: Fingerprint, summary: S) {
+        let id = stable_def_id(tcx, def_id);
+        self.live.insert(id.clone());
+        self.entries.insert(
+            id,
+            CacheEntry {
+                key: to_hex(key),
+                summary,
+            },
+        );
+    }
+
+    /// Write the live entries back to the cache file.
+    pub fn save(mut self) {
+        let Some(path) = self.path.take() else {
+            return;
+        };
+        rap_debug!(
+            "Summary cache {}: {} hits, {} misses.",
+            self.name,
+            self.hits,
+            self.misses
+        );
+        let live = std::mem::take(&mut self.live);
+        let entries = self
+            .entries
+            .into_iter()
+            .filter(|(id, _)| live.contains(id))
+            .collect();
+        let file = CacheFile {
+            version: CACHE_VERSION.to_string(),
+            config: self.config,
+            entries,
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, serde_json::to_string(&file).unwrap()));
+        if let Err(e) = result {
+            rap_warn!("Failed to write the summary cache {}: {e}", path.display());
+        }
+    }
+}
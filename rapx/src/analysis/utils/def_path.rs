@@ -1,4 +1,3 @@
-use rustc_hir::HirId;
 use rustc_hir::PrimTy;
 use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::{CrateNum, DefId, LOCAL_CRATE, LocalDefId};
@@ -6,7 +5,7 @@ use rustc_hir::{ImplItemId, ItemKind, Mutability, Node, OwnerId, TraitItemId};
 use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::fast_reject::SimplifiedType;
 use rustc_middle::ty::{FloatTy, IntTy, UintTy};
-use rustc_span::symbol::{Ident, Symbol};
+use rustc_span::symbol::{Ident, Symbol, kw};
 
 pub fn path_str_def_id<'tcx>(tcx: TyCtxt<'tcx>, path_str: &str) -> DefId {
     let path: Vec<&str> = path_str.split("::").collect();
@@ -166,15 +165,16 @@ fn local_item_children_by_name(tcx: &TyCtxt<'_>, local_id: LocalDefId, name: Sym
     let hir_node = tcx.hir_node_by_def_id(local_id);
     let item_kind = match hir_node {
         Node::Crate(module) => {
-            root_mod = ItemKind::Mod(hir_node.ident().unwrap(), module);
+            root_mod = ItemKind::Mod(Ident::with_dummy_span(kw::Crate), module);
             &root_mod
         }
         Node::Item(item) => &item.kind,
         _ => return Vec::new(),
     };
 
-    let res = |ident: Ident, owner_id: OwnerId| {
-        if ident.name == name {
+    // Items like `use` and impl blocks have no names.
+    let res = |owner_id: OwnerId| {
+        if tcx.opt_item_name(owner_id.to_def_id()) == Some(name) {
             let def_id = owner_id.to_def_id();
             Some(Res::Def(tcx.def_kind(def_id), def_id))
         } else {
@@ -186,20 +186,16 @@ fn local_item_children_by_name(tcx: &TyCtxt<'_>, local_id: LocalDefId, name: Sym
         ItemKind::Mod(_ident, module) => module
             .item_ids
             .iter()
-            .filter_map(|&item_id| res(tcx.hir_ident(item_id.hir_id()), item_id.owner_id))
+            .filter_map(|&item_id| res(item_id.owner_id))
             .collect(),
         ItemKind::Impl(r#impl) => r#impl
             .items
             .iter()
-            .filter_map(|&ImplItemId { owner_id }| {
-                res(tcx.hir_ident(HirId::from(owner_id)), owner_id)
-            })
+            .filter_map(|&ImplItemId { owner_id }| res(owner_id))
             .collect(),
         ItemKind::Trait(.., trait_item_refs) => trait_item_refs
             .iter()
-            .filter_map(|&TraitItemId { owner_id }| {
-                res(tcx.hir_ident(HirId::from(owner_id)), owner_id)
-            })
+            .filter_map(|&TraitItemId { owner_id }| res(owner_id))
             .collect(),
         _ => Vec::new(),
     }
@@ -2,7 +2,7 @@ use super::draw_dot::render_dot_string;
 use crate::analysis::{
     core::dataflow::{DataFlowAnalysis, default::DataFlowAnalyzer},
     senryx::{
-        contracts::{property, property::PropertyContract},
+        contracts::{database::extra_contracts, property, property::PropertyContract},
         matcher::parse_unsafe_api,
     },
 };
@@ -142,8 +142,17 @@ pub fn get_cleaned_def_path_name_ori(tcx: TyCtxt, def_id: DefId) -> String {
 }
 
 pub fn get_sp_tags_json() -> serde_json::Value {
-    let json_data: serde_json::Value =
+    let mut json_data: serde_json::Value =
         serde_json::from_str(include_str!("data/std_sps.json")).expect("Unable to parse JSON");
+    // The APIs with loaded contracts are unsafe APIs with the tags of their contracts.
+    if let Some(map) = json_data.as_object_mut() {
+        for (api, entries) in extra_contracts() {
+            let mut tags: Vec<String> = entries.into_iter().map(|entry| entry.tag).collect();
+            tags.sort();
+            tags.dedup();
+            map.insert(api, serde_json::json!({ "0": tags }));
+        }
+    }
     json_data
 }
 
@@ -154,8 +163,13 @@ pub fn get_std_api_signature_json() -> serde_json::Value {
 }
 
 pub fn get_sp_tags_and_args_json() -> serde_json::Value {
-    let json_data: serde_json::Value =
+    let mut json_data: serde_json::Value =
         serde_json::from_str(include_str!("data/std_sps_args.json")).expect("Unable to parse JSON");
+    if let Some(map) = json_data.as_object_mut() {
+        for (api, entries) in extra_contracts() {
+            map.insert(api, serde_json::json!(entries));
+        }
+    }
     json_data
 }
 
@@ -643,7 +657,9 @@ pub fn match_std_unsafe_callee(tcx: TyCtxt<'_>, terminator: &Terminator<'_>) ->
             if let ty::FnDef(callee_def_id, _raw_list) = func_constant.const_.ty().kind() {
                 let func_name = get_cleaned_def_path_name(tcx, *callee_def_id);
                 // rap_info!("{func_name}");
-                if parse_unsafe_api(&func_name).is_some() {
+                if parse_unsafe_api(&func_name).is_some()
+                    || is_annotated_unsafe_fn(tcx, *callee_def_id)
+                {
                     results.push(func_name);
                 }
             }
@@ -773,45 +789,231 @@ pub fn is_verify_target_func(tcx: TyCtxt, def_id: DefId) -> bool {
     false
 }
 
+/// The contracts annotated on the params as Vec<(local_id, fields of this local, contract)>.
+pub type AnnotatedContracts<'tcx> = Vec<(usize, Vec<(usize, Ty<'tcx>)>, PropertyContract<'tcx>)>;
+
+/// The kind of a contract given by `#[rapx::inner(property = ..., kind = "...")]`, or by
+/// `#[rapx::invariant(property = ...)]` on a type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContractKind {
+    /// Required by the function from its callers.
+    Precond,
+    /// Guaranteed by the function to its callers when it returns.
+    Postcond,
+    /// Established by the constructors of the type and kept by its `&mut self` methods.
+    Invariant,
+}
+
+impl ContractKind {
+    fn from_attr(kind: &str) -> Option<Self> {
+        match kind {
+            "precond" => Some(Self::Precond),
+            "postcond" => Some(Self::Postcond),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the properties with their kinds in a `rapx` tool attribute. Both the form
+/// `#[rapx::inner(property = ValidPtr(ptr, u32, 1), kind = "precond")]` and the tag-std form
+/// `#[rapx::inner(ValidPtr(ptr, u32, 1))]` are accepted, where the latter are preconditions.
+/// All the properties in `#[rapx::invariant(..)]` are invariants.
+/// Properties of other kinds, e.g., hazards, are skipped.
+pub fn parse_contract_attr(attr_str: &str) -> Vec<(ContractKind, String, Vec<Expr>)> {
+    let mut results = Vec::new();
+    let is_invariant = attr_str.trim_start().starts_with("#[rapx::invariant(");
+    if let Some(args) = parse_property_kind_args(attr_str) {
+        let mut properties = Vec::new();
+        let mut kind = Some(ContractKind::Precond);
+        for arg in args {
+            let Expr::Assign(assign) = arg else {
+                continue;
+            };
+            match (access_ident_recursive(&assign.left), *assign.right) {
+                (Some((key, _)), Expr::Call(call)) if key == "property" => {
+                    if let Some((tag, _)) = access_ident_recursive(&call.func) {
+                        properties.push((tag, call.args.into_iter().collect()));
+                    }
+                }
+                (Some((key, _)), Expr::Path(path)) if key == "property" => {
+                    if let Some(tag) = path.path.get_ident() {
+                        properties.push((tag.to_string(), Vec::new()));
+                    }
+                }
+                (
+                    Some((key, _)),
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ) if key == "kind" => {
+                    kind = ContractKind::from_attr(&lit.value());
+                }
+                _ => {}
+            }
+        }
+        if is_invariant {
+            kind = Some(ContractKind::Invariant);
+        }
+        if let Some(kind) = kind {
+            for (tag, exprs) in properties {
+                results.push((kind, tag, exprs));
+            }
+        }
+        return results;
+    }
+    let safety_attr = safety_parser::safety::parse_attr_and_get_properties(attr_str);
+    for par in safety_attr.iter() {
+        for property in par.tags.iter() {
+            let exprs = property.args.clone().into_vec();
+            results.push((
+                if is_invariant {
+                    ContractKind::Invariant
+                } else {
+                    ContractKind::Precond
+                },
+                property.tag.name().to_string(),
+                exprs,
+            ));
+        }
+    }
+    results
+}
+
+/// Return the comma-separated args of the attribute if they are given as `key = value`.
+fn parse_property_kind_args(attr_str: &str) -> Option<Vec<Expr>> {
+    use syn::{Token, parse::Parser, punctuated::Punctuated};
+    let attrs = syn::Attribute::parse_outer
+        .parse_str(attr_str.trim())
+        .ok()?;
+    let args = attrs
+        .first()?
+        .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+        .ok()?;
+    if !args.iter().any(|arg| matches!(arg, Expr::Assign(_))) {
+        return None;
+    }
+    Some(args.into_iter().collect())
+}
+
 /// Get the annotation in tag-std style.
 /// Then generate the contractual invariant states (CIS) for the args.
 /// This function will recognize the args name and record states to MIR variable (represent by usize).
 /// Return value means Vec<(local_id, fields of this local, contracts)>
-pub fn generate_contract_from_annotation(
-    tcx: TyCtxt,
+pub fn generate_contract_from_annotation<'tcx>(
+    tcx: TyCtxt<'tcx>,
     def_id: DefId,
-) -> Vec<(usize, Vec<(usize, Ty)>, PropertyContract)> {
+) -> AnnotatedContracts<'tcx> {
+    generate_contract_from_annotation_of_kind(tcx, def_id, ContractKind::Precond)
+}
+
+/// Return the `rapx` tool attributes of the item except the proof placeholders.
+fn get_contract_attrs(tcx: TyCtxt, def_id: DefId) -> Vec<String> {
     const REGISTER_TOOL: &str = "rapx";
-    let tool_attrs = tcx.get_all_attrs(def_id).into_iter().filter(|attr| {
-        if let Attribute::Unparsed(tool_attr) = attr {
-            if tool_attr.path.segments[0].as_str() == REGISTER_TOOL {
+    tcx.get_all_attrs(def_id)
+        .iter()
+        .filter(|attr| {
+            if let Attribute::Unparsed(tool_attr) = attr
+                && tool_attr.path.segments[0].as_str() == REGISTER_TOOL
+            {
                 return true;
             }
+            false
+        })
+        .map(|attr| rustc_hir_pretty::attribute_to_string(&tcx, attr))
+        // Find proof placeholder, skip it
+        .filter(|attr_str| !attr_str.contains("#[rapx::proof(proof)]"))
+        .collect()
+}
+
+/// Test if the function is an unsafe fn of the current crate annotated with preconditions.
+pub fn is_annotated_unsafe_fn(tcx: TyCtxt, def_id: DefId) -> bool {
+    def_id.is_local()
+        && tcx.def_kind(def_id).is_fn_like()
+        && tcx.fn_sig(def_id).skip_binder().safety().is_unsafe()
+        && get_contract_attrs(tcx, def_id).iter().any(|attr_str| {
+            parse_contract_attr(attr_str)
+                .iter()
+                .any(|(kind, _, _)| *kind == ContractKind::Precond)
+        })
+}
+
+/// Same with `generate_contract_from_annotation` but for the contracts of the given kind.
+pub fn generate_contract_from_annotation_of_kind<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    kind: ContractKind,
+) -> AnnotatedContracts<'tcx> {
+    instantiate_annotated_contracts(tcx, def_id, def_id, kind)
+}
+
+/// Get the invariants annotated on the type of a constructor or method, instantiated with the
+/// signature of the function, where `self` refers to the receiver of a method and to the
+/// constructed value of a constructor.
+///
+/// Example:
+/// ```
+/// #[rapx::invariant(property = ValidNum(self.len <= self.cap))]
+/// pub struct Buffer { ptr: *mut u8, len: usize, cap: usize }
+/// ```
+pub fn generate_invariant_from_annotation<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+) -> AnnotatedContracts<'tcx> {
+    match get_adt_def_id_by_adt_method(tcx, def_id) {
+        Some(adt_def_id) if adt_def_id.is_local() => {
+            instantiate_annotated_contracts(tcx, adt_def_id, def_id, ContractKind::Invariant)
         }
-        false
-    });
+        _ => Vec::new(),
+    }
+}
+
+/// The contracts the function has to prove when it returns, i.e., its postconditions, and the
+/// invariants of its type if it is a constructor or a `&mut self` method.
+pub fn generate_return_obligations<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+) -> Vec<(ContractKind, AnnotatedContracts<'tcx>)> {
     let mut results = Vec::new();
-    for attr in tool_attrs {
-        let attr_str = rustc_hir_pretty::attribute_to_string(&tcx, attr);
-        // Find proof placeholder, skip it
-        if attr_str.contains("#[rapx::proof(proof)]") {
-            continue;
+    let postconds = generate_contract_from_annotation_of_kind(tcx, def_id, ContractKind::Postcond);
+    if !postconds.is_empty() {
+        results.push((ContractKind::Postcond, postconds));
+    }
+    if get_type(tcx, def_id) == FnKind::Constructor || has_mut_self_param(tcx, def_id) {
+        let invariants = generate_invariant_from_annotation(tcx, def_id);
+        if !invariants.is_empty() {
+            results.push((ContractKind::Invariant, invariants));
         }
+    }
+    results
+}
+
+/// Parse the contracts of the given kind annotated on `attr_def_id`, and resolve the names in
+/// them with the signature of the function `fn_def_id`.
+fn instantiate_annotated_contracts<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    attr_def_id: DefId,
+    fn_def_id: DefId,
+    kind: ContractKind,
+) -> AnnotatedContracts<'tcx> {
+    let mut results = Vec::new();
+    for attr_str in get_contract_attrs(tcx, attr_def_id) {
         rap_debug!("{:?}", attr_str);
-        let safety_attr = safety_parser::safety::parse_attr_and_get_properties(attr_str.as_str());
-        for par in safety_attr.iter() {
-            for property in par.tags.iter() {
-                let tag_name = property.tag.name();
-                let exprs = property.args.clone().into_vec();
-                let contract = PropertyContract::new(tcx, def_id, tag_name, &exprs);
-                let (local, fields) = parse_cis_local(tcx, def_id, exprs);
-                results.push((local, fields, contract));
+        for (property_kind, tag_name, exprs) in parse_contract_attr(&attr_str) {
+            if property_kind != kind {
+                continue;
+            }
+            if let Some(counts) = PropertyContract::arg_counts(&tag_name)
+                && !counts.contains(&exprs.len())
+            {
+                rap_warn!("Skip {tag_name} in {attr_str:?}: wrong number of args.");
+                continue;
             }
+            let contract = PropertyContract::new(tcx, fn_def_id, &tag_name, &exprs);
+            let (local, fields) = parse_cis_local(tcx, fn_def_id, exprs);
+            results.push((local, fields, contract));
         }
     }
-    // if results.len() > 0 {
-    //     rap_warn!("results:\n{:?}", results);
-    // }
     results
 }
 
@@ -833,6 +1035,11 @@ pub fn generate_contract_from_annotation(
 pub fn parse_cis_local(tcx: TyCtxt, def_id: DefId, expr: Vec<Expr>) -> (usize, Vec<(usize, Ty)>) {
     // match expr with cis local
     for e in expr {
+        // The value restricted by a comparison, e.g., `region.size >= 0`, is on the left.
+        let e = match e {
+            Expr::Binary(binary) => *binary.left,
+            e => e,
+        };
         if let Some((base, fields, _ty)) = parse_expr_into_local_and_ty(tcx, def_id, &e) {
             return (base, fields);
         }
@@ -841,6 +1048,9 @@ pub fn parse_cis_local(tcx: TyCtxt, def_id: DefId, expr: Vec<Expr>) -> (usize, V
 }
 
 /// parse single expr into (local, fields, ty)
+///
+/// `return` refers to the return value, and so does `self` in a function without receiver,
+/// i.e., the value built by a constructor. Both are parsed as the local 0.
 pub fn parse_expr_into_local_and_ty<'tcx>(
     tcx: TyCtxt<'tcx>,
     def_id: DefId,
@@ -848,11 +1058,19 @@ pub fn parse_expr_into_local_and_ty<'tcx>(
 ) -> Option<(usize, Vec<(usize, Ty<'tcx>)>, Ty<'tcx>)> {
     if let Some((base_ident, fields)) = access_ident_recursive(&expr) {
         let (param_names, param_tys) = parse_signature(tcx, def_id);
-        if param_names[0] == "0".to_string() {
-            return None;
-        }
-        if let Some(param_index) = param_names.iter().position(|name| name == &base_ident) {
-            let mut current_ty = param_tys[param_index];
+        let base = match param_names.iter().position(|name| name == &base_ident) {
+            // Params without names are listed as `0`, `1`, ...
+            Some(_) if param_names[0] == "0" => None,
+            // It's different from default one, we return the result as param_index+1 because param_index count from 0.
+            // But 0 in MIR is the ret index, the args' indexes begin from 1.
+            Some(param_index) => Some((param_index + 1, param_tys[param_index])),
+            None if base_ident == "return" || base_ident == "self" => {
+                let output = tcx.fn_sig(def_id).skip_binder().output().skip_binder();
+                Some((0, output))
+            }
+            None => None,
+        };
+        if let Some((local, mut current_ty)) = base {
             let mut field_indices = Vec::new();
             for field_name in fields {
                 // peel the ref and ptr
@@ -889,9 +1107,7 @@ pub fn parse_expr_into_local_and_ty<'tcx>(
                     break; // TODO:
                 }
             }
-            // It's different from default one, we return the result as param_index+1 because param_index count from 0.
-            // But 0 in MIR is the ret index, the args' indexes begin from 1.
-            return Some((param_index + 1, field_indices, current_ty));
+            return Some((local, field_indices, current_ty));
         }
     }
     None
@@ -1023,6 +1239,9 @@ pub fn access_ident_recursive(expr: &Expr) -> Option<(String, Vec<String>)> {
             fields.push(field_name);
             Some((base_ident, fields))
         }
+        Expr::Return(syn::ExprReturn { expr: None, .. }) => {
+            Some(("return".to_string(), Vec::new()))
+        }
         _ => None,
     }
 }
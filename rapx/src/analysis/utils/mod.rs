@@ -1,5 +1,8 @@
+pub mod budget;
+pub mod cache;
 pub mod def_path;
 pub mod draw_dot;
 #[allow(unused)]
 pub mod fn_info;
 pub mod show_mir;
+pub mod sidecar;
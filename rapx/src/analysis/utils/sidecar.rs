@@ -0,0 +1,151 @@
+//! Sidecar summaries of dependencies.
+//!
+//! With `cargo rapx -summarize-deps`, dependencies are compiled by rapx with `-summarize`, which
+//! writes the alias and owned-heap summaries of the crate into a sidecar file next to its
+//! metadata, e.g., `libfoo-<hash>.rapx.json` besides `libfoo-<hash>.rmeta`. When analyzing the
+//! local crate, the summaries of all dependencies are loaded for the foreign functions whose MIR
+//! is not available.
+
+use crate::analysis::{
+    Analysis,
+    core::{
+        alias_analysis::{
+            AliasAnalysis, FnAliasPairs,
+            default::{AliasAnalyzer, MopFnAliasPairs},
+            mfp::MfpAliasAnalyzer,
+        },
+        ownedheap_analysis::{
+            OHAResultMap, OwnedHeap, OwnedHeapAnalysis, default::OwnedHeapAnalyzer,
+        },
+    },
+    utils::cache::{resolve_stable_def_id, stable_def_id},
+};
+use once_cell::sync::OnceCell;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::{OutFileName, OutputType};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+const SIDECAR_VERSION: &str = env!("CARGO_PKG_VERSION");
+const SIDECAR_EXTENSION: &str = "rapx.json";
+
+/// The content of a sidecar file, where definitions are identified by their def path hashes.
+#[derive(Default, Serialize, Deserialize)]
+struct SidecarFile {
+    version: String,
+    mop: HashMap<String, MopFnAliasPairs>,
+    mfp: HashMap<String, FnAliasPairs>,
+    ownedheap: HashMap<String, Vec<(OwnedHeap, Vec<bool>)>>,
+}
+
+/// The summaries of all dependencies that have sidecar files.
+#[derive(Default)]
+pub struct DepSummaries {
+    /// Alias summaries of meet-over-paths alias analysis.
+    pub mop: FxHashMap<DefId, MopFnAliasPairs>,
+    /// Alias summaries of maximum-fixed-point alias analysis.
+    pub mfp: FxHashMap<DefId, FnAliasPairs>,
+    /// Owned-heap results of the ADTs defined in dependencies.
+    pub ownedheap: OHAResultMap,
+}
+
+static DEP_SUMMARIES: OnceCell<DepSummaries> = OnceCell::new();
+
+fn sidecar_path(rmeta: &Path) -> PathBuf {
+    rmeta.with_extension(SIDECAR_EXTENSION)
+}
+
+/// Return the summaries of the dependencies, which are loaded once per compilation.
+pub fn dep_summaries(tcx: TyCtxt<'_>) -> &'static DepSummaries {
+    DEP_SUMMARIES.get_or_init(|| load(tcx))
+}
+
+fn load(tcx: TyCtxt<'_>) -> DepSummaries {
+    let mut summaries = DepSummaries::default();
+    for cnum in tcx.crates(()) {
+        let Some((rmeta, _)) = &tcx.used_crate_source(*cnum).rmeta else {
+            continue;
+        };
+        let path = sidecar_path(rmeta);
+        let Some(file) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SidecarFile>(&content).ok())
+            .filter(|file| file.version == SIDECAR_VERSION)
+        else {
+            continue;
+        };
+        rap_debug!("Load dependency summaries from {}.", path.display());
+        let resolve = |id: &str| resolve_stable_def_id(tcx, id);
+        for (id, summary) in file.mop {
+            if let Some(def_id) = resolve(&id) {
+                summaries.mop.insert(def_id, summary);
+            }
+        }
+        for (id, summary) in file.mfp {
+            if let Some(def_id) = resolve(&id) {
+                summaries.mfp.insert(def_id, summary);
+            }
+        }
+        for (id, heap) in file.ownedheap {
+            if let Some(def_id) = resolve(&id) {
+                summaries.ownedheap.insert(def_id, heap);
+            }
+        }
+    }
+    summaries
+}
+
+/// Summarize the crate being compiled as a dependency and write the sidecar file next to its
+/// metadata. Failures are reported but do not fail the compilation of the dependency.
+pub fn write_sidecar(tcx: TyCtxt<'_>) {
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let OutFileName::Real(rmeta) = tcx.output_filenames(()).path(OutputType::Metadata) else {
+        return;
+    };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| summarize(tcx)));
+    let Ok(file) = result else {
+        rap_warn!("Failed to summarize the dependency {}.", crate_name);
+        return;
+    };
+    let path = sidecar_path(&rmeta);
+    if let Err(e) = fs::write(&path, serde_json::to_string(&file).unwrap()) {
+        rap_warn!("Failed to write the summaries {}: {e}", path.display());
+    }
+}
+
+fn summarize(tcx: TyCtxt<'_>) -> SidecarFile {
+    let mut mop = AliasAnalyzer::new(tcx);
+    mop.run();
+    let mut mfp = MfpAliasAnalyzer::new(tcx);
+    mfp.run();
+    let mut heap = OwnedHeapAnalyzer::new(tcx);
+    heap.run();
+
+    SidecarFile {
+        version: SIDECAR_VERSION.to_string(),
+        mop: mop
+            .get_all_fn_alias_raw()
+            .into_iter()
+            .filter(|(def_id, _)| def_id.is_local())
+            .map(|(def_id, summary)| (stable_def_id(tcx, def_id), summary))
+            .collect(),
+        mfp: mfp
+            .get_local_fn_alias()
+            .into_iter()
+            .map(|(def_id, summary)| (stable_def_id(tcx, def_id), summary))
+            .collect(),
+        ownedheap: heap
+            .get_all_items()
+            .into_iter()
+            .filter(|(def_id, _)| def_id.is_local())
+            .map(|(def_id, heap)| (stable_def_id(tcx, def_id), heap))
+            .collect(),
+    }
+}
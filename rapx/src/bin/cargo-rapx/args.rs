@@ -13,6 +13,7 @@ struct Arguments {
     args_group2: Vec<String>,
     current_exe_path: PathBuf,
     rap_clean: bool,
+    rap_cache: bool,
 }
 
 impl Arguments {
@@ -41,20 +42,32 @@ impl Arguments {
 
     fn new() -> Self {
         fn rap_clean() -> bool {
-            match env::var("RAP_CLEAN")
-                .ok()
-                .map(|s| s.trim().to_ascii_lowercase())
-                .as_deref()
-            {
-                Some("false") => false,
-                _ => true, // clean is the preferred behavior
-            }
+            // Local crates are analyzed again in every run and the summaries of unchanged
+            // functions are cached, so cleaning is only done on request.
+            matches!(
+                env::var("RAP_CLEAN")
+                    .ok()
+                    .map(|s| s.trim().to_ascii_lowercase())
+                    .as_deref(),
+                Some("true")
+            )
+        }
+
+        fn rap_cache() -> bool {
+            !matches!(
+                env::var("RAP_CACHE")
+                    .ok()
+                    .map(|s| s.trim().to_ascii_lowercase())
+                    .as_deref(),
+                Some("false")
+            )
         }
 
         let args: Vec<_> = env::args().collect();
         let path = env::current_exe().expect("Current executable path invalid.");
         rap_trace!("Current exe: {path:?}\tReceived args: {args:?}");
         let [args_group1, args_group2] = split_args_by_double_dash(&args);
+        let args_group1 = args_group1.into_iter().map(absolutize_path_arg).collect();
 
         Arguments {
             args,
@@ -62,6 +75,7 @@ impl Arguments {
             args_group2,
             current_exe_path: path,
             rap_clean: rap_clean(),
+            rap_cache: rap_cache(),
         }
     }
 
@@ -81,10 +95,16 @@ impl Arguments {
     }
 }
 
+/// Whether to run `cargo clean` before cargo check, only if `RAP_CLEAN` is true.
 pub fn rap_clean() -> bool {
     ARGS.rap_clean
 }
 
+/// Whether analysis summaries are cached under `target/rapx`, unless `RAP_CACHE` is false.
+pub fn rap_cache() -> bool {
+    ARGS.rap_cache
+}
+
 fn split_args_by_double_dash(args: &[String]) -> [Vec<String>; 2] {
     let mut args = args.iter().skip(2).map(|arg| arg.to_owned());
     let rap_args = args.by_ref().take_while(|arg| *arg != "--").collect();
@@ -92,6 +112,31 @@ fn split_args_by_double_dash(args: &[String]) -> [Vec<String>; 2] {
     [rap_args, cargo_args]
 }
 
+/// Options whose values are files, e.g., `-output=<file>`.
+const PATH_OPTIONS: &[&str] = &[
+    "-output=",
+    "-baseline=",
+    "-baseline-write=",
+    "-infer-patch=",
+    "-callgraph-output=",
+    "-taint=",
+    "-contracts=",
+];
+
+/// Resolve the file of options like `-output=<file>` against the current folder, because cargo
+/// invokes rapx from the workspace root instead of the folder where `cargo rapx` runs.
+fn absolutize_path_arg(arg: String) -> String {
+    for option in PATH_OPTIONS {
+        if let Some(path) = arg.strip_prefix(option)
+            && Path::new(path).is_relative()
+        {
+            let cwd = env::current_dir().expect("Current directory invalid.");
+            return format!("{option}{}", cwd.join(path).display());
+        }
+    }
+    arg
+}
+
 static ARGS: LazyLock<Arguments> = LazyLock::new(Arguments::new);
 
 pub fn get_arg_flag_value(name: &str) -> Option<&'static str> {
@@ -138,8 +183,36 @@ pub fn current_exe_path() -> &'static Path {
     &ARGS.current_exe_path
 }
 
+/// In rustc phase: whether cargo nests the workspace wrapper in the wrapper, both of which are
+/// cargo-rapx, i.e., runs `cargo-rapx cargo-rapx rustc ...` for a workspace package.
+pub fn is_nested_wrapper() -> bool {
+    get_arg(1).is_some_and(|arg| Path::new(arg) == current_exe_path())
+}
+
 /// NOTE: for simplicify in rapx argument forwarding, only `-timeout=` is correctly handled,
 /// even though both flavors are accepted here.
 pub fn timeout() -> Option<u64> {
     ARGS.get_arg_flag_value("-timeout")?.parse().ok()
 }
+
+/// Whether dependencies are summarized by `-summarize-deps`. The option is given in the command
+/// line in the cargo phase, and forwarded by `RAP_ARGS` in the rustc phase.
+pub fn summarize_deps() -> bool {
+    ARGS.args_group1.iter().any(|arg| arg == "-summarize-deps")
+        || env::var("RAP_ARGS").is_ok_and(|args| args.contains("\"-summarize-deps\""))
+}
+
+/// The report format given by `-format=<text|sarif|json>`.
+pub fn report_format() -> Option<&'static str> {
+    ARGS.get_arg_flag_value("-format")
+}
+
+/// The report file given by `-output=<file>`, as an absolute path.
+pub fn report_output() -> Option<&'static str> {
+    ARGS.get_arg_flag_value("-output")
+}
+
+/// The baseline file given by `-baseline-write=<file>`, as an absolute path.
+pub fn baseline_output() -> Option<&'static str> {
+    ARGS.get_arg_flag_value("-baseline-write")
+}
@@ -1,7 +1,12 @@
 use crate::args;
-use cargo_metadata::camino::Utf8Path;
+use cargo_metadata::{Metadata, MetadataCommand, camino::Utf8Path};
 use rapx::utils::log::rap_error_and_exit;
-use std::{env, process::Command, time::Duration};
+use std::{
+    collections::HashSet,
+    env, fs,
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use wait_timeout::ChildExt;
 
 mod workspace;
@@ -22,9 +27,13 @@ pub fn run() {
 }
 
 fn cargo_check(dir: &Utf8Path) {
-    // always clean before check due to outdated except `RAP_CLEAN` is false
     rap_trace!("cargo clean in package folder {dir}");
     cargo_clean(dir, args::rap_clean());
+    // Dependencies are only summarized when they are compiled, so those compiled before
+    // without summaries are recompiled.
+    if args::summarize_deps() {
+        clean_unsummarized_deps(dir);
+    }
 
     rap_trace!("cargo check in package folder {dir}");
     let [rap_args, cargo_args] = args::rap_and_cargo_args();
@@ -50,6 +59,21 @@ fn cargo_check(dir: &Utf8Path) {
     // Invoke actual cargo for the job, but with different flags.
     let cargo_rap_path = args::current_exe_path();
     cmd.env("RUSTC_WRAPPER", cargo_rap_path);
+    // Cargo keeps the workspace packages compiled with a workspace wrapper apart from those of
+    // plain cargo check, and rapx makes them depend on `RAP_RUN`, so they are checked again in
+    // every run without cleaning. The dependencies are not recompiled.
+    cmd.env("RUSTC_WORKSPACE_WRAPPER", cargo_rap_path);
+    cmd.env("RAP_RUN", run_id());
+
+    // Analysis summaries of unchanged functions are reused from `target/rapx`.
+    if args::rap_cache()
+        && let Some(metadata) = package_metadata(dir)
+    {
+        cmd.env(
+            "RAP_CACHE_DIR",
+            metadata.target_directory.join(CACHE_FOLDER),
+        );
+    }
 
     rap_trace!("Command is: {:?}.", cmd);
 
@@ -75,12 +99,93 @@ fn cargo_check(dir: &Utf8Path) {
     }
 }
 
+/// The folder under the target directory storing the analysis cache.
+const CACHE_FOLDER: &str = "rapx";
+
+/// The extension of the sidecar summaries written next to `.rmeta` files by `-summarize-deps`.
+const SIDECAR_EXTENSION: &str = "rapx.json";
+
+/// A value unique to each run of cargo-rapx.
+fn run_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}-{}", now.as_nanos(), std::process::id())
+}
+
 fn cargo_clean(dir: &Utf8Path, really: bool) {
-    if really {
-        if let Err(err) = Command::new("cargo").arg("clean").current_dir(dir).output() {
-            rap_error_and_exit(format!("`cargo clean` exits unexpectedly:\n{err}"));
+    if really && let Err(err) = Command::new("cargo").arg("clean").current_dir(dir).output() {
+        rap_error_and_exit(format!("`cargo clean` exits unexpectedly:\n{err}"));
+    }
+}
+
+/// Clean the dependencies compiled without the sidecar summaries of `-summarize-deps`.
+fn clean_unsummarized_deps(dir: &Utf8Path) {
+    let specs = unsummarized_deps(dir);
+    if specs.is_empty() {
+        return;
+    }
+    let mut cmd = Command::new("cargo");
+    cmd.arg("clean").current_dir(dir);
+    cmd.args(specs.iter().flat_map(|spec| ["-p", spec]));
+    if let Err(err) = cmd.output() {
+        rap_error_and_exit(format!("`cargo clean` exits unexpectedly:\n{err}"));
+    }
+}
+
+/// The package specs (`name@version`) of the dependencies whose metadata files have been built
+/// without the sidecar summaries of `-summarize-deps`.
+fn unsummarized_deps(dir: &Utf8Path) -> Vec<String> {
+    let Ok(metadata) = MetadataCommand::new().current_dir(dir).exec() else {
+        return Vec::new();
+    };
+    // Library names of `lib<name>-<hash>.rmeta` without `lib<name>-<hash>.rapx.json`.
+    let mut unsummarized = HashSet::new();
+    let profiles = fs::read_dir(&metadata.target_directory)
+        .into_iter()
+        .flatten();
+    for profile in profiles.flatten() {
+        let files = fs::read_dir(profile.path().join("deps"))
+            .into_iter()
+            .flatten();
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().is_none_or(|ext| ext != "rmeta")
+                || path.with_extension(SIDECAR_EXTENSION).exists()
+            {
+                continue;
+            }
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            if let Some((name, _hash)) = stem
+                .strip_prefix("lib")
+                .and_then(|stem| stem.rsplit_once('-'))
+            {
+                unsummarized.insert(name.to_owned());
+            }
         }
     }
+    let members: HashSet<_> = metadata.workspace_members.iter().collect();
+    metadata
+        .packages
+        .iter()
+        .filter(|pkg| !members.contains(&pkg.id))
+        .filter(|pkg| {
+            pkg.targets
+                .iter()
+                .any(|target| unsummarized.contains(&target.name.replace('-', "_")))
+        })
+        .map(|pkg| format!("{}@{}", pkg.name, pkg.version))
+        .collect()
+}
+
+/// The metadata of the workspace containing the folder, or None if it is not a cargo package,
+/// in which case cargo check reports the error.
+fn package_metadata(dir: &Utf8Path) -> Option<Metadata> {
+    MetadataCommand::new()
+        .current_dir(dir)
+        .no_deps()
+        .exec()
+        .ok()
 }
 
 /// Just like running a cargo check in a folder.
@@ -1,3 +1,4 @@
+use crate::args;
 use cargo_metadata::{
     Metadata,
     camino::{Utf8Path, Utf8PathBuf},
@@ -24,11 +25,9 @@ pub fn deep_run() {
 }
 
 fn check_members(ws_metadata: &Metadata) {
-    // Force clean even if `RAP_CLEAN` is false, because rapx is in control of
-    // caches for all packages and there should be no cache.
     let ws_root = &ws_metadata.workspace_root;
     rap_trace!("cargo clean in workspace root {ws_root}");
-    super::cargo_clean(ws_root, true);
+    super::cargo_clean(ws_root, args::rap_clean());
 
     for pkg_folder in get_member_folders(ws_metadata) {
         super::cargo_check(pkg_folder);
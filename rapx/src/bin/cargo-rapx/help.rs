@@ -8,8 +8,22 @@ Application:
     -F or -uaf      use-after-free/double free detection.
     -M or -mleak    memory leakage detection.
     -O or -opt      automatically detect code optimization chances.
+    -sendsync       detect unsafe Send/Sync impls lacking Send/Sync bounds on generic parameters.
+    -panic-safety   detect broken invariants of unsafe code exposed to panics in generic callbacks.
+    -uninit         detect uninitialized memory exposed by MaybeUninit, alloc, and Vec::set_len.
+    -overflow       detect integer overflows, truncating casts, divisions by zero, and oversized shifts.
+    -oob            detect out-of-bounds indexing, get_unchecked and pointer offsets.
+    -taint=<spec.toml>
+                    detect tainted data flowing from the sources to the sinks given in the spec.
     -I or -infer    (under development) infer the safety properties required by unsafe APIs.
+    -infer-patch=<file>
+                    infer as -infer, and also write the suggested annotations to the file as a patch,
+                    which can be applied by `git apply` in the workspace root. The annotations of
+                    all the crates are merged into the file.
     -V or -verify   (under development) verify if the safety requirements of unsafe API are satisfied.
+    -contracts=<path>
+                    load the contracts of unsafe APIs outside std for -infer and -verify from the json
+                    file or the json files in the directory; rapx-contracts/*.json is loaded by default.
 
 Analysis:
     -alias          perform alias analysis (meet-over-paths by default)
@@ -18,10 +32,24 @@ Analysis:
     -upg            generate unsafety propagation graphs for each module.
     -upg-std        generate unsafety propagation graphs for each module of the Rust standard library
     -callgraph      generate callgraphs
+    -callgraph=<dot|json|graphml>
+                    export the callgraph with call sites, edge kinds, and function attributes
+    -callgraph-output=<file>
+                    write the exported callgraph into the file (callgraph_<crate>.<format> by default)
+    -callgraph-virtual=<cha|rta|vta>
+                    bind virtual calls to all implementors (cha, by default), to those whose types
+                    are instantiated (rta) or coerced to trait objects (vta) in reachable code
+    -callgraph-reach=<path>
+                    print the functions transitively called by the function
+    -callgraph-callers=<path>
+                    print the callers of the function with the call sites
+    -callgraph-instances
+                    generate the callgraph of function instantiations, e.g., `Vec::<u8>::push`
     -dataflow       generate dataflow graphs
     -ownedheap      analyze if the type holds a piece of memory on heap
     -pathcond       extract path constraints
     -range          perform range analysis
+    -range=4        bound the differences between integer variables with a relational domain
     -scan           print basic information of the crate, e.g., the number of APIs.
 
 General command: 
@@ -29,6 +57,17 @@ General command:
     -version                     show the version of RAPx
     -test-crate=<package_name>   specify the tested package in the workspace
     -timeout=<seconds>           specify the timeout seconds in running rapx
+    -fn-visits=<n>               specify the visited paths or iterations per function in SafeDrop,
+                                 alias, rCanary and range analysis (1000 by default)
+    -fn-timeout=<milliseconds>   specify the analysis time per function (10000 by default); the
+                                 functions exceeding the budget are reported as incomplete
+    -format=<text|sarif|json>    specify the format of bug reports (text by default)
+    -output=<file>               write sarif or json bug reports into the file instead of stdout;
+                                 required by sarif reports
+    -baseline-write=<file>       record the fingerprints of current bug reports into the file
+    -baseline=<file>             only report bugs whose fingerprints are not in the file
+    -summarize-deps              compute alias and owned-heap summaries of dependencies when
+                                 building them, which are used for calls into dependencies
 
 NOTE: multiple detections can be processed in single run by 
 appending the options to the arguments. Like `cargo rapx -F -M`
@@ -41,6 +80,17 @@ e.g.
    cargo rapx -F -M -- --tests
 3. detect use-after-free and memory leak for all members:
    cargo rapx -F -M -- --workspace
+4. report only the bugs introduced after the baseline is recorded:
+   cargo rapx -F -M -baseline-write=rapx-baseline.json
+   cargo rapx -F -M -baseline=rapx-baseline.json
+
+Suppression:
+    Bug reports of a function, a closure, or an impl block can be suppressed with the tool
+    attribute `#[rapx::allow(...)]`, which requires `#![feature(register_tool)]` and
+    `#![register_tool(rapx)]` in the crate root. The attribute accepts bug kinds (uaf,
+    double_free, dangling_pointer, leak, bounds_len, send_sync, panic_safety, uninit, taint,
+    arithmetic_overflow, oob, ...), detectors (safedrop, rcanary, opt, verify, sendsync,
+    overflow, oob), or all, e.g., `#[rapx::allow(uaf, leak)]`.
 
 Environment Variables (Values are case insensitive):
     RAP_LOG          verbosity of logging: trace, debug, info, warn
@@ -49,6 +99,10 @@ Environment Variables (Values are case insensitive):
                      warn: show bugs detected only.
 
     RAP_CLEAN        run cargo clean before check: true, false
+                     * false is the default value except that true is set
+                     * the workspace packages are analyzed again in every run without cleaning
+
+    RAP_CACHE        cache analysis summaries of unchanged functions under target/rapx: true, false
                      * true is the default value except that false is set
 
     RAP_RECURSIVE    scope of packages to check: none, shallow, deep
@@ -37,6 +37,24 @@ fn phase_cargo_rap() {
         _ => {}
     }
 
+    // Text reports are printed as annotated snippets, which cannot be merged into a file.
+    if args::report_output().is_some() && args::report_format().is_none_or(|f| f == "text") {
+        rap_error_and_exit("`-output=<file>` requires `-format=sarif` or `-format=json`.");
+    }
+    // Each checked crate produces a SARIF log, and the logs can only be merged in a file.
+    if args::report_output().is_none() && args::report_format() == Some("sarif") {
+        rap_error_and_exit("`-format=sarif` requires `-output=<file>`.");
+    }
+
+    // Findings of all checked crates are merged into the report and baseline files, so remove
+    // the stale ones.
+    for output in [args::report_output(), args::baseline_output()]
+        .into_iter()
+        .flatten()
+    {
+        let _ = std::fs::remove_file(output);
+    }
+
     cargo_check::run();
 }
 
@@ -50,6 +68,12 @@ fn phase_rustc_wrapper() {
         return;
     }
 
+    // summarize dependencies for the analysis of local crates if `-summarize-deps` is given
+    if args::summarize_deps() && args::filter_crate_type() {
+        run_rap_summarize();
+        return;
+    }
+
     // for dependencies and some special crate types, run rustc as usual
     run_rustc();
 }
@@ -60,12 +84,15 @@ fn main() {
       In this step, we set RUSTC_WRAPPER to cargo-rapx, and execute `cargo check ...` command;
        2. Cargo check actually triggers `path/cargo-rapx path/rustc` according to RUSTC_WRAPPER.
           Because RUSTC_WRAPPER is defined, Cargo calls the command: `$RUSTC_WRAPPER path/rustc ...`
+          For workspace packages, cargo-rapx is also RUSTC_WORKSPACE_WRAPPER, which is nested as
+          `$RUSTC_WRAPPER $RUSTC_WORKSPACE_WRAPPER path/rustc ...`.
     */
 
     // Init the log_system
     init_log().expect("Failed to init log.");
 
     match args::get_arg(1).unwrap() {
+        _ if args::is_nested_wrapper() => run_workspace_wrapper(),
         s if s.ends_with("rapx") => phase_cargo_rap(),
         s if s.ends_with("rustc") => phase_rustc_wrapper(),
         _ => rap_error_and_exit(
@@ -23,12 +23,28 @@ pub fn run_cmd(mut cmd: Command) {
     }
 }
 
+/// Run the workspace wrapper nested in this wrapper, which compiles the workspace package.
+pub fn run_workspace_wrapper() {
+    let mut cmd = Command::new(args::current_exe_path());
+    cmd.args(args::skip2());
+    run_cmd(cmd);
+}
+
 pub fn run_rustc() {
     let mut cmd = Command::new("rustc");
     cmd.args(args::skip2());
     run_cmd(cmd);
 }
 
+/// Compile a dependency with rapx, which writes the summaries of the dependency into a sidecar
+/// file next to its metadata.
+pub fn run_rap_summarize() {
+    let mut cmd = Command::new(find_rap());
+    cmd.args(args::skip2());
+    cmd.arg("-summarize");
+    run_cmd(cmd);
+}
+
 pub fn run_rap() {
     let mut cmd = Command::new(find_rap());
     cmd.args(args::skip2());
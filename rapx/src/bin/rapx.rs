@@ -3,7 +3,13 @@
 extern crate rustc_driver;
 extern crate rustc_session;
 
-use rapx::{RAP_DEFAULT_ARGS, RapCallback, rap_info, rap_trace, utils::log::init_log};
+use rapx::{
+    RAP_DEFAULT_ARGS, RapCallback,
+    analysis::core::callgraph::{VirtualCallResolution, export::CallGraphFormat},
+    rap_info, rap_trace,
+    report::ReportFormat,
+    utils::log::{init_log, rap_error_and_exit},
+};
 use regex::Regex;
 use rustc_session::EarlyDiagCtxt;
 use rustc_session::config::ErrorOutputType;
@@ -21,6 +27,14 @@ fn run_complier(args: &mut Vec<String>, callback: &mut RapCallback) {
     rap_trace!("The arg for compilation is {:?}", args);
 }
 
+fn parse_budget<T: std::str::FromStr>(arg: &str, flag: &str) -> T {
+    arg[flag.len()..].parse().unwrap_or_else(|_| {
+        rap_error_and_exit(format!(
+            "Invalid value of `{flag}`; expected a positive integer."
+        ))
+    })
+}
+
 fn main() {
     // Parse the arguments from env.
     let mut args = vec![];
@@ -39,12 +53,29 @@ fn main() {
             "-alias-mfp" => compiler.enable_alias_mfp(),
             "-adg" => compiler.enable_api_dependency(), // api dependency graph
             "-callgraph" => compiler.enable_callgraph(),
+            "-callgraph-instances" => compiler.enable_callgraph_instances(),
+            x if x.starts_with("-callgraph=") => {
+                compiler.set_callgraph_format(CallGraphFormat::from_arg(&x["-callgraph=".len()..]))
+            }
+            x if x.starts_with("-callgraph-virtual=") => compiler.set_callgraph_virtual_calls(
+                VirtualCallResolution::from_arg(&x["-callgraph-virtual=".len()..]),
+            ),
+            x if x.starts_with("-callgraph-output=") => {
+                compiler.set_callgraph_output(&x["-callgraph-output=".len()..])
+            }
+            x if x.starts_with("-callgraph-reach=") => {
+                compiler.add_callgraph_reach(&x["-callgraph-reach=".len()..])
+            }
+            x if x.starts_with("-callgraph-callers=") => {
+                compiler.add_callgraph_callers(&x["-callgraph-callers=".len()..])
+            }
             "-dataflow" => compiler.enable_dataflow(1),
             "-dataflow=debug" => compiler.enable_dataflow(2),
             "-ownedheap" => compiler.enable_ownedheap(),
             "-range" => compiler.enable_range_analysis(1),
             "-range=print_mir" => compiler.enable_range_analysis(2),
             "-pathcond" => compiler.enable_range_analysis(3),
+            "-range=4" => compiler.enable_range_analysis(4),
             "-test" => compiler.enable_test(),
             "-F" | "-F0" | "-F1" | "-F2" | "-uaf" => compiler.enable_safedrop(arg),
             "-I" | "-infer" => compiler.enable_infer(),
@@ -54,12 +85,39 @@ fn main() {
             "-opt=all" => compiler.enable_opt(2),
             "-opt=report" => compiler.enable_opt(0),
             "-scan" => compiler.enable_scan(),
+            "-panic-safety" => compiler.enable_panic_safety(),
+            "-uninit" => compiler.enable_uninit(),
+            "-overflow" => compiler.enable_overflow(),
+            "-oob" => compiler.enable_oob(),
+            "-sendsync" => compiler.enable_sendsync(),
+            "-summarize" => compiler.enable_summarize(),
+            // -summarize-deps has been handled in cargo-rapx
+            "-summarize-deps" => (),
             "-ssa" => compiler.enable_ssa_transform(),
             "-upg" => compiler.enable_upg(1),
             "-upg-std" => compiler.enable_upg(2),
             "-verify-std" => compiler.enable_verify_std(),
             "-mir" => compiler.enable_show_mir(),
             "-dotmir" => compiler.enable_show_mir_dot(),
+            x if x.starts_with("-infer-patch=") => {
+                compiler.set_infer_patch(&x["-infer-patch=".len()..])
+            }
+            x if x.starts_with("-contracts=") => compiler.add_contracts(&x["-contracts=".len()..]),
+            x if x.starts_with("-taint=") => compiler.set_taint_spec(&x["-taint=".len()..]),
+            x if x.starts_with("-format=") => {
+                compiler.set_report_format(ReportFormat::from_arg(&x["-format=".len()..]))
+            }
+            x if x.starts_with("-output=") => compiler.set_report_output(&x["-output=".len()..]),
+            x if x.starts_with("-baseline=") => compiler.set_baseline(&x["-baseline=".len()..]),
+            x if x.starts_with("-baseline-write=") => {
+                compiler.set_baseline_write(&x["-baseline-write=".len()..])
+            }
+            x if x.starts_with("-fn-visits=") => {
+                compiler.set_fn_visits(parse_budget(x, "-fn-visits="))
+            }
+            x if x.starts_with("-fn-timeout=") => {
+                compiler.set_fn_timeout(parse_budget(x, "-fn-timeout="))
+            }
             // -timeout has been handled in cargo-rapx
             x if x.starts_with("-timeout=") => (),
             _ => args.push(arg),
@@ -7,6 +7,7 @@ pub mod utils;
 pub mod analysis;
 pub mod def_id;
 pub mod preprocess;
+pub mod report;
 extern crate intervals;
 extern crate rustc_abi;
 extern crate rustc_ast;
@@ -21,6 +22,7 @@ extern crate rustc_interface;
 extern crate rustc_metadata;
 extern crate rustc_middle;
 extern crate rustc_public;
+extern crate rustc_query_system;
 extern crate rustc_session;
 extern crate rustc_span;
 extern crate rustc_target;
@@ -34,34 +36,53 @@ use analysis::{
     core::{
         alias_analysis::{AliasAnalysis, FnAliasMapWrapper, default::AliasAnalyzer},
         api_dependency::ApiDependencyAnalyzer,
-        callgraph::{CallGraphAnalysis, FnCallDisplay, default::CallGraphAnalyzer},
+        callgraph::{
+            CallGraphAnalysis, Config as CallGraphConfig, FnCallDisplay, VirtualCallResolution,
+            default::CallGraphAnalyzer, export::CallGraphFormat,
+            instance::InstanceCallGraphAnalyzer,
+        },
         dataflow::{
             Arg2RetMapWrapper, DataFlowAnalysis, DataFlowGraphMapWrapper, default::DataFlowAnalyzer,
         },
         ownedheap_analysis::{OHAResultMapWrapper, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
         range_analysis::{
-            PathConstraintMapWrapper, RAResultMapWrapper, RangeAnalysis, default::RangeAnalyzer,
+            PathConstraintMapWrapper, RelationMapWrapper,
+            typed::{TypedRAResultMapWrapper, TypedRangeAnalyzer},
         },
         ssa_transform::SSATrans,
     },
+    oob::OobCheck,
     opt::Opt,
+    overflow::OverflowCheck,
+    panic_safety::PanicSafetyCheck,
     rcanary::rCanary,
     safedrop::SafeDrop,
-    senryx::{CheckLevel, SenryxCheck},
+    sendsync::SendSyncCheck,
+    senryx::{CheckLevel, SenryxCheck, contracts::database::load_contracts},
+    taint::TaintCheck,
     test::Test,
+    uninit::UninitCheck,
     upg::{TargetCrate, UPGAnalysis},
-    utils::show_mir::ShowMir,
+    utils::{
+        budget::{self, BudgetLimits},
+        show_mir::ShowMir,
+        sidecar,
+    },
 };
+use report::ReportFormat;
 use rustc_ast::ast;
 use rustc_driver::{Callbacks, Compilation};
+use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_interface::{
     Config,
     interface::{self, Compiler},
 };
 use rustc_middle::{ty::TyCtxt, util::Providers};
 use rustc_session::search_paths::PathKind;
+use rustc_span::Symbol;
 use std::path::PathBuf;
-use std::{env, sync::Arc};
+use std::{env, sync::Arc, time::Duration};
+use utils::log::rap_error_and_exit;
 
 // Insert rustc arguments at the beginning of the argument list that RAP wants to be
 // set per default, for maximal validation power.
@@ -81,22 +102,42 @@ pub struct RapCallback {
     alias_mfp: bool,
     api_dependency: bool,
     callgraph: bool,
+    callgraph_config: CallGraphConfig,
+    callgraph_format: Option<CallGraphFormat>,
+    callgraph_output: Option<PathBuf>,
+    callgraph_reach: Vec<String>,
+    callgraph_callers: Vec<String>,
+    callgraph_instances: bool,
     dataflow: usize,
     ownedheap: bool,
     range: usize,
     ssa: bool,
     test: bool,
     infer: bool,
+    infer_patch: Option<PathBuf>,
     opt: usize,
+    panic_safety: bool,
     rcanary: bool,
     safedrop: bool,
+    sendsync: bool,
+    uninit: bool,
+    overflow: bool,
+    oob: bool,
+    taint_spec: Option<PathBuf>,
     show_mir: bool,
     show_mir_dot: bool,
     upg: usize,
     verify: bool,
     verify_std: bool,
+    contracts: Vec<PathBuf>,
     scan: bool,
+    summarize: bool,
     test_crate: Option<String>,
+    report_format: ReportFormat,
+    report_output: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    baseline_write: Option<PathBuf>,
+    budget_limits: BudgetLimits,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -107,28 +148,60 @@ impl Default for RapCallback {
             alias_mfp: false,
             api_dependency: false,
             callgraph: false,
+            callgraph_config: CallGraphConfig::default(),
+            callgraph_format: None,
+            callgraph_output: None,
+            callgraph_reach: Vec::new(),
+            callgraph_callers: Vec::new(),
+            callgraph_instances: false,
             dataflow: 0,
             ownedheap: false,
             range: 0,
             ssa: false,
             test: false,
             infer: false,
+            infer_patch: None,
             opt: usize::MAX,
+            panic_safety: false,
             rcanary: false,
             safedrop: false,
+            sendsync: false,
+            uninit: false,
+            overflow: false,
+            oob: false,
+            taint_spec: None,
             show_mir: false,
             show_mir_dot: false,
             upg: 0,
             verify: false,
             verify_std: false,
+            contracts: Vec::new(),
             scan: false,
+            summarize: false,
             test_crate: None,
+            report_format: ReportFormat::Text,
+            report_output: None,
+            baseline: None,
+            baseline_write: None,
+            budget_limits: BudgetLimits::default(),
         }
     }
 }
 
 impl Callbacks for RapCallback {
     fn config(&mut self, config: &mut Config) {
+        // cargo-rapx sets `RAP_RUN` to a value unique to each run. Depending on it, the checked
+        // crates are not fresh to cargo in the next run and are analyzed again without cleaning.
+        if !self.summarize
+            && let Ok(run) = env::var("RAP_RUN")
+        {
+            config.psess_created = Some(Box::new(move |psess| {
+                psess
+                    .env_depinfo
+                    .get_mut()
+                    .insert((Symbol::intern("RAP_RUN"), Some(Symbol::intern(&run))));
+            }));
+        }
         config.override_queries = Some(|_, providers| {
             providers.extern_queries.used_crate_source = |tcx, cnum| {
                 let mut providers = Providers::default();
@@ -248,6 +321,44 @@ impl RapCallback {
     /// Test if call-graph analysis is enabled.
     pub fn is_callgraph_enabled(&self) -> bool {
         self.callgraph
+            || self.callgraph_format.is_some()
+            || !self.callgraph_reach.is_empty()
+            || !self.callgraph_callers.is_empty()
+    }
+
+    /// Select how the targets of virtual calls are resolved in the call graph.
+    pub fn set_callgraph_virtual_calls(&mut self, resolution: VirtualCallResolution) {
+        self.callgraph_config.virtual_calls = resolution;
+    }
+
+    /// Export the call graph in the given format instead of printing it.
+    pub fn set_callgraph_format(&mut self, format: CallGraphFormat) {
+        self.callgraph_format = Some(format);
+    }
+
+    /// Write the exported call graph into the file instead of `callgraph_<crate>.<format>`.
+    pub fn set_callgraph_output(&mut self, path: &str) {
+        self.callgraph_output = Some(PathBuf::from(path));
+    }
+
+    /// Query the functions transitively called by the functions matching the path.
+    pub fn add_callgraph_reach(&mut self, path: &str) {
+        self.callgraph_reach.push(path.to_owned());
+    }
+
+    /// Query the callers of the functions matching the path.
+    pub fn add_callgraph_callers(&mut self, path: &str) {
+        self.callgraph_callers.push(path.to_owned());
+    }
+
+    /// Enable the instance-level call graph, whose nodes are the instantiations of functions.
+    pub fn enable_callgraph_instances(&mut self) {
+        self.callgraph_instances = true;
+    }
+
+    /// Test if the instance-level call graph is enabled.
+    pub fn is_callgraph_instances_enabled(&self) -> bool {
+        self.callgraph_instances
     }
 
     /// Enable owned heap analysis.
@@ -310,6 +421,51 @@ impl RapCallback {
         self.opt
     }
 
+    /// Enable the panic-safety checker for unsafe code.
+    pub fn enable_panic_safety(&mut self) {
+        self.panic_safety = true;
+    }
+
+    /// Test if the panic-safety checker is enabled.
+    pub fn is_panic_safety_enabled(&self) -> bool {
+        self.panic_safety
+    }
+
+    /// Enable the uninitialized-memory exposure checker.
+    pub fn enable_uninit(&mut self) {
+        self.uninit = true;
+    }
+
+    /// Test if the uninitialized-memory exposure checker is enabled.
+    pub fn is_uninit_enabled(&self) -> bool {
+        self.uninit
+    }
+
+    /// Enable the integer overflow checker.
+    pub fn enable_overflow(&mut self) {
+        self.overflow = true;
+    }
+
+    /// Test if the integer overflow checker is enabled.
+    pub fn is_overflow_enabled(&self) -> bool {
+        self.overflow
+    }
+
+    /// Enable the out-of-bounds checker.
+    pub fn enable_oob(&mut self) {
+        self.oob = true;
+    }
+
+    /// Test if the out-of-bounds checker is enabled.
+    pub fn is_oob_enabled(&self) -> bool {
+        self.oob
+    }
+
+    /// Enable the taint analysis with the sources, sanitizers and sinks in the given spec file.
+    pub fn set_taint_spec(&mut self, path: impl Into<PathBuf>) {
+        self.taint_spec = Some(path.into());
+    }
+
     /// Enable rcanary for memory leakage detection.
     pub fn enable_rcanary(&mut self) {
         self.rcanary = true;
@@ -375,6 +531,16 @@ impl RapCallback {
         self.safedrop
     }
 
+    /// Enable the checker of missing bounds in `unsafe impl Send/Sync`.
+    pub fn enable_sendsync(&mut self) {
+        self.sendsync = true;
+    }
+
+    /// Test if the Send/Sync checker is enabled.
+    pub fn is_sendsync_enabled(&self) -> bool {
+        self.sendsync
+    }
+
     /// Enable mir display.
     pub fn enable_show_mir(&mut self) {
         self.show_mir = true;
@@ -417,6 +583,12 @@ impl RapCallback {
         self.verify_std
     }
 
+    /// Load the contracts of unsafe APIs outside std from the json file, or from the json
+    /// files in the directory, besides those in `rapx-contracts/`.
+    pub fn add_contracts(&mut self, path: impl Into<PathBuf>) {
+        self.contracts.push(path.into());
+    }
+
     pub fn enable_infer(&mut self) {
         self.infer = true;
     }
@@ -425,6 +597,12 @@ impl RapCallback {
         self.infer
     }
 
+    /// Write the annotations inferred by `-infer` to the file as a patch, besides the report.
+    pub fn set_infer_patch(&mut self, path: impl Into<PathBuf>) {
+        self.infer = true;
+        self.infer_patch = Some(path.into());
+    }
+
     pub fn enable_scan(&mut self) {
         self.scan = true;
     }
@@ -433,13 +611,67 @@ impl RapCallback {
         self.scan
     }
 
+    /// Summarize the crate as a dependency and write the sidecar file, which is used by
+    /// `cargo rapx -summarize-deps`.
+    pub fn enable_summarize(&mut self) {
+        self.summarize = true;
+    }
+
+    pub fn is_summarize_enabled(&self) -> bool {
+        self.summarize
+    }
+
     pub fn set_test_crate(&mut self, crate_name: impl ToString) {
         self.test_crate = Some(crate_name.to_string())
     }
+
+    /// Set the format of bug reports, e.g., `-format=sarif`.
+    pub fn set_report_format(&mut self, format: ReportFormat) {
+        self.report_format = format;
+    }
+
+    /// Write machine-readable bug reports into the given file instead of stdout.
+    pub fn set_report_output(&mut self, path: impl Into<PathBuf>) {
+        self.report_output = Some(path.into());
+    }
+
+    /// Only report findings that are not recorded in the given baseline file.
+    pub fn set_baseline(&mut self, path: impl Into<PathBuf>) {
+        self.baseline = Some(path.into());
+    }
+
+    /// Record the fingerprints of all findings into the given baseline file.
+    pub fn set_baseline_write(&mut self, path: impl Into<PathBuf>) {
+        self.baseline_write = Some(path.into());
+    }
+
+    /// Set the number of visits each function may take in SafeDrop, MOP alias analysis,
+    /// rCanary and range analysis, e.g., `-fn-visits=1000`.
+    pub fn set_fn_visits(&mut self, visits: usize) {
+        self.budget_limits.visits = visits;
+    }
+
+    /// Set the wall time each function may take in the analyses, e.g., `-fn-timeout=10000`.
+    pub fn set_fn_timeout(&mut self, millis: u64) {
+        self.budget_limits.time = Duration::from_millis(millis);
+    }
 }
 
 /// Start the analysis with the features enabled.
 pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
+    report::set_format(callback.report_format);
+    budget::set_limits(callback.budget_limits);
+    if let Some(path) = &callback.baseline {
+        report::set_baseline(path);
+    }
+    if let Some(path) = &callback.baseline_write {
+        report::set_baseline_output(path);
+    }
+
+    if callback.is_summarize_enabled() {
+        sidecar::write_sidecar(tcx);
+    }
+
     if callback.is_alias_enabled() {
         let mut analyzer = AliasAnalyzer::new(tcx);
         analyzer.run();
@@ -467,19 +699,67 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     }
 
     if callback.is_callgraph_enabled() {
-        let mut analyzer = CallGraphAnalyzer::new(tcx);
+        let mut config = callback.callgraph_config;
+        config.drop_glue = callback.callgraph_format.is_some()
+            || !callback.callgraph_reach.is_empty()
+            || !callback.callgraph_callers.is_empty();
+        let mut analyzer = CallGraphAnalyzer::new(tcx, config);
         analyzer.run();
-        let callgraph = analyzer.get_fn_calls();
-        rap_info!(
-            "{}",
-            FnCallDisplay {
-                fn_calls: &callgraph,
-                tcx
+        if let Some(format) = callback.callgraph_format {
+            let path = callback.callgraph_output.clone().unwrap_or_else(|| {
+                PathBuf::from(format!(
+                    "callgraph_{}.{}",
+                    tcx.crate_name(LOCAL_CRATE),
+                    format.extension()
+                ))
+            });
+            if let Err(err) = analyzer.graph.dump(format, &path) {
+                rap_error_and_exit(format!(
+                    "Failed to dump the call graph to {}: {}",
+                    path.display(),
+                    err
+                ));
             }
-        );
+            rap_info!("Dump call graph to {}", path.display());
+        } else if callback.callgraph {
+            let callgraph = analyzer.get_fn_calls();
+            let indirect_calls = analyzer.get_indirect_fn_calls();
+            rap_info!(
+                "{}",
+                FnCallDisplay {
+                    fn_calls: &callgraph,
+                    indirect_calls: &indirect_calls,
+                    tcx
+                }
+            );
+        }
+        for (paths, reach) in [
+            (&callback.callgraph_reach, true),
+            (&callback.callgraph_callers, false),
+        ] {
+            for path in paths {
+                let def_ids = analyzer.graph.find_fns(path);
+                if def_ids.is_empty() {
+                    rap_warn!("No function in the call graph matches `{}`.", path);
+                }
+                for def_id in def_ids {
+                    if reach {
+                        rap_info!("{}", analyzer.graph.reach_to_string(def_id));
+                    } else {
+                        rap_info!("{}", analyzer.graph.callers_to_string(def_id));
+                    }
+                }
+            }
+        }
         //analyzer.display();
     }
 
+    if callback.is_callgraph_instances_enabled() {
+        let mut analyzer = InstanceCallGraphAnalyzer::new(tcx);
+        analyzer.run();
+        rap_info!("{}", analyzer.graph);
+    }
+
     match callback.is_dataflow_enabled() {
         1 => {
             let mut analyzer = DataFlowAnalyzer::new(tcx, false);
@@ -506,23 +786,29 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     if callback.is_range_analysis_enabled() {
         match callback.range {
             1 => {
-                let mut analyzer = RangeAnalyzer::<i64>::new(tcx, false);
+                let mut analyzer = TypedRangeAnalyzer::new(tcx, false);
                 analyzer.run();
                 let result = analyzer.get_all_fn_ranges();
-                rap_info!("{}", RAResultMapWrapper(result));
+                rap_info!("{}", TypedRAResultMapWrapper(result));
             }
             2 => {
-                let mut analyzer = RangeAnalyzer::<i64>::new(tcx, true);
+                let mut analyzer = TypedRangeAnalyzer::new(tcx, true);
                 analyzer.run();
                 let result = analyzer.get_all_fn_ranges();
-                rap_info!("{}", RAResultMapWrapper(result));
+                rap_info!("{}", TypedRAResultMapWrapper(result));
             }
             3 => {
-                let mut analyzer = RangeAnalyzer::<i64>::new(tcx, false);
+                let mut analyzer = TypedRangeAnalyzer::new(tcx, false);
                 analyzer.start_path_constraints_analysis();
                 let result = analyzer.get_all_path_constraints();
                 rap_info!("{}", PathConstraintMapWrapper(result));
             }
+            4 => {
+                let mut analyzer = TypedRangeAnalyzer::new(tcx, false);
+                analyzer.run();
+                let result = analyzer.get_all_relations();
+                rap_info!("{}", RelationMapWrapper(result));
+            }
             _ => {}
         }
     }
@@ -554,6 +840,30 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
         SafeDrop::new(tcx).start();
     }
 
+    if callback.is_panic_safety_enabled() {
+        PanicSafetyCheck::new(tcx).run();
+    }
+
+    if callback.is_uninit_enabled() {
+        UninitCheck::new(tcx).run();
+    }
+
+    if callback.is_overflow_enabled() {
+        OverflowCheck::new(tcx).run();
+    }
+
+    if callback.is_oob_enabled() {
+        OobCheck::new(tcx).run();
+    }
+
+    if let Some(path) = &callback.taint_spec {
+        TaintCheck::new(tcx, path).run();
+    }
+
+    if callback.is_sendsync_enabled() {
+        SendSyncCheck::new(tcx).run();
+    }
+
     if callback.is_show_mir_enabled() {
         ShowMir::new(tcx).start();
     }
@@ -573,6 +883,13 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
         _ => {}
     }
 
+    if callback.is_verify_enabled()
+        || callback.is_verify_std_enabled()
+        || callback.is_infer_enabled()
+    {
+        load_contracts(&callback.contracts);
+    }
+
     if callback.is_verify_enabled() {
         let check_level = CheckLevel::Medium;
         SenryxCheck::new(tcx, 2).start(check_level, true);
@@ -585,10 +902,15 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
 
     if callback.is_infer_enabled() {
         let check_level = CheckLevel::Medium;
-        SenryxCheck::new(tcx, 2).start(check_level, false);
+        let mut senryx = SenryxCheck::new(tcx, 2);
+        senryx.infer_patch = callback.infer_patch.clone();
+        senryx.start(check_level, false);
     }
 
     if callback.is_scan_enabled() {
         ScanAnalysis::new(tcx).run();
     }
+
+    budget::summarize();
+    report::flush(callback.report_output.as_ref());
 }
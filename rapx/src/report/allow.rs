@@ -0,0 +1,72 @@
+use super::FindingKind;
+use rustc_hir::{Attribute, def_id::DefId};
+use rustc_middle::ty::TyCtxt;
+
+const REGISTER_TOOL: &str = "rapx";
+
+/// Collect the lint names of all `#[rapx::allow(..)]` attributes on the item and its enclosing
+/// items, e.g., the impl block of a method or the function of a closure.
+///
+/// Example:
+/// ```
+/// #[rapx::allow(uaf, double_free)]
+/// fn foo() {...}
+/// ```
+/// The attribute above returns `["uaf", "double_free"]` for `foo` and all closures inside `foo`.
+pub fn allowed_lints(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<String> {
+    let mut lints = Vec::new();
+    let mut current = Some(def_id);
+    while let Some(def_id) = current {
+        if !def_id.is_local() {
+            break;
+        }
+        for attr in tcx.get_all_attrs(def_id) {
+            let Attribute::Unparsed(tool_attr) = attr else {
+                continue;
+            };
+            let segments = &tool_attr.path.segments;
+            if segments.len() != 2
+                || segments[0].as_str() != REGISTER_TOOL
+                || segments[1].as_str() != "allow"
+            {
+                continue;
+            }
+            let attr_str = rustc_hir_pretty::attribute_to_string(&tcx, attr);
+            lints.extend(parse_lints(&attr_str));
+        }
+        current = tcx.opt_parent(def_id);
+    }
+    lints
+}
+
+/// Parse `#[rapx::allow(uaf, double_free)]` into `["uaf", "double_free"]`.
+fn parse_lints(attr_str: &str) -> Vec<String> {
+    let Some(start) = attr_str.find('(') else {
+        return Vec::new();
+    };
+    let Some(end) = attr_str.rfind(')') else {
+        return Vec::new();
+    };
+    attr_str[start + 1..end]
+        .split(',')
+        .map(|lint| lint.trim().replace('-', "_"))
+        .filter(|lint| !lint.is_empty())
+        .collect()
+}
+
+/// Test if findings of the given kind are suppressed in the function by `#[rapx::allow(..)]`.
+pub fn is_allowed(tcx: TyCtxt<'_>, def_id: DefId, kind: FindingKind) -> bool {
+    allowed_lints(tcx, def_id)
+        .iter()
+        .any(|lint| kind.is_matched_by(lint))
+}
+
+/// Test if all the given kinds are suppressed in the function, so that the detector producing
+/// them can skip the function entirely.
+pub fn is_all_allowed(tcx: TyCtxt<'_>, def_id: DefId, kinds: &[FindingKind]) -> bool {
+    let lints = allowed_lints(tcx, def_id);
+    !lints.is_empty()
+        && kinds
+            .iter()
+            .all(|kind| lints.iter().any(|lint| kind.is_matched_by(lint)))
+}
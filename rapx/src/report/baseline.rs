@@ -0,0 +1,91 @@
+use super::Finding;
+use crate::utils::log::rap_error_and_exit;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, env, fs, path::Path};
+
+const BASELINE_VERSION: usize = 1;
+
+/// The content of a baseline file, i.e., the fingerprints of known findings.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: usize,
+    pub fingerprints: BTreeSet<String>,
+}
+
+impl Baseline {
+    pub fn new() -> Self {
+        Baseline {
+            version: BASELINE_VERSION,
+            fingerprints: BTreeSet::new(),
+        }
+    }
+
+    /// Load the baseline file given by `-baseline=<file>`.
+    pub fn load(path: &Path) -> Self {
+        let content = fs::read_to_string(path).unwrap_or_else(|e| {
+            rap_error_and_exit(format!("Failed to read baseline {}: {e}", path.display()))
+        });
+        let baseline: Baseline = serde_json::from_str(&content).unwrap_or_else(|e| {
+            rap_error_and_exit(format!("Failed to parse baseline {}: {e}", path.display()))
+        });
+        if baseline.version != BASELINE_VERSION {
+            rap_error_and_exit(format!(
+                "Unsupported baseline version {} in {}; please regenerate it with -baseline-write.",
+                baseline.version,
+                path.display()
+            ));
+        }
+        baseline
+    }
+
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.fingerprints.contains(fingerprint)
+    }
+
+    /// Merge the fingerprints into the baseline file given by `-baseline-write=<file>`, so that
+    /// the compilation of several crates produce a single baseline.
+    pub fn merge_into_file(&self, path: &Path) {
+        let result = super::update_locked(path, |content| {
+            let mut baseline = serde_json::from_str(content).unwrap_or_else(|_| Baseline::new());
+            baseline.version = BASELINE_VERSION;
+            baseline
+                .fingerprints
+                .extend(self.fingerprints.iter().cloned());
+            serde_json::to_string_pretty(&baseline).unwrap()
+        });
+        if let Err(e) = result {
+            rap_error_and_exit(format!("Failed to write baseline {}: {e}", path.display()));
+        }
+    }
+}
+
+/// Compute a fingerprint that survives unrelated edits of the source file: the function path,
+/// the rule, and the location of the finding relative to the first line of the function.
+///
+/// Example: `main|rapx.double-free|src/main.rs:+4:5`
+pub fn fingerprint(finding: &Finding, fn_start_line: Option<usize>) -> String {
+    let location = &finding.location;
+    let line = match fn_start_line {
+        Some(start) => format!("+{}", location.start_line.saturating_sub(start)),
+        None => location.start_line.to_string(),
+    };
+    format!(
+        "{}|{}|{}:{}:{}",
+        finding.function.as_deref().unwrap_or("<unknown>"),
+        finding.rule,
+        normalize_file(&location.file),
+        line,
+        location.start_column
+    )
+}
+
+/// Strip the current folder (i.e., the workspace root) from absolute paths.
+fn normalize_file(file: &str) -> String {
+    let path = Path::new(file);
+    env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| path.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
@@ -0,0 +1,412 @@
+use rustc_hir::def_id::DefId;
+use rustc_span::{FileName, Span, source_map::get_source_map};
+use serde::Serialize;
+
+/// The kind of a finding. Each kind corresponds to one rule, e.g., `rapx.uaf`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    UseAfterFree,
+    DoubleFree,
+    DoubleFreeUnwind,
+    DanglingPointer,
+    DanglingPointerUnwind,
+    MemoryLeak,
+    BoundsLen,
+    BoundsLoopPush,
+    BoundsExtend,
+    Encoding,
+    StringPush,
+    StringLowercase,
+    UnreservedVec,
+    UnreservedHash,
+    FlattenCollect,
+    VecRemove,
+    Participant,
+    SliceContains,
+    LocalSet,
+    VecInit,
+    NextIterator,
+    HashKeyCloning,
+    UsedAsImmutable,
+    UnsafePrecondition,
+    UnprovenContract,
+    UnsoundSendSync,
+    PanicSafety,
+    UninitExposure,
+    TaintFlow,
+    ArithmeticOverflow,
+    TruncatingCast,
+    DivisionByZero,
+    ShiftOverflow,
+    OutOfBounds,
+    UncheckedInBounds,
+}
+
+impl FindingKind {
+    /// Kinds reported by SafeDrop.
+    pub const SAFEDROP: &'static [FindingKind] = &[
+        FindingKind::UseAfterFree,
+        FindingKind::DoubleFree,
+        FindingKind::DoubleFreeUnwind,
+        FindingKind::DanglingPointer,
+        FindingKind::DanglingPointerUnwind,
+    ];
+
+    /// Kinds reported by the performance checkers of `-O`.
+    pub const OPT: &'static [FindingKind] = &[
+        FindingKind::BoundsLen,
+        FindingKind::BoundsLoopPush,
+        FindingKind::BoundsExtend,
+        FindingKind::Encoding,
+        FindingKind::StringPush,
+        FindingKind::StringLowercase,
+        FindingKind::UnreservedVec,
+        FindingKind::UnreservedHash,
+        FindingKind::FlattenCollect,
+        FindingKind::VecRemove,
+        FindingKind::Participant,
+        FindingKind::SliceContains,
+        FindingKind::LocalSet,
+        FindingKind::VecInit,
+        FindingKind::NextIterator,
+        FindingKind::HashKeyCloning,
+        FindingKind::UsedAsImmutable,
+    ];
+
+    /// Kinds reported by the integer overflow checker.
+    pub const OVERFLOW: &'static [FindingKind] = &[
+        FindingKind::ArithmeticOverflow,
+        FindingKind::TruncatingCast,
+        FindingKind::DivisionByZero,
+        FindingKind::ShiftOverflow,
+    ];
+
+    /// Kinds reported by the out-of-bounds checker.
+    pub const OOB: &'static [FindingKind] =
+        &[FindingKind::OutOfBounds, FindingKind::UncheckedInBounds];
+
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            FindingKind::UseAfterFree => "rapx.uaf",
+            FindingKind::DoubleFree => "rapx.double-free",
+            FindingKind::DoubleFreeUnwind => "rapx.double-free-unwind",
+            FindingKind::DanglingPointer => "rapx.dangling-pointer",
+            FindingKind::DanglingPointerUnwind => "rapx.dangling-pointer-unwind",
+            FindingKind::MemoryLeak => "rapx.leak",
+            FindingKind::BoundsLen => "rapx.opt.bounds-len",
+            FindingKind::BoundsLoopPush => "rapx.opt.bounds-loop-push",
+            FindingKind::BoundsExtend => "rapx.opt.bounds-extend",
+            FindingKind::Encoding => "rapx.opt.encoding",
+            FindingKind::StringPush => "rapx.opt.string-push",
+            FindingKind::StringLowercase => "rapx.opt.string-lowercase",
+            FindingKind::UnreservedVec => "rapx.opt.unreserved-vec",
+            FindingKind::UnreservedHash => "rapx.opt.unreserved-hash",
+            FindingKind::FlattenCollect => "rapx.opt.flatten-collect",
+            FindingKind::VecRemove => "rapx.opt.vec-remove",
+            FindingKind::Participant => "rapx.opt.participant",
+            FindingKind::SliceContains => "rapx.opt.slice-contains",
+            FindingKind::LocalSet => "rapx.opt.local-set",
+            FindingKind::VecInit => "rapx.opt.vec-init",
+            FindingKind::NextIterator => "rapx.opt.next-iterator",
+            FindingKind::HashKeyCloning => "rapx.opt.hash-key-cloning",
+            FindingKind::UsedAsImmutable => "rapx.opt.used-as-immutable",
+            FindingKind::UnsafePrecondition => "rapx.verify.unsafe-precondition",
+            FindingKind::UnprovenContract => "rapx.verify.unproven-contract",
+            FindingKind::UnsoundSendSync => "rapx.send-sync",
+            FindingKind::PanicSafety => "rapx.panic-safety",
+            FindingKind::UninitExposure => "rapx.uninit",
+            FindingKind::TaintFlow => "rapx.taint",
+            FindingKind::ArithmeticOverflow => "rapx.overflow.arithmetic",
+            FindingKind::TruncatingCast => "rapx.overflow.cast",
+            FindingKind::DivisionByZero => "rapx.overflow.div-by-zero",
+            FindingKind::ShiftOverflow => "rapx.overflow.shift",
+            FindingKind::OutOfBounds => "rapx.oob",
+            FindingKind::UncheckedInBounds => "rapx.oob.unchecked-in-bounds",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FindingKind::UseAfterFree => "Use-after-free",
+            FindingKind::DoubleFree => "Double free",
+            FindingKind::DoubleFreeUnwind => "Double free during unwinding",
+            FindingKind::DanglingPointer => "Dangling pointer",
+            FindingKind::DanglingPointerUnwind => "Dangling pointer during unwinding",
+            FindingKind::MemoryLeak => "Memory leak",
+            FindingKind::BoundsLen => "Unnecessary bounds checking",
+            FindingKind::BoundsLoopPush => "Unnecessary bounds checking in loop push",
+            FindingKind::BoundsExtend => "Unnecessary bounds checking in extend",
+            FindingKind::Encoding => "Unnecessary encoding checking",
+            FindingKind::StringPush => "Unnecessary encoding checking in string push",
+            FindingKind::StringLowercase => "Unnecessary encoding checking in string lowercase",
+            FindingKind::UnreservedVec => "Vector space unreserved",
+            FindingKind::UnreservedHash => "Hash table space unreserved",
+            FindingKind::FlattenCollect => "Flatten then collect",
+            FindingKind::VecRemove => "Vec used as a queue",
+            FindingKind::Participant => "Suboptimal data collection",
+            FindingKind::SliceContains => "Slice used as a set",
+            FindingKind::LocalSet => "Unnecessary local collection initialization",
+            FindingKind::VecInit => "Unnecessary vector initialization",
+            FindingKind::NextIterator => "Inefficient iterator",
+            FindingKind::HashKeyCloning => "Unnecessary cloning of hash keys",
+            FindingKind::UsedAsImmutable => "Unnecessary cloning of immutable values",
+            FindingKind::UnsafePrecondition => "Safety precondition of unsafe API not proven",
+            FindingKind::UnprovenContract => "Postcondition or type invariant not proven",
+            FindingKind::UnsoundSendSync => {
+                "Send/Sync implementation lacks bounds on generic parameters"
+            }
+            FindingKind::PanicSafety => {
+                "Broken invariant exposed to a panic in caller-provided code"
+            }
+            FindingKind::UninitExposure => "Uninitialized memory exposed",
+            FindingKind::TaintFlow => "Tainted data flows into a sink",
+            FindingKind::ArithmeticOverflow => "Integer arithmetic may overflow",
+            FindingKind::TruncatingCast => "Integer cast may truncate",
+            FindingKind::DivisionByZero => "Divisor may be zero",
+            FindingKind::ShiftOverflow => "Shift amount may exceed the bit width",
+            FindingKind::OutOfBounds => "Out-of-bounds access",
+            FindingKind::UncheckedInBounds => "Unchecked access proven in bounds",
+        }
+    }
+
+    /// The name used to suppress the kind with `#[rapx::allow(..)]`.
+    /// Findings during unwinding share the name of their normal counterpart.
+    pub fn lint_name(&self) -> &'static str {
+        match self {
+            FindingKind::UseAfterFree => "uaf",
+            FindingKind::DoubleFree | FindingKind::DoubleFreeUnwind => "double_free",
+            FindingKind::DanglingPointer | FindingKind::DanglingPointerUnwind => "dangling_pointer",
+            FindingKind::MemoryLeak => "leak",
+            FindingKind::BoundsLen => "bounds_len",
+            FindingKind::BoundsLoopPush => "bounds_loop_push",
+            FindingKind::BoundsExtend => "bounds_extend",
+            FindingKind::Encoding => "encoding",
+            FindingKind::StringPush => "string_push",
+            FindingKind::StringLowercase => "string_lowercase",
+            FindingKind::UnreservedVec => "unreserved_vec",
+            FindingKind::UnreservedHash => "unreserved_hash",
+            FindingKind::FlattenCollect => "flatten_collect",
+            FindingKind::VecRemove => "vec_remove",
+            FindingKind::Participant => "participant",
+            FindingKind::SliceContains => "slice_contains",
+            FindingKind::LocalSet => "local_set",
+            FindingKind::VecInit => "vec_init",
+            FindingKind::NextIterator => "next_iterator",
+            FindingKind::HashKeyCloning => "hash_key_cloning",
+            FindingKind::UsedAsImmutable => "used_as_immutable",
+            FindingKind::UnsafePrecondition => "unsafe_precondition",
+            FindingKind::UnprovenContract => "unproven_contract",
+            FindingKind::UnsoundSendSync => "send_sync",
+            FindingKind::PanicSafety => "panic_safety",
+            FindingKind::UninitExposure => "uninit",
+            FindingKind::TaintFlow => "taint",
+            FindingKind::ArithmeticOverflow => "arithmetic_overflow",
+            FindingKind::TruncatingCast => "truncating_cast",
+            FindingKind::DivisionByZero => "div_by_zero",
+            FindingKind::ShiftOverflow => "shift_overflow",
+            FindingKind::OutOfBounds => "oob",
+            FindingKind::UncheckedInBounds => "unchecked_in_bounds",
+        }
+    }
+
+    /// The detector producing the kind, which can also be used as a lint group, e.g.,
+    /// `#[rapx::allow(safedrop)]` suppresses all use-after-free, double free and dangling
+    /// pointer findings.
+    pub fn group(&self) -> &'static str {
+        match self {
+            FindingKind::UseAfterFree
+            | FindingKind::DoubleFree
+            | FindingKind::DoubleFreeUnwind
+            | FindingKind::DanglingPointer
+            | FindingKind::DanglingPointerUnwind => "safedrop",
+            FindingKind::MemoryLeak => "rcanary",
+            FindingKind::UnsafePrecondition | FindingKind::UnprovenContract => "verify",
+            FindingKind::UnsoundSendSync => "sendsync",
+            FindingKind::PanicSafety => "panic_safety",
+            FindingKind::UninitExposure => "uninit",
+            FindingKind::TaintFlow => "taint",
+            FindingKind::ArithmeticOverflow
+            | FindingKind::TruncatingCast
+            | FindingKind::DivisionByZero
+            | FindingKind::ShiftOverflow => "overflow",
+            FindingKind::OutOfBounds | FindingKind::UncheckedInBounds => "oob",
+            _ => "opt",
+        }
+    }
+
+    /// Test if the lint name given in `#[rapx::allow(..)]` covers the kind.
+    pub fn is_matched_by(&self, lint: &str) -> bool {
+        lint == "all" || lint == self.lint_name() || lint == self.group()
+    }
+
+    /// Memory-safety bugs, including out-of-bounds accesses, are errors, unproven contracts,
+    /// leaks, unsound Send/Sync implementations, tainted flows and integer overflows are
+    /// warnings, and optimization chances and safe rewrites of unchecked accesses are notes.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            FindingKind::UseAfterFree
+            | FindingKind::DoubleFree
+            | FindingKind::DoubleFreeUnwind
+            | FindingKind::DanglingPointer
+            | FindingKind::DanglingPointerUnwind
+            | FindingKind::UninitExposure
+            | FindingKind::OutOfBounds => Severity::Error,
+            FindingKind::MemoryLeak
+            | FindingKind::UnsafePrecondition
+            | FindingKind::UnprovenContract
+            | FindingKind::UnsoundSendSync
+            | FindingKind::PanicSafety
+            | FindingKind::TaintFlow
+            | FindingKind::ArithmeticOverflow
+            | FindingKind::TruncatingCast
+            | FindingKind::DivisionByZero
+            | FindingKind::ShiftOverflow => Severity::Warning,
+            _ => Severity::Note,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// The corresponding `level` of a SARIF result.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+/// A source code region resolved from a rustc `Span`.
+/// Lines and columns are 1-based, which is what SARIF expects.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct SourceRegion {
+    pub file: String,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceRegion {
+    pub fn from_span(span: Span) -> Self {
+        let source_map = get_source_map().unwrap();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        let file = match &lo.file.name {
+            FileName::Real(realname) => realname
+                .local_path()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "<unknown>".to_string()),
+            _ => "<unknown>".to_string(),
+        };
+        SourceRegion {
+            file,
+            start_line: lo.line,
+            start_column: lo.col.0 + 1,
+            end_line: hi.line,
+            end_column: hi.col.0 + 1,
+        }
+    }
+}
+
+/// A secondary span attached to a finding, e.g., where the aliased value is dropped.
+#[derive(Debug, Clone, Serialize)]
+pub struct Label {
+    pub location: SourceRegion,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: impl ToString) -> Self {
+        Label {
+            location: SourceRegion::from_span(span),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// One bug reported by a detector, independent of how it is finally rendered.
+///
+/// Detectors build findings with the `with_*` methods and push them into the global sink
+/// with `report::record`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// Confidence in percent, if the detector estimates one.
+    pub confidence: Option<usize>,
+    pub message: String,
+    /// The function where the finding is located. It is resolved to `function` when recorded.
+    #[serde(skip)]
+    pub def_id: Option<DefId>,
+    /// The def path of the function where the finding is located.
+    pub function: Option<String>,
+    /// The stable fingerprint used by the baseline, see `baseline::fingerprint`.
+    pub fingerprint: Option<String>,
+    pub location: SourceRegion,
+    pub secondary: Vec<Label>,
+    /// MIR locals involved in the finding, e.g., the aliased values of a double free.
+    pub locals: Vec<usize>,
+    /// MIR basic blocks involved in the finding, e.g., where the values are dropped.
+    pub basic_blocks: Vec<usize>,
+}
+
+impl Finding {
+    pub fn new(kind: FindingKind, message: impl ToString, span: Span) -> Self {
+        Finding {
+            kind,
+            rule: kind.rule_id(),
+            severity: kind.default_severity(),
+            confidence: None,
+            message: message.to_string(),
+            def_id: None,
+            function: None,
+            fingerprint: None,
+            location: SourceRegion::from_span(span),
+            secondary: Vec::new(),
+            locals: Vec::new(),
+            basic_blocks: Vec::new(),
+        }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_confidence(mut self, confidence: usize) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    pub fn with_function(mut self, def_id: DefId) -> Self {
+        self.def_id = Some(def_id);
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: impl ToString) -> Self {
+        self.secondary.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_local(mut self, local: usize) -> Self {
+        self.locals.push(local);
+        self
+    }
+
+    pub fn with_basic_block(mut self, bb: usize) -> Self {
+        self.basic_blocks.push(bb);
+        self
+    }
+}
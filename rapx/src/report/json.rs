@@ -0,0 +1,30 @@
+use super::Finding;
+use crate::utils::log::rap_error_and_exit;
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+/// Serialize the findings as JSON lines, i.e., one JSON object per finding and per line.
+pub fn to_string(findings: &[Finding]) -> String {
+    findings
+        .iter()
+        .map(|finding| serde_json::to_string(finding).unwrap() + "\n")
+        .collect()
+}
+
+/// Append the findings to a JSON-lines file, which is created if it does not exist yet.
+pub fn append_to_file(path: &Path, findings: &[Finding]) {
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| {
+            // The lines of crates compiled in parallel must not interleave.
+            file.lock()?;
+            file.write_all(to_string(findings).as_bytes())
+        });
+    if let Err(e) = result {
+        rap_error_and_exit(format!(
+            "Failed to write findings to {}: {e}",
+            path.display()
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+pub mod allow;
+pub mod baseline;
+pub mod finding;
+pub mod json;
+pub mod sarif;
+
+pub use allow::is_all_allowed;
+pub use finding::{Finding, FindingKind, Label, Severity, SourceRegion};
+
+use crate::utils::log::rap_error_and_exit;
+use baseline::Baseline;
+use lazy_static::lazy_static;
+use rustc_middle::ty::tls;
+use rustc_span::source_map::get_source_map;
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+lazy_static! {
+    static ref FORMAT: Mutex<ReportFormat> = Mutex::new(ReportFormat::Text);
+    static ref FINDINGS: Mutex<Vec<Finding>> = Mutex::new(Vec::new());
+    /// Fingerprints of known findings loaded from `-baseline=<file>`.
+    static ref BASELINE: Mutex<Option<Baseline>> = Mutex::new(None);
+    /// Fingerprints of the current findings to be written into `-baseline-write=<file>`.
+    static ref BASELINE_WRITE: Mutex<Option<(PathBuf, Baseline)>> = Mutex::new(None);
+    static ref SUPPRESSED: Mutex<usize> = Mutex::new(0);
+}
+
+/// The output format of bug reports, selected by `-format=<text|sarif|json>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReportFormat {
+    /// Human-readable annotated snippets printed to stdout (the default).
+    #[default]
+    Text,
+    /// A single SARIF 2.1.0 log covering every finding of the run.
+    Sarif,
+    /// One JSON object per finding and per line.
+    Json,
+}
+
+impl ReportFormat {
+    pub fn from_arg(value: &str) -> Self {
+        match value {
+            "text" => ReportFormat::Text,
+            "sarif" => ReportFormat::Sarif,
+            "json" => ReportFormat::Json,
+            _ => rap_error_and_exit(format!(
+                "Unknown report format `{value}`; expected one of: text, sarif, json."
+            )),
+        }
+    }
+}
+
+/// Select the report format before any detector runs.
+pub fn set_format(format: ReportFormat) {
+    *FORMAT.lock().unwrap() = format;
+}
+
+pub fn format() -> ReportFormat {
+    *FORMAT.lock().unwrap()
+}
+
+/// Suppress the findings whose fingerprints are recorded in the baseline file.
+pub fn set_baseline(path: &Path) {
+    *BASELINE.lock().unwrap() = Some(Baseline::load(path));
+}
+
+/// Write the fingerprints of all findings of this run into the baseline file when flushed.
+pub fn set_baseline_output(path: &Path) {
+    *BASELINE_WRITE.lock().unwrap() = Some((path.to_path_buf(), Baseline::new()));
+}
+
+/// Record a finding in the global sink. Findings are only serialized by `flush`.
+///
+/// Return false if the finding is suppressed by `#[rapx::allow(..)]` or by the baseline, in
+/// which case the detector should not print it either.
+pub fn record(mut finding: Finding) -> bool {
+    let mut fn_start_line = None;
+    if let Some(def_id) = finding.def_id {
+        let allowed = tls::with(|tcx| {
+            if allow::is_allowed(tcx, def_id, finding.kind) {
+                return true;
+            }
+            finding.function = Some(tcx.def_path_str(def_id));
+            let source_map = get_source_map().unwrap();
+            fn_start_line = Some(source_map.lookup_char_pos(tcx.def_span(def_id).lo()).line);
+            false
+        });
+        if allowed {
+            return false;
+        }
+    }
+    let fingerprint = baseline::fingerprint(&finding, fn_start_line);
+
+    if let Some((_, baseline)) = BASELINE_WRITE.lock().unwrap().as_mut() {
+        baseline.fingerprints.insert(fingerprint.clone());
+    }
+    let known = BASELINE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|baseline| baseline.contains(&fingerprint));
+    if known {
+        *SUPPRESSED.lock().unwrap() += 1;
+        return false;
+    }
+
+    finding.fingerprint = Some(fingerprint);
+    FINDINGS.lock().unwrap().push(finding);
+    true
+}
+
+/// Print an annotated snippet rendered by a detector.
+/// Snippets are suppressed for machine-readable formats so that stdout stays parsable.
+pub fn print_snippet(rendered: impl Display) {
+    if format() == ReportFormat::Text {
+        println!("{}", rendered);
+    }
+}
+
+/// Rewrite a file shared by the crates of a run from its current content, which is empty if
+/// the file does not exist yet.
+///
+/// Cargo compiles crates in parallel, so the file is locked during the read-modify-write.
+pub(crate) fn update_locked(path: &Path, update: impl FnOnce(&str) -> String) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    file.lock()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let content = update(&content);
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(content.as_bytes())
+}
+
+/// Serialize all recorded findings in the selected format.
+///
+/// If `output` is given, the findings are merged into that file, so that the compilation of
+/// several crates (e.g., the lib and bin targets of a package) produce a single report.
+/// Otherwise, the report is printed to stdout, and cargo-rapx requires `output` for SARIF
+/// reports because the logs of several crates printed in a row are not a valid log.
+pub fn flush(output: Option<&PathBuf>) {
+    let findings = std::mem::take(&mut *FINDINGS.lock().unwrap());
+    let suppressed = std::mem::take(&mut *SUPPRESSED.lock().unwrap());
+    if suppressed > 0 {
+        rap_info!(
+            "{} known findings are suppressed by the baseline.",
+            suppressed
+        );
+    }
+    if let Some((path, baseline)) = BASELINE_WRITE.lock().unwrap().take() {
+        baseline.merge_into_file(&path);
+        rap_info!(
+            "{} findings are written into the baseline {}.",
+            baseline.fingerprints.len(),
+            path.display()
+        );
+    }
+    match format() {
+        ReportFormat::Text => {}
+        ReportFormat::Sarif => match output {
+            Some(path) => sarif::merge_into_file(path, &findings),
+            None => println!("{}", sarif::to_string(&findings)),
+        },
+        ReportFormat::Json => match output {
+            Some(path) => json::append_to_file(path, &findings),
+            None => print!("{}", json::to_string(&findings)),
+        },
+    }
+}
@@ -0,0 +1,130 @@
+use super::{Finding, FindingKind, SourceRegion};
+use crate::utils::log::rap_error_and_exit;
+use serde_json::{Value, json};
+use std::{collections::BTreeSet, path::Path};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+fn physical_location(region: &SourceRegion) -> Value {
+    json!({
+        "artifactLocation": { "uri": region.file },
+        "region": {
+            "startLine": region.start_line,
+            "startColumn": region.start_column,
+            "endLine": region.end_line,
+            "endColumn": region.end_column,
+        }
+    })
+}
+
+fn result(finding: &Finding) -> Value {
+    let related: Vec<Value> = finding
+        .secondary
+        .iter()
+        .enumerate()
+        .map(|(id, label)| {
+            json!({
+                "id": id,
+                "physicalLocation": physical_location(&label.location),
+                "message": { "text": label.message },
+            })
+        })
+        .collect();
+    let mut location = json!({ "physicalLocation": physical_location(&finding.location) });
+    if let Some(function) = &finding.function {
+        location["logicalLocations"] =
+            json!([{ "fullyQualifiedName": function, "kind": "function" }]);
+    }
+    let mut properties = json!({
+        "locals": finding.locals,
+        "basicBlocks": finding.basic_blocks,
+    });
+    if let Some(confidence) = finding.confidence {
+        properties["confidence"] = json!(confidence);
+    }
+    let mut result = json!({
+        "ruleId": finding.rule,
+        "level": finding.severity.as_str(),
+        "message": { "text": finding.message },
+        "locations": [location],
+        "relatedLocations": related,
+        "properties": properties,
+    });
+    if let Some(fingerprint) = &finding.fingerprint {
+        result["partialFingerprints"] = json!({ "rapx/v1": fingerprint });
+    }
+    result
+}
+
+fn rule(kind: FindingKind) -> Value {
+    json!({
+        "id": kind.rule_id(),
+        "shortDescription": { "text": kind.description() },
+        "defaultConfiguration": { "level": kind.default_severity().as_str() },
+    })
+}
+
+fn rules(kinds: impl IntoIterator<Item = FindingKind>) -> Vec<Value> {
+    let mut kinds: Vec<FindingKind> = kinds.into_iter().collect();
+    kinds.sort_by_key(|kind| kind.rule_id());
+    kinds.dedup();
+    kinds.into_iter().map(rule).collect()
+}
+
+/// Build a SARIF log with a single run that contains the given findings.
+pub fn to_value(findings: &[Finding]) -> Value {
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "RAPx",
+                    "informationUri": "https://github.com/Artisan-Lab/RAPx",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules(findings.iter().map(|f| f.kind)),
+                }
+            },
+            "results": findings.iter().map(result).collect::<Vec<_>>(),
+        }]
+    })
+}
+
+pub fn to_string(findings: &[Finding]) -> String {
+    serde_json::to_string_pretty(&to_value(findings)).unwrap()
+}
+
+/// Append the findings to the run of an existing SARIF log, or create a new log if the file
+/// does not exist yet.
+pub fn merge_into_file(path: &Path, findings: &[Finding]) {
+    let result = super::update_locked(path, |content| {
+        let mut log = if content.is_empty() {
+            to_value(&[])
+        } else {
+            serde_json::from_str::<Value>(content).unwrap_or_else(|e| {
+                rap_error_and_exit(format!("Failed to parse SARIF log {}: {e}", path.display()))
+            })
+        };
+
+        let run = &mut log["runs"][0];
+        if let Some(existing) = run["tool"]["driver"]["rules"].as_array_mut() {
+            let mut known: BTreeSet<String> = existing
+                .iter()
+                .filter_map(|r| r["id"].as_str().map(ToString::to_string))
+                .collect();
+            for rule in rules(findings.iter().map(|f| f.kind)) {
+                if known.insert(rule["id"].as_str().unwrap().to_string()) {
+                    existing.push(rule);
+                }
+            }
+        }
+        if let Some(results) = run["results"].as_array_mut() {
+            results.extend(findings.iter().map(result));
+        }
+        serde_json::to_string_pretty(&log).unwrap()
+    });
+    if let Err(e) = result {
+        rap_error_and_exit(format!("Failed to write SARIF log {}: {e}", path.display()));
+    }
+}
@@ -0,0 +1,9 @@
+//Expected alias analysis result with `-summarize-deps`: (0,1), (0,2), (1,2)
+pub fn wrapper<'a>(x: &'a Vec<i32>, y: &'a Vec<i32>) -> &'a Vec<i32> {
+    alias_dep_util::choose(x, y, true)
+}
+
+//Expected alias analysis result with `-summarize-deps`: (0,1)
+pub fn first<'a>(x: &'a Vec<i32>, y: &'a Vec<i32>) -> &'a Vec<i32> {
+    alias_dep_util::pick_first(x, y)
+}
@@ -0,0 +1,15 @@
+// A dependency of alias_dep, which is summarized by `-summarize-deps`.
+pub fn choose<'a>(x: &'a Vec<i32>, y: &'a Vec<i32>, first: bool) -> &'a Vec<i32> {
+    let mut r = y;
+    if first {
+        r = x;
+    }
+    println!("choose {}", first);
+    r
+}
+
+// The result only aliases the first argument.
+pub fn pick_first<'a>(x: &'a Vec<i32>, y: &'a Vec<i32>) -> &'a Vec<i32> {
+    println!("pick_first {}", y.len());
+    x
+}
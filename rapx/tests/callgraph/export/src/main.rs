@@ -0,0 +1,46 @@
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        println!("released");
+    }
+}
+
+/// # Safety
+/// `ptr` must be valid for reads.
+pub unsafe fn read_raw(ptr: *const f64) -> f64 {
+    unsafe { *ptr }
+}
+
+mod geometry {
+    use super::Shape;
+
+    pub(crate) fn total(shapes: &[Box<dyn Shape>]) -> f64 {
+        shapes.iter().map(|shape| shape.area()).sum()
+    }
+}
+
+fn helper() -> f64 {
+    let value = 1.0;
+    unsafe { read_raw(&value) }
+}
+
+fn main() {
+    let _guard = Guard;
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Square(2.0))];
+    let f: fn() -> f64 = helper;
+    let g = f.clone();
+    println!("{}", geometry::total(&shapes) + g());
+}
@@ -0,0 +1,58 @@
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn negate(x: i32) -> i32 {
+    -x
+}
+
+fn log_event(event: &str) {
+    println!("event: {}", event);
+}
+
+fn log_error(event: &str) {
+    eprintln!("error: {}", event);
+}
+
+// Callback table in a static.
+static HANDLERS: [fn(&str); 2] = [log_event, log_error];
+
+struct Button {
+    on_click: Box<dyn Fn() -> i32>,
+}
+
+impl Button {
+    fn click(&self) -> i32 {
+        (self.on_click)()
+    }
+}
+
+fn apply(f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn pick(flag: bool) -> fn(i32) -> i32 {
+    if flag { double } else { negate }
+}
+
+pub fn main() {
+    // Function pointer passed as an argument.
+    let a = apply(double, 1);
+
+    // Function pointer returned by a function.
+    let f = pick(a > 0);
+    let b = f(a);
+
+    // Closure stored in a struct behind `Box<dyn Fn>`.
+    let offset = b + 1;
+    let button = Button {
+        on_click: Box::new(move || offset + 1),
+    };
+    let c = button.click();
+
+    // Callback table.
+    for handler in HANDLERS.iter() {
+        handler("clicked");
+    }
+    println!("{}", c);
+}
@@ -0,0 +1,28 @@
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+struct Square(u32);
+
+impl Shape for Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+fn store<T>(items: &mut Vec<T>, item: T) {
+    items.push(item);
+}
+
+fn total(shapes: &[Box<dyn Shape>]) -> u32 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+fn main() {
+    let mut bytes: Vec<u8> = Vec::new();
+    store(&mut bytes, 1);
+    let mut names: Vec<String> = Vec::new();
+    store(&mut names, String::from("rapx"));
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Square(2))];
+    println!("{} {} {}", bytes.len(), names.len(), total(&shapes));
+}
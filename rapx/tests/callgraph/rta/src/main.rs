@@ -0,0 +1,39 @@
+trait Animal {
+    fn speak(&self) -> &'static str;
+}
+
+struct Dog;
+impl Animal for Dog {
+    fn speak(&self) -> &'static str {
+        "Woof!"
+    }
+}
+
+// Instantiated, but never used as a trait object.
+struct Cat;
+impl Animal for Cat {
+    fn speak(&self) -> &'static str {
+        "Meow!"
+    }
+}
+
+// Never instantiated.
+#[allow(dead_code)]
+struct Fish;
+impl Animal for Fish {
+    fn speak(&self) -> &'static str {
+        "..."
+    }
+}
+
+fn chorus(animals: &[Box<dyn Animal>]) {
+    for animal in animals {
+        println!("{}", animal.speak());
+    }
+}
+
+fn main() {
+    let animals: Vec<Box<dyn Animal>> = vec![Box::new(Dog)];
+    chorus(&animals);
+    println!("{}", Cat.speak());
+}
@@ -0,0 +1,32 @@
+static mut LAST: usize = 0;
+
+struct Counter {
+    total: usize,
+}
+
+impl Counter {
+    // The parameter flows into the field behind `self`.
+    fn add(&mut self, value: usize) {
+        self.total = self.total + value;
+    }
+}
+
+// The parameter flows into the string borrowed by the first parameter.
+fn append(out: &mut String, part: &str) {
+    out.push_str(part);
+}
+
+// The parameter flows into a static.
+fn record(value: usize) {
+    unsafe {
+        LAST = value;
+    }
+}
+
+fn main() {
+    let mut counter = Counter { total: 0 };
+    counter.add(1);
+    let mut out = String::new();
+    append(&mut out, "rapx");
+    record(counter.total + out.len());
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+fn arr(flag: bool) -> u8 {
+    let a = [1u8; 4];
+    let i = if flag { 5 } else { 6 };
+    a[i]
+}
+
+fn at_len(v: &[u8]) -> u8 {
+    let n = v.len();
+    v[n]
+}
+
+fn past_len(v: &[u8]) -> u8 {
+    let n = v.len() + 1;
+    v[n]
+}
+
+fn vec_at_len(v: Vec<u8>) -> u8 {
+    v[v.len()]
+}
+
+fn unchecked_at_len(v: &[u8]) -> u8 {
+    unsafe { *v.get_unchecked(v.len()) }
+}
+
+fn ptr_at_len(v: &[u8]) -> u8 {
+    let p = v.as_ptr();
+    unsafe { *p.add(v.len()) }
+}
+
+fn ptr_past_len(v: &[u8]) -> *const u8 {
+    let p = v.as_ptr();
+    unsafe { p.add(v.len() + 1) }
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+#![allow(dead_code)]
+
+fn guarded(v: &[u8], i: usize) -> u8 {
+    if i < v.len() { v[i] } else { 0 }
+}
+
+fn last(v: &[u8]) -> u8 {
+    let n = v.len() - 1;
+    v[n]
+}
+
+fn arr(flag: bool) -> u8 {
+    let a = [1u8; 4];
+    let i = if flag { 0 } else { 3 };
+    a[i]
+}
+
+fn ptr_end(v: &[u8]) -> *const u8 {
+    let p = v.as_ptr();
+    unsafe { p.add(v.len()) }
+}
+
+fn unchecked(v: &[u8], i: usize) -> u8 {
+    unsafe { *v.get_unchecked(i) }
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+#![allow(dead_code)]
+
+fn guarded(v: &[u8], i: usize) -> u8 {
+    if i < v.len() {
+        unsafe { *v.get_unchecked(i) }
+    } else {
+        0
+    }
+}
+
+fn constant(flag: bool) -> u8 {
+    let a = [1u8; 4];
+    let i = if flag { 1 } else { 3 };
+    unsafe { *a.get_unchecked(i) }
+}
+
+// `i` is assigned several times, but its copy `j` is below the length wherever it is defined.
+fn sum(v: &[u8]) -> u32 {
+    let mut i = 0;
+    let mut s = 0;
+    while i < v.len() {
+        let j = i;
+        s += unsafe { *v.get_unchecked(j) } as u32;
+        i += 1;
+    }
+    s
+}
+
+fn main() {}
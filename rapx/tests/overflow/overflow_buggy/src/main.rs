@@ -0,0 +1,29 @@
+#![allow(dead_code)]
+
+fn add(x: u8) -> u8 {
+    let a: u8 = if x > 100 { 200 } else { 10 };
+    a + 100
+}
+
+fn diff(x: u8, y: u8) -> u8 {
+    let a: u8 = if x > 10 { 30 } else { 5 };
+    let b: u8 = if y > 10 { 20 } else { 1 };
+    a - b
+}
+
+fn shrink(n: i32) -> u8 {
+    let v = if n > 0 { 300 } else { 5 };
+    v as u8
+}
+
+fn div(x: u32, flag: bool) -> u32 {
+    let d: u32 = if flag { 0 } else { 4 };
+    x / d
+}
+
+fn shift(x: u32, flag: bool) -> u32 {
+    let s: u32 = if flag { 40 } else { 3 };
+    x << s
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+/*
+ * This is a buggy case: a panic in the caller-provided code exposes the broken invariant
+ */
+use std::ptr;
+
+// If `f` panics, `old` is dropped during unwinding while `dest` still owns it.
+pub fn replace_with<T, F: FnOnce(T) -> T>(dest: &mut T, f: F) {
+    unsafe {
+        let old = ptr::read(dest);
+        let new = f(old);
+        ptr::write(dest, new);
+    }
+}
+
+// If `clone` panics, the uninitialized elements are dropped with the vector.
+pub fn extend_cloned<T: Clone>(v: &mut Vec<T>, item: &T, n: usize) {
+    v.reserve(n);
+    let len = v.len();
+    unsafe {
+        v.set_len(len + n);
+        for i in 0..n {
+            ptr::write(v.as_mut_ptr().add(len + i), item.clone());
+        }
+    }
+}
@@ -0,0 +1,45 @@
+/*
+ * This is a correct case: the invariants are restored before calling the caller-provided code
+ */
+use std::ptr;
+
+// The length is only updated after all elements are initialized.
+pub fn extend_cloned<T: Clone>(v: &mut Vec<T>, item: &T, n: usize) {
+    v.reserve(n);
+    let len = v.len();
+    unsafe {
+        for i in 0..n {
+            ptr::write(v.as_mut_ptr().add(len + i), item.clone());
+        }
+        v.set_len(len + n);
+    }
+}
+
+fn double(v: Vec<u8>) -> Vec<u8> {
+    v.iter().chain(v.iter()).copied().collect()
+}
+
+// The function called after `ptr::read` is not provided by the caller.
+pub fn double_in_place(dest: &mut Vec<u8>) {
+    unsafe {
+        let old = ptr::read(dest);
+        ptr::write(dest, double(old));
+    }
+}
+
+// If `f` panics, the elements are leaked instead of being dropped twice.
+pub fn clear_then<T, F: FnOnce()>(v: &mut Vec<T>, f: F) {
+    unsafe {
+        v.set_len(0);
+    }
+    f();
+}
+
+// The duplicated value is forgotten before the callback.
+pub fn size_then<T, F: FnOnce()>(src: &T, f: F) -> usize {
+    let copy = unsafe { ptr::read(src) };
+    let size = std::mem::size_of_val(&copy);
+    std::mem::forget(copy);
+    f();
+    size
+}
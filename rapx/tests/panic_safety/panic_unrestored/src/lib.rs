@@ -0,0 +1,24 @@
+/*
+ * This is a buggy case: the invariants are restored on other places before calling the caller-provided code
+ */
+use std::ptr;
+
+// Writing into `other` does not restore `dest`, which still owns `old` if `f` panics.
+pub fn replace_other<T, F: FnOnce()>(dest: &mut T, other: &mut T, value: T, f: F) {
+    unsafe {
+        let old = ptr::read(dest);
+        ptr::write(other, value);
+        f();
+        ptr::write(dest, old);
+    }
+}
+
+// Forgetting another value does not forget the duplicated one.
+pub fn forget_other<T, F: FnOnce()>(src: &T, other: T, f: F) -> usize {
+    let copy = unsafe { ptr::read(src) };
+    let size = std::mem::size_of_val(&copy);
+    std::mem::forget(other);
+    f();
+    std::mem::forget(copy);
+    size
+}
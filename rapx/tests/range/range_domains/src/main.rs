@@ -0,0 +1,31 @@
+fn main() {
+    big_unsigned(10_000_000_000_000_000_000);
+    wide_signed(-100_000_000_000_000_000_000_000_000);
+    wide_unsigned(200_000_000_000_000_000_000_000_000_000);
+    float(0.5);
+    spread(1e308);
+}
+// The range of `k` exceeds `i64::MAX` and is only representable in the `u64` domain.
+fn big_unsigned(mut k: u64) -> u64 {
+    while k < 10_000_000_000_000_000_010 {
+        k += 1;
+    }
+    k
+}
+// The ranges of 128-bit integers are computed in their own domains.
+fn wide_signed(a: i128) -> i128 {
+    a - 1
+}
+fn wide_unsigned(b: u128) -> u128 {
+    b + 1
+}
+// Floating-point ranges are computed in the float domain.
+fn float(x: f64) -> f64 {
+    let y = x + 1.5;
+    y * 2.0
+}
+// `y` is infinite, and `y - y` may be NaN.
+fn spread(x: f64) -> f64 {
+    let y = x * 10.0;
+    y - y
+}
@@ -0,0 +1,14 @@
+fn main() {
+    let v = vec![1, 2, 3];
+    sum(&v);
+}
+// The loop condition relates `i` to the length of `v`, which proves the bounds check of `v[i]`.
+fn sum(v: &[i32]) -> i32 {
+    let mut i = 0;
+    let mut s = 0;
+    while i < v.len() {
+        s += v[i];
+        i += 1;
+    }
+    s
+}
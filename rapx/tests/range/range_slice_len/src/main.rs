@@ -0,0 +1,8 @@
+fn main() {
+    let args: Vec<u8> = std::env::args().map(|arg| arg.len() as u8).collect();
+    let slice = args.as_slice();
+    let len = slice.len();
+    if len > 0 {
+        let _ = slice[len - 1];
+    }
+}
@@ -0,0 +1,72 @@
+#![feature(register_tool)]
+#![register_tool(rapx)]
+
+#[rapx::inner(property = ValidPtr(ptr, u32, 1), kind = "precond")]
+#[rapx::inner(property = Align(ptr, u32), kind = "precond")]
+unsafe fn load(ptr: *const u32) -> u32 {
+    unsafe { ptr.read() }
+}
+
+#[rapx::inner(property = ValidNum(idx < 4), kind = "precond")]
+unsafe fn get(buf: &[u8; 4], idx: usize) -> u8 {
+    unsafe { *buf.as_ptr().add(idx) }
+}
+
+#[rapx::inner(property = InBound(ptr, u8, len), kind = "precond")]
+unsafe fn sum(ptr: *const u8, len: usize) -> u32 {
+    let mut total = 0;
+    for i in 0..len {
+        total += unsafe { *ptr.add(i) } as u32;
+    }
+    total
+}
+
+#[rapx::inner(property = ValidNum(offset < limit), kind = "precond")]
+unsafe fn shift(offset: isize, limit: isize) -> isize {
+    offset
+}
+
+fn load_local() -> u32 {
+    let x = 7u32;
+    unsafe { load(&x) }
+}
+
+fn load_raw(addr: usize) -> u32 {
+    unsafe { load(addr as *const u32) }
+}
+
+fn get_checked(buf: &[u8; 4], idx: usize) -> u8 {
+    if idx < 4 { unsafe { get(buf, idx) } } else { 0 }
+}
+
+fn get_unchecked(buf: &[u8; 4], idx: usize) -> u8 {
+    unsafe { get(buf, idx) }
+}
+
+fn sum_slice(v: &[u8]) -> u32 {
+    unsafe { sum(v.as_ptr(), v.len()) }
+}
+
+fn sum_len(v: &[u8], len: usize) -> u32 {
+    unsafe { sum(v.as_ptr(), len) }
+}
+
+// `offset` is negative, which is below `limit` as a signed integer only.
+fn shift_back() -> isize {
+    let offset = -1;
+    let limit = 4;
+    unsafe { shift(offset, limit) }
+}
+
+fn main() {
+    let buf = [1u8, 2, 3, 4];
+    let v = vec![1u8, 2, 3];
+    let n = std::env::args().count();
+    println!("{}", load_local());
+    println!("{}", load_raw(n));
+    println!("{}", get_checked(&buf, n));
+    println!("{}", get_unchecked(&buf, n));
+    println!("{}", sum_slice(&v));
+    println!("{}", sum_len(&v, n));
+    println!("{}", shift_back());
+}
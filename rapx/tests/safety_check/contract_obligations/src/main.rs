@@ -0,0 +1,88 @@
+#![feature(register_tool)]
+#![register_tool(rapx)]
+
+#[rapx::invariant(property = ValidNum(self.len <= self.cap))]
+pub struct Counter {
+    len: usize,
+    cap: usize,
+}
+
+impl Counter {
+    pub fn new(cap: usize) -> Self {
+        Counter { len: 0, cap }
+    }
+
+    pub fn with_len(len: usize, cap: usize) -> Self {
+        Counter { len, cap }
+    }
+
+    pub fn push(&mut self) {
+        if self.len < self.cap {
+            self.len += 1;
+        }
+    }
+
+    pub fn push_unchecked(&mut self) {
+        self.len += 1;
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cap - self.len
+    }
+}
+
+#[rapx::invariant(property = ValidNum(self.pos < 4))]
+pub struct Ring {
+    buf: [u8; 4],
+    pos: usize,
+}
+
+impl Ring {
+    pub fn new() -> Self {
+        Ring { buf: [0; 4], pos: 0 }
+    }
+
+    pub fn advance(&mut self) {
+        self.pos = (self.pos + 1) % 4;
+    }
+
+    pub fn skip(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    pub fn peek(&self) -> u8 {
+        unsafe { read_slot(&self.buf, self.pos) }
+    }
+
+    pub fn peek_next(&self) -> u8 {
+        unsafe { read_slot(&self.buf, self.pos + 1) }
+    }
+}
+
+#[rapx::inner(property = ValidNum(idx < 4), kind = "precond")]
+unsafe fn read_slot(buf: &[u8; 4], idx: usize) -> u8 {
+    buf[idx & 3]
+}
+
+#[rapx::inner(property = ValidNum(return <= 3), kind = "postcond")]
+fn clamp(x: usize) -> usize {
+    if x > 3 { 3 } else { x }
+}
+
+#[rapx::inner(property = ValidNum(return <= 3), kind = "postcond")]
+fn identity(x: usize) -> usize {
+    x
+}
+
+fn main() {
+    let n = std::env::args().count();
+    let mut counter = Counter::new(clamp(n));
+    counter.push();
+    counter.push_unchecked();
+    let _ = Counter::with_len(identity(n), n).remaining();
+    let mut ring = Ring::new();
+    ring.advance();
+    ring.skip(n);
+    ring.peek();
+    ring.peek_next();
+}
@@ -0,0 +1,96 @@
+use std::ffi::{CStr, CString, c_char};
+
+/// Pass: the bytes come from a `&str`, so they are valid UTF-8.
+fn string_from_str(s: &str) -> &str {
+    unsafe { std::str::from_utf8_unchecked(s.as_bytes()) }
+}
+
+/// Fail: arbitrary bytes may not be valid UTF-8.
+fn string_from_bytes(bytes: &[u8]) -> &str {
+    unsafe { std::str::from_utf8_unchecked(bytes) }
+}
+
+/// Pass: the pointer comes from a `CString`, so it is NUL-terminated.
+fn cstr_from_cstring() -> usize {
+    let owned = CString::new("rapx").unwrap();
+    let ptr = owned.as_ptr();
+    unsafe { CStr::from_ptr(ptr) }.count_bytes()
+}
+
+/// Fail: nothing is known about the pointer.
+fn cstr_from_raw(ptr: *const c_char) -> usize {
+    unsafe { CStr::from_ptr(ptr) }.count_bytes()
+}
+
+/// Pass: the index is checked against the length of the slice.
+fn read_checked(data: &[u32], index: usize) -> u32 {
+    if index < data.len() {
+        unsafe { data.as_ptr().add(index).read() }
+    } else {
+        0
+    }
+}
+
+/// Fail: the index may be out of the slice.
+fn read_unchecked(data: &[u32], index: usize) -> u32 {
+    unsafe { data.as_ptr().add(index).read() }
+}
+
+/// Pass: the pointer is borrowed from a local.
+fn read_local() -> u32 {
+    let value = 7u32;
+    let ptr = &value as *const u32;
+    unsafe { ptr.read() }
+}
+
+/// Fail: the pointer is not known to be allocated.
+fn read_raw(ptr: *const u32) -> u32 {
+    unsafe { ptr.read() }
+}
+
+/// Pass: the offset is bounded by the branch.
+fn offset_small(data: &[u8], count: usize) -> *const u8 {
+    if count < 16 && count < data.len() {
+        unsafe { data.as_ptr().add(count) }
+    } else {
+        data.as_ptr()
+    }
+}
+
+/// Pass: the only reference to the local is created from the pointer.
+fn as_mut_unique() -> u32 {
+    let mut value = 1u32;
+    let ptr = &raw mut value;
+    if let Some(reference) = unsafe { ptr.as_mut() } {
+        *reference += 1;
+    }
+    value
+}
+
+/// Fail: `first` still borrows the local mutably.
+fn as_mut_aliased() -> u32 {
+    let mut value = 1u32;
+    let first = &mut value;
+    let ptr = &raw mut *first;
+    if let Some(second) = unsafe { ptr.as_mut() } {
+        *second += 1;
+    }
+    *first += 1;
+    value
+}
+
+fn main() {
+    let text = "rapx";
+    let data = [1u32, 2, 3];
+    string_from_str(text);
+    string_from_bytes(text.as_bytes());
+    cstr_from_cstring();
+    cstr_from_raw(c"rapx".as_ptr());
+    read_checked(&data, 1);
+    read_unchecked(&data, 1);
+    read_local();
+    read_raw(&data[0]);
+    offset_small(text.as_bytes(), 2);
+    as_mut_unique();
+    as_mut_aliased();
+}
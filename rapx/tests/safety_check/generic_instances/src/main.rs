@@ -0,0 +1,15 @@
+/// Fail: `T` is instantiated with `u32`, whose alignment the bytes may not have.
+fn read_wide<T>(bytes: &[u8]) -> T {
+    unsafe { (bytes.as_ptr() as *const T).read() }
+}
+
+/// Pass: `T` is only instantiated with `u8`, so the pointer into the bytes is aligned.
+fn read_narrow<T>(bytes: &[u8]) -> T {
+    unsafe { (bytes.as_ptr() as *const T).read() }
+}
+
+fn main() {
+    let bytes = [1u8, 2, 3, 4];
+    let _: u32 = read_wide::<u32>(&bytes);
+    let _: u8 = read_narrow::<u8>(&bytes);
+}
@@ -0,0 +1,50 @@
+#![feature(register_tool)]
+#![register_tool(rapx)]
+
+pub unsafe fn read_at(ptr: *const u32, idx: usize) -> u32 {
+    unsafe { ptr.add(idx).read() }
+}
+
+pub unsafe fn load(p: *const u64) -> u64 {
+    unsafe { p.read() }
+}
+
+pub unsafe fn load_twice(q: *const u64) -> u64 {
+    unsafe { load(q).wrapping_add(load(q)) }
+}
+
+#[rapx::inner(property = ValidPtr(p, u64, 1), kind = "precond")]
+pub unsafe fn load_annotated(p: *const u64) -> u64 {
+    unsafe { p.read() }
+}
+
+pub unsafe fn load_local() -> u64 {
+    let x = 7u64;
+    unsafe { load(&x) }
+}
+
+#[rapx::inner(property = Init(ptr, u32, len), kind = "precond")]
+pub unsafe fn sum_init(ptr: *const u32, len: usize) -> u32 {
+    let mut total = 0;
+    for i in 0..len {
+        total += unsafe { ptr.add(i).read() };
+    }
+    total
+}
+
+pub unsafe fn sum_prefix(p: *const u32, n: usize) -> u32 {
+    unsafe { sum_init(p, n) }
+}
+
+fn main() {
+    let buf = [1u32, 2, 3, 4];
+    let x = 5u64;
+    let n = std::env::args().count();
+    unsafe {
+        println!("{}", read_at(buf.as_ptr(), n));
+        println!("{}", load_twice(&x));
+        println!("{}", load_annotated(&x));
+        println!("{}", load_local());
+        println!("{}", sum_prefix(buf.as_ptr(), n));
+    }
+}
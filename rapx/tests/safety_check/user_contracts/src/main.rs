@@ -0,0 +1,42 @@
+use std::ffi::{CString, c_char};
+
+unsafe extern "C" {
+    fn strlen(s: *const c_char) -> usize;
+}
+
+mod raw {
+    /// Read the byte at `idx` of a four-byte buffer.
+    pub unsafe fn get(ptr: *const u8, idx: usize) -> u8 {
+        unsafe { *ptr.add(idx) }
+    }
+}
+
+fn strlen_cstring() -> usize {
+    let s = CString::new("rapx").unwrap();
+    unsafe { strlen(s.as_ptr()) }
+}
+
+fn strlen_raw(p: *const c_char) -> usize {
+    unsafe { strlen(p) }
+}
+
+fn get_checked(buf: &[u8; 4], idx: usize) -> u8 {
+    if idx < 4 {
+        unsafe { raw::get(buf.as_ptr(), idx) }
+    } else {
+        0
+    }
+}
+
+fn get_unchecked(buf: &[u8; 4], idx: usize) -> u8 {
+    unsafe { raw::get(buf.as_ptr(), idx) }
+}
+
+fn main() {
+    let buf = [1u8, 2, 3, 4];
+    let n = std::env::args().count();
+    println!("{}", strlen_cstring());
+    println!("{}", strlen_raw(buf.as_ptr() as *const c_char));
+    println!("{}", get_checked(&buf, n));
+    println!("{}", get_unchecked(&buf, n));
+}
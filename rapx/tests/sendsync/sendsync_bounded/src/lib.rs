@@ -0,0 +1,84 @@
+/*
+ * This is a correct case: the unsafe Send/Sync impls have the required bounds
+ */
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+
+pub trait Job: Send {}
+
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+impl<T> Wrapper<T> {
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+unsafe impl<T: Send> Send for Wrapper<T> {}
+
+// `T: Send` is implied by the supertrait of `Job`.
+pub struct Queue<J> {
+    jobs: Vec<J>,
+}
+
+impl<J: Job> Queue<J> {
+    pub fn push(&mut self, job: J) {
+        self.jobs.push(job);
+    }
+}
+
+unsafe impl<J: Job> Send for Queue<J> {}
+
+pub struct Slot<T> {
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<T> Slot<T> {
+    pub fn put(&self, value: T) {
+        unsafe { *self.value.get() = Some(value) };
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        unsafe { (*self.value.get()).as_ref() }
+    }
+}
+
+unsafe impl<T> Sync for Slot<T> where T: Send + Sync {}
+
+// `T` is neither moved nor shared through `&Counter<T>`, so no bound is required.
+pub struct Counter<T> {
+    items: Vec<T>,
+    count: UnsafeCell<usize>,
+}
+
+impl<T> Counter<T> {
+    pub fn hit(&self) -> usize {
+        unsafe {
+            *self.count.get() += 1;
+            *self.count.get()
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+}
+
+unsafe impl<T> Sync for Counter<T> {}
+
+// `T` is only a marker of the token type.
+pub struct Token<T> {
+    id: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Token<T> {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+unsafe impl<T> Send for Token<T> {}
+unsafe impl<T> Sync for Token<T> {}
@@ -0,0 +1,59 @@
+/*
+ * This is a buggy case: the unsafe Send/Sync impls lack bounds on the generic parameters
+ */
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+
+// `Wrapper<Rc<T>>` could be sent to another thread although `Rc` is not `Send`.
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+impl<T> Wrapper<T> {
+    pub fn new(inner: T) -> Self {
+        Wrapper { inner }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+unsafe impl<T> Send for Wrapper<T> {}
+
+// Threads sharing `&Slot<T>` can both move `T` in and out and read `&T`.
+pub struct Slot<T> {
+    value: UnsafeCell<Option<T>>,
+}
+
+impl<T> Slot<T> {
+    pub fn new() -> Self {
+        Slot {
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    pub fn put(&self, value: T) {
+        unsafe { *self.value.get() = Some(value) };
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        unsafe { (*self.value.get()).as_ref() }
+    }
+}
+
+unsafe impl<T> Sync for Slot<T> {}
+
+// Sending `RawRef<T>` shares `&T` with another thread.
+pub struct RawRef<T> {
+    ptr: *const T,
+    _marker: PhantomData<*const T>,
+}
+
+impl<T> RawRef<T> {
+    pub fn get(&self) -> &T {
+        unsafe { &*self.ptr }
+    }
+}
+
+unsafe impl<T> Send for RawRef<T> {}
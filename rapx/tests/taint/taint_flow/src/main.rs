@@ -0,0 +1,27 @@
+use std::env;
+use std::process::Command;
+
+fn read_input() -> String {
+    env::var("INPUT").unwrap_or_default()
+}
+
+fn escape(input: &str) -> String {
+    input.replace('\'', "")
+}
+
+fn run(arg: &str) {
+    let _ = Command::new("sh").arg(arg).status();
+}
+
+fn main() {
+    // Tainted data flows directly into the sink.
+    let direct = env::var("DIRECT").unwrap();
+    let _ = Command::new("echo").arg(&direct).status();
+
+    // Tainted data flows through a helper into the sink of another function.
+    let input = read_input();
+    run(input.trim());
+
+    // The sanitizer cleans the tainted data.
+    run(&escape(&read_input()));
+}
@@ -0,0 +1,26 @@
+use std::env;
+use std::process::Command;
+
+fn read_input() -> String {
+    env::var("INPUT").unwrap_or_default()
+}
+
+fn append(out: &mut String, part: &str) {
+    out.push_str(part);
+}
+
+fn run(arg: &str) {
+    let _ = Command::new("sh").arg(arg).status();
+}
+
+fn main() {
+    // Tainted data flows into `script` through a helper writing its first argument.
+    let mut script = String::from("echo ");
+    append(&mut script, &read_input());
+    run(&script);
+
+    // Nothing tainted is written into `safe`.
+    let mut safe = String::from("echo ");
+    append(&mut safe, "done");
+    run(&safe);
+}
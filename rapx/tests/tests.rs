@@ -20,12 +20,17 @@ pub fn detected_high_confidence(output: &str) -> bool {
 
 #[inline(always)]
 fn running_tests_with_arg(dir: &str, arg: &str) -> String {
+    running_tests_with_args(dir, &[arg])
+}
+
+#[inline(always)]
+fn running_tests_with_args(dir: &str, args: &[&str]) -> String {
     let raw_path = "./tests/".to_owned() + dir;
     let project_path = Path::new(&raw_path);
 
     let output = Command::new("cargo")
         .arg("rapx")
-        .arg(arg)
+        .args(args)
         .current_dir(project_path)
         .output()
         .expect("Failed to execute cargo rapx");
@@ -58,6 +63,80 @@ fn test_df_unwinding() {
     assert_eq!(output.contains("Double free detected"), true);
 }
 
+#[test]
+fn test_df_min_sarif() {
+    // The SARIF logs of several crates can only be merged into a file.
+    let output = running_tests_with_args("uaf/df_min_sarif", &["-F", "-format=sarif"]);
+    assert!(output.contains("`-format=sarif` requires `-output=<file>`"));
+
+    let sarif = std::env::temp_dir().join("rapx_df_min.sarif");
+    let output_arg = format!("-output={}", sarif.display());
+    running_tests_with_args("uaf/df_min_sarif", &["-F", "-format=sarif", &output_arg]);
+    let log: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&sarif).unwrap()).unwrap();
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert!(
+        results
+            .iter()
+            .any(|r| r["ruleId"] == "rapx.double-free" && r["properties"]["confidence"].is_u64())
+    );
+}
+
+#[test]
+fn test_df_min_text_output() {
+    // Text reports cannot be written into the output file.
+    let text = std::env::temp_dir().join("rapx_df_min.txt");
+    let _ = std::fs::remove_file(&text);
+    let output_arg = format!("-output={}", text.display());
+    let output = running_tests_with_args("uaf/df_min_text", &["-F", &output_arg]);
+    assert_eq!(
+        output.contains("requires `-format=sarif` or `-format=json`"),
+        true
+    );
+    assert_eq!(text.exists(), false);
+}
+
+#[test]
+fn test_df_min_json() {
+    let json = std::env::temp_dir().join("rapx_df_min.jsonl");
+    let output_arg = format!("-output={}", json.display());
+    running_tests_with_args("uaf/df_min_json", &["-F", "-format=json", &output_arg]);
+    let findings: Vec<serde_json::Value> = std::fs::read_to_string(&json)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(findings.iter().any(|f| f["kind"] == "double_free"
+        && f["function"] == "main"
+        && f["severity"] == "error"));
+}
+
+#[test]
+fn test_df_min_baseline() {
+    let baseline = std::env::temp_dir().join("rapx_df_min_baseline.json");
+    let write_arg = format!("-baseline-write={}", baseline.display());
+    let output = running_tests_with_args("uaf/df_min_baseline", &["-F", &write_arg]);
+    assert_eq!(
+        output.contains("Double free detected in function \"main\""),
+        true
+    );
+    let read_arg = format!("-baseline={}", baseline.display());
+    let output = running_tests_with_args("uaf/df_min_baseline", &["-F", &read_arg]);
+    assert_eq!(output.contains("Double free detected"), false);
+    assert_eq!(output.contains("suppressed by the baseline"), true);
+}
+
+#[test]
+fn test_df_allow() {
+    let output = running_tests_with_arg("uaf/df_allow", "-F");
+    assert_eq!(
+        output.contains("Double free detected in function \"reported\""),
+        true
+    );
+    assert_eq!(output.contains("in function \"double_free\""), false);
+    assert_eq!(output.contains("in function \"suppressed\""), false);
+}
+
 #[test]
 fn test_dp_lengthy() {
     let output = running_tests_with_arg("uaf/dp_lengthy", "-F");
@@ -121,6 +200,44 @@ fn test_uaf_swithint() {
     );
 }
 
+#[test]
+fn test_uaf_budget() {
+    let output = running_tests_with_arg("uaf/uaf_budget", "-F");
+    assert_eq!(
+        output.contains("Analysis incomplete: safedrop of function explode")
+            && output.contains("Use-after-free detected in function \"explode\""),
+        true
+    );
+}
+
+#[test]
+fn test_uaf_budget_raised() {
+    let output = running_tests_with_args("uaf/uaf_budget", &["-F", "-fn-visits=100000"]);
+    assert_eq!(
+        !output.contains("Analysis incomplete")
+            && output.contains("Use-after-free detected in function \"explode\""),
+        true
+    );
+}
+
+#[test]
+fn test_uaf_async() {
+    let output = running_tests_with_arg("uaf/uaf_async", "-F");
+    assert_eq!(
+        output.contains("Use-after-free detected in function \"run\""),
+        true
+    );
+}
+
+#[test]
+fn test_dp_async() {
+    let output = running_tests_with_arg("uaf/dp_async", "-F");
+    assert_eq!(
+        output.contains("Dangling pointer detected in function \"publish\""),
+        true
+    );
+}
+
 #[test]
 fn test_false_wrapper() {
     let output = running_tests_with_arg("uaf/false_wrapper", "-F");
@@ -180,6 +297,12 @@ fn test_false_memtake() {
     assert_eq!(output.contains("detected"), false);
 }
 
+#[test]
+fn test_false_async() {
+    let output = running_tests_with_arg("uaf/false_async", "-F");
+    assert_eq!(output.contains("detected"), false);
+}
+
 #[test]
 fn test_reference() {
     #[allow(unused)]
@@ -258,6 +381,14 @@ fn test_alias_indirect() {
     assert_eq!(output.contains("iter_prop\": (0.0,1.0)"), true);
 }
 
+#[test]
+fn test_alias_dep_summaries() {
+    let output = running_tests_with_args("alias/alias_dep", &["-alias", "-summarize-deps"]);
+    assert_eq!(output.contains("wrapper\": (0,1), (0,2), (1,2)"), true);
+    // The result of `pick_first` does not depend on its second argument.
+    assert_eq!(output.contains("first\": (0,1)\n"), true);
+}
+
 // ===============Alias(MFP) Analysis Test==============
 #[test]
 fn test_alias_mfp_from_raw_parts_in() {
@@ -418,6 +549,297 @@ fn test_heap_proxy() {
     );
 }
 
+#[test]
+fn test_sendsync_missing() {
+    let output = running_tests_with_arg("sendsync/sendsync_missing", "-sendsync");
+    let expected = vec![
+        "Unsafe impl Send for Wrapper<T> lacks bounds: T: Send",
+        "Unsafe impl Sync for Slot<T> lacks bounds: T: Send + Sync",
+        "Unsafe impl Send for RawRef<T> lacks bounds: T: Sync",
+    ];
+    for expected in expected {
+        assert!(
+            output.contains(expected),
+            "Missing '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_sendsync_bounded() {
+    let output = running_tests_with_arg("sendsync/sendsync_bounded", "-sendsync");
+    assert_eq!(output.contains("lacks bounds"), false);
+}
+
+#[test]
+fn test_panic_safety_buggy() {
+    let output = running_tests_with_arg("panic_safety/panic_buggy", "-panic-safety");
+    assert_eq!(
+        output.contains("Panic safety issue detected in function \"replace_with\"")
+            && output.contains("Panic safety issue detected in function \"extend_cloned\""),
+        true
+    );
+}
+
+#[test]
+fn test_panic_safety_guarded() {
+    let output = running_tests_with_arg("panic_safety/panic_guarded", "-panic-safety");
+    assert_eq!(output.contains("Panic safety issue detected"), false);
+}
+
+#[test]
+fn test_panic_safety_unrestored() {
+    let output = running_tests_with_arg("panic_safety/panic_unrestored", "-panic-safety");
+    assert_eq!(
+        output.contains("Panic safety issue detected in function \"replace_other\"")
+            && output.contains("Panic safety issue detected in function \"forget_other\""),
+        true
+    );
+}
+
+#[test]
+fn test_uninit_buggy() {
+    let output = running_tests_with_arg("uninit/uninit_buggy", "-uninit");
+    assert_eq!(
+        output.contains("Uninitialized memory exposure detected in function \"read_buffer\"")
+            && output
+                .contains("Uninitialized memory exposure detected in function \"partial_array\"")
+            && output.contains("Uninitialized memory exposure detected in function \"read_alloc\"")
+            && output
+                .contains("Uninitialized memory exposure detected in function \"zeroed_string\""),
+        true
+    );
+}
+
+#[test]
+fn test_uninit_safe() {
+    let output = running_tests_with_arg("uninit/uninit_safe", "-uninit");
+    assert_eq!(
+        output.contains("Uninitialized memory exposure detected"),
+        false
+    );
+}
+
+#[test]
+fn test_taint_flow() {
+    let output = running_tests_with_arg("taint/taint_flow", "-taint=taint.toml");
+    assert_eq!(
+        output.contains("Tainted data flow detected in function \"main\""),
+        true
+    );
+    assert_eq!(
+        output
+            .matches("Tainted data from `std::env::var` flows into `std::process::Command::arg`.")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_taint_flow_json() {
+    let json = std::env::temp_dir().join("rapx_taint_flow.jsonl");
+    let _ = std::fs::remove_file(&json);
+    let output_arg = format!("-output={}", json.display());
+    running_tests_with_args(
+        "taint/taint_flow",
+        &["-taint=taint.toml", "-format=json", &output_arg],
+    );
+    let findings: Vec<serde_json::Value> = std::fs::read_to_string(&json)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // The flow through `escape` is sanitized.
+    assert_eq!(findings.len(), 2);
+    let steps = |finding: &serde_json::Value| -> Vec<String> {
+        finding["secondary"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|label| label["message"].as_str().unwrap().to_owned())
+            .collect()
+    };
+    assert!(findings.iter().all(|f| f["kind"] == "taint_flow"
+        && f["function"] == "main"
+        && f["severity"] == "warning"));
+    assert!(findings.iter().any(|f| steps(f)
+        == [
+            "tainted by `std::env::var`",
+            "passed through `std::result::Result::<T, E>::unwrap`"
+        ]));
+    assert!(findings.iter().any(|f| {
+        let steps = steps(f);
+        steps.first().map(String::as_str) == Some("tainted by `std::env::var`")
+            && steps.contains(&"returned from `read_input`".to_owned())
+            && steps.contains(&"passed to `run` as argument 0".to_owned())
+            && f["location"]["start_line"] == 13
+    }));
+}
+
+#[test]
+fn test_taint_flow_summary() {
+    let json = std::env::temp_dir().join("rapx_taint_summary.jsonl");
+    let _ = std::fs::remove_file(&json);
+    let output_arg = format!("-output={}", json.display());
+    running_tests_with_args(
+        "taint/taint_summary",
+        &["-taint=taint.toml", "-format=json", &output_arg],
+    );
+    let findings: Vec<serde_json::Value> = std::fs::read_to_string(&json)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // Only `script` is tainted, by `append` writing its second argument into the first one.
+    assert_eq!(findings.len(), 1);
+    let steps: Vec<&str> = findings[0]["secondary"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|label| label["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(steps.contains(&"written by `append`"), true);
+    assert_eq!(steps.last(), Some(&"passed to `run` as argument 0"));
+}
+
+#[test]
+fn test_dataflow_summary() {
+    let output = running_tests_with_arg("dataflow/dataflow_summary", "-dataflow=debug");
+    let _ = std::fs::remove_dir_all("./tests/dataflow/dataflow_summary/DataflowGraph");
+    for (name, expected) in [
+        ("append", "(*_2) ---> (*_1)"),
+        ("{impl#0}::add", "_2 ---> (*_1).0"),
+        ("record", "_1 ---> static LAST"),
+    ] {
+        let start = output
+            .find(&format!("Function: \"{}\"", name))
+            .unwrap_or_else(|| panic!("Missing function {}\nFull output:\n{}", name, output));
+        let graph = &output[start..];
+        let end = graph[1..]
+            .find("Function: ")
+            .map_or(graph.len(), |end| end + 1);
+        assert!(
+            graph[..end].contains(&format!("Summary edges: \n{}\n", expected)),
+            "Missing summary edge '{}' of {}\nFull output:\n{}",
+            expected,
+            name,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_overflow_buggy() {
+    let json = std::env::temp_dir().join("rapx_overflow_buggy.jsonl");
+    let _ = std::fs::remove_file(&json);
+    let output_arg = format!("-output={}", json.display());
+    running_tests_with_args(
+        "overflow/overflow_buggy",
+        &["-overflow", "-format=json", &output_arg],
+    );
+    let findings: Vec<serde_json::Value> = std::fs::read_to_string(&json)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<(&str, &str)> = findings
+        .iter()
+        .map(|f| (f["function"].as_str().unwrap(), f["kind"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("add", "arithmetic_overflow"),
+            ("diff", "arithmetic_overflow"),
+            ("div", "division_by_zero"),
+            ("shift", "shift_overflow"),
+            ("shrink", "truncating_cast"),
+        ]
+    );
+    let message = |function: &str| {
+        findings.iter().find(|f| f["function"] == function).unwrap()["message"]
+            .as_str()
+            .unwrap()
+            .to_owned()
+    };
+    assert!(message("add").contains("[10, 200] + [100, 100] gives [110, 300], outside [0, 255]"));
+    assert!(message("diff").contains("[5, 30] - [1, 20] gives [-15, 29]"));
+    assert!(message("div").contains("it is in [0, 4]"));
+    assert!(message("shift").contains("the amount is in [3, 40], outside [0, 31]"));
+    assert!(message("shrink").contains("the value is in [5, 300], outside [0, 255]"));
+}
+
+#[test]
+fn test_overflow_safe() {
+    let output = running_tests_with_arg("overflow/overflow_safe", "-overflow");
+    assert_eq!(output.contains("Integer arithmetic issue detected"), false);
+}
+
+#[test]
+fn test_oob_buggy() {
+    let json = std::env::temp_dir().join("rapx_oob_buggy.jsonl");
+    let _ = std::fs::remove_file(&json);
+    let output_arg = format!("-output={}", json.display());
+    running_tests_with_args("oob/oob_buggy", &["-oob", "-format=json", &output_arg]);
+    let findings: Vec<serde_json::Value> = std::fs::read_to_string(&json)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let functions: Vec<&str> = findings
+        .iter()
+        .filter(|f| f["kind"] == "out_of_bounds" && f["severity"] == "error")
+        .map(|f| f["function"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        functions,
+        [
+            "arr",
+            "at_len",
+            "past_len",
+            "ptr_at_len",
+            "ptr_past_len",
+            "unchecked_at_len",
+            "vec_at_len",
+        ]
+    );
+    let message = |function: &str| {
+        findings.iter().find(|f| f["function"] == function).unwrap()["message"]
+            .as_str()
+            .unwrap()
+            .to_owned()
+    };
+    assert!(message("arr").contains("the index is in [5, 6] but the length is 4"));
+    assert!(message("past_len").contains("the index is `v.len() + 1`"));
+    assert!(message("ptr_past_len").contains("greater than the length `v.len()`"));
+}
+
+#[test]
+fn test_oob_unchecked() {
+    let output = running_tests_with_arg("oob/oob_unchecked", "-oob");
+    assert_eq!(output.contains("Out-of-bounds access"), false);
+    assert_eq!(
+        output.contains("the index is in [1, 3] and the length is 4"),
+        true
+    );
+    assert_eq!(
+        output.contains("the index `i` is less than `v.len()` on every path"),
+        true
+    );
+    assert_eq!(
+        output.contains("the index `j` is less than `v.len()` wherever both are defined"),
+        true
+    );
+}
+
+#[test]
+fn test_oob_safe() {
+    let output = running_tests_with_arg("oob/oob_safe", "-oob");
+    assert_eq!(output.contains("Bounds issue detected"), false);
+}
+
 #[test]
 fn test_upg_safe_caller() {
     let output = running_tests_with_arg("upg/safe_caller", "-upg");
@@ -486,6 +908,88 @@ fn test_interprocedual_range_analysis() {
     }
 }
 
+#[test]
+fn test_range_analysis_domains() {
+    let output = running_tests_with_arg("range/range_domains", "-range");
+
+    let expected_ranges = vec![
+        "_1 => Regular [10000000000000000010, 10000000000000000010] (u64)",
+        "_5 => Regular [10000000000000000000, 10000000000000000010] (u64)",
+        "_2 => Regular [-100000000000000000000000000, -100000000000000000000000000] (i128)",
+        "_2 => Regular [200000000000000000000000000000, 200000000000000000000000000000] (u128)",
+        "_4 => Regular [4.0, 4.0] (float)",
+        "_2 => Regular [inf, inf] (float)",
+        "_5 => Regular [Min, Max] (float, may be NaN)",
+    ];
+
+    for expected in expected_ranges {
+        assert!(
+            output.contains(expected),
+            "Missing expected range: '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_range_analysis_relational() {
+    let output = running_tests_with_arg("range/range_relational", "-range=4");
+
+    let expected_relations = vec![
+        // The copy of `i` in the loop condition and the length in the bounds check.
+        "_7 - _12 => [Min, -1]",
+        // `v.len()` and the length in the bounds check.
+        "_8 - _12 => [0, 0]",
+        // The index and the length in the bounds check.
+        "_11 - _12 => [Min, -1]",
+    ];
+
+    for expected in expected_relations {
+        assert!(
+            output.contains(expected),
+            "Missing expected relation: '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+    // The places introduced by the SSA form are mapped back to the original ones.
+    assert!(!output.contains("_21 - _22"), "Full output:\n{}", output);
+}
+
+#[test]
+fn test_range_analysis_cached() {
+    // The second run restores the ranges of unchanged functions from target/rapx.
+    let cache_dir = Path::new("./tests/range/range_4/target/rapx");
+    let _ = std::fs::remove_dir_all(cache_dir);
+    let first = running_tests_with_arg("range/range_4", "-range");
+    assert_eq!(cache_dir.exists(), true);
+    let second = running_tests_with_arg("range/range_4", "-range");
+
+    let ranges = |output: &str| {
+        let mut lines: Vec<String> = output
+            .lines()
+            .filter(|line| line.contains("=>"))
+            .map(|line| line.to_owned())
+            .collect();
+        lines.sort();
+        lines
+    };
+    assert_eq!(ranges(&first).is_empty(), false);
+    assert_eq!(ranges(&first), ranges(&second));
+    // The restored functions are neither transformed into SSA nor analyzed again.
+    assert_eq!(first.contains("Processing function"), true);
+    assert_eq!(second.contains("Processing function"), false);
+}
+
+#[test]
+fn test_range_analysis_slice_len() {
+    // The length of a slice with unknown bounds must not be derived from its range.
+    let output = running_tests_with_arg("range/range_slice_len", "-range");
+    assert_eq!(output.contains("Regular [-1, -1]"), false);
+    assert_eq!(output.contains("Regular [-2, -2]"), false);
+}
+
 #[test]
 fn test_callgraph_dynamic_dispatch() {
     let output = running_tests_with_arg("callgraph/dynamic", "-callgraph");
@@ -502,6 +1006,143 @@ fn test_callgraph_dynamic_dispatch() {
     }
 }
 
+#[test]
+fn test_callgraph_indirect() {
+    let output = running_tests_with_arg("callgraph/indirect", "-callgraph");
+
+    let expected_calls = vec![
+        "-> double (indirect)",
+        "-> negate (indirect)",
+        "-> main::{closure#0} (indirect)",
+        "-> log_event (indirect)",
+        "-> log_error (indirect)",
+    ];
+
+    for expected in expected_calls {
+        assert!(
+            output.contains(expected),
+            "Missing indirect call '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_callgraph_export_json() {
+    let json = std::env::temp_dir().join("rapx_callgraph_export.json");
+    let output_arg = format!("-callgraph-output={}", json.display());
+    running_tests_with_args("callgraph/export", &["-callgraph=json", &output_arg]);
+    let graph: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    let nodes = graph["nodes"].as_array().unwrap();
+    let node = |name: &str| {
+        nodes
+            .iter()
+            .find(|node| node["name"] == name)
+            .unwrap_or_else(|| panic!("Missing node '{}'", name))
+    };
+    assert_eq!(node("read_raw")["safety"], "unsafe");
+    assert_eq!(node("read_raw")["visibility"], "public");
+    assert_eq!(node("geometry::total")["visibility"], "restricted");
+    assert_eq!(node("helper")["visibility"], "private");
+    assert_eq!(node("helper")["crate"], "export");
+
+    let edge_kind = |caller: &str, callee: &str| {
+        let (from, to) = (&node(caller)["id"], &node(callee)["id"]);
+        graph["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|edge| &edge["from"] == from && &edge["to"] == to)
+            .map(|edge| {
+                assert_eq!(edge["span"]["file"], "src/main.rs");
+                edge["kind"].as_str().unwrap().to_owned()
+            })
+    };
+    assert_eq!(edge_kind("helper", "read_raw").as_deref(), Some("static"));
+    assert_eq!(
+        edge_kind("geometry::total::{closure#0}", "<Square as Shape>::area").as_deref(),
+        Some("virtual")
+    );
+    assert_eq!(
+        edge_kind("main", "<Guard as std::ops::Drop>::drop").as_deref(),
+        Some("drop_glue")
+    );
+    assert_eq!(
+        edge_kind("main", "std::clone::Clone::clone").as_deref(),
+        Some("shim")
+    );
+    assert_eq!(edge_kind("main", "helper").as_deref(), Some("indirect"));
+}
+
+#[test]
+fn test_callgraph_queries() {
+    let output = running_tests_with_args(
+        "callgraph/export",
+        &["-callgraph-reach=main", "-callgraph-callers=Guard::drop"],
+    );
+    let expected = vec![
+        "Functions reachable from main:",
+        "-> helper",
+        "-> read_raw",
+        "Callers of <Guard as std::ops::Drop>::drop:",
+        "<- main (drop_glue) at src/main.rs:46:1",
+    ];
+    for expected in expected {
+        assert!(
+            output.contains(expected),
+            "Missing '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_callgraph_virtual_pruning() {
+    // Whether `chorus` calls the implementor through `dyn Animal`.
+    let virtual_callees = |mode: &str| {
+        let arg = format!("-callgraph-virtual={}", mode);
+        let output = running_tests_with_args("callgraph/rta", &[&arg, "-callgraph-callers=speak"]);
+        ["Dog", "Cat", "Fish"]
+            .into_iter()
+            .filter(|animal| {
+                let header = format!("Callers of <{} as Animal>::speak:", animal);
+                output
+                    .split("Callers of ")
+                    .map(|section| format!("Callers of {}", section))
+                    .find(|section| section.starts_with(&header))
+                    .is_some_and(|section| section.contains("<- chorus (virtual)"))
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(virtual_callees("cha"), vec!["Dog", "Cat", "Fish"]);
+    assert_eq!(virtual_callees("rta"), vec!["Dog", "Cat"]);
+    assert_eq!(virtual_callees("vta"), vec!["Dog"]);
+}
+
+#[test]
+fn test_callgraph_instances() {
+    let output = running_tests_with_arg("callgraph/instances", "-callgraph-instances");
+    for expected in [
+        "    -> store::<u8>",
+        "    -> store::<std::string::String>",
+        "  store::<u8> calls:\n    -> std::vec::Vec::<u8>::push",
+        "  store::<std::string::String> calls:\n    -> std::vec::Vec::<std::string::String>::push",
+        // The vtable method is reached from the coercion to `dyn Shape` in `main`.
+        "    -> <Square as Shape>::area (virtual)",
+        "  total::{closure#0} calls:\n    -> <dyn Shape as Shape>::area",
+    ] {
+        assert!(
+            output.contains(expected),
+            "Missing instance call '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
 #[test]
 fn test_symbolic_interval() {
     let output = running_tests_with_arg("range/range_symbolic", "-range");
@@ -521,3 +1162,225 @@ fn test_symbolic_interval() {
         );
     }
 }
+
+// ================Safety Contract Verification Test=====================
+/// The results of a function span from its header to the header of the next one.
+fn verification_results(output: &str, name: &str) -> String {
+    let header = format!("Begin verification process for: \"{}\"", name);
+    let start = output
+        .find(&header)
+        .unwrap_or_else(|| panic!("Missing '{}'\nFull output:\n{}", header, output));
+    let rest = &output[start + header.len()..];
+    let end = rest
+        .find("Begin verification process for")
+        .unwrap_or(rest.len());
+    rest[..end].to_owned()
+}
+
+#[test]
+fn test_verify_contracts() {
+    let output = running_tests_with_arg("safety_check/contracts", "-verify");
+
+    for (name, expected) in [
+        ("string_from_str", "Passed: [\"ValidString\"]"),
+        ("string_from_bytes", "Failed: [\"ValidString\"]"),
+        ("cstr_from_cstring", "Passed: [\"ValidCStr\"]"),
+        ("cstr_from_raw", "Failed: [\"ValidCStr\"]"),
+        ("read_checked", "Passed: [\"Inbound\", \"ValidNum\"]"),
+        (
+            "read_checked",
+            "Passed: [\"Align\", \"Typed\", \"ValidPtr\"]",
+        ),
+        ("read_unchecked", "Failed: [\"Inbound\"]"),
+        ("read_unchecked", "Failed: [\"ValidPtr\"]"),
+        ("read_local", "Passed: [\"Align\", \"Typed\", \"ValidPtr\"]"),
+        ("read_raw", "Failed: [\"Align\", \"ValidPtr\"]"),
+        ("offset_small", "Passed: [\"Inbound\", \"ValidNum\"]"),
+        ("as_mut_unique", "Passed: [\"Alias\", \"Ptr2Ref\"]"),
+        ("as_mut_aliased", "Failed: [\"Alias\", \"Ptr2Ref\"]"),
+    ] {
+        let results = verification_results(&output, name);
+        assert!(
+            results.contains(expected),
+            "Expected '{}' for {}\nResults:\n{}",
+            expected,
+            name,
+            results
+        );
+    }
+}
+
+#[test]
+fn test_verify_user_contracts() {
+    let output = running_tests_with_args(
+        "safety_check/user_contracts",
+        &["-verify", "-contracts=contracts/raw.json"],
+    );
+
+    // strlen is loaded from rapx-contracts/ and raw::get from the -contracts file.
+    for (name, expected) in [
+        ("strlen_cstring", "Passed: [\"ValidCStr\"]"),
+        ("strlen_raw", "Failed: [\"ValidCStr\"]"),
+        ("get_checked", "Passed: [\"ValidNum\"]"),
+        ("get_unchecked", "Failed: [\"ValidNum\"]"),
+    ] {
+        let results = verification_results(&output, name);
+        assert!(
+            results.contains(expected),
+            "Expected '{}' for {}\nResults:\n{}",
+            expected,
+            name,
+            results
+        );
+    }
+}
+
+#[test]
+fn test_verify_invalid_user_contracts() {
+    let output = running_tests_with_args(
+        "safety_check/user_contracts",
+        &["-verify", "-contracts=invalid"],
+    );
+    // The folder is resolved against the folder `cargo rapx` runs in.
+    assert!(
+        output.contains("Invalid contract InBound of raw::get in /")
+            && output.contains("/invalid/bad_args.json: expected 3 args"),
+        "Full output:\n{}",
+        output
+    );
+}
+
+#[test]
+fn test_verify_generic_instances() {
+    // The generic parameters are narrowed to the types they are instantiated with.
+    let output = running_tests_with_arg("safety_check/generic_instances", "-verify");
+    for (name, expected) in [
+        ("read_narrow", "Passed: [\"Align\", \"Typed\"]"),
+        ("read_wide", "Failed: [\"Align\", \"ValidPtr\"]"),
+    ] {
+        let results = verification_results(&output, name);
+        assert!(
+            results.contains(expected),
+            "Expected '{}' for {}\nResults:\n{}",
+            expected,
+            name,
+            results
+        );
+    }
+}
+
+#[test]
+fn test_verify_annotated_calls() {
+    let output = running_tests_with_arg("safety_check/annotated_calls", "-verify");
+    for (name, expected) in [
+        ("load_local", "Passed: [\"Align\", \"ValidPtr\"]"),
+        ("load_raw", "Failed: [\"ValidPtr\"]"),
+        ("get_checked", "Passed: [\"ValidNum\"]"),
+        ("get_unchecked", "Failed: [\"ValidNum\"]"),
+        ("sum_slice", "Passed: [\"Inbound\"]"),
+        ("sum_len", "Failed: [\"Inbound\"]"),
+        ("shift_back", "Passed: [\"ValidNum\"]"),
+    ] {
+        let results = verification_results(&output, name);
+        assert!(
+            results.contains(expected),
+            "Expected '{}' for {}\nResults:\n{}",
+            expected,
+            name,
+            results
+        );
+    }
+}
+
+#[test]
+fn test_verify_contract_obligations() {
+    let output = running_tests_with_arg("safety_check/contract_obligations", "-verify");
+    for (name, expected) in [
+        ("clamp", "Passed: [\"ValidNum\"]"),
+        ("identity", "Failed: [\"ValidNum\"]"),
+        ("Counter::new", "Passed: [\"ValidNum\"]"),
+        ("Counter::with_len", "Failed: [\"ValidNum\"]"),
+        ("Counter::push", "Passed: [\"ValidNum\"]"),
+        ("Counter::push_unchecked", "Failed: [\"ValidNum\"]"),
+        ("Ring::advance", "Passed: [\"ValidNum\"]"),
+        ("Ring::skip", "Failed: [\"ValidNum\"]"),
+        ("Ring::peek", "Passed: [\"ValidNum\"]"),
+        ("Ring::peek_next", "Failed: [\"ValidNum\"]"),
+    ] {
+        let results = verification_results(&output, name);
+        assert!(
+            results.contains(expected),
+            "Expected '{}' for {}\nResults:\n{}",
+            expected,
+            name,
+            results
+        );
+    }
+}
+
+#[test]
+fn test_infer_annotations() {
+    let patch = std::env::temp_dir().join("rapx_infer_annotations.patch");
+    let _ = std::fs::remove_file(&patch);
+    let patch_arg = format!("-infer-patch={}", patch.display());
+    let output = running_tests_with_arg("safety_check/infer_annotations", &patch_arg);
+    let annotation =
+        |property: &str| format!("#[rapx::inner(property = {property}, kind = \"precond\")]");
+    for (name, expected, unexpected) in [
+        (
+            "read_at",
+            vec![
+                "InBound(ptr, u32, idx)",
+                "ValidNum(idx <= 9223372036854775807)",
+            ],
+            vec![],
+        ),
+        ("load", vec!["ValidPtr(p, u64, 1)", "Align(p, u64)"], vec![]),
+        // The preconditions inferred for `load` are propagated to the caller.
+        (
+            "load_twice",
+            vec!["ValidPtr(q, u64, 1)", "Align(q, u64)"],
+            vec![],
+        ),
+        // The annotated preconditions are not suggested again.
+        (
+            "load_annotated",
+            vec!["Align(p, u64)"],
+            vec!["ValidPtr(p, u64, 1)"],
+        ),
+        // The length of the `Init` contract is mapped to the argument at the call site.
+        (
+            "sum_prefix",
+            vec!["Init(p, u32, n)"],
+            vec!["Init(p, u32, p)"],
+        ),
+    ] {
+        let results = verification_results(&output, name);
+        for property in expected {
+            assert!(
+                results.contains(&annotation(property)),
+                "Expected {} for {}\nResults:\n{}",
+                property,
+                name,
+                results
+            );
+        }
+        for property in unexpected {
+            assert!(!results.contains(&annotation(property)));
+        }
+    }
+    // The preconditions of `load` hold for the pointer to a local.
+    assert!(!verification_results(&output, "load_local").contains("Lack safety annotations"));
+
+    let content = std::fs::read_to_string(&patch).unwrap();
+    assert!(content.starts_with("--- a/src/main.rs\n+++ b/src/main.rs\n"));
+    assert!(content.contains(&format!(
+        "+{}\n pub unsafe fn load(p: *const u64) -> u64 {{",
+        annotation("Align(p, u64)")
+    )));
+    assert_eq!(content.matches("\n+#[rapx::inner(").count(), 8);
+
+    // Running again replaces the section of the file instead of appending to it.
+    running_tests_with_arg("safety_check/infer_annotations", &patch_arg);
+    assert_eq!(std::fs::read_to_string(&patch).unwrap(), content);
+}
@@ -0,0 +1,36 @@
+/*
+ * The double frees in `Suppressed` and `suppressed` are suppressed by `#[rapx::allow(..)]`,
+ * while the one in `reported` should still be reported.
+ */
+#![feature(register_tool)]
+#![register_tool(rapx)]
+
+struct Suppressed;
+
+#[rapx::allow(double_free)]
+impl Suppressed {
+    fn double_free() {
+        let mut s = String::from("a tmp string");
+        let ptr = s.as_mut_ptr();
+        let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+    }
+}
+
+#[rapx::allow(safedrop)]
+fn suppressed() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn reported() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn main() {
+    Suppressed::double_free();
+    suppressed();
+    reported();
+}
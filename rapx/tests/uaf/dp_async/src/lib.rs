@@ -0,0 +1,43 @@
+/*
+ * This is a buggy case: dangling pointer into the state of an async fn
+ */
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub struct Data {
+    value: Box<i32>,
+}
+
+impl Data {
+    pub fn new(value: i32) -> Data {
+        Data {
+            value: Box::new(value),
+        }
+    }
+
+    pub fn print_value(&self) {
+        println!("Value: {}", self.value);
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+pub async fn publish(slot: &mut *const Data) {
+    let data = Data::new(42);
+    *slot = &data as *const Data;
+    YieldNow(false).await;
+    data.print_value();
+}
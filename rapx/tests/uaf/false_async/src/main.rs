@@ -0,0 +1,62 @@
+/*
+ * This is a correct case: references and pointers into the state of an async fn across awaits
+ */
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+struct Data {
+    value: Box<i32>,
+}
+
+impl Data {
+    fn new(value: i32) -> Data {
+        Data {
+            value: Box::new(value),
+        }
+    }
+
+    fn print_value(&self) {
+        println!("Value: {}", self.value);
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+async fn run() {
+    let data = Data::new(1);
+    let data_ref = &data;
+    YieldNow(false).await;
+    data_ref.print_value();
+
+    let other = Data::new(2);
+    let other_ptr = &other as *const Data;
+    YieldNow(false).await;
+    unsafe {
+        (*other_ptr).print_value();
+    }
+    data.print_value();
+}
+
+async fn read(data: &Data) -> i32 {
+    YieldNow(false).await;
+    *data.value
+}
+
+fn main() {
+    let _ = run();
+    let data = Data::new(3);
+    let _ = read(&data);
+}
@@ -0,0 +1,55 @@
+/*
+ * This is a buggy case: use-after-free across a suspension point
+ */
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+struct Data {
+    value: Box<i32>,
+}
+
+impl Data {
+    fn new(value: i32) -> Data {
+        Data {
+            value: Box::new(value),
+        }
+    }
+
+    fn print_value(&self) {
+        println!("Value: {}", self.value);
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+async fn run() {
+    let data_ptr: *const Data;
+
+    {
+        let data = Data::new(42);
+        data_ptr = &data as *const Data;
+    }
+
+    YieldNow(false).await;
+
+    unsafe {
+        (*data_ptr).print_value();
+    }
+}
+
+fn main() {
+    let _ = run();
+}
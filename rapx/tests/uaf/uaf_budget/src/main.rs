@@ -0,0 +1,49 @@
+/*
+ * This is a buggy case: use-after-free before a path explosion exceeding the budget
+ */
+struct Data {
+    value: Box<i32>,
+}
+
+impl Data {
+    fn new(value: i32) -> Data {
+        Data {
+            value: Box::new(value),
+        }
+    }
+
+    fn print_value(&self) {
+        println!("Value: {}", self.value);
+    }
+}
+
+fn explode(flags: &[bool]) -> i32 {
+    let data_ptr: *const Data;
+    {
+        let data = Data::new(42);
+        data_ptr = &data as *const Data;
+    }
+    unsafe {
+        (*data_ptr).print_value();
+    }
+
+    let mut sum = 0;
+    if flags[0] { sum += 1; } else { sum -= 1; }
+    if flags[1] { sum += 2; } else { sum -= 2; }
+    if flags[2] { sum += 3; } else { sum -= 3; }
+    if flags[3] { sum += 4; } else { sum -= 4; }
+    if flags[4] { sum += 5; } else { sum -= 5; }
+    if flags[5] { sum += 6; } else { sum -= 6; }
+    if flags[6] { sum += 7; } else { sum -= 7; }
+    if flags[7] { sum += 8; } else { sum -= 8; }
+    if flags[8] { sum += 9; } else { sum -= 9; }
+    if flags[9] { sum += 10; } else { sum -= 10; }
+    if flags[10] { sum += 11; } else { sum -= 11; }
+    if flags[11] { sum += 12; } else { sum -= 12; }
+    sum
+}
+
+fn main() {
+    let flags = [true; 12];
+    println!("{}", explode(&flags));
+}
@@ -0,0 +1,35 @@
+#![allow(deprecated, invalid_value)]
+use std::alloc::{alloc, Layout};
+use std::mem::{self, MaybeUninit};
+
+pub fn read_buffer(len: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(len);
+    unsafe {
+        buf.set_len(len);
+    }
+    buf
+}
+
+pub fn partial_array() -> [u32; 4] {
+    let mut arr: MaybeUninit<[u32; 4]> = MaybeUninit::uninit();
+    let ptr = arr.as_mut_ptr() as *mut u32;
+    unsafe {
+        ptr.write(1);
+        ptr.add(1).write(2);
+        arr.assume_init()
+    }
+}
+
+pub fn read_alloc() -> u64 {
+    let layout = Layout::new::<u64>();
+    unsafe {
+        let ptr = alloc(layout) as *mut u64;
+        let value = *ptr;
+        std::alloc::dealloc(ptr as *mut u8, layout);
+        value
+    }
+}
+
+pub fn zeroed_string() -> String {
+    unsafe { mem::uninitialized() }
+}
@@ -0,0 +1,62 @@
+use std::alloc::{alloc, dealloc, Layout};
+use std::mem::MaybeUninit;
+use std::ptr;
+
+pub fn fill_buffer(len: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(len);
+    unsafe {
+        ptr::write_bytes(buf.as_mut_ptr(), 0, len);
+        buf.set_len(len);
+    }
+    buf
+}
+
+pub fn clear_buffer(len: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(len);
+    unsafe {
+        buf.set_len(0);
+    }
+    buf
+}
+
+pub fn full_array() -> [u32; 2] {
+    let mut arr: MaybeUninit<[u32; 2]> = MaybeUninit::uninit();
+    let ptr = arr.as_mut_ptr() as *mut u32;
+    unsafe {
+        ptr.write(1);
+        ptr.add(1).write(2);
+        arr.assume_init()
+    }
+}
+
+pub fn written_value() -> String {
+    let mut value = MaybeUninit::uninit();
+    value.write(String::from("init"));
+    unsafe { value.assume_init() }
+}
+
+pub fn write_alloc() -> u64 {
+    let layout = Layout::new::<u64>();
+    unsafe {
+        let ptr = alloc(layout) as *mut u64;
+        ptr.write(7);
+        let value = *ptr;
+        dealloc(ptr as *mut u8, layout);
+        value
+    }
+}
+
+pub fn init_by_callee(len: usize) -> Vec<u8> {
+    let mut buf: Vec<u8> = Vec::with_capacity(len);
+    fill(buf.as_mut_ptr(), len);
+    unsafe {
+        buf.set_len(len);
+    }
+    buf
+}
+
+fn fill(ptr: *mut u8, len: usize) {
+    for i in 0..len {
+        unsafe { ptr.add(i).write(i as u8) };
+    }
+}